@@ -0,0 +1,71 @@
+use std::{env, fs, path::Path, process::Command};
+
+use chrono::{DateTime, Utc};
+
+/// Record where and when Renom was built, so that bug reports can identify
+/// the exact binary through `renom --version`.
+fn main() {
+    let version = env::var("CARGO_PKG_VERSION").unwrap_or_default();
+    let commit = git_commit().unwrap_or_else(|| "unknown".to_owned());
+    let date = build_date().format("%Y-%m-%d").to_string();
+    let target = env::var("TARGET").unwrap_or_default();
+    let features = enabled_features();
+    println!("cargo:rustc-env=RENOM_FEATURES={}", features.join(","));
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=src");
+    // Rebuilt on commit so that the commit stays current
+    for path in [".git/HEAD", ".git/refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+    // Environment variables cannot span lines, so the text of the version is
+    // included from a file instead
+    let long_version = format!(
+        "{}\ncommit: {}\nbuilt: {}\ntarget: {}\nfeatures: {}",
+        version,
+        commit,
+        date,
+        target,
+        match features.is_empty() {
+            true => "none".to_owned(),
+            false => features.join(", "),
+        }
+    );
+    let out_dir = env::var("OUT_DIR").expect("cargo should set OUT_DIR");
+    fs::write(Path::new(&out_dir).join("long_version.txt"), long_version)
+        .expect("long version should be written");
+}
+
+fn git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()?;
+    let commit = String::from_utf8(output.stdout).ok()?;
+    match output.status.success() && !commit.trim().is_empty() {
+        true => Some(commit.trim().to_owned()),
+        false => None,
+    }
+}
+
+/// Get the time of the build, or the time given in `SOURCE_DATE_EPOCH` for
+/// reproducible builds.
+fn build_date() -> DateTime<Utc> {
+    env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok())
+        .and_then(|epoch| DateTime::from_timestamp(epoch, 0))
+        .unwrap_or_else(Utc::now)
+}
+
+fn enabled_features() -> Vec<String> {
+    let mut features = env::vars()
+        .filter_map(|(name, _)| {
+            name.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<String>>();
+    features.sort();
+    features
+}
@@ -0,0 +1,386 @@
+use std::{
+    cmp::Reverse,
+    fmt::{self, Display, Formatter},
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Local, SecondsFormat};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use walkdir::WalkDir;
+
+use crate::{
+    changes::{Change, RevertAction},
+    journal,
+};
+
+const ARCHIVE_FILE_NAME: &str = "backups.tar.gz";
+const SNAPSHOT_FILE_NAME: &str = "snapshot.json";
+
+/// Limits on the backups kept for a project. Backups of older runs are
+/// pruned first. Unset limits are not enforced.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BackupPolicy {
+    /// The most disk space, in megabytes, that backups may take up in total.
+    pub max_total_size_mb: Option<u64>,
+    /// The most days that backups are kept for.
+    pub max_age_days: Option<u64>,
+    /// The most runs that backups are kept for.
+    pub max_count: Option<usize>,
+}
+
+/// The backups removed by [`prune`].
+#[derive(Debug, Default, PartialEq)]
+pub struct Pruned {
+    /// The backup directories removed.
+    pub removed: Vec<PathBuf>,
+    /// The disk space freed, in bytes.
+    pub freed: u64,
+}
+
+/// The tag of the backups of a run, written when the run starts, so that
+/// runs can be told apart and restored by id, see [`create_snapshot`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The id of the snapshot, which names the directory of the run.
+    pub id: String,
+    /// When the run started, in RFC 3339 format.
+    pub created: String,
+    /// The command line that started the run, unless the run was made by a
+    /// version that did not tag its backups.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// A snapshot in a backup root, as listed by [`snapshots`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotEntry {
+    pub snapshot: Snapshot,
+    /// The directory of the run.
+    pub path: PathBuf,
+    /// The disk space taken up by the run, in bytes.
+    pub size: u64,
+    pub state: SnapshotState,
+}
+
+/// Whether the run of a snapshot can still be restored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotState {
+    /// The run completed, and can be restored.
+    Completed,
+    /// The run was interrupted, and can be resumed or restored.
+    Interrupted,
+    /// The run was restored already, so there is nothing left to restore.
+    Restored,
+}
+
+impl Display for SnapshotState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotState::Completed => write!(f, "completed"),
+            SnapshotState::Interrupted => write!(f, "interrupted"),
+            SnapshotState::Restored => write!(f, "restored"),
+        }
+    }
+}
+
+/// A backup left behind by an earlier run.
+struct Backup {
+    path: PathBuf,
+    created: SystemTime,
+    size: u64,
+    /// Whether the run still has a journal or an operation log to revert it
+    /// from.
+    revertible: bool,
+    /// Whether the run was interrupted, i.e. has a journal or an operation
+    /// log but no record of the changes it completed.
+    in_progress: bool,
+}
+
+/// Get the directory that contains the backups of every run for a project.
+pub fn backup_root(project_root: &Path) -> PathBuf {
+    project_root.join(".renom").join("backup")
+}
+
+/// Create a directory in a backup root to store the backups of a new run in.
+/// Directories are named after a ULID, so they sort by creation time.
+pub fn create_run_dir(backup_root: &Path) -> io::Result<PathBuf> {
+    let run_dir = backup_root.join(Ulid::new().to_string());
+    fs::create_dir_all(&run_dir)?;
+    Ok(run_dir)
+}
+
+/// Create a directory in a backup root to store the backups of a new run in,
+/// tagged with a snapshot of the command that started the run, see
+/// [`snapshots`]. The id of the snapshot is the name of the directory.
+pub fn create_snapshot(backup_root: &Path, command: impl Into<String>) -> io::Result<PathBuf> {
+    let run_dir = create_run_dir(backup_root)?;
+    let snapshot = Snapshot {
+        id: file_name_of(&run_dir),
+        created: Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+        command: Some(command.into()),
+    };
+    let json = serde_json::to_string_pretty(&snapshot).map_err(io::Error::other)?;
+    fs::write(snapshot_path(&run_dir), json)?;
+    Ok(run_dir)
+}
+
+/// List the snapshots in a backup root, oldest first. Runs that were not
+/// tagged are listed as well, with the time they were created at.
+pub fn snapshots(backup_root: &Path) -> io::Result<Vec<SnapshotEntry>> {
+    let mut snapshots = list(backup_root)?
+        .into_iter()
+        .filter(|backup| backup.path.is_dir())
+        .map(|backup| {
+            let snapshot = fs::read_to_string(snapshot_path(&backup.path))
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_else(|| Snapshot {
+                    id: file_name_of(&backup.path),
+                    created: DateTime::<Local>::from(backup.created)
+                        .to_rfc3339_opts(SecondsFormat::Secs, false),
+                    command: None,
+                });
+            let state = match (backup.in_progress, backup.revertible) {
+                (true, _) => SnapshotState::Interrupted,
+                (false, true) => SnapshotState::Completed,
+                (false, false) => SnapshotState::Restored,
+            };
+            SnapshotEntry {
+                snapshot,
+                path: backup.path,
+                size: backup.size,
+                state,
+            }
+        })
+        .collect::<Vec<SnapshotEntry>>();
+    snapshots.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(snapshots)
+}
+
+/// Find the directory of the run of a snapshot in a backup root, if the run
+/// can still be restored.
+pub fn find_snapshot(backup_root: &Path, id: &str) -> io::Result<Option<PathBuf>> {
+    Ok(snapshots(backup_root)?
+        .into_iter()
+        .find(|entry| entry.snapshot.id == id && entry.state != SnapshotState::Restored)
+        .map(|entry| entry.path))
+}
+
+/// Get the path to the archive that the backups of a run are compressed into
+/// once it completes, see [`archive`].
+pub fn archive_path(run_dir: &Path) -> PathBuf {
+    run_dir.join(ARCHIVE_FILE_NAME)
+}
+
+/// Compress the backups of a run into a single archive in its directory and
+/// remove them, leaving the journal and the other records of the run as they
+/// are. Returns whether anything was archived. Runs that were archived
+/// already are left alone.
+pub fn archive(run_dir: &Path) -> io::Result<bool> {
+    let archive = archive_path(run_dir);
+    let backups = loose_backups(run_dir)?;
+    if backups.is_empty() || archive.exists() {
+        return Ok(false);
+    }
+    // Written aside first, so that a partly written archive is never taken
+    // for the backups
+    let temp = archive.with_extension("gz.tmp");
+    let mut builder =
+        tar::Builder::new(GzEncoder::new(File::create(&temp)?, Compression::default()));
+    for backup in &backups {
+        let name = backup.strip_prefix(run_dir).unwrap_or(backup);
+        builder.append_path_with_name(backup, name)?;
+    }
+    builder.into_inner()?.finish()?.sync_all()?;
+    fs::rename(&temp, &archive)?;
+    for backup in backups {
+        fs::remove_file(backup)?;
+    }
+    Ok(true)
+}
+
+/// Compress the backups of every completed run in a backup root, see
+/// [`archive`]. Interrupted runs are left as is, so that they can still be
+/// resumed. Returns the directories of the runs that were archived.
+pub fn archive_completed(backup_root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut archived = vec![];
+    for backup in list(backup_root)? {
+        if backup.path.is_dir() && !backup.in_progress && archive(&backup.path)? {
+            archived.push(backup.path);
+        }
+    }
+    archived.sort();
+    Ok(archived)
+}
+
+/// Unpack the backups of a run from its archive, if it has one, so that the
+/// run can be reverted, and remove the archive.
+pub fn extract(run_dir: &Path) -> io::Result<()> {
+    let archive = archive_path(run_dir);
+    if !archive.is_file() {
+        return Ok(());
+    }
+    tar::Archive::new(GzDecoder::new(File::open(&archive)?)).unpack(run_dir)?;
+    fs::remove_file(archive)
+}
+
+/// Find the most recent run in a backup root that can still be reverted, i.e.
+/// that still has a journal or an operation log.
+pub fn last_revertible_run(backup_root: &Path) -> io::Result<Option<PathBuf>> {
+    Ok(list(backup_root)?
+        .into_iter()
+        .filter(|backup| backup.revertible && backup.path.is_dir())
+        .max_by_key(|backup| backup.created)
+        .map(|backup| backup.path))
+}
+
+/// Find the most recent run in a backup root if it was interrupted, i.e. it
+/// has a journal or an operation log but did not complete, so that it can be
+/// resumed.
+pub fn interrupted_run(backup_root: &Path) -> io::Result<Option<PathBuf>> {
+    Ok(list(backup_root)?
+        .into_iter()
+        .filter(|backup| backup.path.is_dir())
+        .max_by_key(|backup| backup.created)
+        .filter(|backup| backup.in_progress)
+        .map(|backup| backup.path))
+}
+
+/// Read the changes applied by every run in a backup root that can still be
+/// reverted, oldest first, see [`journal::read_applied`].
+pub fn applied_changes(backup_root: &Path) -> io::Result<Vec<(Change, RevertAction)>> {
+    let mut runs = list(backup_root)?
+        .into_iter()
+        .filter(|backup| backup.revertible && backup.path.is_dir())
+        .collect::<Vec<Backup>>();
+    runs.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut applied = vec![];
+    for run in runs {
+        applied.extend(journal::read_applied(&run.path)?);
+    }
+    Ok(applied)
+}
+
+/// Remove backups of earlier runs in a backup root that exceed the limits of a
+/// policy, oldest first. Backups of runs that still have a journal, i.e. that
+/// were interrupted and may need to be reverted, are never removed.
+pub fn prune(backup_root: &Path, policy: &BackupPolicy, now: SystemTime) -> io::Result<Pruned> {
+    let mut backups = list(backup_root)?;
+    backups.sort_by_key(|backup| Reverse(backup.created));
+
+    let max_age = policy
+        .max_age_days
+        .map(|days| Duration::from_secs(days * 24 * 60 * 60));
+    let max_total_size = policy
+        .max_total_size_mb
+        .map(|megabytes| megabytes * 1024 * 1024);
+    let mut kept_count = 0;
+    let mut kept_size = 0;
+    let mut pruned = Pruned::default();
+    for backup in backups {
+        let too_old = max_age.is_some_and(|max_age| {
+            now.duration_since(backup.created)
+                .is_ok_and(|age| age > max_age)
+        });
+        let too_many = policy.max_count.is_some_and(|max| kept_count >= max);
+        let too_big = max_total_size.is_some_and(|max| kept_size + backup.size > max);
+        if backup.in_progress || !(too_old || too_many || too_big) {
+            kept_count += 1;
+            kept_size += backup.size;
+            continue;
+        }
+        match backup.path.is_dir() {
+            true => fs::remove_dir_all(&backup.path)?,
+            false => fs::remove_file(&backup.path)?,
+        }
+        pruned.freed += backup.size;
+        pruned.removed.push(backup.path);
+    }
+    Ok(pruned)
+}
+
+/// List the backups in the backup root. Each entry is a backup, including
+/// files left directly in the root by versions that did not keep runs apart.
+fn list(backup_root: &Path) -> io::Result<Vec<Backup>> {
+    if !backup_root.is_dir() {
+        return Ok(vec![]);
+    }
+    let legacy_in_progress = journal::journal_path(backup_root).is_file();
+    let mut backups = vec![];
+    for entry in fs::read_dir(backup_root)? {
+        let path = entry?.path();
+        let created = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| Ulid::from_string(name).ok())
+            .map(|ulid| ulid.datetime())
+            .map_or_else(|| fs::metadata(&path).and_then(|m| m.modified()), Ok)?;
+        let (revertible, in_progress) = match path.is_dir() {
+            true => {
+                let revertible =
+                    journal::journal_path(&path).is_file() || journal::log_path(&path).is_file();
+                (
+                    revertible,
+                    revertible && !journal::changes_path(&path).is_file(),
+                )
+            }
+            false => (false, legacy_in_progress),
+        };
+        backups.push(Backup {
+            size: size_of(&path),
+            path,
+            created,
+            revertible,
+            in_progress,
+        });
+    }
+    Ok(backups)
+}
+
+/// List the backups of a run that are not archived yet, i.e. every file in
+/// its directory but its records and temporary files.
+fn loose_backups(run_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let records = [
+        journal::journal_path(run_dir),
+        journal::changes_path(run_dir),
+        journal::log_path(run_dir),
+        archive_path(run_dir),
+        snapshot_path(run_dir),
+    ];
+    let mut backups = vec![];
+    for entry in fs::read_dir(run_dir)? {
+        let path = entry?.path();
+        let temporary = path.extension().is_some_and(|extension| extension == "tmp");
+        if path.is_file() && !temporary && !records.contains(&path) {
+            backups.push(path);
+        }
+    }
+    backups.sort();
+    Ok(backups)
+}
+
+fn snapshot_path(run_dir: &Path) -> PathBuf {
+    run_dir.join(SNAPSHOT_FILE_NAME)
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn size_of(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
@@ -0,0 +1,142 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use walkdir::WalkDir;
+
+use crate::{
+    changes::simulate_revert,
+    error::RenomError,
+    matcher::Matcher,
+    presentation::log,
+    unreal,
+    workflows::{plan_rename_project, rename_project, Options},
+};
+
+/// Params needed to benchmark Renom against a project.
+pub struct Params {
+    /// The root of the project.
+    pub project_root: PathBuf,
+    /// Memory-map files when scanning.
+    pub mmap: bool,
+}
+
+/// Time scanning, changeset generation, and a dry run of the changeset and its
+/// revert for a project rename, then print a breakdown. Nothing is written to disk.
+pub fn bench(params: Params) -> Result<(), RenomError> {
+    let project_name = detect_project_name(&params.project_root)?;
+
+    let start = Instant::now();
+    let (files, bytes, matches) = scan(&params.project_root, &project_name, params.mmap);
+    let scan_time = start.elapsed();
+
+    let start = Instant::now();
+    let changeset = plan_rename_project(&rename_project::Params {
+        project_root: params.project_root.clone(),
+        new_name: bench_name(&project_name),
+        options: Options {
+            mmap: params.mmap,
+            ..Options::default()
+        },
+    })?;
+    let generate_time = start.elapsed();
+
+    let start = Instant::now();
+    let failures = changeset
+        .changes()
+        .iter()
+        .filter(|change| change.dry_run().is_err())
+        .count();
+    let revert_issues = simulate_revert(changeset.changes()).len();
+    let execute_time = start.elapsed();
+
+    log::header("Benchmark");
+    log::basic(format!("project:  {}", params.project_root.display()));
+    log::basic(format!(
+        "scan:     {} ({} files, {} bytes, {} matching)",
+        format_duration(scan_time),
+        files,
+        bytes,
+        matches
+    ));
+    log::basic(format!(
+        "generate: {} ({} changes)",
+        format_duration(generate_time),
+        changeset.changes().len()
+    ));
+    log::basic(format!(
+        "execute:  {} (dry run, {} failed, {} not revertible)",
+        format_duration(execute_time),
+        failures,
+        revert_issues
+    ));
+    log::basic(format!(
+        "total:    {}",
+        format_duration(scan_time + generate_time + execute_time)
+    ));
+    Ok(())
+}
+
+/// Walk the project and search every file for the project name, in
+/// parallel. Returns the number of files, total bytes, and number of files
+/// that match.
+fn scan(project_root: &Path, project_name: &str, mmap: bool) -> (usize, u64, usize) {
+    let matcher = Matcher::literals([project_name]);
+    let mut bytes = 0;
+    let files = WalkDir::new(project_root)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_none_or(|name| !unreal::is_ignored_dir(name))
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .inspect(|entry| bytes += entry.metadata().map(|metadata| metadata.len()).unwrap_or(0))
+        .map(|entry| entry.into_path())
+        .collect::<Vec<PathBuf>>();
+    let count = files.len();
+    let matches = matcher.files_matching(files, mmap).len();
+    (count, bytes, matches)
+}
+
+fn detect_project_name(project_root: &Path) -> Result<String, RenomError> {
+    fs::read_dir(project_root)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "uproject"))
+        .and_then(|descriptor| {
+            descriptor
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(|name| name.to_owned())
+        })
+        .ok_or_else(|| {
+            RenomError::Validation("project root must contain a project descriptor".into())
+        })
+}
+
+/// Get a valid name to plan renaming a project to: the project name with a
+/// suffix, cut short so that it is not too long for a project name.
+fn bench_name(project_name: &str) -> String {
+    ["Bench", "Bench2"]
+        .iter()
+        .map(|suffix| {
+            let mut len = project_name
+                .len()
+                .min(rename_project::NEW_NAME_MAX_LEN - suffix.len());
+            while !project_name.is_char_boundary(len) {
+                len -= 1;
+            }
+            format!("{}{}", &project_name[..len], suffix)
+        })
+        .find(|name| name != project_name)
+        .expect("names with different suffixes should differ")
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:>9.3} ms", duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,13 @@
+/// The version along with the commit, build date, target triple, and enabled
+/// features of the build, as printed by `--version`, so that bug reports can
+/// identify the exact binary.
+pub const LONG_VERSION: &str = include_str!(concat!(env!("OUT_DIR"), "/long_version.txt"));
+
+/// The Cargo features compiled into this build.
+pub fn features() -> Vec<String> {
+    env!("RENOM_FEATURES")
+        .split(',')
+        .filter(|feature| !feature.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
@@ -0,0 +1,38 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::error::RenomError;
+
+/// A cooperative cancellation flag shared between the caller of a workflow and
+/// the workflow itself. Cancelling stops the workflow at the next safe
+/// boundary: between scan steps, before generating the changeset, or between
+/// changes during execution, in which case applied changes are reverted.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation. Clones of the token observe the request.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Return an error if cancellation has been requested.
+    pub fn check(&self) -> Result<(), RenomError> {
+        match self.is_cancelled() {
+            true => Err(RenomError::Cancelled),
+            false => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use clap::Arg;
+use serde::Serialize;
+
+use crate::{build_info, changes::Change, plan};
+
+/// What this build of Renom supports, so that frontends and scripts can adapt
+/// to the installed version instead of parsing help text.
+#[derive(Debug, Serialize)]
+pub struct Capabilities {
+    /// The version of Renom.
+    pub version: String,
+    /// The commands that can be run, with their options.
+    pub commands: Vec<CommandInfo>,
+    /// The kinds of change that changesets, plans, and extra ops can hold.
+    pub change_kinds: Vec<String>,
+    /// The version of the format plans are written in.
+    pub plan_schema_version: u32,
+    /// The Cargo features compiled into this build.
+    pub features: Vec<String>,
+}
+
+/// A command and the options it accepts.
+#[derive(Debug, Serialize)]
+pub struct CommandInfo {
+    /// The name of the command, as typed on the command line.
+    pub name: String,
+    /// What the command does.
+    pub about: String,
+    /// The options and positional arguments of the command.
+    pub options: Vec<OptionInfo>,
+    /// The commands nested in the command, e.g. the renames that can be
+    /// planned.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub subcommands: Vec<CommandInfo>,
+}
+
+/// An option or positional argument of a command.
+#[derive(Debug, Serialize)]
+pub struct OptionInfo {
+    /// The long name of the option, or the name of the positional argument.
+    pub name: String,
+    /// The short name of the option, if any.
+    pub short: Option<char>,
+    /// Whether the argument is given by position rather than by name.
+    pub positional: bool,
+    /// Whether the option is followed by a value, rather than being a flag.
+    pub takes_value: bool,
+    /// The values the option accepts, if it only accepts some.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+    /// The environment variable the option can be set through, if any.
+    pub env: Option<String>,
+    /// What the option is for.
+    pub help: String,
+}
+
+/// Describe the capabilities of this build, with the commands and options of
+/// a command line.
+pub fn describe(command: clap::Command) -> Capabilities {
+    let mut command = command;
+    command.build();
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        commands: subcommands_of(&command),
+        change_kinds: Change::KINDS.iter().map(|&kind| kind.to_owned()).collect(),
+        plan_schema_version: plan::SCHEMA_VERSION,
+        features: build_info::features(),
+    }
+}
+
+fn subcommands_of(command: &clap::Command) -> Vec<CommandInfo> {
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .map(|subcommand| CommandInfo {
+            name: subcommand.get_name().to_owned(),
+            about: subcommand
+                .get_about()
+                .map(|about| about.to_string())
+                .unwrap_or_default(),
+            options: subcommand
+                .get_arguments()
+                .filter(|arg| !arg.is_hide_set())
+                .map(option_of)
+                .collect(),
+            subcommands: subcommands_of(subcommand),
+        })
+        .collect()
+}
+
+fn option_of(arg: &Arg) -> OptionInfo {
+    let takes_value = arg.get_action().takes_values();
+    OptionInfo {
+        name: match (arg.get_long(), arg.get_value_names()) {
+            (Some(long), _) => long.to_owned(),
+            (None, Some([name, ..])) => name.to_string(),
+            (None, _) => arg.get_id().to_string(),
+        },
+        short: arg.get_short(),
+        positional: arg.is_positional(),
+        takes_value,
+        values: match takes_value {
+            true => arg
+                .get_possible_values()
+                .iter()
+                .filter(|value| !value.is_hide_set())
+                .map(|value| value.get_name().to_owned())
+                .collect(),
+            false => vec![],
+        },
+        env: arg.get_env().map(|env| env.to_string_lossy().into_owned()),
+        help: arg
+            .get_help()
+            .map(|help| help.to_string())
+            .unwrap_or_default(),
+    }
+}
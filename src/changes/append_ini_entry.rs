@@ -1,8 +1,11 @@
 use std::{fmt::Display, path::PathBuf};
 
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Clone)]
+/// Append an entry to a section of a config file, e.g. a redirect. An entry
+/// that is already present with the same value is not appended again.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct AppendIniEntry {
     pub path: PathBuf,
     pub section: String,
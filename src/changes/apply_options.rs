@@ -0,0 +1,45 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use crate::text::LineEnding;
+
+use super::{Change, RevertAction};
+
+/// Options that control how changes are applied to disk.
+#[derive(Debug, Clone, Default)]
+pub struct ApplyOptions {
+    /// Restore the original modification time of edited files.
+    pub preserve_mtime: bool,
+    /// Line endings to write edited files with, e.g. those expected by version
+    /// control. Files that are not listed keep their own line endings.
+    pub line_endings: BTreeMap<PathBuf, LineEnding>,
+    /// Keep the content of edited files in memory to revert them instead of
+    /// backing them up to the backup directory, which is left untouched.
+    pub no_backup: bool,
+    /// Clear the read-only flag of files to edit and set it again once they
+    /// are edited, instead of failing to write them.
+    pub make_writable: bool,
+    /// The size in bytes from which files are edited a line at a time,
+    /// reading them back from their backup rather than into memory, e.g. for
+    /// giant generated files or logs, or `None` to edit every file whole.
+    /// Files are still edited whole where the result could differ, e.g. when
+    /// a pattern may match across lines, see [`is_line_bounded`].
+    ///
+    /// [`is_line_bounded`]: crate::changes::is_line_bounded
+    pub stream_threshold: Option<u64>,
+    /// Skip changes whose effect is already there, e.g. a file renamed or an
+    /// entry set by an earlier run that partly succeeded, instead of failing
+    /// or applying them twice, see [`find_applied`].
+    ///
+    /// [`find_applied`]: crate::changes::find_applied
+    pub idempotent: bool,
+    /// The changes applied by earlier runs, oldest first, along with how to
+    /// revert them, which idempotent executions take edits to be applied
+    /// from, see [`backup::applied_changes`].
+    ///
+    /// [`backup::applied_changes`]: crate::backup::applied_changes
+    pub applied_earlier: Vec<(Change, RevertAction)>,
+    /// The directory that changes must not follow symlinks out of, e.g. the
+    /// project root, or `None` to follow them anywhere. A symlink inside of
+    /// it can still be renamed, which moves the link rather than its target.
+    pub root: Option<PathBuf>,
+}
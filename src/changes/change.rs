@@ -1,16 +1,27 @@
 use std::{
     fmt::Display,
-    io,
+    io::{self, BufRead},
     path::{Path, PathBuf},
 };
 
 use ini::{Ini, ParseOption};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-use super::{rename_file::RenameFile, AppendIniEntry, ReplaceInFile, SetIniEntry};
+use crate::{
+    fs::{force_write, Fs, Metadata, RealFs},
+    text::{Encoding, TextFile},
+};
+
+use super::{
+    checksum_in, is_line_bounded,
+    rename_file::{is_same_file, rename_path, RenameFile},
+    replace_lines, AppendIniEntry, ApplyOptions, ReplaceInFile, RevertAction, SetIniEntry,
+};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum Change {
     RenameFile(RenameFile),
     ReplaceInFile(ReplaceInFile),
@@ -19,110 +30,425 @@ pub enum Change {
 }
 
 impl Change {
+    /// The kinds of change, as tagged in serialized changes.
+    pub const KINDS: &'static [&'static str] = &[
+        "rename_file",
+        "replace_in_file",
+        "set_ini_entry",
+        "append_ini_entry",
+    ];
+
     pub fn apply(&self, backup_dir: &Path) -> io::Result<Revert> {
-        match &*self {
-            Change::RenameFile(params) => Change::rename_file(&params),
-            Change::ReplaceInFile(params) => Change::replace_in_file(params, backup_dir),
-            Change::SetIniEntry(params) => Change::set_ini_entry(params, backup_dir),
-            Change::AppendIniEntry(params) => Change::append_ini_entry(params, backup_dir),
+        self.apply_with(backup_dir, &ApplyOptions::default())
+    }
+
+    /// Apply the change with custom options.
+    pub fn apply_with(&self, backup_dir: &Path, options: &ApplyOptions) -> io::Result<Revert> {
+        self.apply_recorded(backup_dir, options)
+            .map(RevertAction::into_revert)
+    }
+
+    /// Apply the change and describe how to revert it, so that the revert can
+    /// be persisted and performed later.
+    pub fn apply_recorded(
+        &self,
+        backup_dir: &Path,
+        options: &ApplyOptions,
+    ) -> io::Result<RevertAction> {
+        self.apply_recorded_in(&RealFs, backup_dir, options)
+    }
+
+    /// Apply the change to a filesystem and describe how to revert it on the
+    /// same filesystem. Backups are written to the backup directory on it.
+    pub fn apply_recorded_in(
+        &self,
+        fs: &impl Fs,
+        backup_dir: &Path,
+        options: &ApplyOptions,
+    ) -> io::Result<RevertAction> {
+        match self {
+            Change::RenameFile(params) => Change::rename_file(fs, params),
+            Change::ReplaceInFile(params) => {
+                Change::replace_in_file(fs, params, backup_dir, options)
+            }
+            Change::SetIniEntry(params) => Change::set_ini_entry(fs, params, backup_dir, options),
+            Change::AppendIniEntry(params) => {
+                Change::append_ini_entry(fs, params, backup_dir, options)
+            }
+        }
+    }
+
+    /// The file or directory touched by the change. For renames, this is the
+    /// original path.
+    pub fn path(&self) -> &Path {
+        match self {
+            Change::RenameFile(params) => &params.from,
+            Change::ReplaceInFile(params) => &params.path,
+            Change::SetIniEntry(params) => &params.path,
+            Change::AppendIniEntry(params) => &params.path,
+        }
+    }
+
+    /// Check whether an applied change holds for the file at a path, i.e.
+    /// applying an edit again would leave the file as is, e.g. no occurrence
+    /// of the old name is left to replace. A rename holds wherever its
+    /// destination exists.
+    pub fn holds_in(&self, fs: &impl Fs, path: &Path) -> io::Result<bool> {
+        let read = || TextFile::decode(&fs.read(path)?);
+        match self {
+            Change::RenameFile(_) => Ok(fs.metadata(path).is_ok()),
+            Change::ReplaceInFile(params) => {
+                let file = read()?;
+                Ok(Change::edit_replace(params, &file)? == file.content)
+            }
+            Change::SetIniEntry(params) => Ok(Change::parse_ini(&read()?)?
+                .section(Some(&params.section))
+                .and_then(|properties| properties.get(&params.key))
+                == Some(params.value.as_str())),
+            Change::AppendIniEntry(params) => Ok(Change::parse_ini(&read()?)?
+                .section(Some(&params.section))
+                .is_some_and(|properties| {
+                    properties
+                        .get_all(&params.key)
+                        .any(|value| value == params.value)
+                })),
+        }
+    }
+
+    /// Perform every step of applying the change except writing to disk, so
+    /// that missing files and unparsable config surface up front. Does not
+    /// account for earlier changes in the same changeset.
+    pub fn dry_run(&self) -> io::Result<()> {
+        match self {
+            Change::RenameFile(params) => {
+                std::fs::symlink_metadata(&params.from)?;
+                match params.to.exists() && !is_same_file(&params.from, &params.to) {
+                    true => Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("{} already exists", params.to.display()),
+                    )),
+                    false => Ok(()),
+                }
+            }
+            _ => {
+                let file = TextFile::read(self.path())?;
+                self.edit(&file).map(|_| ())
+            }
+        }
+    }
+
+    /// Compute the content of a file after applying the change to it, without
+    /// writing to disk. Returns `None` for renames, which leave content as is.
+    pub fn edit(&self, file: &TextFile) -> io::Result<Option<String>> {
+        match self {
+            Change::RenameFile(_) => Ok(None),
+            Change::ReplaceInFile(params) => Change::edit_replace(params, file).map(Some),
+            Change::SetIniEntry(params) => Change::edit_set_ini(params, file).map(Some),
+            Change::AppendIniEntry(params) => Change::edit_append_ini(params, file).map(Some),
         }
     }
 
-    fn rename_file(params: &RenameFile) -> io::Result<Revert> {
+    fn rename_file(fs: &impl Fs, params: &RenameFile) -> io::Result<RevertAction> {
         let from = params.from.clone();
         let to = params.to.clone();
-        std::fs::rename(&from, &to)?;
+        rename_path(fs, &from, &to)?;
 
-        Ok(Box::new(move || std::fs::rename(&to, &from)))
+        Ok(RevertAction::Rename { from, to })
     }
 
-    fn replace_in_file(params: &ReplaceInFile, backup_dir: &Path) -> io::Result<Revert> {
-        let backup = Change::backup_file(&params.path, backup_dir)?;
+    fn replace_in_file(
+        fs: &impl Fs,
+        params: &ReplaceInFile,
+        backup_dir: &Path,
+        options: &ApplyOptions,
+    ) -> io::Result<RevertAction> {
+        if Change::should_stream(fs, params, options)? {
+            return Change::stream_replace_in_file(fs, params, backup_dir, options);
+        }
+        let backup = Change::backup_file(fs, &params.path, backup_dir, options)?;
         let target = params.path.clone();
-        let content = std::fs::read_to_string(&target)?;
-        let regex = Regex::new(&params.from).expect("regex should be valid");
-        let content_after_replace = regex.replace_all(&content, params.to.as_str()).to_string();
-        std::fs::write(&target, &content_after_replace)?;
+        let metadata = fs.metadata(&target)?;
+
+        let mut file = TextFile::decode(&fs.read(&target)?)?;
+        file.content = Change::edit_replace(params, &file)?;
+        Change::normalize_line_endings(&mut file, &target, options);
+        Change::write_file(fs, &target, &file.encode(), &metadata, options)?;
+        Change::restore_metadata(fs, &target, &metadata, options)?;
+        let checksum = Some(checksum_in(fs, &target)?);
 
-        Ok(Box::new(move || {
-            std::fs::copy(&backup, &target).map(|_| ())
-        }))
+        Ok(backup.into_revert_action(target, checksum))
     }
 
-    fn set_ini_entry(params: &SetIniEntry, backup_dir: &Path) -> io::Result<Revert> {
-        let SetIniEntry {
-            section,
-            key,
-            value,
-            path,
-        } = params;
+    /// Whether a file is big enough to be edited a line at a time, see
+    /// [`ApplyOptions::stream_threshold`], and would be edited the same as
+    /// whole.
+    fn should_stream(
+        fs: &impl Fs,
+        params: &ReplaceInFile,
+        options: &ApplyOptions,
+    ) -> io::Result<bool> {
+        let threshold = match options.stream_threshold {
+            // Lines are read back from the backup
+            Some(threshold) if !options.no_backup => threshold,
+            _ => return Ok(false),
+        };
+        if fs.metadata(&params.path)?.len < threshold
+            || options.line_endings.contains_key(&params.path)
+            || params.to.contains('\n')
+            || !is_line_bounded(&params.from)
+        {
+            return Ok(false);
+        }
+        let mut file = fs.open(&params.path)?;
+        Ok(matches!(
+            Encoding::detect(file.fill_buf()?),
+            Encoding::Utf8 | Encoding::Utf8Bom
+        ))
+    }
 
-        let backup = Change::backup_file(path, backup_dir)?;
-        let target = path.clone();
+    /// Replace in a file a line at a time, reading it back from its backup
+    /// rather than into memory.
+    fn stream_replace_in_file(
+        fs: &impl Fs,
+        params: &ReplaceInFile,
+        backup_dir: &Path,
+        options: &ApplyOptions,
+    ) -> io::Result<RevertAction> {
+        let target = params.path.clone();
+        let metadata = fs.metadata(&target)?;
+        let backup_checksum = checksum_in(fs, &target)?;
+        let backup = Change::store_backup(fs, backup_dir, &backup_checksum, |temp| {
+            fs.copy(&target, temp)
+        })?;
+        let regex = Regex::new(&params.from).map_err(io::Error::other)?;
 
-        let read_opts = ParseOption {
-            enabled_escape: false,
-            enabled_quote: false,
-        };
-        let mut ini = match Ini::load_from_file_opt(&target, read_opts) {
-            Ok(ini) => ini,
-            Err(err) => match err {
-                ini::ini::Error::Io(io) => return Err(io),
-                ini::ini::Error::Parse(p) => return Err(io::Error::new(io::ErrorKind::Other, p)),
-            },
+        let writable = !metadata.readonly || options.make_writable;
+        if metadata.readonly && writable {
+            fs.set_permissions(&target, &metadata.writable())?;
+        }
+        let written = fs.write_with(&target, &mut |output| {
+            replace_lines(&mut *fs.open(&backup)?, output, &regex, &params.to)
+        });
+        if let Err(err) = written {
+            // The file may have been partly written
+            if writable {
+                fs.copy(&backup, &target)?;
+                fs.set_permissions(&target, &metadata)?;
+            }
+            return Err(err);
+        }
+        Change::restore_metadata(fs, &target, &metadata, options)?;
+        let checksum = Some(checksum_in(fs, &target)?);
+
+        Ok(Backup::File(backup, backup_checksum).into_revert_action(target, checksum))
+    }
+
+    fn edit_replace(params: &ReplaceInFile, file: &TextFile) -> io::Result<String> {
+        let regex = Regex::new(&params.from).map_err(io::Error::other)?;
+        let replacement = match params.to.contains('\n') {
+            true => file.line_ending.apply(&params.to),
+            false => params.to.clone(),
         };
-        ini.with_section(Some(section)).set(key, value);
-        ini.write_to_file(&target)?;
+        Ok(regex
+            .replace_all(&file.content, replacement.as_str())
+            .to_string())
+    }
 
-        Ok(Box::new(move || {
-            std::fs::copy(&backup, &target).map(|_| ())
-        }))
+    fn set_ini_entry(
+        fs: &impl Fs,
+        params: &SetIniEntry,
+        backup_dir: &Path,
+        options: &ApplyOptions,
+    ) -> io::Result<RevertAction> {
+        let backup = Change::backup_file(fs, &params.path, backup_dir, options)?;
+        let target = params.path.clone();
+        let metadata = fs.metadata(&target)?;
+
+        let mut file = TextFile::decode(&fs.read(&target)?)?;
+        file.content = Change::edit_set_ini(params, &file)?;
+        Change::normalize_line_endings(&mut file, &target, options);
+        Change::write_file(fs, &target, &file.encode(), &metadata, options)?;
+        Change::restore_metadata(fs, &target, &metadata, options)?;
+        let checksum = Some(checksum_in(fs, &target)?);
+
+        Ok(backup.into_revert_action(target, checksum))
     }
 
-    fn append_ini_entry(params: &AppendIniEntry, backup_dir: &Path) -> io::Result<Revert> {
+    fn append_ini_entry(
+        fs: &impl Fs,
+        params: &AppendIniEntry,
+        backup_dir: &Path,
+        options: &ApplyOptions,
+    ) -> io::Result<RevertAction> {
+        let backup = Change::backup_file(fs, &params.path, backup_dir, options)?;
+        let target = params.path.clone();
+        let metadata = fs.metadata(&target)?;
+
+        let mut file = TextFile::decode(&fs.read(&target)?)?;
+        file.content = Change::edit_append_ini(params, &file)?;
+        Change::normalize_line_endings(&mut file, &target, options);
+        Change::write_file(fs, &target, &file.encode(), &metadata, options)?;
+        Change::restore_metadata(fs, &target, &metadata, options)?;
+        let checksum = Some(checksum_in(fs, &target)?);
+
+        Ok(backup.into_revert_action(target, checksum))
+    }
+
+    fn edit_set_ini(params: &SetIniEntry, file: &TextFile) -> io::Result<String> {
+        let mut ini = Change::parse_ini(file)?;
+        ini.with_section(Some(&params.section))
+            .set(&params.key, &params.value);
+        Change::write_ini(&ini, file)
+    }
+
+    fn edit_append_ini(params: &AppendIniEntry, file: &TextFile) -> io::Result<String> {
         let AppendIniEntry {
             section,
             key,
             value,
-            path,
+            ..
         } = params;
 
-        let backup = Change::backup_file(path, backup_dir)?;
-        let target = path.clone();
+        let mut ini = Change::parse_ini(file)?;
+        // Entries added by an earlier run, e.g. redirects, are not duplicated
+        if ini
+            .section(Some(section))
+            .is_some_and(|properties| properties.get_all(key).any(|other| other == value))
+        {
+            return Ok(file.content.clone());
+        }
+        ini.with_section(Some(section)).set("dummy", "dummy"); // create if does not exist
+        ini.section_mut(Some(section)).unwrap().append(key, value);
+        ini.with_section(Some(section)).delete(&"dummy");
+        Change::write_ini(&ini, file)
+    }
 
+    fn parse_ini(file: &TextFile) -> io::Result<Ini> {
         let read_opts = ParseOption {
             enabled_escape: false,
             enabled_quote: false,
         };
-        let mut ini = match Ini::load_from_file_opt(&target, read_opts) {
-            Ok(ini) => ini,
-            Err(err) => match err {
-                ini::ini::Error::Io(io) => return Err(io),
-                ini::ini::Error::Parse(p) => return Err(io::Error::new(io::ErrorKind::Other, p)),
-            },
-        };
-        ini.with_section(Some(section)).set("dummy", "dummy"); // create if does not exist
-        ini.section_mut(Some(section)).unwrap().append(key, value);
-        ini.with_section(Some(section)).delete(&"dummy");
-        ini.write_to_file(&params.path)?;
+        Ini::load_from_str_opt(&file.content, read_opts).map_err(io::Error::other)
+    }
 
-        Ok(Box::new(move || {
-            std::fs::copy(&backup, &target).map(|_| ())
-        }))
+    /// Write config back in the line endings of the file it was parsed from,
+    /// rather than those of the platform.
+    fn write_ini(ini: &Ini, file: &TextFile) -> io::Result<String> {
+        let mut content = vec![];
+        ini.write_to(&mut content)?;
+        let content = String::from_utf8(content).map_err(io::Error::other)?;
+        Ok(file.line_ending.apply(&content))
     }
 
-    fn backup_file(file: &Path, backup_dir: &Path) -> io::Result<PathBuf> {
-        let content = std::fs::read_to_string(file)?;
-        let hash = Sha256::digest(&content);
-        let path = backup_dir.join(format!("{:x}", hash));
-        std::fs::write(&path, &content)?;
+    /// Convert the line endings of an edited file if the options call for
+    /// specific ones.
+    fn normalize_line_endings(file: &mut TextFile, target: &Path, options: &ApplyOptions) {
+        if let Some(line_ending) = options.line_endings.get(target) {
+            file.content = line_ending.apply(&file.content);
+            file.line_ending = *line_ending;
+        }
+    }
+
+    /// Restore the permissions (and optionally the modification time) that a
+    /// file had before it was edited, e.g. the executable bit of a script.
+    fn restore_metadata(
+        fs: &impl Fs,
+        target: &Path,
+        metadata: &Metadata,
+        options: &ApplyOptions,
+    ) -> io::Result<()> {
+        if let Some(modified) = metadata.modified.filter(|_| options.preserve_mtime) {
+            // Setting the time needs write access
+            if metadata.readonly {
+                fs.set_permissions(target, &metadata.writable())?;
+            }
+            fs.set_modified(target, modified)?;
+        }
+        fs.set_permissions(target, metadata)
+    }
+
+    /// Write an edited file, through its read-only flag if the options call
+    /// for it, see [`ApplyOptions::make_writable`].
+    fn write_file(
+        fs: &impl Fs,
+        target: &Path,
+        content: &[u8],
+        metadata: &Metadata,
+        options: &ApplyOptions,
+    ) -> io::Result<()> {
+        match metadata.readonly && options.make_writable {
+            true => force_write(fs, target, content),
+            false => fs.write(target, content),
+        }
+    }
+
+    /// Back up a file before it is edited, to the backup directory or to
+    /// memory if backups are skipped.
+    fn backup_file(
+        fs: &impl Fs,
+        file: &Path,
+        backup_dir: &Path,
+        options: &ApplyOptions,
+    ) -> io::Result<Backup> {
+        let content = fs.read(file)?;
+        if options.no_backup {
+            return Ok(Backup::Memory(content));
+        }
+        let hash = format!("{:x}", Sha256::digest(&content));
+        let path = Change::store_backup(fs, backup_dir, &hash, |temp| fs.write(temp, &content))?;
+        Ok(Backup::File(path, hash))
+    }
+
+    /// Store the backup of a file with the given checksum in the backup
+    /// directory, written to a temporary path by `write`, and get its path.
+    fn store_backup(
+        fs: &impl Fs,
+        backup_dir: &Path,
+        hash: &str,
+        write: impl FnOnce(&Path) -> io::Result<()>,
+    ) -> io::Result<PathBuf> {
+        let path = backup_dir.join(hash);
+        // Files with the same content share a backup, which may be written by
+        // several threads at once, so it is moved into place whole. A backup
+        // that was corrupted since is replaced.
+        if checksum_in(fs, &path).ok().as_deref() != Some(hash) {
+            let temp = backup_dir.join(format!("{}.{}.tmp", hash, ulid::Ulid::new()));
+            write(&temp)?;
+            fs.rename(&temp, &path)?;
+        }
         Ok(path)
     }
 }
 
+/// The content of a file before it was edited.
+enum Backup {
+    /// A copy of the file in the backup directory, along with its checksum.
+    File(PathBuf, String),
+    /// The content itself, when backups are skipped.
+    Memory(Vec<u8>),
+}
+
+impl Backup {
+    fn into_revert_action(self, target: PathBuf, checksum: Option<String>) -> RevertAction {
+        match self {
+            Backup::File(backup, backup_checksum) => RevertAction::Restore {
+                backup,
+                target,
+                checksum,
+                backup_checksum: Some(backup_checksum),
+            },
+            Backup::Memory(content) => RevertAction::Rewrite {
+                content,
+                target,
+                checksum,
+            },
+        }
+    }
+}
+
 impl Display for Change {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &*self {
+        match self {
             Change::RenameFile(params) => write!(f, "{}", &params),
             Change::ReplaceInFile(params) => write!(f, "{}", &params),
             Change::SetIniEntry(params) => write!(f, "{}", &params),
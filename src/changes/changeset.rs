@@ -0,0 +1,221 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::RenomError;
+
+use super::{find_conflicts, Change};
+
+/// The kind of file touched by a change, used to group changes for preview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// Project and plugin descriptors (`.uproject`, `.uplugin`).
+    Descriptor,
+    /// Config files (`.ini`).
+    Config,
+    /// Build and target rules (`.Build.cs`, `.Target.cs`).
+    Build,
+    /// C++ and C# source files.
+    Source,
+    /// Scripts that may invoke build tools, such as RunUAT (`.bat`, `.sh`).
+    Script,
+    /// Directories.
+    Directory,
+    /// Anything else.
+    Other,
+}
+
+impl Category {
+    /// Determine the category of a path. Directories are detected on disk, so
+    /// this should be called before the changeset is applied.
+    pub fn of(path: &Path) -> Self {
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        if file_name.ends_with(".Build.cs") || file_name.ends_with(".Target.cs") {
+            return Category::Build;
+        }
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("uproject" | "uplugin") => Category::Descriptor,
+            Some("ini") => Category::Config,
+            Some("h" | "hpp" | "inl" | "c" | "cpp" | "cs") => Category::Source,
+            Some("bat" | "cmd" | "sh" | "ps1" | "command") => Category::Script,
+            _ if path.is_dir() => Category::Directory,
+            _ => Category::Other,
+        }
+    }
+
+    /// A short singular name for the category, used to tag files.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Category::Descriptor => "descriptor",
+            Category::Config => "config",
+            Category::Build => "build",
+            Category::Source => "source",
+            Category::Script => "script",
+            Category::Directory => "directory",
+            Category::Other => "other",
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Category::Descriptor => "descriptors",
+            Category::Config => "config files",
+            Category::Build => "build files",
+            Category::Source => "source files",
+            Category::Script => "scripts",
+            Category::Directory => "directories",
+            Category::Other => "other files",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An ordered set of changes produced by a workflow. It is serialized as the
+/// list of its changes, in the format that plans keep them in, so that it can
+/// be saved, compared, attached to bug reports, or read by other tools.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Changeset {
+    changes: Vec<Change>,
+}
+
+impl Changeset {
+    pub fn new(changes: Vec<Change>) -> Self {
+        Self { changes }
+    }
+
+    /// The changes in the order they will be applied.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    pub fn into_changes(self) -> Vec<Change> {
+        self.changes
+    }
+
+    /// Serialize the changeset to pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, RenomError> {
+        serde_json::to_string_pretty(self).map_err(|err| RenomError::Parse(err.to_string()))
+    }
+
+    /// Deserialize a changeset from JSON written by [`Changeset::to_json`].
+    pub fn from_json(json: &str) -> Result<Self, RenomError> {
+        serde_json::from_str(json).map_err(|err| RenomError::Parse(err.to_string()))
+    }
+
+    /// The files and directories touched by the changeset, grouped by
+    /// category. Each path is listed once, in order of first appearance.
+    pub fn files_by_category(&self) -> BTreeMap<Category, Vec<PathBuf>> {
+        let mut files: BTreeMap<Category, Vec<PathBuf>> = BTreeMap::new();
+        for change in &self.changes {
+            let path = change.path();
+            let paths = files.entry(Category::of(path)).or_default();
+            if !paths.iter().any(|existing| existing == path) {
+                paths.push(path.to_owned());
+            }
+        }
+        files
+    }
+
+    /// Check invariants that every generated changeset should satisfy:
+    /// - no two changes conflict, e.g. two renames to the same destination;
+    /// - config changes only target config files;
+    /// - no change targets a path that an earlier change has renamed away.
+    ///
+    /// Returns a description of every violation found, see
+    /// [`Changeset::violations`].
+    pub fn validate(&self) -> Result<(), RenomError> {
+        let violations = self.violations();
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(RenomError::Validation(
+                violations
+                    .iter()
+                    .map(|violation| violation.description.as_str())
+                    .collect::<Vec<&str>>()
+                    .join("\n"),
+            )),
+        }
+    }
+
+    /// Find every violation of the invariants checked by
+    /// [`Changeset::validate`], along with the changes involved.
+    pub fn violations(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+
+        for conflict in find_conflicts(&self.changes) {
+            violations.push(Violation {
+                description: format!(
+                    "changes {:?} conflict: {}",
+                    conflict.changes, conflict.reason
+                ),
+                changes: conflict.changes,
+            });
+        }
+
+        for (index, change) in self.changes.iter().enumerate() {
+            if let Change::SetIniEntry(_) | Change::AppendIniEntry(_) = change {
+                if Category::of(change.path()) != Category::Config {
+                    violations.push(Violation {
+                        changes: vec![index],
+                        description: format!(
+                            "change {} edits config in {}, which is not a config file",
+                            index,
+                            change.path().display()
+                        ),
+                    });
+                }
+            }
+
+            let renamed_away =
+                self.changes[..index]
+                    .iter()
+                    .enumerate()
+                    .find_map(|(earlier, other)| match other {
+                        Change::RenameFile(rename) if change.path().starts_with(&rename.from) => {
+                            Some(earlier)
+                        }
+                        _ => None,
+                    });
+            if let Some(earlier) = renamed_away {
+                violations.push(Violation {
+                    changes: vec![earlier, index],
+                    description: format!(
+                        "change {} targets {}, which change {} has already renamed",
+                        index,
+                        change.path().display(),
+                        earlier
+                    ),
+                });
+            }
+        }
+
+        violations
+    }
+}
+
+/// A violation of the invariants of a changeset, found by
+/// [`Changeset::violations`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct Violation {
+    /// Indices of the changes involved in the changeset.
+    pub changes: Vec<usize>,
+    /// What is wrong.
+    pub description: String,
+}
+
+impl From<Vec<Change>> for Changeset {
+    fn from(changes: Vec<Change>) -> Self {
+        Self::new(changes)
+    }
+}
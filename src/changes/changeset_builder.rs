@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use super::{AppendIniEntry, Change, Changeset, RenameFile, ReplaceInFile, SetIniEntry};
+
+/// Compose a custom changeset one change at a time, to be applied with the
+/// same backup and revert machinery as the built-in workflows.
+///
+/// ```
+/// use renom::changes::ChangesetBuilder;
+///
+/// let changeset = ChangesetBuilder::new()
+///     .replace_in_file("Source/Old/Old.Build.cs", "Old", "New")
+///     .rename_file("Source/Old/Old.Build.cs", "Source/Old/New.Build.cs")
+///     .set_ini_key("Config/DefaultEngine.ini", "URL", "GameName", "New")
+///     .build();
+/// assert_eq!(changeset.changes().len(), 3);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ChangesetBuilder {
+    changes: Vec<Change>,
+}
+
+impl ChangesetBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rename a file or directory.
+    pub fn rename_file(self, from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Self {
+        self.change(Change::RenameFile(RenameFile::new(from, to)))
+    }
+
+    /// Replace all matches of a regex in a file.
+    pub fn replace_in_file(
+        self,
+        path: impl Into<PathBuf>,
+        from: impl Into<String>,
+        to: impl Into<String>,
+    ) -> Self {
+        self.change(Change::ReplaceInFile(ReplaceInFile::new(path, from, to)))
+    }
+
+    /// Set a key in a section of a config file, replacing any existing value.
+    pub fn set_ini_key(
+        self,
+        path: impl Into<PathBuf>,
+        section: impl Into<String>,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.change(Change::SetIniEntry(SetIniEntry::new(
+            path, section, key, value,
+        )))
+    }
+
+    /// Append an entry to a section of a config file, keeping existing
+    /// entries with the same key.
+    pub fn append_ini_entry(
+        self,
+        path: impl Into<PathBuf>,
+        section: impl Into<String>,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.change(Change::AppendIniEntry(AppendIniEntry::new(
+            path, section, key, value,
+        )))
+    }
+
+    /// Add an arbitrary change.
+    pub fn change(mut self, change: Change) -> Self {
+        self.changes.push(change);
+        self
+    }
+
+    pub fn build(self) -> Changeset {
+        Changeset::new(self.changes)
+    }
+}
@@ -1,11 +1,28 @@
 mod append_ini_entry;
+mod apply_options;
 mod change;
+mod changeset;
+mod changeset_builder;
+mod normalize;
 mod rename_file;
 mod replace_in_file;
+mod revert_action;
 mod set_ini_entry;
+mod simulation;
+mod snapshot;
+mod streaming;
+mod verification;
 
 pub use append_ini_entry::*;
+pub use apply_options::*;
 pub use change::*;
+pub use changeset::*;
+pub use changeset_builder::*;
+pub use normalize::*;
 pub use rename_file::*;
 pub use replace_in_file::*;
+pub use revert_action::*;
 pub use set_ini_entry::*;
+pub use simulation::*;
+pub use streaming::*;
+pub use verification::*;
@@ -0,0 +1,88 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Change, RenameFile, SetIniEntry};
+
+/// A set of changes that cannot all be applied together, e.g. two renames of
+/// the same file to different destinations.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Conflict {
+    /// Indices of the conflicting changes in the changeset.
+    pub changes: Vec<usize>,
+    /// Why the changes conflict.
+    pub reason: String,
+}
+
+/// Remove exact duplicate changes from a changeset, keeping the first
+/// occurrence of each.
+pub fn dedup(changeset: Vec<Change>) -> Vec<Change> {
+    let mut unique: Vec<Change> = vec![];
+    for change in changeset {
+        if !unique.contains(&change) {
+            unique.push(change);
+        }
+    }
+    unique
+}
+
+/// Detect changes in a changeset that conflict with each other. Assumes that
+/// the changeset contains no exact duplicates.
+pub fn find_conflicts(changeset: &[Change]) -> Vec<Conflict> {
+    let mut renames_by_source: BTreeMap<&PathBuf, Vec<usize>> = BTreeMap::new();
+    let mut renames_by_destination: BTreeMap<&PathBuf, Vec<usize>> = BTreeMap::new();
+    let mut sets_by_key: BTreeMap<(&PathBuf, &str, &str), Vec<usize>> = BTreeMap::new();
+
+    for (index, change) in changeset.iter().enumerate() {
+        match change {
+            Change::RenameFile(RenameFile { from, to }) => {
+                renames_by_source.entry(from).or_default().push(index);
+                renames_by_destination.entry(to).or_default().push(index);
+            }
+            Change::SetIniEntry(SetIniEntry {
+                path, section, key, ..
+            }) => {
+                sets_by_key
+                    .entry((path, section, key))
+                    .or_default()
+                    .push(index);
+            }
+            _ => {}
+        }
+    }
+
+    let mut conflicts = vec![];
+    conflicts.extend(
+        renames_by_source
+            .into_iter()
+            .filter(|(_, changes)| changes.len() > 1)
+            .map(|(from, changes)| Conflict {
+                changes,
+                reason: format!("{} is renamed to different destinations", from.display()),
+            }),
+    );
+    conflicts.extend(
+        renames_by_destination
+            .into_iter()
+            .filter(|(_, changes)| changes.len() > 1)
+            .map(|(to, changes)| Conflict {
+                changes,
+                reason: format!("different files are renamed to {}", to.display()),
+            }),
+    );
+    conflicts.extend(
+        sets_by_key
+            .into_iter()
+            .filter(|(_, changes)| changes.len() > 1)
+            .map(|((path, section, key), changes)| Conflict {
+                changes,
+                reason: format!(
+                    "[{}] {} is set to different values in {}",
+                    section,
+                    key,
+                    path.display()
+                ),
+            }),
+    );
+    conflicts
+}
@@ -1,8 +1,16 @@
-use std::{fmt::Display, path::PathBuf};
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+};
 
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
 
-#[derive(Debug, PartialEq)]
+use crate::fs::Fs;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct RenameFile {
     pub from: PathBuf,
     pub to: PathBuf,
@@ -15,6 +23,44 @@ impl RenameFile {
             to: to.into(),
         }
     }
+
+    /// Check whether the rename only changes the case of the path, e.g.
+    /// `MyGame` to `MYGAME`.
+    pub fn is_case_only(&self) -> bool {
+        is_case_only(&self.from, &self.to)
+    }
+}
+
+fn is_case_only(from: &Path, to: &Path) -> bool {
+    from != to && from.to_string_lossy().to_lowercase() == to.to_string_lossy().to_lowercase()
+}
+
+/// Check whether two paths lead to the same file or directory on disk, as
+/// paths that only differ in case do on a case-insensitive filesystem.
+pub fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Move a file or directory. A move that only changes the case of the path
+/// goes through a temporary name, as case-insensitive filesystems take the
+/// source and the destination for the same file.
+pub fn rename_path(fs: &impl Fs, from: &Path, to: &Path) -> io::Result<()> {
+    if !is_case_only(from, to) {
+        return fs.rename(from, to);
+    }
+    let name = from
+        .file_name()
+        .unwrap_or(from.as_os_str())
+        .to_string_lossy();
+    let temp = from.with_file_name(format!("{}.{}.tmp", name, Ulid::new()));
+    fs.rename(from, &temp)?;
+    fs.rename(&temp, to).inspect_err(|_| {
+        // Leave the file where it was rather than under the temporary name
+        let _ = fs.rename(&temp, from);
+    })
 }
 
 impl Display for RenameFile {
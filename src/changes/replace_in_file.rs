@@ -1,12 +1,17 @@
 use std::{fmt::Display, path::PathBuf};
 
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ReplaceInFile {
     pub path: PathBuf,
     pub from: String,
     pub to: String,
+    /// Whether the change edits names in string literals in code, e.g. names
+    /// passed to engine APIs, which are flagged for a closer look.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub in_literals: bool,
 }
 
 impl ReplaceInFile {
@@ -15,17 +20,28 @@ impl ReplaceInFile {
             path: path.into(),
             from: from.into(),
             to: to.into(),
+            in_literals: false,
         }
     }
+
+    /// Flag the change as editing names in string literals in code.
+    pub fn in_literals(mut self) -> Self {
+        self.in_literals = true;
+        self
+    }
 }
 
 impl Display for ReplaceInFile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "replace {} with {} in file {}",
+            "replace {} with {} in {}file {}",
             &self.from.dimmed(),
             &self.to.dimmed(),
+            match self.in_literals {
+                true => "string literals in ",
+                false => "",
+            },
             &self
                 .path
                 .to_str()
@@ -0,0 +1,162 @@
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::fs::{force_write, Fs, RealFs};
+
+use super::{rename_file::rename_path, Revert};
+
+/// How to undo an applied change. Unlike a [`Revert`] closure, this can be
+/// persisted and performed later, possibly by another process.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RevertAction {
+    /// Move a renamed file or directory back to where it was.
+    Rename { from: PathBuf, to: PathBuf },
+    /// Restore the content of an edited file from its backup.
+    Restore {
+        backup: PathBuf,
+        target: PathBuf,
+        /// Checksum of the file right after it was edited, to tell whether
+        /// it was edited again since.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        checksum: Option<String>,
+        /// Checksum of the backup when it was taken, to tell whether it was
+        /// corrupted since.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        backup_checksum: Option<String>,
+    },
+    /// Write back the content of an edited file, kept in memory when no
+    /// backups are taken. It is lost if the process exits, so it is not
+    /// journaled.
+    Rewrite {
+        content: Vec<u8>,
+        target: PathBuf,
+        /// Checksum of the file right after it was edited, to tell whether
+        /// it was edited again since.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        checksum: Option<String>,
+    },
+}
+
+impl RevertAction {
+    /// Perform the revert.
+    pub fn run(&self) -> io::Result<()> {
+        self.run_in(&RealFs)
+    }
+
+    /// Perform the revert on the filesystem the change was applied to. Files
+    /// left read-only after their edit are restored through their flag.
+    pub fn run_in(&self, fs: &impl Fs) -> io::Result<()> {
+        match self {
+            RevertAction::Rename { from, to } => rename_path(fs, to, from),
+            RevertAction::Restore {
+                backup,
+                target,
+                backup_checksum,
+                ..
+            } => {
+                let content = fs.read(backup)?;
+                verify_backup(backup, &content, backup_checksum.as_deref())?;
+                force_write(fs, target, &content)
+            }
+            RevertAction::Rewrite {
+                content, target, ..
+            } => force_write(fs, target, content),
+        }
+    }
+
+    /// Check whether the file to restore was edited or removed since the
+    /// change was applied, e.g. by hand after a failed rename, in which case
+    /// restoring it would clobber those edits.
+    pub fn is_modified(&self) -> io::Result<bool> {
+        self.is_modified_in(&RealFs)
+    }
+
+    /// Check whether the file to restore was edited or removed since, on the
+    /// filesystem the change was applied to, see [`RevertAction::is_modified`].
+    pub fn is_modified_in(&self, fs: &impl Fs) -> io::Result<bool> {
+        match self {
+            RevertAction::Restore {
+                target,
+                checksum: Some(checksum),
+                ..
+            }
+            | RevertAction::Rewrite {
+                target,
+                checksum: Some(checksum),
+                ..
+            } => match checksum_in(fs, target) {
+                Ok(current) => Ok(&current != checksum),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(true),
+                Err(err) => Err(err),
+            },
+            _ => Ok(false),
+        }
+    }
+
+    pub fn into_revert(self) -> Revert {
+        Box::new(move || self.run())
+    }
+}
+
+impl Display for RevertAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RevertAction::Rename { from, to } => write!(
+                f,
+                "rename {} back to {}",
+                to.to_str().unwrap_or("invalid Unicode path").dimmed(),
+                from.to_str().unwrap_or("invalid Unicode path").dimmed()
+            ),
+            RevertAction::Restore { backup, target, .. } => write!(
+                f,
+                "restore file {} from backup {}",
+                target.to_str().unwrap_or("invalid Unicode path").dimmed(),
+                backup.to_str().unwrap_or("invalid Unicode path").dimmed()
+            ),
+            RevertAction::Rewrite { target, .. } => write!(
+                f,
+                "restore file {} from memory",
+                target.to_str().unwrap_or("invalid Unicode path").dimmed()
+            ),
+        }
+    }
+}
+
+/// Check that a backup still has the checksum it had when it was taken, so
+/// that a corrupted or tampered backup is not restored over the project.
+/// Backups taken without a checksum are trusted.
+pub fn verify_backup(backup: &Path, content: &[u8], checksum: Option<&str>) -> io::Result<()> {
+    let actual = format!("{:x}", Sha256::digest(content));
+    match checksum {
+        Some(checksum) if actual != checksum => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "backup {} is corrupted, its checksum is {} instead of {}",
+                backup.display(),
+                actual,
+                checksum
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Get the SHA-256 checksum of the content of a file.
+pub fn checksum_of(path: &Path) -> io::Result<String> {
+    checksum_in(&RealFs, path)
+}
+
+/// Get the SHA-256 checksum of the content of a file on a filesystem.
+pub fn checksum_in(fs: &impl Fs, path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs.open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
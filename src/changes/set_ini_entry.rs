@@ -1,8 +1,9 @@
 use std::{fmt::Display, path::PathBuf};
 
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct SetIniEntry {
     pub path: PathBuf,
     pub section: String,
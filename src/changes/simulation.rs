@@ -0,0 +1,279 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{diff::FileDiff, error::RenomError, journal, text::TextFile};
+
+use super::{rename_file::is_same_file, Change};
+
+/// A change that would fail if the changeset were executed, stopping it
+/// midway so that the changes applied before it have to be reverted.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RevertIssue {
+    /// The position of the change in the changeset.
+    pub index: usize,
+    /// The change itself.
+    pub change: Change,
+    /// What would go wrong.
+    pub problem: String,
+}
+
+/// Why a change could not be applied, as found by [`simulate_execution`].
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// The file or directory to rename or edit would not exist.
+    SourceMissing,
+    /// The destination of a rename would already exist.
+    DestinationExists,
+    /// The file to edit, or the directory of the file to rename, is
+    /// read-only.
+    NotWritable,
+    /// The path would be longer than the platform allows.
+    PathTooLong,
+}
+
+/// A change that would fail if the changeset were executed, because of the
+/// state of the files on disk.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ExecutionConflict {
+    /// The position of the change in the changeset.
+    pub index: usize,
+    /// The change itself.
+    pub change: Change,
+    /// What would go wrong.
+    pub kind: ConflictKind,
+    /// The path at fault.
+    pub path: PathBuf,
+}
+
+/// The longest path that can be opened on this platform, in bytes. Paths
+/// are extended on Windows to reach past `MAX_PATH`, see [`long_path`].
+///
+/// [`long_path`]: crate::fs::long_path
+pub const MAX_PATH_LEN: usize = if cfg!(windows) { 32_766 } else { 4095 };
+
+/// The longest file name that can be created on this platform, in bytes.
+pub const MAX_NAME_LEN: usize = 255;
+
+/// Walk through a changeset in order without touching the disk, accounting
+/// for earlier renames, and find every change that would fail because of the
+/// files on disk: a file to change that would be missing, a rename onto a file
+/// that would already exist, a file that cannot be written, or a path that
+/// would be too long. Changes after a conflict are checked as if it were
+/// resolved.
+pub fn simulate_execution(changeset: &[Change]) -> Vec<ExecutionConflict> {
+    let mut simulation = Simulation::default();
+    let mut conflicts = vec![];
+    for (index, change) in changeset.iter().enumerate() {
+        if let Some((kind, path)) = simulation.check(change) {
+            conflicts.push(ExecutionConflict {
+                index,
+                change: change.clone(),
+                kind,
+                path,
+            });
+        }
+        if let Change::RenameFile(params) = change {
+            simulation
+                .renames
+                .push((params.from.clone(), params.to.clone()));
+        }
+    }
+    conflicts
+}
+
+/// Walk through a changeset in order without touching the disk, accounting
+/// for earlier renames and edits, and get the content of every file it edits
+/// before and after, in order of their path. Files whose edits change nothing
+/// are left out.
+/// Fails if a change could not be applied.
+pub fn simulate_edits(changeset: &[Change]) -> Result<Vec<FileDiff>, RenomError> {
+    let mut simulation = Simulation::default();
+    for change in changeset {
+        simulation
+            .apply(change)
+            .map_err(|err| err.context(change))?;
+    }
+    let mut originals = simulation.originals;
+    Ok(simulation
+        .contents
+        .into_iter()
+        .filter_map(|(path, file)| {
+            let before = originals.remove(&path)?;
+            (before != file.content).then_some(FileDiff {
+                path,
+                before,
+                after: file.content,
+            })
+        })
+        .collect())
+}
+
+/// Walk through a changeset in order without touching the disk, accounting
+/// for earlier renames and edits, and check that every change can be applied.
+/// A change that would fail stops execution midway, so it is reported as the
+/// project could not be fully reverted afterwards.
+pub fn simulate_revert(changeset: &[Change]) -> Vec<RevertIssue> {
+    let mut simulation = Simulation::default();
+    let mut issues = vec![];
+    for (index, change) in changeset.iter().enumerate() {
+        if let Err(err) = simulation.apply(change) {
+            issues.push(RevertIssue {
+                index,
+                change: change.clone(),
+                problem: err.to_string(),
+            });
+        }
+    }
+    issues
+}
+
+/// The effect of the changes simulated so far.
+#[derive(Default)]
+struct Simulation {
+    /// Renames in the order they were applied.
+    renames: Vec<(PathBuf, PathBuf)>,
+    /// The edited content of files, by their path before any renames.
+    contents: BTreeMap<PathBuf, TextFile>,
+    /// The content of edited files before their first edit, by their path
+    /// before any renames.
+    originals: BTreeMap<PathBuf, String>,
+}
+
+impl Simulation {
+    fn apply(&mut self, change: &Change) -> Result<(), RenomError> {
+        match change {
+            Change::RenameFile(params) => {
+                if !self.exists(&params.from) {
+                    return Err(RenomError::Validation(format!(
+                        "{} would not exist",
+                        params.from.display()
+                    )));
+                }
+                if self.is_taken(&params.from, &params.to) {
+                    return Err(RenomError::Validation(format!(
+                        "{} would already exist",
+                        params.to.display()
+                    )));
+                }
+                self.renames.push((params.from.clone(), params.to.clone()));
+                Ok(())
+            }
+            _ => {
+                let original = self.original(change.path()).ok_or_else(|| {
+                    RenomError::Validation(format!("{} would not exist", change.path().display()))
+                })?;
+                let mut file = match self.contents.remove(&original) {
+                    Some(file) => file,
+                    None => {
+                        let file = TextFile::read(&original)?;
+                        self.originals
+                            .insert(original.clone(), file.content.clone());
+                        file
+                    }
+                };
+                if let Some(content) = change.edit(&file)? {
+                    file.content = content;
+                }
+                self.contents.insert(original, file);
+                Ok(())
+            }
+        }
+    }
+
+    /// Find what would keep a change from being applied after the renames so
+    /// far, if anything.
+    fn check(&self, change: &Change) -> Option<(ConflictKind, PathBuf)> {
+        let source = change.path();
+        let original = match self.original(source).filter(|path| path.exists()) {
+            Some(original) => original,
+            None => return Some((ConflictKind::SourceMissing, source.to_owned())),
+        };
+        let guarded = match change {
+            Change::RenameFile(params) => {
+                if self.is_taken(&params.from, &params.to) {
+                    return Some((ConflictKind::DestinationExists, params.to.clone()));
+                }
+                if is_too_long(&params.to) {
+                    return Some((ConflictKind::PathTooLong, params.to.clone()));
+                }
+                // Renaming needs the directory to be writable, but only on Unix
+                original.parent().filter(|_| cfg!(unix)).map(Path::to_owned)
+            }
+            _ => Some(original),
+        };
+        guarded
+            .filter(|path| is_read_only(path))
+            .map(|path| (ConflictKind::NotWritable, path))
+    }
+
+    /// Get the path that a file or directory had before the renames so far,
+    /// or `None` if it was moved away.
+    fn original(&self, path: &Path) -> Option<PathBuf> {
+        let mut path = path.to_owned();
+        for (from, to) in self.renames.iter().rev() {
+            if let Some(moved) = journal::follow_rename(&path, to, from) {
+                path = moved;
+            } else if path.starts_with(from) {
+                return None;
+            }
+        }
+        Some(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.original(path).is_some_and(|path| path.exists())
+    }
+
+    /// Check whether the destination of a rename would already exist as
+    /// another file than the source. Renames that only change case are taken
+    /// for the same file on case-insensitive filesystems.
+    fn is_taken(&self, from: &Path, to: &Path) -> bool {
+        let same_file = match (self.original(from), self.original(to)) {
+            (Some(from), Some(to)) => is_same_file(&from, &to),
+            _ => false,
+        };
+        self.exists(to) && !same_file
+    }
+}
+
+fn is_too_long(path: &Path) -> bool {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_owned());
+    path.as_os_str().len() > MAX_PATH_LEN
+        || path
+            .file_name()
+            .is_some_and(|name| name.len() > MAX_NAME_LEN)
+}
+
+fn is_read_only(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|metadata| metadata.permissions().readonly())
+}
+
+impl Display for ConflictKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConflictKind::SourceMissing => write!(f, "would not exist"),
+            ConflictKind::DestinationExists => write!(f, "would already exist"),
+            ConflictKind::NotWritable => write!(f, "is read-only"),
+            ConflictKind::PathTooLong => write!(f, "would be too long"),
+        }
+    }
+}
+
+impl Display for ExecutionConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({} {})", self.change, self.path.display(), self.kind)
+    }
+}
+
+impl Display for RevertIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.change, self.problem)
+    }
+}
@@ -0,0 +1,49 @@
+use std::path::{Path, PathBuf};
+
+use super::{Change, Changeset};
+
+impl Changeset {
+    /// Render the changeset deterministically, so that its generation can be
+    /// snapshot-tested across versions. Paths are made relative to the
+    /// project root (or to its parent, e.g. when the root itself is renamed)
+    /// and use forward slashes, so the snapshot does not depend on where or
+    /// on which platform it was taken. Changes are rendered as pretty JSON in
+    /// the same format as plans.
+    pub fn snapshot(&self, project_root: &Path) -> String {
+        let changes = self
+            .changes()
+            .iter()
+            .cloned()
+            .map(|change| relativize(change, project_root))
+            .collect::<Vec<Change>>();
+        serde_json::to_string_pretty(&changes).expect("changes should serialize") + "\n"
+    }
+}
+
+fn relativize(mut change: Change, project_root: &Path) -> Change {
+    let relative = |path: &mut PathBuf| {
+        let relative = match (path.strip_prefix(project_root), project_root.parent()) {
+            (Ok(rest), _) => Path::new(".").join(rest),
+            (Err(_), Some(parent)) => match path.strip_prefix(parent) {
+                Ok(rest) => Path::new("..").join(rest),
+                Err(_) => path.clone(),
+            },
+            (Err(_), None) => path.clone(),
+        };
+        let parts = relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<String>>();
+        *path = PathBuf::from(parts.join("/"));
+    };
+    match &mut change {
+        Change::RenameFile(params) => {
+            relative(&mut params.from);
+            relative(&mut params.to);
+        }
+        Change::ReplaceInFile(params) => relative(&mut params.path),
+        Change::SetIniEntry(params) => relative(&mut params.path),
+        Change::AppendIniEntry(params) => relative(&mut params.path),
+    }
+    change
+}
@@ -0,0 +1,76 @@
+use std::{
+    io::{self, BufRead, Write},
+    str,
+};
+
+use regex::Regex;
+use regex_syntax::{
+    hir::{Class, Hir, HirKind, Look},
+    Parser,
+};
+
+use crate::text::Encoding;
+
+/// Check whether a pattern only ever matches within a line, never across a
+/// line break or against the start or end of the whole text, so that a file
+/// can be edited a line at a time with the same result as whole. Patterns
+/// that may match nothing are not, as they also match after the last line
+/// break of a file.
+pub fn is_line_bounded(pattern: &str) -> bool {
+    match Parser::new().parse(pattern) {
+        Ok(hir) => hir.properties().minimum_len() != Some(0) && within_line(&hir),
+        Err(_) => false,
+    }
+}
+
+fn within_line(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Empty => true,
+        HirKind::Literal(literal) => !literal.0.contains(&b'\n'),
+        HirKind::Class(Class::Unicode(class)) => class
+            .iter()
+            .all(|range| !(range.start()..=range.end()).contains(&'\n')),
+        HirKind::Class(Class::Bytes(class)) => class
+            .iter()
+            .all(|range| !(range.start()..=range.end()).contains(&b'\n')),
+        // Word boundaries and line anchors hold the same on a single line,
+        // only the start and end of the whole text do not
+        HirKind::Look(look) => !matches!(look, Look::Start | Look::End),
+        HirKind::Repetition(repetition) => within_line(&repetition.sub),
+        HirKind::Capture(capture) => within_line(&capture.sub),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().all(within_line),
+    }
+}
+
+/// Replace every match of a line-bounded pattern in UTF-8 text a line at a
+/// time, see [`is_line_bounded`]. Line breaks and a byte order mark are
+/// copied as they are.
+pub fn replace_lines(
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+    regex: &Regex,
+    replacement: &str,
+) -> io::Result<()> {
+    let bom = Encoding::Utf8Bom.bom();
+    let mut line = vec![];
+    let mut first = true;
+    while input.read_until(b'\n', &mut line)? > 0 {
+        let start = match first && line.starts_with(bom) {
+            true => bom.len(),
+            false => 0,
+        };
+        let end = line.len() - usize::from(line.ends_with(b"\n"));
+        let text = str::from_utf8(&line[start..end]).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("content is not valid UTF-8 ({})", err),
+            )
+        })?;
+        output.write_all(&line[..start])?;
+        output.write_all(regex.replace_all(text, replacement).as_bytes())?;
+        output.write_all(&line[end..])?;
+        line.clear();
+        first = false;
+    }
+    Ok(())
+}
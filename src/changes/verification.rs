@@ -0,0 +1,223 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{fs::Fs, journal};
+
+use super::{checksum_in, Change, RevertAction};
+
+/// Why an applied change does not hold, as found by [`verify_applied`].
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscrepancyKind {
+    /// The destination of a rename, or an edited file, does not exist.
+    Missing,
+    /// The source of a rename still exists.
+    Leftover,
+    /// An edited file no longer holds the edit, e.g. an occurrence of the old
+    /// name is left to replace.
+    Unedited,
+}
+
+/// An applied change that does not hold once a changeset was executed, e.g.
+/// because another process wrote to the files meanwhile.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Discrepancy {
+    /// The change itself.
+    pub change: Change,
+    /// What is wrong.
+    pub kind: DiscrepancyKind,
+    /// The path at fault, where the file is after the later renames.
+    pub path: PathBuf,
+}
+
+/// Check that applied changes, in the order they were applied along with
+/// how to revert them, hold on a filesystem: every renamed file is at its
+/// destination and gone from its source, and every edited file is as it was
+/// written. An edited file that changed since is still taken to hold the edit
+/// if applying the edit again would leave it as is. Only the last edit to a
+/// file is checked, where the later renames moved it.
+pub fn verify_applied(fs: &impl Fs, applied: &[(Change, RevertAction)]) -> Vec<Discrepancy> {
+    let mut discrepancies = vec![];
+    // Walked backwards, so the renames seen so far are those after a change
+    let mut later_moves: Vec<(&Path, &Path)> = vec![];
+    let mut edited = HashSet::new();
+    for (change, action) in applied.iter().rev() {
+        let follow = |path: &Path| {
+            later_moves
+                .iter()
+                .rev()
+                .fold(path.to_owned(), |path, (from, to)| {
+                    journal::follow_rename(&path, from, to).unwrap_or(path)
+                })
+        };
+        let found = match (change, action) {
+            (Change::RenameFile(params), RevertAction::Rename { from, to }) => {
+                let destination = follow(to);
+                let refilled = later_moves.iter().any(|(_, to)| to.starts_with(from));
+                if fs.metadata(&destination).is_err() {
+                    Some((DiscrepancyKind::Missing, destination))
+                } else if !params.is_case_only() && !refilled && fs.metadata(from).is_ok() {
+                    Some((DiscrepancyKind::Leftover, from.to_owned()))
+                } else {
+                    None
+                }
+            }
+            (
+                _,
+                RevertAction::Restore {
+                    target, checksum, ..
+                }
+                | RevertAction::Rewrite {
+                    target, checksum, ..
+                },
+            ) => {
+                let target = follow(target);
+                match edited.insert(target.clone()) {
+                    true => verify_edit(fs, change, &target, checksum.as_deref()),
+                    false => None,
+                }
+            }
+            (_, RevertAction::Rename { .. }) => None,
+        };
+        if let Some((kind, path)) = found {
+            discrepancies.push(Discrepancy {
+                change: change.clone(),
+                kind,
+                path,
+            });
+        }
+        if let RevertAction::Rename { from, to } = action {
+            later_moves.push((from, to));
+        }
+    }
+    discrepancies.reverse();
+    discrepancies
+}
+
+/// Find the changes of a changeset whose effect is already there, e.g.
+/// because an earlier run that partly succeeded applied them: renames whose
+/// source is gone and whose destination exists, and edits that an earlier run
+/// recorded as applied, see [`find_recorded_edits`]. Paths are followed
+/// through the later renames found to be applied. Case-only renames are never
+/// taken as applied.
+///
+/// Edits are not taken as applied from the content of their file, since an
+/// edit whose replacement contains what it replaces, e.g. `Demo` to `XDemo`,
+/// would still match once applied.
+pub fn find_applied(
+    fs: &impl Fs,
+    changeset: &[Change],
+    earlier: &[(Change, RevertAction)],
+) -> BTreeSet<usize> {
+    let recorded = find_recorded_edits(fs, earlier);
+    let mut applied = BTreeSet::new();
+    // Walked backwards, so the renames seen so far are those after a change
+    let mut later_moves: Vec<(&Path, &Path)> = vec![];
+    for (index, change) in changeset.iter().enumerate().rev() {
+        let follow = |path: &Path| {
+            later_moves
+                .iter()
+                .rev()
+                .fold(path.to_owned(), |path, (from, to)| {
+                    journal::follow_rename(&path, from, to).unwrap_or(path)
+                })
+        };
+        let found = match change {
+            Change::RenameFile(params) => {
+                !params.is_case_only()
+                    && fs.metadata(&follow(&params.from)).is_err()
+                    && fs.metadata(&follow(&params.to)).is_ok()
+            }
+            _ => recorded.contains(&change),
+        };
+        if !found {
+            continue;
+        }
+        applied.insert(index);
+        if let Change::RenameFile(params) = change {
+            later_moves.push((&params.from, &params.to));
+        }
+    }
+    applied
+}
+
+/// Find the edits that earlier runs applied, from the changes they recorded
+/// in order along with how to revert them, oldest first, see
+/// [`journal::read_applied`]. Each edited file is followed through the
+/// renames recorded after it, and its edits are taken as applied unless it is
+/// missing or was put back as it was before them, e.g. by a restore.
+fn find_recorded_edits<'a>(fs: &impl Fs, earlier: &'a [(Change, RevertAction)]) -> Vec<&'a Change> {
+    // The path of each edited file, its checksum before it was first edited,
+    // and its edits
+    let mut files: Vec<(PathBuf, Option<String>, Vec<&Change>)> = vec![];
+    for (change, action) in earlier {
+        let (target, before) = match action {
+            RevertAction::Rename { from, to } => {
+                for (path, _, _) in &mut files {
+                    if let Some(moved) = journal::follow_rename(path, from, to) {
+                        *path = moved;
+                    }
+                }
+                continue;
+            }
+            RevertAction::Restore {
+                target,
+                backup_checksum,
+                ..
+            } => (target, backup_checksum.clone()),
+            RevertAction::Rewrite {
+                target, content, ..
+            } => (target, Some(format!("{:x}", Sha256::digest(content)))),
+        };
+        match files.iter_mut().find(|(path, _, _)| path == target) {
+            Some((_, _, edits)) => edits.push(change),
+            None => files.push((target.clone(), before, vec![change])),
+        }
+    }
+    files
+        .into_iter()
+        .filter(|(path, before, _)| {
+            checksum_in(fs, path).is_ok_and(|current| before.as_ref() != Some(&current))
+        })
+        .flat_map(|(_, _, edits)| edits)
+        .collect()
+}
+
+fn verify_edit(
+    fs: &impl Fs,
+    change: &Change,
+    target: &Path,
+    checksum: Option<&str>,
+) -> Option<(DiscrepancyKind, PathBuf)> {
+    if fs.metadata(target).is_err() {
+        return Some((DiscrepancyKind::Missing, target.to_owned()));
+    }
+    let as_written = checksum
+        .is_some_and(|checksum| checksum_in(fs, target).is_ok_and(|current| current == checksum));
+    match as_written || change.holds_in(fs, target).unwrap_or(false) {
+        true => None,
+        false => Some((DiscrepancyKind::Unedited, target.to_owned())),
+    }
+}
+
+impl Display for DiscrepancyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiscrepancyKind::Missing => write!(f, "is missing"),
+            DiscrepancyKind::Leftover => write!(f, "still exists"),
+            DiscrepancyKind::Unedited => write!(f, "does not hold the edit"),
+        }
+    }
+}
+
+impl Display for Discrepancy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({} {})", self.change, self.path.display(), self.kind)
+    }
+}
@@ -1,14 +1,52 @@
-use std::path::PathBuf;
+use std::{
+    env, fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use clap::{Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 
-use crate::workflows::{rename_module, rename_plugin, rename_project, rename_target};
+use crate::{
+    backup::BackupPolicy,
+    bench, build_info, capabilities,
+    changes::{simulate_revert, Change, Changeset},
+    compatibility,
+    completions::{self, Shell},
+    doctor,
+    engine::Engine,
+    error::RenomError,
+    plan::{self, Plan},
+    presentation::{log, progress},
+    report::{BatchReport, Report},
+    settings::{ColorChoice, Settings},
+    vcs::{self, Vcs},
+    workflows::{
+        self, apply_planned_rename_module, apply_planned_rename_plugin,
+        apply_planned_rename_project, apply_planned_rename_target, list_components, list_snapshots,
+        plan_rename_module, plan_rename_modules, plan_rename_plugin, plan_rename_plugins,
+        plan_rename_project, plan_rename_target, plan_transaction, prune_project_backups,
+        read_batch_file, rename_in_transaction, rename_module, rename_modules, rename_plugin,
+        rename_plugins, rename_project, rename_target, restore_last_backup, restore_snapshot,
+        resume_interrupted_run, BatchItem, BatchRename, ComponentKind,
+    },
+};
 
 #[derive(Parser)]
-#[command(author, version, about, arg_required_else_help(true))]
+#[command(
+    author,
+    version,
+    long_version = build_info::LONG_VERSION,
+    about,
+    arg_required_else_help(true)
+)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Command>,
+    /// Also write every operation, warning, and error to this file as JSON
+    /// lines, for post-mortem analysis
+    #[arg(long, global = true, env = "RENOM_LOG_FILE", value_name = "FILE")]
+    pub log_file: Option<PathBuf>,
 }
 
 #[derive(PartialEq, Debug, Subcommand)]
@@ -21,94 +59,1696 @@ pub enum Command {
     RenameTarget(RenameTarget),
     /// Rename an Unreal Engine project module
     RenameModule(RenameModule),
+    /// Apply a list of renames from a YAML or JSON file in order
+    Batch(Batch),
+    /// Revert the most recent rename of a project from its backup
+    Restore(Restore),
+    /// Complete the most recent rename of a project if it was interrupted
+    Resume(Resume),
+    /// Manage the backups of a project
+    Backups(Backups),
+    /// List the plugins, modules, and targets of a project
+    List(List),
+    /// Check a project for problems that would make a rename fail halfway
+    Doctor(Doctor),
+    /// Write the changes a rename would make to a file for review
+    Plan(MakePlan),
+    /// Apply the changes written by plan, if they still match the project
+    Apply(ApplyPlan),
+    /// Check files for naming inconsistencies, e.g. from a pre-commit hook
+    Check(Check),
     /// Start an interactive session
     Wizard,
+    /// Print a shell completion script
+    Completions(Completions),
+    /// Print the commands, options, and formats this version supports
+    Capabilities(Capabilities),
+    /// Time the stages of a rename against a project without changing it
+    #[command(hide = true)]
+    Bench(Bench),
 }
 
 #[derive(PartialEq, Debug, Parser)]
 pub struct RenameProject {
     /// Path to the project to rename
-    #[arg(long)]
-    project: PathBuf,
+    #[arg(value_name = "PROJECT", conflicts_with_all = ["apply", "project"])]
+    project_arg: Option<PathBuf>,
     /// New name for the project
-    #[arg(long)]
-    new_name: String,
+    #[arg(value_name = "NEW_NAME", conflicts_with_all = ["apply", "new_name"])]
+    new_name_arg: Option<String>,
+    /// Path to the project to rename (same as PROJECT) [env: RENOM_PROJECT]
+    #[arg(short, long, conflicts_with = "apply")]
+    project: Option<PathBuf>,
+    /// New name for the project (same as NEW_NAME) [env: RENOM_NEW_NAME]
+    #[arg(short, long, conflicts_with = "apply")]
+    new_name: Option<String>,
+    /// File to read the new name from, or `-` for stdin, to avoid quoting it
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["apply", "new_name", "new_name_arg"]
+    )]
+    names_file: Option<PathBuf>,
+    #[command(flatten)]
+    plan: PlanOptions,
+    #[command(flatten)]
+    options: Options,
 }
 
-impl From<RenameProject> for rename_project::Params {
-    fn from(params: RenameProject) -> Self {
-        Self {
-            project_root: params.project,
-            new_name: params.new_name,
+impl RenameProject {
+    /// Rename the project, or plan or apply the rename depending on the options.
+    pub fn run(self) -> Result<(), RenomError> {
+        let output = self.options.prepare();
+        if let Some(path) = &self.plan.apply {
+            let plan = check(output, "project", read_plan(path, "project"))?;
+            return apply_plan::<rename_project::Params>(output, plan, self.options);
         }
+        let plan = self.plan.clone();
+        run_rename(output, plan, self.into_params())
+    }
+
+    fn into_params(self) -> Result<rename_project::Params, RenomError> {
+        let (project_root, prompts, options) = project_of(
+            self.project,
+            self.project_arg,
+            rename_project::get_project_root_from_user,
+            self.options,
+        )?;
+        Ok(rename_project::Params {
+            new_name: new_name(
+                self.new_name,
+                self.new_name_arg,
+                self.names_file,
+                prompts.then_some(|| rename_project::get_new_name_from_user(&project_root)),
+            )?,
+            project_root,
+            options,
+        })
     }
 }
 
 #[derive(PartialEq, Debug, Parser)]
 pub struct RenamePlugin {
     /// Path to the project that the plugin is part of
-    #[arg(long)]
-    project: PathBuf,
+    #[arg(value_name = "PROJECT", conflicts_with_all = ["apply", "project"])]
+    project_arg: Option<PathBuf>,
     /// Plugin in the project to rename
-    #[arg(long)]
-    plugin: String,
+    #[arg(value_name = "PLUGIN", conflicts_with_all = ["apply", "plugin"])]
+    plugin_arg: Option<String>,
     /// New name for the plugin
-    #[arg(long)]
-    new_name: String,
+    #[arg(value_name = "NEW_NAME", conflicts_with_all = ["apply", "new_name"])]
+    new_name_arg: Option<String>,
+    /// Path to the project that the plugin is part of (same as PROJECT) [env: RENOM_PROJECT]
+    #[arg(short, long, conflicts_with = "apply")]
+    project: Option<PathBuf>,
+    /// Plugin in the project to rename (same as PLUGIN) [env: RENOM_PLUGIN]
+    #[arg(long, conflicts_with = "apply")]
+    plugin: Option<String>,
+    /// Root of the plugin to rename, needed when several plugins share its name
+    /// [env: RENOM_PLUGIN_PATH]
+    #[arg(long, value_name = "DIR", conflicts_with = "apply")]
+    plugin_path: Option<PathBuf>,
+    /// New name for the plugin (same as NEW_NAME) [env: RENOM_NEW_NAME]
+    #[arg(short, long, conflicts_with = "apply")]
+    new_name: Option<String>,
+    /// File to read the new name from, or `-` for stdin, to avoid quoting it
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["apply", "new_name", "new_name_arg"]
+    )]
+    names_file: Option<PathBuf>,
+    /// Rename several plugins at once with a single backup, e.g.
+    /// `--rename Props=Scenery --rename Tools=Kit`
+    #[arg(
+        long = "rename",
+        value_name = "OLD=NEW",
+        value_parser = parse_rename,
+        conflicts_with_all = [
+            "apply",
+            "plan_only",
+            "plugin",
+            "plugin_arg",
+            "plugin_path",
+            "new_name",
+            "new_name_arg",
+            "names_file"
+        ]
+    )]
+    renames: Vec<(String, String)>,
+    #[command(flatten)]
+    plan: PlanOptions,
+    #[command(flatten)]
+    options: Options,
 }
 
-impl From<RenamePlugin> for rename_plugin::Params {
-    fn from(params: RenamePlugin) -> Self {
-        Self {
-            project_root: params.project,
-            plugin: params.plugin,
-            new_name: params.new_name,
+impl RenamePlugin {
+    /// Rename the plugin, or plan or apply the rename depending on the options.
+    pub fn run(self) -> Result<(), RenomError> {
+        let output = self.options.prepare();
+        if let Some(path) = &self.plan.apply {
+            let plan = check(output, "plugin", read_plan(path, "plugin"))?;
+            return apply_plan::<rename_plugin::Params>(output, plan, self.options);
+        }
+        if !self.renames.is_empty() {
+            return self.run_batch(output);
+        }
+        let plan = self.plan.clone();
+        run_rename(output, plan, self.into_params())
+    }
+
+    /// Rename several plugins at once, or only plan the renames in a dry run.
+    fn run_batch(self, output: Output) -> Result<(), RenomError> {
+        let dry_run = self.plan.dry_run;
+        let diff = self.plan.diff;
+        let params = check(output, "plugin", self.into_batch_params())?;
+        if !dry_run && output == Output::Text {
+            return rename_plugins(params);
+        }
+        let changeset = check(output, "plugin", plan_rename_plugins(&params))?;
+        if dry_run {
+            return print_dry_run(output, "plugin", changeset, diff);
         }
+        apply(output, "plugin", changeset, |_| rename_plugins(params))
+    }
+
+    fn into_batch_params(self) -> Result<Vec<rename_plugin::Params>, RenomError> {
+        let (project_root, _, options) = project_of(
+            self.project,
+            self.project_arg,
+            rename_plugin::get_project_root_from_user,
+            self.options,
+        )?;
+        Ok(self
+            .renames
+            .into_iter()
+            .map(|(plugin, new_name)| rename_plugin::Params {
+                project_root: project_root.clone(),
+                plugin,
+                new_name,
+                plugin_root: None,
+                options: options.clone(),
+            })
+            .collect())
+    }
+
+    fn into_params(self) -> Result<rename_plugin::Params, RenomError> {
+        let (project_root, prompts, options) = project_of(
+            self.project,
+            self.project_arg,
+            rename_plugin::get_project_root_from_user,
+            self.options,
+        )?;
+        // A plugin chosen at the prompt is known by its root as well, in case
+        // several plugins share its name
+        let mut chosen_root = None;
+        let plugin = required(
+            self.plugin,
+            self.plugin_arg,
+            "plugin",
+            "RENOM_PLUGIN",
+            prompts.then_some(|| {
+                let plugin = rename_plugin::get_plugin_from_user(&project_root)?;
+                chosen_root = Some(plugin.root);
+                Ok(plugin.name)
+            }),
+        )?;
+        Ok(rename_plugin::Params {
+            plugin,
+            new_name: new_name(
+                self.new_name,
+                self.new_name_arg,
+                self.names_file,
+                prompts.then_some(|| rename_plugin::get_new_name_from_user(&project_root)),
+            )?,
+            plugin_root: self
+                .plugin_path
+                .or_else(|| env::var("RENOM_PLUGIN_PATH").ok().map(PathBuf::from))
+                .or(chosen_root),
+            project_root,
+            options,
+        })
     }
 }
 
 #[derive(PartialEq, Debug, Parser)]
 pub struct RenameTarget {
     /// Path to the project that the target is part of
-    #[arg(long)]
-    project: PathBuf,
+    #[arg(value_name = "PROJECT", conflicts_with_all = ["apply", "project"])]
+    project_arg: Option<PathBuf>,
     /// Target in the project to rename
-    #[arg(long)]
-    target: String,
+    #[arg(value_name = "TARGET", conflicts_with_all = ["apply", "target"])]
+    target_arg: Option<String>,
     /// New name for the target
-    #[arg(long)]
-    new_name: String,
+    #[arg(value_name = "NEW_NAME", conflicts_with_all = ["apply", "new_name"])]
+    new_name_arg: Option<String>,
+    /// Path to the project that the target is part of (same as PROJECT) [env: RENOM_PROJECT]
+    #[arg(short, long, conflicts_with = "apply")]
+    project: Option<PathBuf>,
+    /// Target in the project to rename (same as TARGET) [env: RENOM_TARGET]
+    #[arg(short, long, conflicts_with = "apply")]
+    target: Option<String>,
+    /// New name for the target (same as NEW_NAME) [env: RENOM_NEW_NAME]
+    #[arg(short, long, conflicts_with = "apply")]
+    new_name: Option<String>,
+    /// File to read the new name from, or `-` for stdin, to avoid quoting it
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["apply", "new_name", "new_name_arg"]
+    )]
+    names_file: Option<PathBuf>,
+    #[command(flatten)]
+    plan: PlanOptions,
+    #[command(flatten)]
+    options: Options,
 }
 
-impl From<RenameTarget> for rename_target::Params {
-    fn from(params: RenameTarget) -> Self {
-        Self {
-            project_root: params.project,
-            target: params.target,
-            new_name: params.new_name,
+impl RenameTarget {
+    /// Rename the target, or plan or apply the rename depending on the options.
+    pub fn run(self) -> Result<(), RenomError> {
+        let output = self.options.prepare();
+        if let Some(path) = &self.plan.apply {
+            let plan = check(output, "target", read_plan(path, "target"))?;
+            return apply_plan::<rename_target::Params>(output, plan, self.options);
         }
+        let plan = self.plan.clone();
+        run_rename(output, plan, self.into_params())
+    }
+
+    fn into_params(self) -> Result<rename_target::Params, RenomError> {
+        let (project_root, prompts, options) = project_of(
+            self.project,
+            self.project_arg,
+            rename_target::get_project_root_from_user,
+            self.options,
+        )?;
+        Ok(rename_target::Params {
+            target: required(
+                self.target,
+                self.target_arg,
+                "target",
+                "RENOM_TARGET",
+                prompts.then_some(|| {
+                    rename_target::get_target_from_user(&project_root).map(|target| target.name)
+                }),
+            )?,
+            new_name: new_name(
+                self.new_name,
+                self.new_name_arg,
+                self.names_file,
+                prompts.then_some(|| rename_target::get_new_name_from_user(&project_root)),
+            )?,
+            project_root,
+            options,
+        })
     }
 }
 
 #[derive(PartialEq, Debug, Parser)]
 pub struct RenameModule {
     /// Path to the project that the module is part of
+    #[arg(value_name = "PROJECT", conflicts_with_all = ["apply", "project"])]
+    project_arg: Option<PathBuf>,
+    /// Module in the project to rename
+    #[arg(value_name = "MODULE", conflicts_with_all = ["apply", "module"])]
+    module_arg: Option<String>,
+    /// New name for the module
+    #[arg(value_name = "NEW_NAME", conflicts_with_all = ["apply", "new_name"])]
+    new_name_arg: Option<String>,
+    /// Path to the project that the module is part of (same as PROJECT) [env: RENOM_PROJECT]
+    #[arg(short, long, conflicts_with = "apply")]
+    project: Option<PathBuf>,
+    /// Module in the project to rename (same as MODULE) [env: RENOM_MODULE]
+    #[arg(short, long, conflicts_with = "apply")]
+    module: Option<String>,
+    /// New name for the module (same as NEW_NAME) [env: RENOM_NEW_NAME]
+    #[arg(short, long, conflicts_with = "apply")]
+    new_name: Option<String>,
+    /// File to read the new name from, or `-` for stdin, to avoid quoting it
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["apply", "new_name", "new_name_arg"]
+    )]
+    names_file: Option<PathBuf>,
+    /// Rename several modules at once with a single backup, e.g.
+    /// `--rename Core=Kernel --rename Tools=Kit`
+    #[arg(
+        long = "rename",
+        value_name = "OLD=NEW",
+        value_parser = parse_rename,
+        conflicts_with_all = [
+            "apply",
+            "plan_only",
+            "module",
+            "module_arg",
+            "new_name",
+            "new_name_arg",
+            "names_file"
+        ]
+    )]
+    renames: Vec<(String, String)>,
+    #[command(flatten)]
+    plan: PlanOptions,
+    #[command(flatten)]
+    options: Options,
+}
+
+impl RenameModule {
+    /// Rename the module, or plan or apply the rename depending on the options.
+    pub fn run(self) -> Result<(), RenomError> {
+        let output = self.options.prepare();
+        if let Some(path) = &self.plan.apply {
+            let plan = check(output, "module", read_plan(path, "module"))?;
+            return apply_plan::<rename_module::Params>(output, plan, self.options);
+        }
+        if !self.renames.is_empty() {
+            return self.run_batch(output);
+        }
+        let plan = self.plan.clone();
+        run_rename(output, plan, self.into_params())
+    }
+
+    /// Rename several modules at once, or only plan the renames in a dry run.
+    fn run_batch(self, output: Output) -> Result<(), RenomError> {
+        let dry_run = self.plan.dry_run;
+        let diff = self.plan.diff;
+        let params = check(output, "module", self.into_batch_params())?;
+        if !dry_run && output == Output::Text {
+            return rename_modules(params);
+        }
+        let changeset = check(output, "module", plan_rename_modules(&params))?;
+        if dry_run {
+            return print_dry_run(output, "module", changeset, diff);
+        }
+        apply(output, "module", changeset, |_| rename_modules(params))
+    }
+
+    fn into_batch_params(self) -> Result<Vec<rename_module::Params>, RenomError> {
+        let (project_root, _, options) = project_of(
+            self.project,
+            self.project_arg,
+            rename_module::get_project_root_from_user,
+            self.options,
+        )?;
+        Ok(self
+            .renames
+            .into_iter()
+            .map(|(module, new_name)| rename_module::Params {
+                project_root: project_root.clone(),
+                module,
+                new_name,
+                options: options.clone(),
+            })
+            .collect())
+    }
+
+    fn into_params(self) -> Result<rename_module::Params, RenomError> {
+        let (project_root, prompts, options) = project_of(
+            self.project,
+            self.project_arg,
+            rename_module::get_project_root_from_user,
+            self.options,
+        )?;
+        Ok(rename_module::Params {
+            module: required(
+                self.module,
+                self.module_arg,
+                "module",
+                "RENOM_MODULE",
+                prompts.then_some(|| {
+                    rename_module::get_module_from_user(&project_root).map(|module| module.name)
+                }),
+            )?,
+            new_name: new_name(
+                self.new_name,
+                self.new_name_arg,
+                self.names_file,
+                prompts.then_some(|| rename_module::get_new_name_from_user(&project_root)),
+            )?,
+            project_root,
+            options,
+        })
+    }
+}
+
+/// Get the project that a rename is part of, asking for it with the prompt if
+/// it is not given and prompting is possible, along with whether the other
+/// missing arguments can be asked for and the options resolved for the
+/// project.
+fn project_of(
+    project: Option<PathBuf>,
+    project_arg: Option<PathBuf>,
+    prompt: fn() -> Result<PathBuf, String>,
+    options: Options,
+) -> Result<(PathBuf, bool, workflows::Options), RenomError> {
+    let project_root = required(
+        project,
+        project_arg,
+        "project",
+        "RENOM_PROJECT",
+        Settings::load()
+            .is_ok_and(|settings| can_prompt(&settings))
+            .then_some(prompt),
+    )?;
+    let settings = load_settings(&project_root);
+    let prompts = can_prompt(&settings);
+    Ok((project_root, prompts, options.with_settings(settings)))
+}
+
+/// Get an argument that a rename needs, given either as an option or
+/// positionally, or else from an environment variable so that CI pipelines
+/// can set it once. If it is not given at all, it is asked for with the
+/// prompt, if any.
+fn required<T: From<String>>(
+    option: Option<T>,
+    positional: Option<T>,
+    name: &str,
+    var: &str,
+    prompt: Option<impl FnOnce() -> Result<T, String>>,
+) -> Result<T, RenomError> {
+    if let Some(value) = option
+        .or(positional)
+        .or_else(|| env::var(var).ok().map(T::from))
+    {
+        return Ok(value);
+    }
+    match prompt {
+        Some(prompt) => prompt().map_err(RenomError::Argument),
+        None => Err(RenomError::Argument(format!(
+            "{} must be given, or set through {}",
+            name, var
+        ))),
+    }
+}
+
+/// Check whether missing arguments can be asked for, i.e. whether stdin is a
+/// terminal and prompting was not turned off with `non_interactive` in the
+/// settings.
+fn can_prompt(settings: &Settings) -> bool {
+    io::stdin().is_terminal() && !settings.general.non_interactive
+}
+
+/// Load the settings of a project once for a whole command, falling back to
+/// the defaults if they cannot be loaded.
+fn load_settings(project_root: &Path) -> Settings {
+    Settings::load_for(project_root).unwrap_or_else(|err| {
+        log::warning(format!("Settings were not loaded: {}", err));
+        Settings::default()
+    })
+}
+
+/// Parse a rename of a batch, given as `OLD=NEW`.
+fn parse_rename(rename: &str) -> Result<(String, String), String> {
+    match rename.split_once('=') {
+        Some((old_name, new_name)) if !old_name.is_empty() && !new_name.is_empty() => {
+            Ok((old_name.to_owned(), new_name.to_owned()))
+        }
+        _ => Err(format!("expected OLD=NEW, found {}", rename)),
+    }
+}
+
+/// Get the new name for a rename, given like any other argument or else in a
+/// names file. A name of `-` is read from stdin, so that scripts can pass
+/// names without quoting them for the shell.
+fn new_name(
+    option: Option<String>,
+    positional: Option<String>,
+    names_file: Option<PathBuf>,
+    prompt: Option<impl FnOnce() -> Result<String, String>>,
+) -> Result<String, RenomError> {
+    let name = match names_file {
+        Some(path) => return single_name(&path),
+        None => required(option, positional, "new name", "RENOM_NEW_NAME", prompt)?,
+    };
+    match name.as_str() {
+        "-" => single_name(Path::new("-")),
+        _ => Ok(name),
+    }
+}
+
+/// Read the only name in a names file.
+fn single_name(path: &Path) -> Result<String, RenomError> {
+    let mut names = read_names(path)?;
+    match names.len() {
+        1 => Ok(names.remove(0)),
+        count => Err(RenomError::Argument(format!(
+            "{} must contain a single name, found {}",
+            describe_names_file(path),
+            count
+        ))),
+    }
+}
+
+/// Read the names in a names file, one per line, or from stdin if the path
+/// is `-`. Blank lines are skipped.
+fn read_names(path: &Path) -> Result<Vec<String>, RenomError> {
+    let content = match path == Path::new("-") {
+        true => io::read_to_string(io::stdin()),
+        false => fs::read_to_string(path),
+    }
+    .map_err(|err| {
+        RenomError::Argument(format!(
+            "could not read {}: {}",
+            describe_names_file(path),
+            err
+        ))
+    })?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect())
+}
+
+fn describe_names_file(path: &Path) -> String {
+    match path == Path::new("-") {
+        true => "stdin".to_owned(),
+        false => path.display().to_string(),
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct MakePlan {
+    /// File to write the planned changes to
+    #[arg(short, long, value_name = "FILE", global = true)]
+    out: Option<PathBuf>,
+    #[command(subcommand)]
+    rename: PlanRename,
+}
+
+/// A rename to plan, with the same arguments as when running it.
+#[derive(PartialEq, Debug, Subcommand)]
+pub enum PlanRename {
+    /// Plan renaming an Unreal Engine project
+    RenameProject(RenameProject),
+    /// Plan renaming an Unreal Engine project plugin
+    RenamePlugin(RenamePlugin),
+    /// Plan renaming an Unreal Engine project target
+    RenameTarget(RenameTarget),
+    /// Plan renaming an Unreal Engine project module
+    RenameModule(RenameModule),
+}
+
+impl MakePlan {
+    /// Plan the rename and write its changes to the output file instead of
+    /// applying them.
+    pub fn run(self) -> Result<(), RenomError> {
+        let out = self
+            .out
+            .ok_or_else(|| RenomError::Argument("--out must be given".into()))?;
+        match self.rename {
+            PlanRename::RenameProject(mut command) => {
+                command.plan.plan_to(out)?;
+                command.run()
+            }
+            PlanRename::RenamePlugin(mut command) => {
+                reject_batch(&command.renames)?;
+                command.plan.plan_to(out)?;
+                command.run()
+            }
+            PlanRename::RenameTarget(mut command) => {
+                command.plan.plan_to(out)?;
+                command.run()
+            }
+            PlanRename::RenameModule(mut command) => {
+                reject_batch(&command.renames)?;
+                command.plan.plan_to(out)?;
+                command.run()
+            }
+        }
+    }
+}
+
+/// Batches of renames cannot be planned, only renames of a single element.
+fn reject_batch(renames: &[(String, String)]) -> Result<(), RenomError> {
+    match renames.is_empty() {
+        true => Ok(()),
+        false => Err(RenomError::Argument(
+            "--rename cannot be planned, plan one rename at a time".into(),
+        )),
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct ApplyPlan {
+    /// File with the changes written by plan or --plan-only
+    #[arg(value_name = "PLAN")]
+    plan: PathBuf,
+    #[command(flatten)]
+    options: Options,
+}
+
+impl ApplyPlan {
+    /// Apply the planned rename of whatever kind it is.
+    pub fn run(self) -> Result<(), RenomError> {
+        let output = self.options.prepare();
+        let plan = check(output, "unknown", plan::read(&self.plan))?;
+        match plan.kind.as_str() {
+            "project" => apply_plan::<rename_project::Params>(output, plan, self.options),
+            "plugin" => apply_plan::<rename_plugin::Params>(output, plan, self.options),
+            "target" => apply_plan::<rename_target::Params>(output, plan, self.options),
+            "module" => apply_plan::<rename_module::Params>(output, plan, self.options),
+            kind => check(
+                output,
+                kind,
+                Err(RenomError::Parse(format!(
+                    "cannot apply a plan for renaming a {}",
+                    kind
+                ))),
+            ),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct Batch {
+    /// YAML or JSON file listing the renames to apply, in order
+    #[arg(short, long, value_name = "FILE")]
+    manifest: PathBuf,
+    /// Apply every rename as one transaction with a single backup, so that
+    /// either all of them are applied or none are
     #[arg(long)]
+    transaction: bool,
+    #[command(flatten)]
+    options: Options,
+}
+
+impl Batch {
+    /// Apply the renames of the batch file in order, stopping at the first
+    /// one that fails, and report on all of them at once.
+    pub fn run(self) -> Result<(), RenomError> {
+        let output = self.options.prepare();
+        let items = read_batch_file(&self.manifest)?;
+        let settings = load_settings(&items[0].project_root);
+        let options = self.options.with_settings(settings);
+        if self.transaction {
+            return run_transaction(output, items, &options);
+        }
+        let total = items.len();
+        // Project renames move the project for the renames after them
+        let mut moved: Vec<(PathBuf, PathBuf)> = vec![];
+        let mut reports = vec![];
+        let mut result = Ok(());
+        for (index, item) in items.into_iter().enumerate() {
+            let project_root = moved
+                .iter()
+                .rev()
+                .find(|(from, _)| *from == item.project_root)
+                .map_or(item.project_root.clone(), |(_, to)| to.clone());
+            log::header(format!(
+                "Rename {} of {}: {}",
+                index + 1,
+                total,
+                item.rename
+            ));
+            let (report, outcome) = run_batch_rename(&project_root, item.rename.clone(), &options);
+            reports.push(report);
+            if let Err(err) = outcome {
+                result = Err(err.context(format!("rename {} of {} failed", index + 1, total)));
+                break;
+            }
+            if let BatchRename::Project { new_name } = &item.rename {
+                let renamed_root = project_root.with_file_name(new_name);
+                if renamed_root.is_dir() {
+                    moved.push((item.project_root, renamed_root));
+                }
+            }
+        }
+        let applied = match result {
+            Ok(()) => reports.len(),
+            Err(_) => reports.len() - 1,
+        };
+        let report = BatchReport::new(total, reports);
+        match output {
+            Output::Text => log::basic(format!("\nApplied {} of {} rename(s).", applied, total)),
+            Output::Json => report.print()?,
+            Output::Csv => report.print_csv()?,
+        }
+        result
+    }
+}
+
+/// Apply the renames of a batch as one transaction, which must all be of the
+/// same project.
+fn run_transaction(
+    output: Output,
+    items: Vec<BatchItem>,
+    options: &workflows::Options,
+) -> Result<(), RenomError> {
+    let project_root = items[0].project_root.clone();
+    if items.iter().any(|item| item.project_root != project_root) {
+        return check(
+            output,
+            "transaction",
+            Err(RenomError::Validation(
+                "renames of a transaction must all be of the same project".into(),
+            )),
+        );
+    }
+    let renames = items
+        .into_iter()
+        .map(|item| item.rename)
+        .collect::<Vec<BatchRename>>();
+    if output == Output::Text {
+        return rename_in_transaction(&project_root, &renames, options);
+    }
+    let changeset = check(
+        output,
+        "transaction",
+        plan_transaction(&project_root, &renames, options),
+    )?;
+    apply(output, "transaction", changeset, |_| {
+        rename_in_transaction(&project_root, &renames, options)
+    })
+}
+
+/// Plan and apply a rename of a batch, getting its report along with the
+/// outcome.
+fn run_batch_rename(
+    project_root: &Path,
+    rename: BatchRename,
+    options: &workflows::Options,
+) -> (Report, Result<(), RenomError>) {
+    let project_root = project_root.to_owned();
+    let options = options.clone();
+    match rename {
+        BatchRename::Project { new_name } => plan_and_apply(rename_project::Params {
+            project_root,
+            new_name,
+            options,
+        }),
+        BatchRename::Plugin {
+            name,
+            new_name,
+            plugin_path,
+        } => plan_and_apply(rename_plugin::Params {
+            plugin_root: plugin_path.map(|path| project_root.join(path)),
+            project_root,
+            plugin: name,
+            new_name,
+            options,
+        }),
+        BatchRename::Module { name, new_name } => plan_and_apply(rename_module::Params {
+            project_root,
+            module: name,
+            new_name,
+            options,
+        }),
+        BatchRename::Target { name, new_name } => plan_and_apply(rename_target::Params {
+            project_root,
+            target: name,
+            new_name,
+            options,
+        }),
+    }
+}
+
+fn plan_and_apply<P: RenameParams>(params: P) -> (Report, Result<(), RenomError>) {
+    match params.plan() {
+        Ok(changeset) => {
+            let result = params.apply_planned(&changeset);
+            (
+                Report::applied(P::KIND, changeset.into_changes(), &result),
+                result,
+            )
+        }
+        Err(err) => (Report::invalid(P::KIND, err.to_string()), Err(err)),
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct Restore {
+    /// Path to the project to restore
+    #[arg(short, long, env = "RENOM_PROJECT")]
     project: PathBuf,
-    /// Module in the project to rename
+    /// Directory the backups are kept in instead of .renom/backup in the
+    /// project
+    #[arg(long, env = "RENOM_BACKUP_DIR", value_name = "DIR")]
+    backup_dir: Option<PathBuf>,
+    /// Revert the rename that took this snapshot instead of the most recent
+    /// one, see `renom backups list`
+    #[arg(long, value_name = "ID")]
+    snapshot: Option<String>,
+}
+
+impl Restore {
+    /// Revert the most recent rename that can still be reverted, or the
+    /// rename of a snapshot.
+    pub fn run(self) -> Result<(), RenomError> {
+        let settings = load_settings(&self.project);
+        settings.apply();
+        let options = workflows::Options {
+            backup_dir: self
+                .backup_dir
+                .map(|dir| std::path::absolute(&dir).unwrap_or(dir))
+                .or(settings.general.backup_dir),
+            ..workflows::Options::default()
+        };
+        match &self.snapshot {
+            Some(id) => restore_snapshot(&self.project, id, &options).map(|_| ()),
+            None => restore_last_backup(&self.project, &options).map(|_| ()),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct Resume {
+    /// Path to the project whose rename to resume
+    #[arg(short, long, env = "RENOM_PROJECT")]
+    project: PathBuf,
+    /// Directory the backups are kept in instead of .renom/backup in the
+    /// project
+    #[arg(long, env = "RENOM_BACKUP_DIR", value_name = "DIR")]
+    backup_dir: Option<PathBuf>,
+}
+
+impl Resume {
+    /// Complete the most recent rename if it was interrupted.
+    pub fn run(self) -> Result<(), RenomError> {
+        let settings = load_settings(&self.project);
+        settings.apply();
+        let options = workflows::Options {
+            backup_dir: self
+                .backup_dir
+                .map(|dir| std::path::absolute(&dir).unwrap_or(dir))
+                .or(settings.general.backup_dir),
+            chunk_size: settings.engine.chunk_size,
+            threads: settings.engine.threads,
+            ..workflows::Options::default()
+        };
+        resume_interrupted_run(&self.project, &options).map(|_| ())
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct Backups {
+    #[command(subcommand)]
+    command: BackupsCommand,
+}
+
+#[derive(PartialEq, Debug, Subcommand)]
+pub enum BackupsCommand {
+    /// List the snapshots taken by the renames of a project, oldest first
+    List(ListBackups),
+    /// Archive the backups of completed renames and remove those that exceed
+    /// the limits under [backup] in the settings
+    Prune(PruneBackups),
+}
+
+impl Backups {
+    /// Run the backups subcommand.
+    pub fn run(self) -> Result<(), RenomError> {
+        match self.command {
+            BackupsCommand::List(command) => command.run(),
+            BackupsCommand::Prune(command) => command.run(),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct ListBackups {
+    /// Path to the project whose backups to list
+    #[arg(short, long, env = "RENOM_PROJECT")]
+    project: PathBuf,
+    /// Directory the backups are kept in instead of .renom/backup in the
+    /// project
+    #[arg(long, env = "RENOM_BACKUP_DIR", value_name = "DIR")]
+    backup_dir: Option<PathBuf>,
+}
+
+impl ListBackups {
+    /// Print the id, time, state, size, and command of each snapshot on
+    /// stdout, one per line.
+    pub fn run(self) -> Result<(), RenomError> {
+        let settings = load_settings(&self.project);
+        settings.apply();
+        let options = workflows::Options {
+            backup_dir: self
+                .backup_dir
+                .map(|dir| std::path::absolute(&dir).unwrap_or(dir))
+                .or(settings.general.backup_dir),
+            ..workflows::Options::default()
+        };
+        for entry in list_snapshots(&self.project, &options)? {
+            println!(
+                "{}  {}  {:11}  {:>8.1} MB  {}",
+                entry.snapshot.id,
+                entry.snapshot.created,
+                entry.state.to_string(),
+                entry.size as f64 / (1024.0 * 1024.0),
+                entry.snapshot.command.as_deref().unwrap_or("-")
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct PruneBackups {
+    /// Path to the project whose backups to prune
+    #[arg(short, long, env = "RENOM_PROJECT")]
+    project: PathBuf,
+    /// Directory the backups are kept in instead of .renom/backup in the
+    /// project
+    #[arg(long, env = "RENOM_BACKUP_DIR", value_name = "DIR")]
+    backup_dir: Option<PathBuf>,
+    /// Keep the backups of this many renames at most, instead of max_count
+    #[arg(long, value_name = "COUNT")]
+    keep_last: Option<usize>,
+    /// Keep this many megabytes of backups at most, instead of
+    /// max_total_size_mb
+    #[arg(long, value_name = "MB")]
+    max_size: Option<u64>,
+    /// Keep backups for this many days at most, instead of max_age_days
+    #[arg(long, value_name = "DAYS")]
+    max_age: Option<u64>,
+}
+
+impl PruneBackups {
+    /// Archive and prune the backups of the project.
+    pub fn run(self) -> Result<(), RenomError> {
+        let settings = load_settings(&self.project);
+        settings.apply();
+        let policy = BackupPolicy {
+            max_count: self.keep_last.or(settings.backup.max_count),
+            max_total_size_mb: self.max_size.or(settings.backup.max_total_size_mb),
+            max_age_days: self.max_age.or(settings.backup.max_age_days),
+        };
+        let options = workflows::Options {
+            backup_dir: self
+                .backup_dir
+                .map(|dir| std::path::absolute(&dir).unwrap_or(dir))
+                .or(settings.general.backup_dir),
+            backup_policy: Some(policy),
+            ..workflows::Options::default()
+        };
+        prune_project_backups(&self.project, &options)
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct List {
+    /// Path to the project
+    #[arg(short, long, env = "RENOM_PROJECT")]
+    project: PathBuf,
+    /// Kind of component to list, or every kind if omitted
+    #[arg(value_enum)]
+    kind: Option<ComponentKind>,
+}
+
+impl List {
+    /// Print the name and path of each component on stdout, one per line.
+    pub fn run(self) -> Result<(), RenomError> {
+        let components = list_components(&self.project, self.kind)?;
+        let width = components
+            .iter()
+            .map(|component| component.name.len())
+            .max()
+            .unwrap_or_default();
+        for component in components {
+            let path = component
+                .path
+                .strip_prefix(&self.project)
+                .unwrap_or(&component.path);
+            println!(
+                "{}  {:width$}  {}",
+                component.kind,
+                component.name,
+                path.display(),
+                width = width
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct Doctor {
+    /// Path to the project to check
+    #[arg(short, long, env = "RENOM_PROJECT")]
+    project: PathBuf,
+}
+
+impl Doctor {
+    /// Print the problems found in the project, failing if there are any.
+    pub fn run(self) -> Result<(), RenomError> {
+        let diagnostics = doctor::check_project(&self.project)?;
+        if diagnostics.is_empty() {
+            log::success(format!("No problems found in {}.", self.project.display()));
+            return Ok(());
+        }
+        log::header("Problems");
+        for diagnostic in &diagnostics {
+            log::basic(format!("\t- {}", diagnostic));
+        }
+        Err(RenomError::Validation(format!(
+            "{} problem(s) found; fix them before renaming",
+            diagnostics.len()
+        )))
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct Check {
+    /// Path to the project to check
+    #[arg(short, long, env = "RENOM_PROJECT", default_value = ".")]
+    project: PathBuf,
+    /// Only check the files staged for the next git commit
     #[arg(long)]
-    module: String,
-    /// New name for the module
+    staged: bool,
+}
+
+impl Check {
+    /// Print the problems found in the checked files, failing if there are
+    /// any.
+    pub fn run(self) -> Result<(), RenomError> {
+        let diagnostics = match self.staged {
+            true => {
+                let staged = vcs::git_staged_files(&self.project)?;
+                doctor::check_project_files(&self.project, |path| {
+                    path.canonicalize().is_ok_and(|path| staged.contains(&path))
+                })?
+            }
+            false => doctor::check_project(&self.project)?,
+        };
+        if diagnostics.is_empty() {
+            return Ok(());
+        }
+        for diagnostic in &diagnostics {
+            log::basic(format!("{}", diagnostic));
+        }
+        Err(RenomError::Validation(format!(
+            "{} naming inconsistency(s) found",
+            diagnostics.len()
+        )))
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct Completions {
+    /// Shell to generate the completion script for
+    shell: Shell,
+}
+
+impl Completions {
+    /// Print the completion script for the shell on stdout.
+    pub fn run(self) -> Result<(), RenomError> {
+        print!("{}", completions::generate(self.shell, Cli::command()));
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct Capabilities {
+    /// Format of the output
+    #[arg(long, env = "RENOM_OUTPUT", value_enum, default_value_t = Output::Text)]
+    output: Output,
+}
+
+impl Capabilities {
+    /// Print what this version of Renom supports, e.g. as JSON for frontends.
+    pub fn run(self) -> Result<(), RenomError> {
+        let capabilities = capabilities::describe(Cli::command());
+        match self.output {
+            Output::Json => {
+                let json = serde_json::to_string_pretty(&capabilities)
+                    .map_err(|err| RenomError::Parse(err.to_string()))?;
+                println!("{}", json);
+            }
+            Output::Csv => {
+                return Err(RenomError::Validation(
+                    "capabilities cannot be printed as CSV".into(),
+                ))
+            }
+            Output::Text => {
+                log::basic(format!("Renom {}", capabilities.version));
+                log::header("Commands");
+                for command in &capabilities.commands {
+                    log::basic(format!("\t- {}: {}", command.name, command.about));
+                }
+                log::header("Changes");
+                log::basic(format!("\t- {}", capabilities.change_kinds.join("\n\t- ")));
+                log::basic(format!(
+                    "\nPlan format version: {}",
+                    capabilities.plan_schema_version
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Debug, Parser)]
+pub struct Bench {
+    /// Path to the project to benchmark against
+    #[arg(long)]
+    project: PathBuf,
+    /// Memory-map files when scanning
     #[arg(long)]
-    new_name: String,
+    mmap: bool,
 }
 
-impl From<RenameModule> for rename_module::Params {
-    fn from(params: RenameModule) -> Self {
+impl From<Bench> for bench::Params {
+    fn from(params: Bench) -> Self {
         Self {
             project_root: params.project,
-            module: params.module,
-            new_name: params.new_name,
+            mmap: params.mmap,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Args)]
+pub struct PlanOptions {
+    /// Print the changes that the rename would make without making them
+    #[arg(long, conflicts_with_all = ["plan_only", "apply"])]
+    dry_run: bool,
+    /// Write the planned changes to a file instead of applying them
+    #[arg(long, value_name = "FILE", conflicts_with = "apply")]
+    plan_only: Option<PathBuf>,
+    /// Apply changes planned with --plan-only, if they still match the project
+    #[arg(long, value_name = "FILE")]
+    apply: Option<PathBuf>,
+    /// Also print a unified diff of the lines that each edited file would
+    /// change
+    #[arg(long, requires = "dry_run")]
+    diff: bool,
+}
+
+impl PlanOptions {
+    /// Write the planned changes to a file instead of applying them, as if
+    /// given --plan-only.
+    fn plan_to(&mut self, path: PathBuf) -> Result<(), RenomError> {
+        if self.dry_run || self.apply.is_some() {
+            return Err(RenomError::Argument(
+                "--dry-run and --apply cannot be used when planning".into(),
+            ));
+        }
+        self.plan_only = Some(path);
+        Ok(())
+    }
+}
+
+/// Read a plan made for a kind of rename.
+fn read_plan(path: &Path, kind: &str) -> Result<Plan, RenomError> {
+    let plan = plan::read(path)?;
+    plan.expect_kind(kind)?;
+    Ok(plan)
+}
+
+/// The params of a rename of a single element, which every kind of rename
+/// is run with the same way, see [`run_rename`] and [`apply_plan`].
+trait RenameParams: Sized {
+    /// The kind of element renamed, as reported and recorded in plans.
+    const KIND: &'static str;
+
+    /// Rename the element, reporting the changes as they are applied.
+    fn rename(self) -> Result<(), RenomError>;
+
+    /// Plan the changes of the rename.
+    fn plan(&self) -> Result<Changeset, RenomError>;
+
+    /// Apply the planned changes of the rename.
+    fn apply_planned(self, changeset: &Changeset) -> Result<(), RenomError>;
+
+    /// Get the params of a planned rename.
+    fn from_plan(plan: &Plan, options: workflows::Options) -> Result<Self, RenomError>;
+
+    /// Make a plan of the rename with its planned changes.
+    fn into_plan(self, changeset: Changeset) -> Plan;
+}
+
+impl RenameParams for rename_project::Params {
+    const KIND: &'static str = "project";
+
+    fn rename(self) -> Result<(), RenomError> {
+        rename_project(self)
+    }
+
+    fn plan(&self) -> Result<Changeset, RenomError> {
+        plan_rename_project(self)
+    }
+
+    fn apply_planned(self, changeset: &Changeset) -> Result<(), RenomError> {
+        apply_planned_rename_project(self, changeset)
+    }
+
+    fn from_plan(plan: &Plan, options: workflows::Options) -> Result<Self, RenomError> {
+        Ok(Self {
+            project_root: plan.project_root.clone(),
+            new_name: plan.new_name.clone(),
+            options,
+        })
+    }
+
+    fn into_plan(self, changeset: Changeset) -> Plan {
+        Plan::new(
+            Self::KIND,
+            self.project_root,
+            None,
+            self.new_name,
+            changeset,
+        )
+    }
+}
+
+impl RenameParams for rename_plugin::Params {
+    const KIND: &'static str = "plugin";
+
+    fn rename(self) -> Result<(), RenomError> {
+        rename_plugin(self)
+    }
+
+    fn plan(&self) -> Result<Changeset, RenomError> {
+        plan_rename_plugin(self)
+    }
+
+    fn apply_planned(self, changeset: &Changeset) -> Result<(), RenomError> {
+        apply_planned_rename_plugin(self, changeset)
+    }
+
+    fn from_plan(plan: &Plan, options: workflows::Options) -> Result<Self, RenomError> {
+        Ok(Self {
+            project_root: plan.project_root.clone(),
+            plugin: plan.expect_name()?,
+            new_name: plan.new_name.clone(),
+            plugin_root: plan.path.clone(),
+            options,
+        })
+    }
+
+    fn into_plan(self, changeset: Changeset) -> Plan {
+        Plan::new(
+            Self::KIND,
+            self.project_root,
+            Some(self.plugin),
+            self.new_name,
+            changeset,
+        )
+        .with_path(self.plugin_root)
+    }
+}
+
+impl RenameParams for rename_target::Params {
+    const KIND: &'static str = "target";
+
+    fn rename(self) -> Result<(), RenomError> {
+        rename_target(self)
+    }
+
+    fn plan(&self) -> Result<Changeset, RenomError> {
+        plan_rename_target(self)
+    }
+
+    fn apply_planned(self, changeset: &Changeset) -> Result<(), RenomError> {
+        apply_planned_rename_target(self, changeset)
+    }
+
+    fn from_plan(plan: &Plan, options: workflows::Options) -> Result<Self, RenomError> {
+        Ok(Self {
+            project_root: plan.project_root.clone(),
+            target: plan.expect_name()?,
+            new_name: plan.new_name.clone(),
+            options,
+        })
+    }
+
+    fn into_plan(self, changeset: Changeset) -> Plan {
+        Plan::new(
+            Self::KIND,
+            self.project_root,
+            Some(self.target),
+            self.new_name,
+            changeset,
+        )
+    }
+}
+
+impl RenameParams for rename_module::Params {
+    const KIND: &'static str = "module";
+
+    fn rename(self) -> Result<(), RenomError> {
+        rename_module(self)
+    }
+
+    fn plan(&self) -> Result<Changeset, RenomError> {
+        plan_rename_module(self)
+    }
+
+    fn apply_planned(self, changeset: &Changeset) -> Result<(), RenomError> {
+        apply_planned_rename_module(self, changeset)
+    }
+
+    fn from_plan(plan: &Plan, options: workflows::Options) -> Result<Self, RenomError> {
+        Ok(Self {
+            project_root: plan.project_root.clone(),
+            module: plan.expect_name()?,
+            new_name: plan.new_name.clone(),
+            options,
+        })
+    }
+
+    fn into_plan(self, changeset: Changeset) -> Plan {
+        Plan::new(
+            Self::KIND,
+            self.project_root,
+            Some(self.module),
+            self.new_name,
+            changeset,
+        )
+    }
+}
+
+/// Rename a single element, or else plan the rename to print it in a dry
+/// run, write it to a plan, or apply it while reporting in another format.
+fn run_rename<P: RenameParams>(
+    output: Output,
+    plan: PlanOptions,
+    params: Result<P, RenomError>,
+) -> Result<(), RenomError> {
+    let params = check(output, P::KIND, params)?;
+    if !plan.dry_run && plan.plan_only.is_none() && output == Output::Text {
+        return params.rename();
+    }
+    let changeset = check(output, P::KIND, params.plan())?;
+    if plan.dry_run {
+        return print_dry_run(output, P::KIND, changeset, plan.diff);
+    }
+    match plan.plan_only {
+        Some(path) => write_plan(output, &path, &params.into_plan(changeset)),
+        None => apply(output, P::KIND, changeset, |changeset| {
+            params.apply_planned(changeset)
+        }),
+    }
+}
+
+/// Apply a rename planned with `renom plan` or --plan-only.
+fn apply_plan<P: RenameParams>(
+    output: Output,
+    plan: Plan,
+    options: Options,
+) -> Result<(), RenomError> {
+    let options = options.with_settings(load_settings(&plan.project_root));
+    let params = check(output, P::KIND, P::from_plan(&plan, options))?;
+    apply(output, P::KIND, plan.changeset(), |changeset| {
+        params.apply_planned(changeset)
+    })
+}
+
+/// Report an error that stops a rename before any change is applied.
+fn check<T>(output: Output, kind: &str, result: Result<T, RenomError>) -> Result<T, RenomError> {
+    if let Err(err) = &result {
+        output.report(Report::invalid(kind, err.to_string()))?;
+    }
+    result
+}
+
+/// Apply planned changes and report the outcome.
+fn apply(
+    output: Output,
+    kind: &str,
+    changeset: Changeset,
+    apply: impl FnOnce(&Changeset) -> Result<(), RenomError>,
+) -> Result<(), RenomError> {
+    let result = apply(&changeset);
+    output.report(Report::applied(kind, changeset.into_changes(), &result))?;
+    result
+}
+
+fn print_dry_run(
+    output: Output,
+    kind: &str,
+    changeset: Changeset,
+    diff: bool,
+) -> Result<(), RenomError> {
+    if output != Output::Text {
+        return output.report(Report::planned(kind, changeset.into_changes()));
+    }
+    log::header("Dry run");
+    for change in changeset.changes() {
+        log::step("would apply", change);
+    }
+    if diff {
+        print_diff(changeset.changes());
+    }
+    warn_about_revert_issues(changeset.changes());
+    warn_about_compatibility(changeset.changes());
+    log::basic(format!(
+        "\n{} change(s) would be applied. Nothing was changed.",
+        changeset.changes().len()
+    ));
+    Ok(())
+}
+
+/// Print a unified diff of every file that the changes would edit.
+fn print_diff(changes: &[Change]) {
+    match Engine::preview(changes) {
+        Ok(files) => {
+            log::header("Diff");
+            for file in files {
+                log::basic(file.to_unified().trim_end());
+            }
+        }
+        Err(err) => log::warning(format!("Edits could not be previewed: {}", err)),
+    }
+}
+
+/// Warn about changes that would stop the rename midway or leave it unable
+/// to be undone.
+fn warn_about_revert_issues(changes: &[Change]) {
+    for issue in simulate_revert(changes) {
+        log::warning(format!(
+            "Change {} would stop the rename midway or could not be undone: {}",
+            issue.index + 1,
+            issue
+        ));
+    }
+}
+
+/// Warn about builds and data made before the rename that it affects, such
+/// as the save games of packaged builds.
+fn warn_about_compatibility(changes: &[Change]) {
+    let notes = compatibility::notes(changes);
+    if !notes.is_empty() {
+        log::warning(format!("Compatibility:\n\t- {}", notes.join("\n\t- ")));
+    }
+}
+
+fn write_plan(output: Output, path: &Path, plan: &Plan) -> Result<(), RenomError> {
+    plan::write(path, plan)?;
+    warn_about_revert_issues(&plan.changes);
+    warn_about_compatibility(&plan.changes);
+    if output != Output::Text {
+        return output.report(Report::planned(&plan.kind, plan.changes.clone()));
+    }
+    log::success(format!(
+        "Planned {} change(s) in {}.",
+        plan.changes.len(),
+        path.display()
+    ));
+    Ok(())
+}
+
+/// The format of the output of a command.
+#[derive(PartialEq, Debug, Clone, Copy, ValueEnum)]
+pub enum Output {
+    /// Human-readable log output
+    Text,
+    /// A JSON report on stdout, with log output moved to stderr
+    Json,
+    /// A CSV report on stdout with a row per changed file, with log output
+    /// moved to stderr
+    Csv,
+}
+
+impl Output {
+    /// Set up logging for the output format.
+    fn prepare(self) {
+        if self != Output::Text {
+            log::redirect_to_stderr();
+        }
+    }
+
+    /// Print a report in the output format, if it is machine-readable.
+    fn report(self, report: Report) -> Result<(), RenomError> {
+        match self {
+            Output::Text => Ok(()),
+            Output::Json => Ok(report.print()?),
+            Output::Csv => Ok(report.print_csv()?),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Args)]
+pub struct Options {
+    /// Record the rename in the project ledger (RENAMES.md)
+    #[arg(long, env = "RENOM_LEDGER")]
+    ledger: bool,
+    /// Restore the original modification time of edited files
+    #[arg(long, env = "RENOM_PRESERVE_MTIME")]
+    preserve_mtime: bool,
+    /// Remove redirects made redundant by the rename
+    #[arg(long, env = "RENOM_COLLAPSE_REDIRECTS")]
+    collapse_redirects: bool,
+    /// Rewrite content paths in config that include the old project name
+    #[arg(long, env = "RENOM_REWRITE_CONTENT_PATHS")]
+    rewrite_content_paths: bool,
+    /// Rename the generated Visual Studio solution and project files
+    #[arg(long, env = "RENOM_UPDATE_PROJECT_FILES")]
+    update_project_files: bool,
+    /// Add a module declared only in target files to the project descriptor
+    #[arg(long, env = "RENOM_NORMALIZE_DESCRIPTOR")]
+    normalize_descriptor: bool,
+    /// Rename config entries with this key whose value is exactly the old
+    /// module name, e.g. ModuleNames for +ModuleNames=OldName
+    #[arg(
+        long = "config-value-key",
+        env = "RENOM_CONFIG_VALUE_KEYS",
+        value_name = "KEY",
+        value_delimiter = ','
+    )]
+    config_value_keys: Vec<String>,
+    /// Old name to search for in file contents, overriding the detected name
+    #[arg(long, env = "RENOM_FROM_NAME")]
+    from_name: Option<String>,
+    /// Memory-map files when scanning large projects
+    #[arg(long, env = "RENOM_MMAP")]
+    mmap: bool,
+    /// Traverse symlinked folders when scanning the project for references.
+    /// Changes never follow symlinks out of the project either way
+    #[arg(long, env = "RENOM_FOLLOW_SYMLINKS")]
+    follow_symlinks: bool,
+    /// Version control system the project is managed with, so that edited
+    /// files keep the line endings it expects
+    #[arg(long, env = "RENOM_VCS", value_enum)]
+    vcs: Option<Vcs>,
+    /// Keep every backup instead of pruning them as configured
+    #[arg(long, env = "RENOM_NO_PRUNE")]
+    no_prune: bool,
+    /// Take no backups, e.g. when the project is under version control. A
+    /// failed rename is still reverted, but cannot be restored later
+    #[arg(long, env = "RENOM_NO_BACKUP", conflicts_with = "backup_dir")]
+    no_backup: bool,
+    /// Make read-only files writable to edit them, e.g. files not checked out
+    /// of Perforce, and read-only again afterwards
+    #[arg(long, env = "RENOM_MAKE_WRITABLE")]
+    make_writable: bool,
+    /// Skip changes already applied by an earlier run that partly succeeded,
+    /// such as files already renamed, instead of failing on them
+    #[arg(long, env = "RENOM_IDEMPOTENT")]
+    idempotent: bool,
+    /// Directory to keep backups in instead of .renom/backup in the project
+    #[arg(long, env = "RENOM_BACKUP_DIR", value_name = "DIR")]
+    backup_dir: Option<PathBuf>,
+    /// Apply extra changes from an NDJSON file, one change per line, along
+    /// with the rename
+    #[arg(long, env = "RENOM_EXTRA_OPS", value_name = "FILE")]
+    extra_ops: Option<PathBuf>,
+    /// Only touch files matching this glob, relative to the project, e.g.
+    /// Source/**
+    #[arg(
+        long,
+        env = "RENOM_INCLUDE",
+        value_name = "GLOB",
+        value_delimiter = ','
+    )]
+    include: Vec<String>,
+    /// Leave files matching this glob alone, relative to the project, e.g.
+    /// ThirdParty/**
+    #[arg(
+        long,
+        env = "RENOM_EXCLUDE",
+        value_name = "GLOB",
+        value_delimiter = ','
+    )]
+    exclude: Vec<String>,
+    /// Format of the output
+    #[arg(
+        long,
+        alias = "report",
+        env = "RENOM_OUTPUT",
+        value_enum,
+        default_value_t = Output::Text
+    )]
+    output: Output,
+    /// When to color output, overriding the configured choice
+    #[arg(long, env = "RENOM_COLOR", value_enum)]
+    color: Option<ColorChoice>,
+    /// Print the details of each change as it is applied
+    #[arg(short, long, env = "RENOM_VERBOSE")]
+    verbose: bool,
+    /// Print nothing but errors, even when verbose
+    #[arg(short, long, env = "RENOM_QUIET")]
+    quiet: bool,
+    /// Show a progress bar on stderr as changes are applied, when it is a
+    /// terminal
+    #[arg(long, env = "RENOM_PROGRESS")]
+    progress: bool,
+}
+
+impl Options {
+    /// Set up logging for the options and get the format of the output.
+    fn prepare(&self) -> Output {
+        self.output.prepare();
+        log::set_level(match (self.quiet, self.verbose) {
+            (true, _) => log::Level::Quiet,
+            (false, true) => log::Level::Verbose,
+            (false, false) => log::Level::Normal,
+        });
+        self.output
+    }
+
+    /// Resolve the options for a rename of a project, falling back to the
+    /// settings loaded for it for anything not given on the command line.
+    fn with_settings(self, mut settings: Settings) -> workflows::Options {
+        if let Some(color) = self.color {
+            settings.general.color = color;
+        }
+        settings.apply();
+        workflows::Options {
+            interactive: false,
+            ledger: self.ledger,
+            preserve_mtime: self.preserve_mtime,
+            collapse_redirects: self.collapse_redirects,
+            rewrite_content_paths: self.rewrite_content_paths,
+            update_project_files: self.update_project_files,
+            normalize_descriptor: self.normalize_descriptor,
+            config_value_keys: self.config_value_keys,
+            from_name: self.from_name,
+            mmap: self.mmap,
+            follow_symlinks: self.follow_symlinks,
+            vcs: self.vcs,
+            backup_policy: match self.no_prune {
+                true => None,
+                false => Some(settings.backup),
+            },
+            // Relative to where Renom runs rather than to the project
+            backup_dir: self
+                .backup_dir
+                .map(|dir| std::path::absolute(&dir).unwrap_or(dir))
+                .or(settings.general.backup_dir),
+            no_backup: self.no_backup,
+            make_writable: self.make_writable,
+            idempotent: self.idempotent,
+            chunk_size: settings.engine.chunk_size,
+            threads: settings.engine.threads,
+            stream_threshold: Some(settings.engine.stream_threshold()),
+            extra_ops: self.extra_ops,
+            include: match self.include.is_empty() {
+                true => settings.general.include,
+                false => self.include,
+            },
+            exclude: match self.exclude.is_empty() {
+                true => settings.general.exclude,
+                false => self.exclude,
+            },
+            cancellation: Default::default(),
+            progress: match self.progress && io::stderr().is_terminal() {
+                true => Some(Arc::new(progress::Bar)),
+                false => None,
+            },
         }
     }
 }
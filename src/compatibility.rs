@@ -0,0 +1,42 @@
+use std::path::Path;
+
+use crate::changes::Change;
+
+/// Describe how a changeset affects builds and data made before it was
+/// applied. Renaming a project renames the folder that packaged and
+/// development builds keep save games and user config in, which orphans the
+/// saves of players and testers unless they are moved.
+pub fn notes(changes: &[Change]) -> Vec<String> {
+    changes
+        .iter()
+        .filter_map(|change| match change {
+            Change::RenameFile(params) => renamed_project(&params.from, &params.to),
+            _ => None,
+        })
+        .flat_map(|(old_name, new_name)| save_game_notes(&old_name, &new_name))
+        .collect()
+}
+
+/// Get the old and new name of a project if a rename moves its descriptor.
+fn renamed_project(from: &Path, to: &Path) -> Option<(String, String)> {
+    let stem_of = |path: &Path| match path.extension().is_some_and(|ext| ext == "uproject") {
+        true => path.file_stem()?.to_str().map(str::to_owned),
+        false => None,
+    };
+    Some((stem_of(from)?, stem_of(to)?))
+}
+
+fn save_game_notes(old_name: &str, new_name: &str) -> Vec<String> {
+    vec![
+        format!(
+            "Packaged builds keep save games and user config in a folder named after the project, e.g. %LOCALAPPDATA%/{old}/Saved/SaveGames on Windows. Builds of {new} look in %LOCALAPPDATA%/{new}/Saved instead, so saves and settings made by earlier builds are no longer found.",
+            old = old_name,
+            new = new_name
+        ),
+        "Save games and config in the Saved folder of the project move along with it and are kept.".to_owned(),
+        format!(
+            "The engine has no config setting for the save folder of packaged builds, so no shim was added. To keep existing saves, move them to the new folder or have the game load them from the {} folder.",
+            old_name
+        ),
+    ]
+}
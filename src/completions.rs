@@ -0,0 +1,374 @@
+use clap::{Arg, ValueEnum, ValueHint};
+
+/// A shell that completion scripts can be generated for.
+#[derive(PartialEq, Debug, Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// What an option expects to follow it.
+enum Value {
+    /// Nothing, the option is a flag.
+    None,
+    /// Any text, such as a name.
+    Text,
+    /// A path to a file or directory.
+    Path,
+    /// One of a fixed set of values.
+    OneOf(Vec<String>),
+}
+
+/// An option that a command accepts.
+struct Opt {
+    long: Option<String>,
+    short: Option<char>,
+    help: String,
+    value: Value,
+}
+
+/// A subcommand along with everything that can follow it.
+struct Subcommand {
+    name: String,
+    about: String,
+    options: Vec<Opt>,
+    values: Vec<String>,
+}
+
+/// What can be completed for a command line tool.
+struct Spec {
+    bin: String,
+    options: Vec<Opt>,
+    subcommands: Vec<Subcommand>,
+}
+
+/// Generate a completion script for a shell that covers the visible
+/// subcommands and options of a command.
+pub fn generate(shell: Shell, command: clap::Command) -> String {
+    let spec = describe(command);
+    match shell {
+        Shell::Bash => bash(&spec),
+        Shell::Zsh => zsh(&spec),
+        Shell::Fish => fish(&spec),
+        Shell::Powershell => powershell(&spec),
+    }
+}
+
+fn describe(mut command: clap::Command) -> Spec {
+    command.build();
+    Spec {
+        bin: command.get_name().to_owned(),
+        options: options_of(command.get_arguments()),
+        subcommands: command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .map(|subcommand| Subcommand {
+                name: subcommand.get_name().to_owned(),
+                about: subcommand
+                    .get_about()
+                    .map(|about| about.to_string())
+                    .unwrap_or_default(),
+                options: options_of(subcommand.get_arguments()),
+                values: subcommand
+                    .get_arguments()
+                    .filter(|arg| arg.is_positional() && !arg.is_hide_set())
+                    .flat_map(values_of)
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+fn options_of<'a>(args: impl Iterator<Item = &'a Arg>) -> Vec<Opt> {
+    args.filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+        .map(|arg| Opt {
+            long: arg.get_long().map(str::to_owned),
+            short: arg.get_short(),
+            help: arg
+                .get_help()
+                .map(|help| help.to_string())
+                .unwrap_or_default(),
+            value: match (arg.get_action().takes_values(), values_of(arg)) {
+                (false, _) => Value::None,
+                (true, values) if !values.is_empty() => Value::OneOf(values),
+                (true, _) if arg.get_value_hint() == ValueHint::AnyPath => Value::Path,
+                (true, _) => Value::Text,
+            },
+        })
+        .collect()
+}
+
+fn values_of(arg: &Arg) -> Vec<String> {
+    match arg.get_action().takes_values() {
+        true => arg
+            .get_possible_values()
+            .iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| value.get_name().to_owned())
+            .collect(),
+        false => vec![],
+    }
+}
+
+impl Opt {
+    /// The ways to spell the option on the command line, e.g. `-p` and
+    /// `--project`.
+    fn spellings(&self) -> Vec<String> {
+        self.short
+            .map(|short| format!("-{}", short))
+            .into_iter()
+            .chain(self.long.as_ref().map(|long| format!("--{}", long)))
+            .collect()
+    }
+}
+
+fn words(options: &[Opt]) -> Vec<String> {
+    options.iter().flat_map(Opt::spellings).collect()
+}
+
+fn bash(spec: &Spec) -> String {
+    let top_level = spec
+        .subcommands
+        .iter()
+        .map(|subcommand| subcommand.name.clone())
+        .chain(words(&spec.options))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let mut cases = String::new();
+    for subcommand in &spec.subcommands {
+        let mut values = String::new();
+        for option in &subcommand.options {
+            let completion = match &option.value {
+                Value::None => continue,
+                Value::Text => "compgen -W \"\" -- \"$cur\"".to_owned(),
+                Value::Path => "compgen -f -- \"$cur\"".to_owned(),
+                Value::OneOf(values) => {
+                    format!("compgen -W \"{}\" -- \"$cur\"", values.join(" "))
+                }
+            };
+            values += &format!(
+                "                {})\n                    COMPREPLY=($({}))\n                    return\n                    ;;\n",
+                option.spellings().join("|"),
+                completion
+            );
+        }
+        let candidates = subcommand
+            .values
+            .iter()
+            .cloned()
+            .chain(words(&subcommand.options))
+            .collect::<Vec<String>>()
+            .join(" ");
+        cases += &format!(
+            "        {name})\n            case \"$prev\" in\n{values}            esac\n            COMPREPLY=($(compgen -W \"{candidates}\" -- \"$cur\"))\n            ;;\n",
+            name = subcommand.name,
+            values = values,
+            candidates = candidates
+        );
+    }
+    format!(
+        r#"_{bin}() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    if [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{top_level}" -- "$cur"))
+        return
+    fi
+    case "${{COMP_WORDS[1]}}" in
+{cases}    esac
+}}
+
+complete -F _{bin} -o bashdefault -o default {bin}
+"#,
+        bin = spec.bin,
+        top_level = top_level,
+        cases = cases
+    )
+}
+
+fn zsh(spec: &Spec) -> String {
+    let escape = |text: &str| {
+        text.replace('\'', "'\\''")
+            .replace('[', "\\[")
+            .replace(']', "\\]")
+            .replace(':', "\\:")
+    };
+    let arguments = |options: &[Opt], indent: &str| {
+        options
+            .iter()
+            .flat_map(|option| {
+                let action = match &option.value {
+                    Value::None => String::new(),
+                    Value::Text => ":value: ".to_owned(),
+                    Value::Path => ":path:_files".to_owned(),
+                    Value::OneOf(values) => format!(":value:({})", values.join(" ")),
+                };
+                option
+                    .spellings()
+                    .into_iter()
+                    .map(|spelling| {
+                        format!(
+                            "{}'{}[{}]{}' \\\n",
+                            indent,
+                            spelling,
+                            escape(&option.help),
+                            action
+                        )
+                    })
+                    .collect::<Vec<String>>()
+            })
+            .collect::<String>()
+    };
+    let commands = spec
+        .subcommands
+        .iter()
+        .map(|subcommand| {
+            format!(
+                "        '{}:{}'\n",
+                subcommand.name,
+                subcommand.about.replace('\'', "'\\''").replace(':', "\\:")
+            )
+        })
+        .collect::<String>();
+    let mut cases = String::new();
+    for subcommand in &spec.subcommands {
+        let positional = match subcommand.values.is_empty() {
+            true => String::new(),
+            false => format!(
+                "                ':value:({})' \\\n",
+                subcommand.values.join(" ")
+            ),
+        };
+        cases += &format!(
+            "        {})\n            _arguments \\\n{}{}                && return\n            ;;\n",
+            subcommand.name,
+            arguments(&subcommand.options, "                "),
+            positional
+        );
+    }
+    format!(
+        r#"#compdef {bin}
+
+_{bin}() {{
+    local -a commands
+    commands=(
+{commands}    )
+    if (( CURRENT == 2 )); then
+        _describe 'command' commands
+        _arguments \
+{options}            && return
+        return
+    fi
+    shift words
+    (( CURRENT-- ))
+    case "$words[1]" in
+{cases}    esac
+}}
+
+compdef _{bin} {bin}
+"#,
+        bin = spec.bin,
+        commands = commands,
+        options = arguments(&spec.options, "            "),
+        cases = cases
+    )
+}
+
+fn fish(spec: &Spec) -> String {
+    let escape = |text: &str| text.replace('\\', "\\\\").replace('\'', "\\'");
+    let complete = |condition: &str, option: &Opt| {
+        let mut line = format!("complete -c {} -n '{}'", spec.bin, condition);
+        if let Some(short) = option.short {
+            line += &format!(" -s {}", short);
+        }
+        if let Some(long) = &option.long {
+            line += &format!(" -l {}", long);
+        }
+        match &option.value {
+            Value::None => {}
+            Value::Text => line += " -r",
+            Value::Path => line += " -r -F",
+            Value::OneOf(values) => line += &format!(" -r -f -a '{}'", values.join(" ")),
+        }
+        line + &format!(" -d '{}'\n", escape(&option.help))
+    };
+    let mut script = format!("complete -c {} -f\n", spec.bin);
+    for option in &spec.options {
+        script += &complete("__fish_use_subcommand", option);
+    }
+    for subcommand in &spec.subcommands {
+        script += &format!(
+            "complete -c {} -n '__fish_use_subcommand' -a {} -d '{}'\n",
+            spec.bin,
+            subcommand.name,
+            escape(&subcommand.about)
+        );
+    }
+    for subcommand in &spec.subcommands {
+        let condition = format!("__fish_seen_subcommand_from {}", subcommand.name);
+        if !subcommand.values.is_empty() {
+            script += &format!(
+                "complete -c {} -n '{}' -a '{}'\n",
+                spec.bin,
+                condition,
+                subcommand.values.join(" ")
+            );
+        }
+        for option in &subcommand.options {
+            script += &complete(&condition, option);
+        }
+    }
+    script
+}
+
+fn powershell(spec: &Spec) -> String {
+    let quote = |words: Vec<String>| {
+        words
+            .iter()
+            .map(|word| format!("'{}'", word))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    let mut cases = String::new();
+    for subcommand in &spec.subcommands {
+        let candidates = subcommand
+            .values
+            .iter()
+            .cloned()
+            .chain(words(&subcommand.options))
+            .collect();
+        cases += &format!(
+            "        '{}' {{ @({}) }}\n",
+            subcommand.name,
+            quote(candidates)
+        );
+    }
+    let top_level = spec
+        .subcommands
+        .iter()
+        .map(|subcommand| subcommand.name.clone())
+        .chain(words(&spec.options))
+        .collect();
+    format!(
+        r#"Register-ArgumentCompleter -Native -CommandName '{bin}' -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $words = @($commandAst.CommandElements | ForEach-Object {{ $_.ToString() }})
+    $command = ''
+    if ($words.Count -gt 2 -or ($words.Count -eq 2 -and $wordToComplete -eq '')) {{
+        $command = $words[1]
+    }}
+    $candidates = switch ($command) {{
+{cases}        default {{ @({top_level}) }}
+    }}
+    $candidates | Where-Object {{ $_ -like "$wordToComplete*" }} | ForEach-Object {{
+        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+    }}
+}}
+"#,
+        bin = spec.bin,
+        cases = cases,
+        top_level = quote(top_level)
+    )
+}
@@ -0,0 +1,134 @@
+use std::{
+    backtrace::Backtrace,
+    fs,
+    panic::{self, PanicHookInfo},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::Serialize;
+
+/// What Renom was doing, kept up to date so that a crash report can include it.
+struct State {
+    operation: Option<String>,
+    backup_dir: Option<PathBuf>,
+    journal: Option<PathBuf>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    operation: None,
+    backup_dir: None,
+    journal: None,
+});
+
+/// A crash report. Never sent anywhere, only written to disk.
+#[derive(Serialize)]
+struct Report {
+    captured_at: String,
+    version: String,
+    operating_system: String,
+    panic_message: Option<String>,
+    panic_location: Option<String>,
+    operation: Option<String>,
+    journal: Option<PathBuf>,
+    backtrace: String,
+}
+
+/// Record the operation in progress, or `None` once it has finished.
+pub fn set_operation(operation: Option<String>) {
+    if let Ok(mut state) = STATE.lock() {
+        state.operation = operation;
+    }
+}
+
+/// Record the backup directory and journal of the changes being applied.
+pub fn set_journal(backup_dir: &Path, journal: &Path) {
+    if let Ok(mut state) = STATE.lock() {
+        state.backup_dir = Some(backup_dir.to_owned());
+        state.journal = Some(journal.to_owned());
+    }
+}
+
+/// Install a panic hook that writes a crash report and tells the user how to
+/// restore their project. Reports are written to `.renom/crash` next to the
+/// backups of the changes being applied, or to the temp directory if no
+/// changes have been applied yet.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let report = create_report(info);
+        let crash_dir = report_dir();
+        match write_report(&report, &crash_dir) {
+            Ok(path) => eprintln!(
+                "Renom has crashed. A crash report was saved to {}.",
+                path.display()
+            ),
+            Err(err) => eprintln!(
+                "Renom has crashed. The crash report could not be saved to {}: {}",
+                crash_dir.display(),
+                err
+            ),
+        }
+        if let Some(operation) = &report.operation {
+            eprintln!("The crash happened while performing: {}", operation);
+        }
+        if let Some(journal) = &report.journal {
+            eprintln!(
+                "Changes applied before the crash are listed in {}, along with the backups needed to undo them.",
+                journal.display()
+            );
+            eprintln!("Run `renom resume` to complete the rename, or `renom restore` to undo it.");
+        }
+        default_hook(info);
+    }));
+}
+
+fn create_report(info: &PanicHookInfo) -> Report {
+    let (operation, journal) = match STATE.lock() {
+        Ok(state) => (state.operation.clone(), state.journal.clone()),
+        Err(_) => (None, None),
+    };
+    let panic_message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned());
+
+    Report {
+        captured_at: chrono::Local::now().to_rfc3339(),
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        operating_system: std::env::consts::OS.to_owned(),
+        panic_message,
+        panic_location: info
+            .location()
+            .map(|loc| format!("{}:{}:{}", loc.file(), loc.line(), loc.column())),
+        operation,
+        journal,
+        backtrace: Backtrace::force_capture().to_string(),
+    }
+}
+
+fn report_dir() -> PathBuf {
+    let backup_dir = STATE.lock().ok().and_then(|state| state.backup_dir.clone());
+    // Backups of each run live in .renom/backup/<run>, reports in .renom/crash
+    let renom_dir = backup_dir.as_deref().and_then(|backup_dir| {
+        backup_dir
+            .ancestors()
+            .find(|dir| dir.file_name().is_some_and(|name| name == ".renom"))
+            .or_else(|| backup_dir.parent())
+    });
+    match renom_dir {
+        Some(renom_dir) => renom_dir.join("crash"),
+        None => std::env::temp_dir().join("renom").join("crash"),
+    }
+}
+
+fn write_report(report: &Report, crash_dir: &Path) -> Result<PathBuf, String> {
+    let content = toml::to_string_pretty(report).map_err(|err| err.to_string())?;
+    let path = crash_dir
+        .join(ulid::Ulid::new().to_string())
+        .with_extension("toml");
+    fs::create_dir_all(crash_dir).map_err(|err| err.to_string())?;
+    fs::write(&path, content).map_err(|err| err.to_string())?;
+    Ok(path)
+}
@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+const CONTEXT: usize = 2;
+/// Largest number of line pairs to compare when diffing. Bigger edits are
+/// shown as the removal of the old lines followed by the new ones.
+const MAX_COMPARISONS: usize = 4_000_000;
+
+/// The content of a file before and after the edits of a changeset, see
+/// [`Engine::preview`].
+///
+/// [`Engine::preview`]: crate::engine::Engine::preview
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct FileDiff {
+    /// The file, where it is before the changeset is applied.
+    pub path: PathBuf,
+    /// The content of the file on disk.
+    pub before: String,
+    /// The content of the file once every edit is applied.
+    pub after: String,
+}
+
+impl FileDiff {
+    /// Get the hunks of lines that the edits change, see [`unified`].
+    pub fn hunks(&self) -> Vec<String> {
+        unified(&self.before, &self.after)
+    }
+
+    /// Render the diff in the unified format, as `diff -u` and `git diff` do,
+    /// with the path of the file as both the old and the new name.
+    pub fn to_unified(&self) -> String {
+        let path = self.path.display();
+        let mut text = format!("--- {}\n+++ {}\n", path, path);
+        for line in self.hunks() {
+            text += &line;
+            text += "\n";
+        }
+        text
+    }
+}
+
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Produce a line-based diff of two texts in the unified format, i.e. hunks
+/// of removed and added lines with a few lines of context around them, each
+/// under a `@@` header with their line numbers.
+pub fn unified(before: &str, after: &str) -> Vec<String> {
+    let old = before.lines().collect::<Vec<&str>>();
+    let new = after.lines().collect::<Vec<&str>>();
+    let lines = compare(&old, &new);
+
+    // Line numbers before each line, for hunk headers
+    let mut positions = Vec::with_capacity(lines.len());
+    let (mut old_no, mut new_no) = (1, 1);
+    for line in &lines {
+        positions.push((old_no, new_no));
+        match line {
+            Line::Same(_) => {
+                old_no += 1;
+                new_no += 1;
+            }
+            Line::Removed(_) => old_no += 1,
+            Line::Added(_) => new_no += 1,
+        }
+    }
+
+    let changed = (0..lines.len())
+        .filter(|index| !matches!(lines[*index], Line::Same(_)))
+        .collect::<Vec<usize>>();
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for index in changed {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut output = vec![];
+    for (start, end) in hunks {
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|l| !matches!(l, Line::Added(_))).count();
+        let new_len = hunk
+            .iter()
+            .filter(|l| !matches!(l, Line::Removed(_)))
+            .count();
+        let (old_start, new_start) = positions[start];
+        output.push(format!(
+            "@@ -{},{} +{},{} @@",
+            old_start, old_len, new_start, new_len
+        ));
+        output.extend(hunk.iter().map(|line| match line {
+            Line::Same(text) => format!(" {}", text),
+            Line::Removed(text) => format!("-{}", text),
+            Line::Added(text) => format!("+{}", text),
+        }));
+    }
+    output
+}
+
+/// Align two sequences of lines using their longest common subsequence.
+fn compare<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Line<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lines = old[..prefix]
+        .iter()
+        .map(|text| Line::Same(text))
+        .collect::<Vec<Line>>();
+    if old_mid.len() * new_mid.len() > MAX_COMPARISONS {
+        lines.extend(old_mid.iter().map(|text| Line::Removed(text)));
+        lines.extend(new_mid.iter().map(|text| Line::Added(text)));
+    } else {
+        // lengths[i][j] is the LCS length of old_mid[i..] and new_mid[j..]
+        let width = new_mid.len() + 1;
+        let mut lengths = vec![0u32; (old_mid.len() + 1) * width];
+        for i in (0..old_mid.len()).rev() {
+            for j in (0..new_mid.len()).rev() {
+                lengths[i * width + j] = match old_mid[i] == new_mid[j] {
+                    true => lengths[(i + 1) * width + j + 1] + 1,
+                    false => lengths[(i + 1) * width + j].max(lengths[i * width + j + 1]),
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < old_mid.len() && j < new_mid.len() {
+            if old_mid[i] == new_mid[j] {
+                lines.push(Line::Same(old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+                lines.push(Line::Removed(old_mid[i]));
+                i += 1;
+            } else {
+                lines.push(Line::Added(new_mid[j]));
+                j += 1;
+            }
+        }
+        lines.extend(old_mid[i..].iter().map(|text| Line::Removed(text)));
+        lines.extend(new_mid[j..].iter().map(|text| Line::Added(text)));
+    }
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|text| Line::Same(text)),
+    );
+    lines
+}
@@ -0,0 +1,425 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+use walkdir::WalkDir;
+
+use crate::{
+    error::RenomError, ledger, matcher::Matcher, presentation::log, text::TextFile, unreal,
+};
+
+/// A problem found while checking a project.
+pub struct Diagnostic {
+    /// The file that the problem was found in.
+    pub path: PathBuf,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+/// Run every consistency check on a project, reporting problems that would
+/// make a rename fail halfway or leave the project broken: modules declared
+/// in descriptors but missing on disk, build, target, and plugin files whose
+/// names do not match their folders or classes, inconsistent redirects, and
+/// default maps that do not resolve.
+pub fn check_project(project_root: &Path) -> Result<Vec<Diagnostic>, RenomError> {
+    check_project_files(project_root, |_| true)
+}
+
+/// Run the checks of [`check_project`] on the files that `include` accepts
+/// only, e.g. the files staged for a commit. Files that are left out are not
+/// read, so checking a few files of a large project is fast.
+pub fn check_project_files(
+    project_root: &Path,
+    include: impl Fn(&Path) -> bool,
+) -> Result<Vec<Diagnostic>, RenomError> {
+    let descriptor = find_project_descriptor(project_root)?;
+    let mut diagnostics = vec![];
+    if include(&descriptor) {
+        diagnostics.extend(check_descriptor_modules(
+            &descriptor,
+            &project_root.join("Source"),
+        ));
+    }
+    for plugin_descriptor in find_files(&unreal::plugin_directories(project_root), ".uplugin")
+        .into_iter()
+        .filter(|path| include(path))
+    {
+        let plugin_root = plugin_descriptor.parent().unwrap_or(project_root);
+        diagnostics.extend(check_descriptor_modules(
+            &plugin_descriptor,
+            &plugin_root.join("Source"),
+        ));
+        diagnostics.extend(check_name_matches_folder(&plugin_descriptor, ".uplugin"));
+    }
+    diagnostics.extend(check_build_files(project_root, &include));
+    if include(&project_root.join("Config").join("DefaultEngine.ini")) {
+        diagnostics.extend(check_redirects(project_root));
+        diagnostics.extend(check_default_maps(project_root));
+    }
+    Ok(diagnostics)
+}
+
+fn find_project_descriptor(project_root: &Path) -> Result<PathBuf, RenomError> {
+    fs::read_dir(project_root)
+        .map_err(|err| {
+            RenomError::from(err).context(format!("could not read {}", project_root.display()))
+        })?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "uproject"))
+        .ok_or_else(|| {
+            RenomError::Validation("project root must contain a project descriptor".into())
+        })
+}
+
+/// Find files with a suffix, e.g. `.Build.cs`, in directories, skipping the
+/// directories that are never scanned.
+fn find_files(dirs: &[PathBuf], suffix: &str) -> Vec<PathBuf> {
+    dirs.iter()
+        .flat_map(|dir| {
+            WalkDir::new(dir)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|entry| {
+                    entry
+                        .file_name()
+                        .to_str()
+                        .is_none_or(|name| !unreal::is_ignored_dir(name))
+                })
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.ends_with(suffix))
+        })
+        .map(|entry| entry.path().to_owned())
+        .collect()
+}
+
+/// Get the name of a file without a suffix, e.g. the module name of
+/// `Demo.Build.cs`.
+fn name_of<'a>(path: &'a Path, suffix: &str) -> Option<&'a str> {
+    path.file_name()?.to_str()?.strip_suffix(suffix)
+}
+
+/// Check that the modules declared in a project or plugin descriptor have a
+/// build file in the Source folder next to it.
+fn check_descriptor_modules(descriptor: &Path, source_dir: &Path) -> Vec<Diagnostic> {
+    let declared = fs::read_to_string(descriptor)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|json| {
+            json.get("Modules")
+                .and_then(|modules| modules.as_array().cloned())
+        })
+        .unwrap_or_default();
+    let modules = find_files(&[source_dir.to_owned()], ".Build.cs");
+    declared
+        .iter()
+        .filter_map(|module| module.get("Name").and_then(|name| name.as_str()))
+        .filter(|name| {
+            !modules
+                .iter()
+                .any(|module| name_of(module, ".Build.cs") == Some(name))
+        })
+        .map(|name| Diagnostic {
+            path: descriptor.to_owned(),
+            message: format!(
+                "declares module {}, but no {}.Build.cs exists under {}",
+                name,
+                name,
+                source_dir.display()
+            ),
+        })
+        .collect()
+}
+
+/// Check that a file is named after the folder it is in, as the engine
+/// expects of module build files and plugin descriptors.
+fn check_name_matches_folder(path: &Path, suffix: &str) -> Option<Diagnostic> {
+    let name = name_of(path, suffix)?;
+    let folder = path.parent()?.file_name()?.to_str()?;
+    match name == folder {
+        true => None,
+        false => Some(Diagnostic {
+            path: path.to_owned(),
+            message: format!("is named {}, but its folder is named {}", name, folder),
+        }),
+    }
+}
+
+/// Check that module build files are named after their folders and that
+/// build and target files declare classes named after them, e.g. `Demo` in
+/// `Demo.Build.cs` and `DemoTarget` in `Demo.Target.cs`.
+fn check_build_files(project_root: &Path, include: impl Fn(&Path) -> bool) -> Vec<Diagnostic> {
+    let dirs = std::iter::once(project_root.join("Source"))
+        .chain(unreal::plugin_directories(project_root))
+        .collect::<Vec<PathBuf>>();
+    let mut diagnostics = vec![];
+    for (suffix, rules, class_suffix) in [
+        (".Build.cs", "ModuleRules", ""),
+        (".Target.cs", "TargetRules", "Target"),
+    ] {
+        let class = Regex::new(&format!(r"class\s+(\w+)\s*:\s*{}\b", rules))
+            .expect("regex should be valid");
+        for path in find_files(&dirs, suffix)
+            .into_iter()
+            .filter(|path| include(path))
+        {
+            let Some(name) = name_of(&path, suffix) else {
+                continue;
+            };
+            if suffix == ".Build.cs" {
+                diagnostics.extend(check_name_matches_folder(&path, suffix));
+            }
+            let expected = format!("{}{}", name, class_suffix);
+            let declared = TextFile::read(&path).ok().and_then(|file| {
+                class
+                    .captures(&file.content)
+                    .map(|captures| captures[1].to_owned())
+            });
+            if let Some(declared) = declared.filter(|declared| declared != &expected) {
+                diagnostics.push(Diagnostic {
+                    path,
+                    message: format!(
+                        "declares class {}, but the engine expects {}",
+                        declared, expected
+                    ),
+                });
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Check the redirects in the engine config of a project for redirects that
+/// point at themselves, names redirected to several places, and redirects
+/// that form a cycle.
+fn check_redirects(project_root: &Path) -> Vec<Diagnostic> {
+    let config = project_root.join("Config").join("DefaultEngine.ini");
+    let Ok(file) = TextFile::read(&config) else {
+        return vec![];
+    };
+    let redirect = Regex::new(
+        r#"(?m)^\s*\+?(?P<kind>\w+Redirects)\s*=\s*\(\s*OldName\s*=\s*"(?P<old>[^"]*)"\s*,\s*NewName\s*=\s*"(?P<new>[^"]*)""#,
+    )
+    .expect("regex should be valid");
+    let redirects = redirect
+        .captures_iter(&file.content)
+        .map(|captures| {
+            (
+                captures["kind"].to_owned(),
+                captures["old"].to_owned(),
+                captures["new"].to_owned(),
+            )
+        })
+        .collect::<Vec<(String, String, String)>>();
+
+    let mut problems = vec![];
+    for (index, (kind, old, new)) in redirects.iter().enumerate() {
+        if old == new {
+            problems.push(format!("{} redirects {} to itself", kind, old));
+            continue;
+        }
+        let conflicting = redirects[..index]
+            .iter()
+            .find(|(other_kind, other_old, other_new)| {
+                other_kind == kind && other_old == old && other_new != new
+            });
+        if let Some((_, _, other_new)) = conflicting {
+            problems.push(format!(
+                "{} redirects {} to both {} and {}",
+                kind, old, other_new, new
+            ));
+        }
+        // Follow the chain of redirects to see if it leads back here
+        let mut current = new;
+        for _ in 0..redirects.len() {
+            match redirects.iter().find(|(other_kind, other_old, other_new)| {
+                other_kind == kind && other_old == current && other_old != other_new
+            }) {
+                Some((_, _, next)) if next == old => {
+                    problems.push(format!("{} redirect of {} is part of a cycle", kind, old));
+                    break;
+                }
+                Some((_, _, next)) => current = next,
+                None => break,
+            }
+        }
+    }
+    problems
+        .into_iter()
+        .map(|message| Diagnostic {
+            path: config.clone(),
+            message,
+        })
+        .collect()
+}
+
+/// Config keys that reference maps by package path.
+const MAP_KEYS: [&str; 4] = [
+    "GameDefaultMap",
+    "EditorStartupMap",
+    "ServerDefaultMap",
+    "TransitionMap",
+];
+
+/// Check that the maps referenced in the engine config of a project (default
+/// map, editor startup map, etc.) resolve to map files on disk. Maps under
+/// the `/Game/` mount point are looked up in the project Content folder and
+/// maps under a plugin mount point in the Content folder of that plugin.
+/// Engine maps are not checked.
+pub fn check_default_maps(project_root: &Path) -> Vec<Diagnostic> {
+    let config = project_root.join("Config").join("DefaultEngine.ini");
+    let Ok(file) = TextFile::read(&config) else {
+        return vec![];
+    };
+
+    file.content
+        .lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .filter(|(key, _)| MAP_KEYS.contains(key))
+        .filter_map(|(key, value)| {
+            check_map_reference(project_root, value.trim()).map(|problem| Diagnostic {
+                path: config.clone(),
+                message: format!("{} {}", key, problem),
+            })
+        })
+        .collect()
+}
+
+/// Check that a map reference such as `/Game/Maps/Start.Start` resolves to a
+/// map file. Returns a description of the problem if it does not.
+fn check_map_reference(project_root: &Path, reference: &str) -> Option<String> {
+    if reference.is_empty() || reference == "None" {
+        return None;
+    }
+    let package = reference.split('.').next().unwrap_or(reference);
+    let (mount_point, package_path) = package.trim_start_matches('/').split_once('/')?;
+    let content_dir = match mount_point {
+        "Game" => project_root.join("Content"),
+        "Engine" | "Script" => return None,
+        plugin => match find_plugin_root(project_root, plugin) {
+            Some(plugin_root) => plugin_root.join("Content"),
+            None => {
+                return Some(format!(
+                    "references {} under mount point /{}/, which does not match the project or any project plugin",
+                    reference, plugin
+                ))
+            }
+        },
+    };
+    let map_file = content_dir.join(package_path).with_extension("umap");
+    match map_file.is_file() {
+        true => None,
+        false => Some(format!(
+            "references {}, but {} does not exist",
+            reference,
+            map_file.display()
+        )),
+    }
+}
+
+fn find_plugin_root(project_root: &Path, plugin: &str) -> Option<PathBuf> {
+    unreal::plugin_directories(project_root)
+        .into_iter()
+        .flat_map(WalkDir::new)
+        .filter_map(Result::ok)
+        .find(|entry| {
+            entry.path().extension().is_some_and(|ext| ext == "uplugin")
+                && entry.path().file_stem().is_some_and(|stem| stem == plugin)
+        })
+        .and_then(|entry| entry.path().parent().map(Path::to_owned))
+}
+
+/// File extensions that rename workflows know how to update, or that are
+/// binary assets handled through redirects.
+const HANDLED_EXTENSIONS: [&str; 11] = [
+    "uproject", "uplugin", "ini", "cs", "h", "hpp", "inl", "c", "cpp", "uasset", "umap",
+];
+
+/// Find text files of types that no rename workflow updates (e.g. `.json`,
+/// `.xml`, `.ini.template`) but that reference a name about to be renamed,
+/// so the user can fix them manually.
+pub fn check_unhandled_references(project_root: &Path, name: &str) -> Vec<Diagnostic> {
+    let matcher = Matcher::literals([name]);
+    let ledger = ledger::ledger_path(project_root);
+    WalkDir::new(project_root)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_none_or(|name| !unreal::is_ignored_dir(name))
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file() && entry.path() != ledger)
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_none_or(|ext| !HANDLED_EXTENSIONS.contains(&ext))
+        })
+        .filter_map(|entry| {
+            let file = TextFile::read(entry.path()).ok()?;
+            if file.content.contains('\0') || !matcher.is_match(&file.content) {
+                return None;
+            }
+            let count = file.content.matches(name).count();
+            Some(Diagnostic {
+                path: entry.path().to_owned(),
+                message: format!(
+                    "{} reference(s) to {} in a file type that is not updated ({})",
+                    count,
+                    name,
+                    full_extension(entry.path())
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Warn the user about files that reference a name about to be renamed but
+/// are not updated by the rename, see [`check_unhandled_references`]. Files
+/// in `updated` are left out, e.g. generated project files that the rename
+/// updates after all.
+pub fn warn_about_unhandled_references(project_root: &Path, name: &str, updated: &[PathBuf]) {
+    let diagnostics = check_unhandled_references(project_root, name)
+        .into_iter()
+        .filter(|diagnostic| !updated.contains(&diagnostic.path))
+        .collect::<Vec<Diagnostic>>();
+    if diagnostics.is_empty() {
+        return;
+    }
+    let files = diagnostics
+        .iter()
+        .map(|diagnostic| format!("\t- {}", diagnostic))
+        .collect::<Vec<String>>()
+        .join("\n");
+    log::warning(format!(
+        "The following files reference {} but will not be updated:\n{}",
+        name, files
+    ));
+}
+
+/// Get everything after the first dot of a file name, e.g. `ini.template`.
+fn full_extension(path: &Path) -> String {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split_once('.'))
+        .map(|(_, ext)| format!(".{}", ext))
+        .unwrap_or_else(|| "no extension".into())
+}
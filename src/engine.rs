@@ -1,51 +1,1115 @@
-use std::path::Path;
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fs::OpenOptions,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
 
 use crate::{
-    changes::{Change, Revert},
+    backup,
+    cancel::CancellationToken,
+    changes::{
+        checksum_in, find_applied, simulate_edits, simulate_execution, verify_applied,
+        verify_backup, ApplyOptions, Change, Discrepancy, ExecutionConflict, RevertAction,
+    },
+    crash,
+    diff::FileDiff,
+    error::RenomError,
+    fs::{long_path, Fs, RealFs},
+    journal::{self, Entry},
     presentation::log,
+    progress::{Progress, ProgressObserver},
 };
 
-pub struct Engine {
-    history: Vec<(Change, Revert)>,
+pub struct Engine<F: Fs = RealFs> {
+    /// The filesystem that changes are applied to and reverted on.
+    fs: F,
+    history: Vec<(Change, RevertAction)>,
+    /// Points in the history between groups of changes, oldest first.
+    points: Vec<RollbackPoint>,
+    options: ApplyOptions,
+    cancellation: CancellationToken,
+    journal: Option<PathBuf>,
+    /// The operation log, see [`journal::log_path`].
+    log: Option<PathBuf>,
+    /// The number of changes logged so far, to index the next ones by.
+    logged: usize,
+    chunk_size: usize,
+    /// The threads to apply edits to different files on at once, if more
+    /// than one.
+    pool: Option<ThreadPool>,
+    /// Changes applied since the last checkpoint.
+    unflushed: usize,
+    /// Reverts skipped because their files were edited since.
+    skipped: Vec<RevertAction>,
+    observer: Option<Arc<dyn ProgressObserver>>,
+    /// Hooks run before each change is applied.
+    before_op: Vec<OpHook>,
+    /// Hooks run after each change is applied.
+    after_op: Vec<OpHook>,
+    /// The number of changes executed, applied or not.
+    total: usize,
+}
+
+/// A point in the history of an engine between groups of changes, e.g.
+/// between the renames of a changeset and its edits, that the changes applied
+/// since can be reverted to, see [`Engine::revert_to`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct RollbackPoint {
+    /// The name of the group of changes that follows the point.
+    pub label: String,
+    /// The number of changes applied before the point.
+    pub position: usize,
+}
+
+/// A hook run around each change that an engine applies, see
+/// [`Engine::on_before_op`] and [`Engine::on_after_op`].
+pub type OpHook = Arc<dyn Fn(&Change) -> Result<(), RenomError> + Send + Sync>;
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Engine {
     pub fn new() -> Self {
-        Self { history: vec![] }
+        Self::with_options(ApplyOptions::default())
+    }
+
+    /// Create an engine that applies changes with custom options.
+    pub fn with_options(options: ApplyOptions) -> Self {
+        Self::with_fs(RealFs, options)
+    }
+
+    /// Walk through a changeset against the files on disk without changing
+    /// anything, and report every change that would fail if it were
+    /// executed, e.g. because its file is missing or read-only, see
+    /// [`simulate_execution`].
+    pub fn simulate(changeset: &[Change]) -> Vec<ExecutionConflict> {
+        simulate_execution(changeset)
+    }
+
+    /// Get a diff of every file that a changeset would edit, without changing
+    /// anything, to show exactly which lines would change before they do.
+    /// Edits are previewed in order, on top of the renames and edits before
+    /// them, see [`simulate_edits`]. Upon a change that could not be applied,
+    /// returns the error.
+    pub fn preview(changeset: &[Change]) -> Result<Vec<FileDiff>, RenomError> {
+        simulate_edits(changeset)
+    }
+
+    /// Resume an execution that was interrupted, e.g. because the process
+    /// was killed, from the operation log in its backup directory, see
+    /// [`journal::log_path`]. Changes that were interrupted midway are found
+    /// to be applied or not from the files they touch. Changes are applied
+    /// and journaled as with [`Engine::execute`] from there on.
+    /// Upon error, it will halt execution and return the error; revert the
+    /// whole execution with [`Engine::revert`].
+    pub fn resume(&mut self, backup_dir: impl AsRef<Path>) -> Result<(), RenomError> {
+        let backup_dir = backup_dir.as_ref();
+        let recovered = recover(backup_dir)?;
+        let journal = journal::journal_path(backup_dir);
+        crash::set_journal(backup_dir, &journal);
+        self.journal = Some(journal);
+        self.log = Some(journal::log_path(backup_dir));
+        self.logged = recovered.changes.len();
+        self.total = recovered.changes.len();
+        // Log the recovered state afresh, so that a change found to be applied
+        // is not taken for interrupted again
+        let mut entries = vec![Entry::Start {
+            changes: recovered.changes.clone(),
+        }];
+        entries.extend(
+            recovered
+                .applied
+                .iter()
+                .map(|(index, _, action)| Entry::After {
+                    index: *index,
+                    action: action.clone(),
+                }),
+        );
+        journal::write_log(&journal::log_path(backup_dir), &entries)?;
+        log::basic(format!(
+            "Resuming after {} of {} change(s).",
+            recovered.applied.len(),
+            recovered.changes.len()
+        ));
+        self.unflushed = recovered.applied.len();
+        self.history = recovered
+            .applied
+            .into_iter()
+            .map(|(_, change, action)| (change, action))
+            .collect();
+        self.notify(|observer, progress| observer.started(progress));
+        let mut execution = Execution {
+            first: 0,
+            engine: self,
+            checked: false,
+            start: None,
+            pending: recovered.pending.into(),
+            backup_dir: backup_dir.to_owned(),
+        };
+        while execution.step(usize::MAX)? > 0 {}
+        Ok(())
+    }
+
+    /// Revert the changes recorded in a journal written by an earlier
+    /// execution, possibly by another process. The backups referenced by the
+    /// journal must still exist. The journal is updated as changes are
+    /// reverted and removed once all of them have been. Files edited since
+    /// their change was applied are left as is, with a warning. Returns where
+    /// the journal was last kept, which differs from `path` if a revert moved
+    /// the directory containing it. Backups archived since the execution
+    /// completed, see [`backup::archive`], are unpacked first.
+    /// Upon error, it will halt execution and return the error.
+    pub fn revert_from_journal(path: impl AsRef<Path>) -> Result<PathBuf, RenomError> {
+        Self::revert_journal(path.as_ref()).map_err(RenomError::revert)
+    }
+
+    fn revert_journal(path: &Path) -> Result<PathBuf, RenomError> {
+        let mut path = path.to_owned();
+        // Completed runs keep their backups in an archive
+        if let Some(backup_dir) = path.parent() {
+            backup::extract(backup_dir)?;
+        }
+        let log = path.parent().map(journal::log_path);
+        let mut actions = match log.as_deref().filter(|log| log.is_file()) {
+            // The log is ahead of the journal if the execution was killed
+            Some(log) => {
+                let backup_dir = log.parent().unwrap_or(Path::new("."));
+                let actions = recover(backup_dir)?
+                    .applied
+                    .into_iter()
+                    .map(|(_, _, action)| action)
+                    .collect::<Vec<RevertAction>>();
+                journal::write(&path, &actions)?;
+                journal::remove(log)?;
+                actions
+            }
+            None => journal::read(&path)?,
+        };
+        verify_backups(&RealFs, actions.iter())
+            .map_err(|err| RenomError::Revert(format!("{}; nothing was reverted", err)))?;
+        while let Some(action) = actions.pop() {
+            log::step("revert", &action);
+            match action.is_modified()? {
+                true => warn_about_modified(&action),
+                false => action.run()?,
+            }
+            if let RevertAction::Rename { from, to } = &action {
+                path = journal::follow_rename(&path, to, from).unwrap_or(path);
+            }
+            journal::write(&path, &actions)?;
+        }
+        remove_records(&path)?;
+        Ok(path)
+    }
+}
+
+impl<F: Fs> Engine<F> {
+    /// Create an engine that applies changes to a filesystem other than the
+    /// disk, e.g. a [`MemoryFs`](crate::fs::MemoryFs) to try a changeset out.
+    /// Executions are only journaled on a durable filesystem, see
+    /// [`Fs::DURABLE`].
+    pub fn with_fs(fs: F, options: ApplyOptions) -> Self {
+        Self {
+            fs,
+            history: vec![],
+            points: vec![],
+            options,
+            cancellation: CancellationToken::default(),
+            journal: None,
+            log: None,
+            logged: 0,
+            chunk_size: 1,
+            pool: None,
+            unflushed: 0,
+            skipped: vec![],
+            observer: None,
+            before_op: vec![],
+            after_op: vec![],
+            total: 0,
+        }
+    }
+
+    /// Get the filesystem that changes are applied to.
+    pub fn fs(&self) -> &F {
+        &self.fs
+    }
+
+    /// Stop execution before the next change once the token is cancelled.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Checkpoint execution after every `chunk_size` changes instead of after
+    /// every change. At a checkpoint, the journal and the backups taken since
+    /// the last one are flushed to disk, so an interruption loses at most one
+    /// chunk of progress. Larger chunks speed up big changesets.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Notify an observer as changes are applied and reverted, e.g. to show
+    /// the progress of big changesets.
+    pub fn with_observer(mut self, observer: Arc<dyn ProgressObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Apply edits to different files on up to `threads` threads at once, or
+    /// on a thread per CPU if `threads` is 0, to speed up big changesets.
+    /// Renames are applied one at a time in order, as the changes after them
+    /// may depend on them, and edits to the same file are applied in order.
+    /// The changes are journaled and reverted as if applied one by one.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.pool = match threads {
+            1 => None,
+            _ => ThreadPoolBuilder::new().num_threads(threads).build().ok(),
+        };
+        self
+    }
+
+    /// Register a hook to run before each change is applied, e.g. to check
+    /// its file out of version control. Hooks run in the order they were
+    /// registered, and a hook that fails stops the execution before the
+    /// change is applied.
+    pub fn on_before_op(
+        &mut self,
+        hook: impl Fn(&Change) -> Result<(), RenomError> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.before_op.push(Arc::new(hook));
+        self
+    }
+
+    /// Register a hook to run after each change is applied, e.g. to stage a
+    /// rename in version control or to log it to an external system. Hooks
+    /// run in the order they were registered, and a hook that fails stops
+    /// the execution with the change applied, to be reverted with the rest.
+    pub fn on_after_op(
+        &mut self,
+        hook: impl Fn(&Change) -> Result<(), RenomError> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.after_op.push(Arc::new(hook));
+        self
     }
 
     /// Execute a series of changes in sequential order and stores the
     /// applied changes in history with appropriate revert actions.
-    /// Upon error, it will halt execution and return the error.
+    /// The revert actions are also persisted to a journal in the backup
+    /// directory at each checkpoint, see [`Engine::with_chunk_size`] and
+    /// [`Engine::revert_from_journal`]. Once every change is applied, they are
+    /// verified to hold, see [`Engine::verify`], and any discrepancy fails the
+    /// execution.
+    /// Upon error or cancellation, it will halt execution and return the error.
     pub fn execute(
         &mut self,
         changeset: Vec<Change>,
         backup_dir: impl AsRef<Path>,
-    ) -> Result<(), String> {
-        for change in changeset {
-            log::step("apply", &change);
-            self.execute_single(change, backup_dir.as_ref())?;
+    ) -> Result<(), RenomError> {
+        let mut execution = self.start(changeset, backup_dir);
+        while execution.step(usize::MAX)? > 0 {}
+        Ok(())
+    }
+
+    /// Start executing a series of changes without applying any of them yet.
+    /// The returned [`Execution`] applies them a few at a time, so that
+    /// callers on an async runtime can yield between steps instead of
+    /// blocking a worker thread for the whole changeset. Changes are applied,
+    /// journaled, and reverted exactly as with [`Engine::execute`].
+    /// When backups are skipped, see [`ApplyOptions::no_backup`], nothing is
+    /// written to the backup directory and the changes are only tracked in
+    /// memory, so they can be reverted in process but not journaled.
+    pub fn start(
+        &mut self,
+        changeset: Vec<Change>,
+        backup_dir: impl AsRef<Path>,
+    ) -> Execution<'_, F> {
+        let next = self.logged;
+        self.total = self.history.len() + changeset.len();
+        self.notify(|observer, progress| observer.started(progress));
+        let start = match self.options.no_backup || !F::DURABLE {
+            true => None,
+            false => {
+                let journal = journal::journal_path(backup_dir.as_ref());
+                crash::set_journal(backup_dir.as_ref(), &journal);
+                self.journal = Some(journal);
+                self.log = Some(journal::log_path(backup_dir.as_ref()));
+                self.logged += changeset.len();
+                Some(changeset.clone())
+            }
+        };
+        Execution {
+            first: self.history.len(),
+            engine: self,
+            checked: false,
+            start,
+            pending: (next..).zip(changeset).collect(),
+            backup_dir: backup_dir.as_ref().to_owned(),
+        }
+    }
+
+    /// Check that every change applied so far holds, see [`verify_applied`],
+    /// e.g. that no other process wrote to the files meanwhile.
+    pub fn verify(&self) -> Vec<Discrepancy> {
+        verify_applied(&self.fs, &self.history)
+    }
+
+    /// Verify the changes applied from a position in the history on, and
+    /// fail with the discrepancies if any.
+    fn check_applied(&self, first: usize) -> Result<(), RenomError> {
+        let discrepancies = verify_applied(&self.fs, self.history.get(first..).unwrap_or_default());
+        if discrepancies.is_empty() {
+            return Ok(());
+        }
+        let details = discrepancies
+            .iter()
+            .map(|discrepancy| format!("\t- {}", discrepancy))
+            .collect::<Vec<String>>()
+            .join("\n");
+        Err(RenomError::Verification(format!(
+            "changes did not hold once applied:\n{}",
+            details
+        )))
+    }
+
+    /// Check that no file touched by the changes is held open by another
+    /// process, see [`Fs::locked`], so that the changes do not fail partway
+    /// through, before any of them is applied.
+    fn check_locked<'c>(
+        &self,
+        changes: impl Iterator<Item = &'c Change>,
+    ) -> Result<(), RenomError> {
+        let locked = changes
+            .flat_map(|change| self.fs.locked(change.path()))
+            .collect::<BTreeSet<PathBuf>>();
+        if locked.is_empty() {
+            return Ok(());
+        }
+        let details = locked
+            .iter()
+            .map(|path| format!("\t- {}", path.display()))
+            .collect::<Vec<String>>()
+            .join("\n");
+        Err(RenomError::Validation(format!(
+            "files are held open by another process, close them and try again:\n{}",
+            details
+        )))
+    }
+
+    /// Check that no change follows a symlink out of the root of the apply
+    /// options, e.g. an edit to a header in a plugin folder linked in from
+    /// another project, before any of them is applied. A renamed symlink is
+    /// moved itself, so only the folders it moves between are resolved.
+    fn check_links<'c>(&self, changes: impl Iterator<Item = &'c Change>) -> Result<(), RenomError> {
+        let root = match &self.options.root {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+        let resolved_root = self.resolve(root);
+        let escaping = changes
+            .flat_map(|change| match change {
+                Change::RenameFile(params) => vec![parent_of(&params.from), parent_of(&params.to)],
+                _ => vec![change.path()],
+            })
+            .filter(|path| path.starts_with(root))
+            .map(|path| (path, self.resolve(path)))
+            .filter(|(_, resolved)| !resolved.starts_with(&resolved_root))
+            .collect::<BTreeMap<&Path, PathBuf>>();
+        if escaping.is_empty() {
+            return Ok(());
+        }
+        let details = escaping
+            .iter()
+            .map(|(path, resolved)| {
+                format!("\t- {} leads to {}", path.display(), resolved.display())
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        Err(RenomError::Validation(format!(
+            "changes would follow symlinks out of {}, exclude them and try again:\n{}",
+            root.display(),
+            details
+        )))
+    }
+
+    /// Resolve the symlinks along a path that may not exist yet, e.g. the
+    /// destination of a rename, up to its closest existing ancestor.
+    fn resolve(&self, path: &Path) -> PathBuf {
+        path.ancestors()
+            .find_map(|ancestor| Some((ancestor, self.fs.canonicalize(ancestor).ok()?)))
+            .and_then(|(ancestor, resolved)| Some(resolved.join(path.strip_prefix(ancestor).ok()?)))
+            .unwrap_or_else(|| path.to_owned())
+    }
+
+    fn execute_next(
+        &mut self,
+        index: usize,
+        change: Change,
+        backup_dir: &Path,
+    ) -> Result<(), RenomError> {
+        self.cancellation.check()?;
+        log::step("apply", &change);
+        self.notify(|observer, progress| observer.change_started(&change, progress));
+        crash::set_operation(Some(format!("apply {:?}", change)));
+        run_hooks(&self.before_op, &change)?;
+        self.log_entry(&Entry::Before {
+            index,
+            checksum: checksum_before(&self.fs, &change),
+        })?;
+        self.execute_single(change, backup_dir)?;
+        if let Some((change, action)) = self.history.last() {
+            let action = action.clone();
+            self.notify(|observer, progress| observer.change_completed(change, progress));
+            self.log_entry(&Entry::After { index, action })?;
+            run_hooks(&self.after_op, change)?;
+        }
+        if self.unflushed >= self.chunk_size {
+            self.checkpoint()?;
         }
         Ok(())
     }
 
-    fn execute_single(&mut self, change: Change, backup_dir: &Path) -> Result<(), String> {
-        match change.apply(backup_dir) {
-            Ok(revert) => {
-                self.history.push((change, revert));
+    /// Apply changes that do not depend on each other at once, see
+    /// [`Engine::with_threads`]. Changes to the same file are applied in
+    /// order, and the other changes to a file are skipped once one fails.
+    fn execute_wave(
+        &mut self,
+        mut wave: Vec<(usize, Change)>,
+        backup_dir: &Path,
+    ) -> Result<(), RenomError> {
+        if wave.len() == 1 {
+            let (index, change) = wave.remove(0);
+            return self.execute_next(index, change, backup_dir);
+        }
+        self.cancellation.check()?;
+        for (_, change) in &wave {
+            run_hooks(&self.before_op, change)?;
+        }
+        for (index, change) in &wave {
+            log::step("apply", change);
+            self.notify(|observer, progress| observer.change_started(change, progress));
+            self.log_entry(&Entry::Before {
+                index: *index,
+                checksum: checksum_before(&self.fs, change),
+            })?;
+        }
+        crash::set_operation(Some(format!("apply {} changes at once", wave.len())));
+        let mut result = Ok(());
+        for (index, change, applied) in self.apply_at_once(wave, backup_dir) {
+            match applied {
+                Ok(action) => {
+                    log::detail("recorded", &action);
+                    self.history.push((change, action.clone()));
+                    self.unflushed += 1;
+                    if let Some((change, _)) = self.history.last() {
+                        self.notify(|observer, progress| {
+                            observer.change_completed(change, progress)
+                        });
+                    }
+                    self.log_entry(&Entry::After { index, action })?;
+                    if let Some((change, _)) = self.history.last() {
+                        if let Err(err) = run_hooks(&self.after_op, change) {
+                            result = result.and(Err(err));
+                        }
+                    }
+                }
+                Err(err) if result.is_ok() => result = Err(RenomError::from(err)),
+                Err(_) => {}
+            }
+        }
+        result?;
+        if self.unflushed >= self.chunk_size {
+            self.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    /// Apply edits on the thread pool, a file per task, and return the
+    /// outcome of each edit that was attempted in the order of the changeset.
+    fn apply_at_once(
+        &self,
+        wave: Vec<(usize, Change)>,
+        backup_dir: &Path,
+    ) -> Vec<(usize, Change, io::Result<RevertAction>)> {
+        let mut files = BTreeMap::<PathBuf, Vec<(usize, Change)>>::new();
+        for (index, change) in wave {
+            files
+                .entry(change.path().to_owned())
+                .or_default()
+                .push((index, change));
+        }
+        let apply_to_file = |changes: Vec<(usize, Change)>| {
+            let mut outcomes = vec![];
+            for (index, change) in changes {
+                let applied = match self.cancellation.check() {
+                    Ok(()) => change.apply_recorded_in(&self.fs, backup_dir, &self.options),
+                    Err(err) => Err(io::Error::other(err)),
+                };
+                let failed = applied.is_err();
+                outcomes.push((index, change, applied));
+                if failed {
+                    break;
+                }
+            }
+            outcomes
+        };
+        let files = files.into_values().collect::<Vec<_>>();
+        let mut outcomes = match &self.pool {
+            Some(pool) => pool.install(|| {
+                files
+                    .into_par_iter()
+                    .flat_map_iter(apply_to_file)
+                    .collect::<Vec<_>>()
+            }),
+            None => files.into_iter().flat_map(apply_to_file).collect(),
+        };
+        outcomes.sort_by_key(|(index, ..)| *index);
+        outcomes
+    }
+
+    fn execute_single(&mut self, change: Change, backup_dir: &Path) -> Result<(), RenomError> {
+        match change.apply_recorded_in(&self.fs, backup_dir, &self.options) {
+            Ok(action) => {
+                log::detail("recorded", &action);
+                if let RevertAction::Rename { from, to } = &action {
+                    self.follow_rename(from, to);
+                }
+                self.history.push((change, action));
+                self.unflushed += 1;
                 Ok(())
             }
-            Err(err) => Err(err.to_string()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Flush the journal and the backups taken since the last checkpoint to
+    /// disk. Without a journal, there is nothing to flush.
+    fn checkpoint(&mut self) -> Result<(), RenomError> {
+        if self.journal.is_none() {
+            self.unflushed = 0;
+        }
+        if self.unflushed == 0 {
+            return Ok(());
+        }
+        let backups = self.history[self.history.len() - self.unflushed..]
+            .iter()
+            .filter_map(|(_, action)| match action {
+                RevertAction::Restore { backup, .. } => Some(backup),
+                RevertAction::Rename { .. } | RevertAction::Rewrite { .. } => None,
+            });
+        for backup in backups {
+            sync(backup)?;
+        }
+        self.write_journal()?;
+        if let Some(log) = &self.log {
+            sync(log)?;
+        }
+        if let Some(journal) = &self.journal {
+            sync(journal)?;
+            log::detail("checkpoint", journal.display());
+        }
+        self.unflushed = 0;
+        Ok(())
+    }
+
+    fn notify(&self, event: impl FnOnce(&dyn ProgressObserver, Progress)) {
+        if let Some(observer) = &self.observer {
+            let progress = Progress {
+                completed: self.history.len(),
+                total: self.total,
+            };
+            event(observer.as_ref(), progress);
+        }
+    }
+
+    fn log_entry(&self, entry: &Entry) -> Result<(), RenomError> {
+        match &self.log {
+            Some(path) => journal::append(path, entry).map_err(RenomError::from),
+            None => Ok(()),
         }
     }
 
-    /// Revert entire history of actions.
+    /// Revert entire history of actions. Files edited since their change was
+    /// applied are left as is with their backups kept, see
+    /// [`Engine::skipped_reverts`].
     /// Upon error, it will halt execution and return the error.
-    pub fn revert(&mut self) -> Result<(), String> {
-        while let Some((change, revert)) = self.history.pop() {
+    pub fn revert(&mut self) -> Result<(), RenomError> {
+        self.revert_from(0).map_err(RenomError::revert)
+    }
+
+    /// Record a rollback point after the changes applied so far, e.g. between
+    /// executing the renames of a changeset and executing its edits, so that
+    /// the changes applied after it can be reverted on their own with
+    /// [`Engine::revert_to`].
+    pub fn rollback_point(&mut self, label: impl Into<String>) -> RollbackPoint {
+        let point = RollbackPoint {
+            label: label.into(),
+            position: self.history.len(),
+        };
+        self.points.push(point.clone());
+        point
+    }
+
+    /// Get the rollback points recorded so far, oldest first, e.g. for the
+    /// user to pick the group of changes to revert upon failure.
+    pub fn rollback_points(&self) -> &[RollbackPoint] {
+        &self.points
+    }
+
+    /// Revert the changes applied since a rollback point, keeping those
+    /// applied before it, e.g. to undo only the group of changes that failed.
+    /// Changes are reverted as with [`Engine::revert`], and the rollback
+    /// points after this one are dropped.
+    /// Upon error, it will halt execution and return the error.
+    pub fn revert_to(&mut self, point: &RollbackPoint) -> Result<(), RenomError> {
+        self.revert_from(point.position).map_err(RenomError::revert)
+    }
+
+    /// Revert the changes applied from a position in the history on. Once
+    /// some changes are left, they are recorded as the applied changes.
+    fn revert_from(&mut self, position: usize) -> Result<(), RenomError> {
+        let reverted = self.history.get(position..).unwrap_or_default();
+        verify_backups(&self.fs, reverted.iter().map(|(_, action)| action))
+            .map_err(|err| RenomError::Revert(format!("{}; nothing was reverted", err)))?;
+        self.points.retain(|point| point.position <= position);
+        self.unflushed = 0;
+        // From here on the journal is kept exact, so the operation log that
+        // may be ahead of it is no longer needed
+        self.write_journal()?;
+        if let Some(log) = &self.log {
+            journal::remove(log)?;
+        }
+        self.logged = 0;
+        while self.history.len() > position {
+            let Some((change, action)) = self.history.pop() else {
+                break;
+            };
             log::step("revert", &change);
-            revert().map_err(|err| err.to_string())?;
+            crash::set_operation(Some(format!("revert {:?}", change)));
+            if action.is_modified_in(&self.fs)? {
+                warn_about_modified(&action);
+                self.skipped.push(action);
+                self.write_journal()?;
+                self.notify(|observer, progress| observer.change_reverted(&change, progress));
+                continue;
+            }
+            action.run_in(&self.fs)?;
+            if let RevertAction::Rename { from, to } = &action {
+                self.follow_rename(to, from);
+            }
+            self.write_journal()?;
+            self.notify(|observer, progress| observer.change_reverted(&change, progress));
+        }
+        crash::set_operation(None);
+        match &self.journal {
+            Some(path) if self.history.is_empty() => remove_records(path),
+            Some(_) => self.write_changes(),
+            None => Ok(()),
+        }
+    }
+
+    /// Get the reverts skipped because their files were edited since their
+    /// changes were applied, for the user to resolve by hand.
+    pub fn skipped_reverts(&self) -> &[RevertAction] {
+        &self.skipped
+    }
+
+    /// Keep track of the journal and the operation log if a rename moves the
+    /// directory containing them.
+    fn follow_rename(&mut self, from: &Path, to: &Path) {
+        if let Some(journal) = &self.journal {
+            if let Some(moved) = journal::follow_rename(journal, from, to) {
+                self.journal = Some(moved);
+            }
+        }
+        if let Some(log) = &self.log {
+            if let Some(moved) = journal::follow_rename(log, from, to) {
+                self.log = Some(moved);
+            }
+        }
+    }
+
+    /// Persist the applied changes next to the journal once all of them have
+    /// been applied, for the rename to be restored later, see
+    /// [`journal::write_changes`].
+    fn write_changes(&self) -> Result<(), RenomError> {
+        let path = match self.journal.as_deref().and_then(Path::parent) {
+            Some(backup_dir) => journal::changes_path(backup_dir),
+            None => return Ok(()),
+        };
+        let changes = self
+            .history
+            .iter()
+            .map(|(change, _)| change.clone())
+            .collect::<Vec<Change>>();
+        journal::write_changes(&path, &changes).map_err(RenomError::from)
+    }
+
+    /// Log the start of an execution, before any of its changes is applied.
+    /// A record of changes completed earlier is removed, as they are no
+    /// longer all there is to the backup.
+    fn begin_log(&self, changes: Vec<Change>) -> Result<(), RenomError> {
+        if let Some(backup_dir) = self.log.as_deref().and_then(Path::parent) {
+            journal::remove(&journal::changes_path(backup_dir))?;
+        }
+        self.log_entry(&Entry::Start { changes })?;
+        if let Some(log) = &self.log {
+            sync(log)?;
         }
         Ok(())
     }
+
+    fn write_journal(&self) -> Result<(), RenomError> {
+        let path = match &self.journal {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let actions = self
+            .history
+            .iter()
+            .map(|(_, action)| action.clone())
+            .collect::<Vec<RevertAction>>();
+        journal::write(path, &actions).map_err(RenomError::from)
+    }
+}
+
+/// Remove the journal and the records of applied changes next to it once
+/// everything has been reverted.
+fn remove_records(journal: &Path) -> Result<(), RenomError> {
+    journal::remove(journal)?;
+    match journal.parent() {
+        Some(backup_dir) => {
+            journal::remove(&journal::changes_path(backup_dir))?;
+            journal::remove(&journal::log_path(backup_dir)).map_err(RenomError::from)
+        }
+        None => Ok(()),
+    }
+}
+
+/// The state of an execution as recovered from its operation log.
+struct Recovered {
+    /// Every change of the execution, in order.
+    changes: Vec<Change>,
+    /// The changes applied so far by index, with how to revert them.
+    applied: Vec<(usize, Change, RevertAction)>,
+    /// The changes left to apply by index.
+    pending: Vec<(usize, Change)>,
+}
+
+/// Recover the state of an execution from the operation log in its backup
+/// directory, including changes that were interrupted midway. Changes applied
+/// at once may have been logged out of order.
+fn recover(backup_dir: &Path) -> Result<Recovered, RenomError> {
+    let path = journal::log_path(backup_dir);
+    let entries = journal::read_log(&path)?;
+    let change_at = |changes: &[Change], index: usize| {
+        changes.get(index).cloned().ok_or_else(|| {
+            RenomError::Parse(format!(
+                "{} refers to change {}, which it does not list",
+                path.display(),
+                index
+            ))
+        })
+    };
+    let mut changes = vec![];
+    let mut applied = BTreeMap::new();
+    let mut in_flight = BTreeMap::new();
+    for entry in entries {
+        match entry {
+            Entry::Start { changes: started } => changes.extend(started),
+            Entry::Before { index, checksum } => {
+                in_flight.insert(index, checksum);
+            }
+            Entry::After { index, action } => {
+                in_flight.remove(&index);
+                applied.insert(index, action);
+            }
+        }
+    }
+    for (index, checksum) in in_flight {
+        let change = change_at(&changes, index)?;
+        let action = interrupted_action(&change, checksum.as_deref(), backup_dir)?;
+        if let Some(action) = action {
+            log::detail("recovered", &action);
+            applied.insert(index, action);
+        }
+    }
+    let pending = changes
+        .iter()
+        .cloned()
+        .enumerate()
+        .filter(|(index, _)| !applied.contains_key(index))
+        .collect();
+    let applied = applied
+        .into_iter()
+        .map(|(index, action)| Ok((index, change_at(&changes, index)?, action)))
+        .collect::<Result<Vec<_>, RenomError>>()?;
+    Ok(Recovered {
+        changes,
+        applied,
+        pending,
+    })
+}
+
+/// Check every backup about to be restored before reverting anything, so that
+/// a corrupted backup leaves the project as is rather than half reverted.
+fn verify_backups<'a>(
+    fs: &impl Fs,
+    actions: impl DoubleEndedIterator<Item = &'a RevertAction>,
+) -> Result<(), RenomError> {
+    let mut renames = vec![];
+    for action in actions.rev() {
+        match action {
+            RevertAction::Rename { from, to } => renames.push((from, to)),
+            RevertAction::Restore {
+                backup,
+                backup_checksum,
+                ..
+            } => {
+                // Backups are moved by the renames applied after them, which
+                // are reverted first
+                let backup = renames
+                    .iter()
+                    .rev()
+                    .fold(backup.clone(), |path, (from, to)| {
+                        journal::follow_rename(&path, from, to).unwrap_or(path)
+                    });
+                let content = fs.read(&backup).map_err(|err| {
+                    RenomError::from(err)
+                        .context(format!("backup {} cannot be read", backup.display()))
+                })?;
+                verify_backup(&backup, &content, backup_checksum.as_deref())?;
+            }
+            RevertAction::Rewrite { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+/// Find out whether a change that was interrupted midway was applied, judging
+/// by the files it touches, and if so how to revert it. An edit was applied if
+/// its file no longer has the checksum it had beforehand, which also names
+/// its backup.
+fn interrupted_action(
+    change: &Change,
+    checksum: Option<&str>,
+    backup_dir: &Path,
+) -> io::Result<Option<RevertAction>> {
+    if let Change::RenameFile(params) = change {
+        let applied =
+            params.from.symlink_metadata().is_err() && params.to.symlink_metadata().is_ok();
+        return Ok(applied.then(|| RevertAction::Rename {
+            from: params.from.clone(),
+            to: params.to.clone(),
+        }));
+    }
+    let checksum = match checksum {
+        Some(checksum) => checksum,
+        None => return Ok(None),
+    };
+    let current = checksum_in(&RealFs, change.path())?;
+    if current == checksum {
+        return Ok(None);
+    }
+    let backup = backup_dir.join(checksum);
+    match backup.is_file() {
+        true => Ok(Some(RevertAction::Restore {
+            backup,
+            target: change.path().to_owned(),
+            checksum: Some(current),
+            backup_checksum: Some(checksum.to_owned()),
+        })),
+        false => Err(io::Error::other(format!(
+            "{} was edited while its change was interrupted, but it has no backup in {}",
+            change.path().display(),
+            backup_dir.display()
+        ))),
+    }
+}
+
+/// Get the checksum of the file an edit is about to change, which names its
+/// backup. Renames have none.
+fn checksum_before(fs: &impl Fs, change: &Change) -> Option<String> {
+    match change {
+        Change::RenameFile(_) => None,
+        _ => checksum_in(fs, change.path()).ok(),
+    }
+}
+
+/// Warn that a file was left as is instead of being restored, as it was
+/// edited since the change was applied. Its backup is kept so that the user
+/// can resolve it.
+fn warn_about_modified(action: &RevertAction) {
+    match action {
+        RevertAction::Restore { backup, target, .. } => log::warning(format!(
+            "{} was edited after the change was applied and was left as is. Its previous content is kept in {} to resolve by hand.",
+            target.display(),
+            backup.display()
+        )),
+        RevertAction::Rewrite { target, .. } => log::warning(format!(
+            "{} was edited after the change was applied and was left as is. No backup was taken of its previous content.",
+            target.display()
+        )),
+        RevertAction::Rename { .. } => {}
+    }
+}
+
+/// A changeset being executed step by step, see [`Engine::start`]. The
+/// execution is `Send`, so it can be driven from an async task, e.g.
+/// applying a chunk of changes per poll and yielding in between.
+pub struct Execution<'a, F: Fs = RealFs> {
+    engine: &'a mut Engine<F>,
+    /// The position in the history of the first change of the execution, to
+    /// verify the changes from once all are applied.
+    first: usize,
+    /// Whether the changes were checked for locked files, which happens
+    /// before the first step.
+    checked: bool,
+    /// The changes to log as started before the first step, if logged.
+    start: Option<Vec<Change>>,
+    /// The changes left to apply, along with their index in the operation
+    /// log.
+    pending: VecDeque<(usize, Change)>,
+    backup_dir: PathBuf,
+}
+
+impl<F: Fs> Execution<'_, F> {
+    /// Apply up to `count` of the remaining changes and return how many are
+    /// left. Once none are left, or upon error or cancellation, the journal
+    /// is checkpointed; after an error, revert the applied changes with
+    /// [`Engine::revert`]. The first step fails without applying anything if
+    /// any of the files to change are locked by another process, or if a
+    /// change would follow a symlink out of the root of the apply options.
+    /// Changes already applied are skipped then if the options call for it.
+    pub fn step(&mut self, count: usize) -> Result<usize, RenomError> {
+        if !self.checked {
+            self.checked = true;
+            if self.engine.options.idempotent {
+                self.skip_applied();
+            }
+            self.engine
+                .check_locked(self.pending.iter().map(|(_, change)| change))?;
+            self.engine
+                .check_links(self.pending.iter().map(|(_, change)| change))?;
+        }
+        if let Some(changes) = self.start.take() {
+            self.engine.begin_log(changes)?;
+        }
+        let mut result = Ok(());
+        let mut left = count;
+        while left > 0 && !self.pending.is_empty() {
+            let wave = self.next_wave(left);
+            left -= wave.len();
+            let applied = self.engine.history.len();
+            result = self
+                .engine
+                .execute_wave(wave, &self.backup_dir)
+                .map_err(RenomError::execution);
+            self.follow_backup_dir(applied);
+            if result.is_err() {
+                self.pending.clear();
+                break;
+            }
+        }
+        if self.pending.is_empty() {
+            // Keep the journal complete up to the point of failure
+            let checkpoint = self.engine.checkpoint();
+            crash::set_operation(None);
+            result = result.and(checkpoint);
+            if result.is_ok() {
+                result = self.engine.check_applied(self.first);
+            }
+            if result.is_ok() {
+                result = self.engine.write_changes();
+            }
+            if result.is_ok() {
+                self.engine
+                    .notify(|observer, progress| observer.finished(progress));
+            }
+        }
+        result.map(|_| self.pending.len())
+    }
+
+    /// Keep track of the backup directory if a change applied since the
+    /// `applied` first ones moved it, e.g. along with the project root.
+    fn follow_backup_dir(&mut self, applied: usize) {
+        for (_, action) in &self.engine.history[applied..] {
+            if let RevertAction::Rename { from, to } = action {
+                if let Some(moved) = journal::follow_rename(&self.backup_dir, from, to) {
+                    self.backup_dir = moved;
+                }
+            }
+        }
+    }
+
+    /// Leave out the changes that an earlier run already applied, see
+    /// [`ApplyOptions::idempotent`].
+    fn skip_applied(&mut self) {
+        let changes = self
+            .pending
+            .iter()
+            .map(|(_, change)| change.clone())
+            .collect::<Vec<Change>>();
+        let applied = find_applied(
+            &self.engine.fs,
+            &changes,
+            &self.engine.options.applied_earlier,
+        );
+        let mut position = 0;
+        self.pending.retain(|(_, change)| {
+            let skipped = applied.contains(&position);
+            if skipped {
+                log::step("skip", change);
+            }
+            position += 1;
+            !skipped
+        });
+        self.engine.total -= applied.len();
+    }
+
+    /// Get the number of changes left to apply.
+    pub fn remaining(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Take up to `count` of the next changes that can be applied at once,
+    /// i.e. a rename on its own or the edits up to the next rename. Without a
+    /// thread pool, changes are taken one at a time.
+    fn next_wave(&mut self, count: usize) -> Vec<(usize, Change)> {
+        let mut wave = vec![];
+        while let Some((_, change)) = self.pending.front() {
+            let alone = self.engine.pool.is_none() || matches!(change, Change::RenameFile(_));
+            if wave.len() == count || (alone && !wave.is_empty()) {
+                break;
+            }
+            wave.extend(self.pending.pop_front());
+            if alone {
+                break;
+            }
+        }
+        wave
+    }
+}
+
+fn run_hooks(hooks: &[OpHook], change: &Change) -> Result<(), RenomError> {
+    hooks.iter().try_for_each(|hook| hook(change))
+}
+
+fn parent_of(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new(""))
+}
+
+/// Flush a file to disk. Opened for writing, as Windows requires it.
+fn sync(path: &Path) -> io::Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .open(long_path(path))?
+        .sync_all()
 }
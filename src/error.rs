@@ -0,0 +1,100 @@
+use std::{fmt::Display, io};
+
+use thiserror::Error;
+
+use crate::exit::ExitCode;
+
+/// Why a rename, or an operation of the engine, failed, so that library
+/// consumers can tell failures apart instead of matching on messages.
+#[derive(Debug, Error)]
+pub enum RenomError {
+    /// Reading or writing a file failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The arguments of a command were invalid or missing.
+    #[error("{0}")]
+    Argument(String),
+    /// The rename was rejected before any change was applied, e.g. because
+    /// of invalid params or conflicting changes.
+    #[error("{0}")]
+    Validation(String),
+    /// A file could not be parsed, e.g. a plan, a batch file, or an
+    /// operation log.
+    #[error("{0}")]
+    Parse(String),
+    /// Applying the changes failed midway, after some of them may have been
+    /// applied, with the error that stopped them.
+    #[error(transparent)]
+    Execution(Box<RenomError>),
+    /// Reverting applied changes failed, so the project may be left partly
+    /// renamed.
+    #[error("{0}")]
+    Revert(String),
+    /// The changes were applied, but some of them were found not to hold
+    /// afterwards, e.g. because another process wrote to the files meanwhile.
+    #[error("{0}")]
+    Verification(String),
+    /// The operation was cancelled, see
+    /// [`CancellationToken`](crate::cancel::CancellationToken).
+    #[error("operation was cancelled")]
+    Cancelled,
+}
+
+impl RenomError {
+    /// Wrap an error that stopped changes from being applied, unless it
+    /// already is an execution failure.
+    pub fn execution(err: RenomError) -> Self {
+        match err {
+            RenomError::Execution(_) => err,
+            err => RenomError::Execution(Box::new(err)),
+        }
+    }
+
+    /// Wrap an error that stopped changes from being reverted, unless it
+    /// already is a revert failure.
+    pub fn revert(err: RenomError) -> Self {
+        match err {
+            RenomError::Revert(_) => err,
+            err => RenomError::Revert(err.to_string()),
+        }
+    }
+
+    /// Prefix the message of the error with what was being done, keeping its
+    /// kind.
+    pub fn context(self, context: impl Display) -> Self {
+        match self {
+            RenomError::Io(err) => {
+                RenomError::Io(io::Error::new(err.kind(), format!("{}: {}", context, err)))
+            }
+            RenomError::Argument(message) => {
+                RenomError::Argument(format!("{}: {}", context, message))
+            }
+            RenomError::Validation(message) => {
+                RenomError::Validation(format!("{}: {}", context, message))
+            }
+            RenomError::Parse(message) => RenomError::Parse(format!("{}: {}", context, message)),
+            RenomError::Execution(err) => RenomError::Execution(Box::new(err.context(context))),
+            RenomError::Revert(message) => RenomError::Revert(format!("{}: {}", context, message)),
+            RenomError::Verification(message) => {
+                RenomError::Verification(format!("{}: {}", context, message))
+            }
+            RenomError::Cancelled => RenomError::Cancelled,
+        }
+    }
+
+    /// Get the exit code of a command that failed with the error.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            RenomError::Argument(_) => ExitCode::Argument,
+            RenomError::Execution(_) | RenomError::Verification(_) => ExitCode::Execution,
+            RenomError::Revert(_) => ExitCode::Revert,
+            _ => ExitCode::Validation,
+        }
+    }
+}
+
+impl From<RenomError> for String {
+    fn from(err: RenomError) -> Self {
+        err.to_string()
+    }
+}
@@ -0,0 +1,16 @@
+/// The exit code of the process, so that scripts can tell whether and how a
+/// command failed. Failure classes are ordered by severity.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum ExitCode {
+    /// The command succeeded.
+    Success = 0,
+    /// The arguments were invalid or missing.
+    Argument = 2,
+    /// The rename was rejected before any change was applied.
+    Validation = 3,
+    /// Applying the changes failed, and the changes applied so far were
+    /// reverted.
+    Execution = 4,
+    /// Reverting changes failed, so the project may be left partly renamed.
+    Revert = 5,
+}
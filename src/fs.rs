@@ -0,0 +1,485 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+/// The filesystem that an [`Engine`] applies changes to and reverts them on,
+/// so that changesets can be executed against something other than the disk,
+/// e.g. in memory for a dry run or a test.
+///
+/// [`Engine`]: crate::engine::Engine
+pub trait Fs: Send + Sync {
+    /// Whether changes made through the filesystem outlive the process. Only
+    /// executions on a durable filesystem are journaled, as the journal is
+    /// kept on disk to be reverted from later.
+    const DURABLE: bool = true;
+
+    /// Read the whole content of a file.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Write the whole content of a file, creating it if it does not exist.
+    /// An existing file keeps its permissions.
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()>;
+
+    /// Open a file to read it a piece at a time, e.g. to edit a big file
+    /// without holding all of it in memory.
+    fn open(&self, path: &Path) -> io::Result<Box<dyn BufRead + '_>> {
+        Ok(Box::new(io::Cursor::new(self.read(path)?)))
+    }
+
+    /// Write a file a piece at a time through a writer handed to `write`,
+    /// creating it if it does not exist. An existing file keeps its
+    /// permissions.
+    fn write_with(
+        &self,
+        path: &Path,
+        write: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut content = vec![];
+        write(&mut content)?;
+        self.write(path, &content)
+    }
+
+    /// Copy the content of a file to another, creating it if it does not
+    /// exist.
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.write(to, &self.read(from)?)
+    }
+
+    /// Move a file or directory, replacing the file at `to` if any.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Create a directory along with any missing parent.
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Get the metadata of a file or directory.
+    fn metadata(&self, path: &Path) -> io::Result<Metadata>;
+
+    /// Set the modification time of a file.
+    fn set_modified(&self, path: &Path, modified: SystemTime) -> io::Result<()>;
+
+    /// Set the permissions of a file to those in some metadata, i.e. its
+    /// mode on Unix, or else its read-only flag.
+    fn set_permissions(&self, path: &Path, metadata: &Metadata) -> io::Result<()>;
+
+    /// Get the files at or under a path that another process holds open
+    /// exclusively, so that writing or moving them would fail. Nothing is
+    /// reported if the filesystem has no such locks.
+    fn locked(&self, _path: &Path) -> Vec<PathBuf> {
+        vec![]
+    }
+
+    /// Resolve the symlinks along an existing path to get where it leads.
+    /// Filesystems without symlinks return the path as is.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        Ok(path.to_owned())
+    }
+}
+
+/// The metadata of a file or directory, see [`Fs::metadata`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct Metadata {
+    pub is_dir: bool,
+    /// The size of a file in bytes, or 0 for a directory.
+    pub len: u64,
+    pub readonly: bool,
+    /// The permission bits on Unix.
+    pub mode: Option<u32>,
+    /// The modification time, if the platform keeps it.
+    pub modified: Option<SystemTime>,
+}
+
+impl Metadata {
+    /// Get the metadata with the file made writable by its owner.
+    pub fn writable(&self) -> Self {
+        Self {
+            readonly: false,
+            mode: self.mode.map(|mode| mode | 0o200),
+            ..self.clone()
+        }
+    }
+}
+
+/// Write a file even if it is read-only, leaving it read-only, e.g. to revert
+/// an edit made with [`ApplyOptions::make_writable`].
+///
+/// [`ApplyOptions::make_writable`]: crate::changes::ApplyOptions::make_writable
+pub fn force_write(fs: &impl Fs, path: &Path, content: &[u8]) -> io::Result<()> {
+    let metadata = match fs.metadata(path) {
+        Ok(metadata) if metadata.readonly => metadata,
+        _ => return fs.write(path, content),
+    };
+    fs.set_permissions(path, &metadata.writable())?;
+    let written = fs.write(path, content);
+    fs.set_permissions(path, &metadata)?;
+    written
+}
+
+/// The filesystem on disk.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealFs;
+
+impl Fs for RealFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(long_path(path))
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        fs::write(long_path(path), content)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn BufRead + '_>> {
+        let file = fs::File::open(long_path(path))?;
+        Ok(Box::new(io::BufReader::new(file)))
+    }
+
+    fn write_with(
+        &self,
+        path: &Path,
+        write: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut file = io::BufWriter::new(fs::File::create(long_path(path))?);
+        write(&mut file)?;
+        file.flush()
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut source = fs::File::open(long_path(from))?;
+        let mut target = fs::File::create(long_path(to))?;
+        io::copy(&mut source, &mut target).map(|_| ())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(long_path(from), long_path(to))
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(long_path(path))
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let metadata = fs::metadata(long_path(path))?;
+        Ok(Metadata {
+            is_dir: metadata.is_dir(),
+            len: if metadata.is_dir() { 0 } else { metadata.len() },
+            readonly: metadata.permissions().readonly(),
+            mode: mode_of(&metadata.permissions()),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    fn set_modified(&self, path: &Path, modified: SystemTime) -> io::Result<()> {
+        fs::OpenOptions::new()
+            .write(true)
+            .open(long_path(path))?
+            .set_modified(modified)
+    }
+
+    fn set_permissions(&self, path: &Path, metadata: &Metadata) -> io::Result<()> {
+        let path = long_path(path);
+        let mut permissions = fs::metadata(&path)?.permissions();
+        match metadata.mode {
+            #[cfg(unix)]
+            Some(mode) => std::os::unix::fs::PermissionsExt::set_mode(&mut permissions, mode),
+            _ => permissions.set_readonly(metadata.readonly),
+        }
+        fs::set_permissions(path, permissions)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(long_path(path))
+    }
+
+    /// Probe every file under the path for exclusive access, as files open in
+    /// the Unreal Editor or Visual Studio cannot be moved on Windows. Other
+    /// platforms only have advisory locks, which do not stop a rename.
+    #[cfg(windows)]
+    fn locked(&self, path: &Path) -> Vec<PathBuf> {
+        walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(walkdir::DirEntry::into_path)
+            .filter(|path| is_locked(path))
+            .collect()
+    }
+}
+
+/// Get the extended-length form of a path on Windows, i.e. its absolute path
+/// prefixed with `\\?\`, so that files nested in deep plugin and module
+/// trees can be reached past the `MAX_PATH` limit of 260 characters. Paths
+/// are returned as is on other platforms, or if they cannot be extended.
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    match extended(path) {
+        Some(extended) => Cow::Owned(extended),
+        None => Cow::Borrowed(path),
+    }
+}
+
+#[cfg(windows)]
+fn extended(path: &Path) -> Option<PathBuf> {
+    use std::path::{Component, Prefix};
+
+    let absolute = std::path::absolute(path).ok()?;
+    let prefix = match absolute.components().next() {
+        Some(Component::Prefix(prefix)) => prefix.kind(),
+        _ => return None,
+    };
+    let absolute = absolute.to_str()?;
+    match prefix {
+        Prefix::Disk(_) => Some(format!(r"\\?\{}", absolute).into()),
+        // \\server\share becomes \\?\UNC\server\share
+        Prefix::UNC(..) => Some(format!(r"\\?\UNC{}", &absolute[1..]).into()),
+        // Already verbatim, or a device path
+        _ => None,
+    }
+}
+
+#[cfg(not(windows))]
+fn extended(_path: &Path) -> Option<PathBuf> {
+    None
+}
+
+/// Check whether a file or directory cannot be opened without sharing it,
+/// i.e. whether another process has it open.
+#[cfg(windows)]
+fn is_locked(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    // Directories can only be opened with backup semantics
+    let opened = fs::OpenOptions::new()
+        .read(true)
+        .share_mode(0)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(long_path(path));
+    matches!(
+        opened.map_err(|err| err.raw_os_error()),
+        Err(Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION))
+    )
+}
+
+#[cfg(unix)]
+fn mode_of(permissions: &fs::Permissions) -> Option<u32> {
+    Some(std::os::unix::fs::PermissionsExt::mode(permissions))
+}
+
+#[cfg(not(unix))]
+fn mode_of(_permissions: &fs::Permissions) -> Option<u32> {
+    None
+}
+
+/// A filesystem kept in memory, which starts out empty. Nothing written to it
+/// reaches the disk, and it is lost when dropped.
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    nodes: Mutex<BTreeMap<PathBuf, Node>>,
+    locked: Mutex<BTreeSet<PathBuf>>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    File {
+        content: Vec<u8>,
+        modified: SystemTime,
+        readonly: bool,
+        mode: Option<u32>,
+    },
+    Dir,
+}
+
+impl MemoryFs {
+    /// Get the paths of every file and directory, in order.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.nodes().keys().cloned().collect()
+    }
+
+    /// Hold a file open, as another process would, so that it is reported as
+    /// locked, see [`Fs::locked`].
+    pub fn lock(&self, path: impl Into<PathBuf>) {
+        self.locked_paths().insert(path.into());
+    }
+
+    fn locked_paths(&self) -> std::sync::MutexGuard<'_, BTreeSet<PathBuf>> {
+        self.locked.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn nodes(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, Node>> {
+        // The nodes are consistent after every operation, even one that
+        // panicked on another thread
+        self.nodes.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Check that the parent of a path is a directory, as on disk.
+    fn check_parent(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> io::Result<()> {
+        match path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            Some(parent) if !matches!(nodes.get(parent), Some(Node::Dir)) => Err(not_found(parent)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Fs for MemoryFs {
+    const DURABLE: bool = false;
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.nodes().get(path) {
+            Some(Node::File { content, .. }) => Ok(content.clone()),
+            Some(Node::Dir) => Err(is_a_directory(path)),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        let mut nodes = self.nodes();
+        Self::check_parent(&nodes, path)?;
+        let (readonly, mode) = match nodes.get(path) {
+            Some(Node::Dir) => return Err(is_a_directory(path)),
+            Some(Node::File { readonly: true, .. }) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("{} is read-only", path.display()),
+                ))
+            }
+            Some(Node::File { mode, .. }) => (false, *mode),
+            None => (false, None),
+        };
+        nodes.insert(
+            path.to_owned(),
+            Node::File {
+                content: content.to_vec(),
+                modified: SystemTime::now(),
+                readonly,
+                mode,
+            },
+        );
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes();
+        let node = nodes.get(from).cloned().ok_or_else(|| not_found(from))?;
+        Self::check_parent(&nodes, to)?;
+        match (&node, nodes.get(to)) {
+            (_, Some(Node::Dir)) | (Node::Dir, Some(Node::File { .. })) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", to.display()),
+                ))
+            }
+            _ => {}
+        }
+        // A directory moves along with everything in it
+        let moved = nodes
+            .keys()
+            .filter(|path| path.starts_with(from))
+            .cloned()
+            .collect::<Vec<PathBuf>>();
+        for path in moved {
+            if let Some(node) = nodes.remove(&path) {
+                let rest = path.strip_prefix(from).unwrap_or(Path::new(""));
+                let path = match rest.as_os_str().is_empty() {
+                    true => to.to_owned(),
+                    false => to.join(rest),
+                };
+                nodes.insert(path, node);
+            }
+        }
+        Ok(())
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes();
+        for dir in path.ancestors().filter(|dir| !dir.as_os_str().is_empty()) {
+            match nodes.get(dir) {
+                Some(Node::File { .. }) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("{} is a file", dir.display()),
+                    ))
+                }
+                Some(Node::Dir) => {}
+                None => {
+                    nodes.insert(dir.to_owned(), Node::Dir);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        match self.nodes().get(path) {
+            Some(Node::File {
+                content,
+                modified,
+                readonly,
+                mode,
+            }) => Ok(Metadata {
+                is_dir: false,
+                len: content.len() as u64,
+                readonly: *readonly,
+                mode: *mode,
+                modified: Some(*modified),
+            }),
+            Some(Node::Dir) => Ok(Metadata {
+                is_dir: true,
+                len: 0,
+                readonly: false,
+                mode: None,
+                modified: None,
+            }),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn set_modified(&self, path: &Path, time: SystemTime) -> io::Result<()> {
+        match self.nodes().get_mut(path) {
+            Some(Node::File { modified, .. }) => {
+                *modified = time;
+                Ok(())
+            }
+            Some(Node::Dir) => Err(is_a_directory(path)),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn set_permissions(&self, path: &Path, metadata: &Metadata) -> io::Result<()> {
+        match self.nodes().get_mut(path) {
+            Some(Node::File { readonly, mode, .. }) => {
+                *readonly = metadata.readonly;
+                *mode = metadata.mode;
+                Ok(())
+            }
+            Some(Node::Dir) => Err(is_a_directory(path)),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn locked(&self, path: &Path) -> Vec<PathBuf> {
+        self.locked_paths()
+            .iter()
+            .filter(|locked| locked.starts_with(path))
+            .cloned()
+            .collect()
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} does not exist", path.display()),
+    )
+}
+
+fn is_a_directory(path: &Path) -> io::Error {
+    io::Error::other(format!("{} is a directory", path.display()))
+}
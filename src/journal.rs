@@ -0,0 +1,171 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::changes::{Change, RevertAction};
+
+const JOURNAL_FILE_NAME: &str = "journal.json";
+const CHANGES_FILE_NAME: &str = "changes.json";
+const LOG_FILE_NAME: &str = "operations.jsonl";
+
+/// An entry of the operation log of an execution, see [`log_path`].
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Entry {
+    /// The execution started, with the changes to apply in order.
+    Start { changes: Vec<Change> },
+    /// The change at an index is about to be applied. For edits, this is the
+    /// checksum of the file beforehand, which names its backup.
+    Before {
+        index: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        checksum: Option<String>,
+    },
+    /// The change at an index was applied, and how to revert it.
+    After { index: usize, action: RevertAction },
+}
+
+/// Get the path to the journal kept alongside the backups in a backup
+/// directory.
+pub fn journal_path(backup_dir: &Path) -> PathBuf {
+    backup_dir.join(JOURNAL_FILE_NAME)
+}
+
+/// Get the path to the record of the changes applied by a completed
+/// execution, kept alongside the journal in a backup directory.
+pub fn changes_path(backup_dir: &Path) -> PathBuf {
+    backup_dir.join(CHANGES_FILE_NAME)
+}
+
+/// Get the path to the operation log kept alongside the journal in a backup
+/// directory. An entry is appended to it before and after each change, so
+/// that an execution that was killed midway can be resumed or reverted. It is
+/// removed once the execution completes or is reverted.
+pub fn log_path(backup_dir: &Path) -> PathBuf {
+    backup_dir.join(LOG_FILE_NAME)
+}
+
+/// Get where a path ends up after a file or directory is moved from `from`
+/// to `to`, or `None` if the path is not affected. Used to keep track of the
+/// journal when the directory containing it is renamed.
+pub fn follow_rename(path: &Path, from: &Path, to: &Path) -> Option<PathBuf> {
+    // Joining an empty rest would add a trailing separator, which only
+    // directories resolve with
+    path.strip_prefix(from)
+        .ok()
+        .map(|rest| match rest.as_os_str().is_empty() {
+            true => to.to_owned(),
+            false => to.join(rest),
+        })
+}
+
+/// Persist the revert actions for the changes applied so far, in the order
+/// they were applied.
+pub fn write(path: &Path, actions: &[RevertAction]) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(actions).map_err(io::Error::other)?;
+    fs::write(path, json)
+}
+
+/// Read the revert actions persisted in a journal.
+pub fn read(path: &Path) -> io::Result<Vec<RevertAction>> {
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(io::Error::other)
+}
+
+/// Persist the changes applied by a completed execution, so that they can be
+/// reviewed before they are reverted, possibly by another process.
+pub fn write_changes(path: &Path, changes: &[Change]) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(changes).map_err(io::Error::other)?;
+    fs::write(path, json)
+}
+
+/// Read the changes persisted by [`write_changes`].
+pub fn read_changes(path: &Path) -> io::Result<Vec<Change>> {
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(io::Error::other)
+}
+
+/// Append an entry to an operation log, as a line of JSON.
+pub fn append(path: &Path, entry: &Entry) -> io::Result<()> {
+    let mut line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Replace the entries of an operation log. The new entries are written to a
+/// temporary file first, so that the log is never left half written.
+pub fn write_log(path: &Path, entries: &[Entry]) -> io::Result<()> {
+    let mut content = String::new();
+    for entry in entries {
+        content += &serde_json::to_string(entry).map_err(io::Error::other)?;
+        content.push('\n');
+    }
+    let temp = path.with_extension("jsonl.tmp");
+    fs::write(&temp, content)?;
+    fs::rename(temp, path)
+}
+
+/// Read the entries of an operation log. A last line that was only partly
+/// written before the process was killed is skipped.
+pub fn read_log(path: &Path) -> io::Result<Vec<Entry>> {
+    let content = fs::read_to_string(path)?;
+    let lines = content.lines().collect::<Vec<&str>>();
+    let mut entries = vec![];
+    for (number, line) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) if number + 1 == lines.len() => break,
+            Err(err) => return Err(io::Error::other(format!("{}: {}", path.display(), err))),
+        }
+    }
+    Ok(entries)
+}
+
+/// Read the changes applied by an execution from the records in its backup
+/// directory, in the order they were applied along with how to revert them:
+/// from its operation log if it did not complete, or else from its journal
+/// and the changes it recorded once it completed. Executions without either
+/// have nothing recorded.
+pub fn read_applied(backup_dir: &Path) -> io::Result<Vec<(Change, RevertAction)>> {
+    let log = log_path(backup_dir);
+    if log.is_file() {
+        let entries = read_log(&log)?;
+        let changes = match entries.first() {
+            Some(Entry::Start { changes }) => changes,
+            _ => return Ok(vec![]),
+        };
+        return Ok(entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::After { index, action } => changes
+                    .get(*index)
+                    .map(|change| (change.clone(), action.clone())),
+                _ => None,
+            })
+            .collect());
+    }
+    let (journal, changes) = (journal_path(backup_dir), changes_path(backup_dir));
+    if !journal.is_file() || !changes.is_file() {
+        return Ok(vec![]);
+    }
+    Ok(read_changes(&changes)?
+        .into_iter()
+        .zip(read(&journal)?)
+        .collect())
+}
+
+/// Remove a journal once there is nothing left to revert.
+pub fn remove(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
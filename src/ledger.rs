@@ -0,0 +1,199 @@
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+
+use crate::changes::{AppendIniEntry, Change};
+
+/// A record of a single rename applied to a project.
+pub struct Entry {
+    /// The date that the rename was applied on.
+    pub date: String,
+    /// The user that applied the rename.
+    pub actor: String,
+    /// The kind of element that was renamed (project, plugin, etc.).
+    pub kind: String,
+    /// The name of the element before the rename.
+    pub old_name: String,
+    /// The name of the element after the rename.
+    pub new_name: String,
+    /// Redirects added to config as part of the rename.
+    pub redirects: Vec<String>,
+}
+
+impl Entry {
+    /// Create an entry for a rename applied today by the current user.
+    pub fn new(
+        kind: impl Into<String>,
+        old_name: impl Into<String>,
+        new_name: impl Into<String>,
+        redirects: Vec<String>,
+    ) -> Self {
+        Self {
+            date: Local::now().format("%Y-%m-%d").to_string(),
+            actor: current_actor(),
+            kind: kind.into(),
+            old_name: old_name.into(),
+            new_name: new_name.into(),
+            redirects,
+        }
+    }
+
+    fn from_row(row: &str) -> Option<Self> {
+        let cells = row
+            .trim()
+            .strip_prefix('|')?
+            .strip_suffix('|')?
+            .split('|')
+            .map(str::trim)
+            .collect::<Vec<&str>>();
+        if cells.len() != 6 || cells[0] == "Date" || cells[0].starts_with("---") {
+            return None;
+        }
+        let redirects = match cells[5] {
+            "-" => vec![],
+            redirects => redirects
+                .split("<br>")
+                .map(|redirect| redirect.trim_matches('`').to_owned())
+                .collect(),
+        };
+        Some(Self {
+            date: cells[0].to_owned(),
+            actor: cells[1].to_owned(),
+            kind: cells[2].to_owned(),
+            old_name: cells[3].to_owned(),
+            new_name: cells[4].to_owned(),
+            redirects,
+        })
+    }
+
+    fn to_row(&self) -> String {
+        let redirects = match self.redirects.is_empty() {
+            true => "-".to_owned(),
+            false => self
+                .redirects
+                .iter()
+                .map(|redirect| format!("`{}`", redirect))
+                .collect::<Vec<_>>()
+                .join("<br>"),
+        };
+        format!(
+            "| {} | {} | {} | {} | {} | {} |",
+            self.date, self.actor, self.kind, self.old_name, self.new_name, redirects
+        )
+    }
+}
+
+const HEADER: &str = "# Renames
+
+This file is maintained by Renom. Each row records a rename applied to this
+project, including any redirects added to config so that existing references
+keep working.
+
+| Date | Actor | Kind | Old name | New name | Redirects |
+| --- | --- | --- | --- | --- | --- |
+";
+
+/// Get the path to the ledger for a project.
+pub fn ledger_path(project_root: &Path) -> PathBuf {
+    project_root.join("RENAMES.md")
+}
+
+/// Append an entry to the ledger of a project, creating the ledger if it does
+/// not exist yet.
+pub fn append(project_root: &Path, entry: &Entry) -> io::Result<()> {
+    let path = ledger_path(project_root);
+    let mut content = match path.is_file() {
+        true => fs::read_to_string(&path)?,
+        false => HEADER.to_owned(),
+    };
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&entry.to_row());
+    content.push('\n');
+    fs::write(&path, content)
+}
+
+/// Read all entries from the ledger of a project, from oldest to newest.
+/// Returns no entries if the project does not have a ledger.
+pub fn read(project_root: &Path) -> io::Result<Vec<Entry>> {
+    let path = ledger_path(project_root);
+    if !path.is_file() {
+        return Ok(vec![]);
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(Entry::from_row)
+        .collect())
+}
+
+/// Infer the naming prefix used by recent renames, i.e. the longest prefix
+/// shared by the new names of the most recent entries. Requires at least two
+/// entries to avoid suggesting a full name.
+pub fn naming_prefix(entries: &[Entry]) -> Option<String> {
+    let recent = entries.iter().rev().take(5).collect::<Vec<&Entry>>();
+    if recent.len() < 2 {
+        return None;
+    }
+    let prefix = recent
+        .iter()
+        .skip(1)
+        .fold(recent[0].new_name.clone(), |prefix, entry| {
+            prefix
+                .chars()
+                .zip(entry.new_name.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        });
+    match prefix.len() >= 2 {
+        true => Some(prefix),
+        false => None,
+    }
+}
+
+/// Find the most recent entry that a rename would reverse, i.e. one that
+/// renamed an element of the same kind from the new name to the old name.
+pub fn find_reversal<'a>(
+    entries: &'a [Entry],
+    kind: &str,
+    old_name: &str,
+    new_name: &str,
+) -> Option<&'a Entry> {
+    entries.iter().rev().find(|entry| {
+        entry.kind == kind && entry.old_name == new_name && entry.new_name == old_name
+    })
+}
+
+/// Get the redirect that a change adds to config, formatted as it appears in
+/// the config file, if it adds one.
+pub fn redirect_in(change: &Change) -> Option<String> {
+    match change {
+        Change::AppendIniEntry(AppendIniEntry {
+            section,
+            key,
+            value,
+            ..
+        }) if section == "CoreRedirects" => Some(format!("{}={}", key, value)),
+        _ => None,
+    }
+}
+
+/// Collect every redirect recorded in the ledger, i.e. the redirects that
+/// Renom added to config as opposed to those written by hand.
+pub fn recorded_redirects(entries: &[Entry]) -> BTreeSet<&str> {
+    entries
+        .iter()
+        .flat_map(|entry| entry.redirects.iter().map(String::as_str))
+        .collect()
+}
+
+fn current_actor() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".into())
+}
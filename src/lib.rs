@@ -1,7 +1,31 @@
+pub mod backup;
+pub mod bench;
+pub mod build_info;
+pub mod cancel;
+pub mod capabilities;
 pub mod changes;
 pub mod cli;
+pub mod compatibility;
+pub mod completions;
+pub mod crash;
+pub mod diff;
+pub mod doctor;
 pub mod engine;
+pub mod error;
+pub mod exit;
+pub mod fs;
+pub mod journal;
+pub mod ledger;
+pub mod manifest;
+pub mod matcher;
+pub mod plan;
 pub mod presentation;
+pub mod progress;
+pub mod report;
+pub mod settings;
+pub mod suggest;
+pub mod text;
 pub mod unreal;
+pub mod vcs;
 pub mod wizard;
 pub mod workflows;
@@ -1,34 +1,67 @@
-use clap::Parser;
-use lmk::init_crash_reporter;
+use std::process::ExitCode;
+
 use renom::{
+    bench::bench,
     cli::{
         Cli,
-        Command::{RenameModule, RenamePlugin, RenameProject, RenameTarget, Wizard},
+        Command::{
+            Apply, Backups, Batch, Bench, Capabilities, Check, Completions, Doctor, List, Plan,
+            RenameModule, RenamePlugin, RenameProject, RenameTarget, Restore, Resume, Wizard,
+        },
     },
+    crash,
+    exit::{self, ExitCode::Success},
     presentation::log,
+    suggest::parse_args,
     wizard::start_interactive_dialogue,
-    workflows::{rename_module, rename_plugin, rename_project, rename_target},
 };
 
-fn main() {
-    init_crash_reporter!();
+fn main() -> ExitCode {
+    crash::install_panic_hook();
 
-    let cli = Cli::parse();
+    let cli: Cli = match parse_args(std::env::args_os()) {
+        Ok(cli) => cli,
+        Err(err) => err.exit(),
+    };
+    if let Some(path) = &cli.log_file {
+        if let Err(err) = log::open_log_file(path) {
+            log::error(format!(
+                "could not open log file {}: {}",
+                path.display(),
+                err
+            ));
+            return ExitCode::from(exit::ExitCode::Argument as u8);
+        }
+    }
     match cli.command {
         None => { /* noop, clap will handle top-level help and version */ }
         Some(command) => {
             if let Err(e) = match command {
-                RenameProject(params) => rename_project(params.into()),
-                RenamePlugin(params) => rename_plugin(params.into()),
-                RenameTarget(params) => rename_target(params.into()),
-                RenameModule(params) => rename_module(params.into()),
+                RenameProject(command) => command.run(),
+                RenamePlugin(command) => command.run(),
+                RenameTarget(command) => command.run(),
+                RenameModule(command) => command.run(),
+                Batch(command) => command.run(),
+                Restore(command) => command.run(),
+                Resume(command) => command.run(),
+                Backups(command) => command.run(),
+                List(command) => command.run(),
+                Doctor(command) => command.run(),
+                Plan(command) => command.run(),
+                Apply(command) => command.run(),
+                Check(command) => command.run(),
+                Completions(command) => command.run(),
+                Capabilities(command) => command.run(),
+                Bench(params) => bench(params.into()),
                 Wizard => {
                     start_interactive_dialogue();
                     Ok(())
                 }
             } {
-                log::error(e);
+                log::error(e.to_string());
+                return ExitCode::from(e.exit_code() as u8);
             }
         }
     };
+    ExitCode::from(Success as u8)
 }
@@ -0,0 +1,111 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    changes::{Category, Change, Changeset},
+    ledger,
+    vcs::Vcs,
+};
+
+/// The name of the manifest file in the project root.
+pub const MANIFEST_FILE_NAME: &str = "rename-manifest.json";
+
+/// A machine-readable summary of a rename, written to the project root in VCS
+/// mode so that it is committed along with the rename and can be picked up by
+/// review bots and release-note generators.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The kind of element that was renamed (project, plugin, etc.).
+    pub kind: String,
+    /// The name of the element before the rename.
+    pub old_name: String,
+    /// The name of the element after the rename.
+    pub new_name: String,
+    /// Redirects added to config as part of the rename.
+    pub redirects: Vec<String>,
+    /// The number of files touched in each category.
+    pub categories: BTreeMap<Category, usize>,
+    /// The number of files and directories touched by the rename.
+    pub files_changed: usize,
+    /// The number of individual changes applied.
+    pub changes: usize,
+}
+
+impl Manifest {
+    /// Summarize a changeset. Directories are detected on disk, so this should
+    /// be called before the changeset is applied.
+    pub fn new(
+        kind: impl Into<String>,
+        old_name: impl Into<String>,
+        new_name: impl Into<String>,
+        changeset: &[Change],
+    ) -> Self {
+        let categories = Changeset::new(changeset.to_vec())
+            .files_by_category()
+            .into_iter()
+            .map(|(category, files)| (category, files.len()))
+            .collect::<BTreeMap<Category, usize>>();
+        Self {
+            kind: kind.into(),
+            old_name: old_name.into(),
+            new_name: new_name.into(),
+            redirects: changeset.iter().filter_map(ledger::redirect_in).collect(),
+            files_changed: categories.values().sum(),
+            categories,
+            changes: changeset.len(),
+        }
+    }
+}
+
+/// Get the path to the manifest for a project.
+pub fn manifest_path(project_root: &Path) -> PathBuf {
+    project_root.join(MANIFEST_FILE_NAME)
+}
+
+/// Write the manifest of the latest rename to the project root, replacing
+/// the manifest of any earlier rename.
+pub fn write(project_root: &Path, manifest: &Manifest) -> io::Result<()> {
+    let content = serde_json::to_string_pretty(manifest).map_err(io::Error::other)?;
+    fs::write(manifest_path(project_root), content + "\n")
+}
+
+/// Write the manifest of a rename to the project root for review bots to pick
+/// up, if the project is under version control, see [`write`].
+pub fn write_for_review(
+    project_root: &Path,
+    manifest: &Manifest,
+    vcs: Option<Vcs>,
+) -> io::Result<()> {
+    match vcs {
+        Some(_) => write(project_root, manifest),
+        None => Ok(()),
+    }
+}
+
+/// Get the path to the report of the latest rename of a project, i.e. its
+/// manifest kept in `.renom` for the user to open after a wizard rename.
+pub fn report_path(project_root: &Path) -> PathBuf {
+    project_root.join(".renom").join("rename-report.json")
+}
+
+/// Write the manifest of the latest rename as the report of a project,
+/// replacing the report of any earlier rename.
+pub fn write_report(project_root: &Path, manifest: &Manifest) -> io::Result<()> {
+    let path = report_path(project_root);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content = serde_json::to_string_pretty(manifest).map_err(io::Error::other)?;
+    fs::write(path, content + "\n")
+}
+
+/// Read the manifest of the latest rename from the project root.
+pub fn read(project_root: &Path) -> io::Result<Manifest> {
+    let content = fs::read_to_string(manifest_path(project_root))?;
+    serde_json::from_str(&content).map_err(io::Error::other)
+}
@@ -0,0 +1,197 @@
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use aho_corasick::AhoCorasick;
+use memmap2::Mmap;
+use rayon::prelude::*;
+use regex::RegexSet;
+
+use crate::{
+    error::RenomError,
+    text::{Encoding, TextFile},
+};
+
+/// A set of literals and patterns compiled once and reused across every file
+/// in a scan. Literals are matched with Aho-Corasick and patterns with a single
+/// regex set, so each file is searched in one pass per kind regardless of how
+/// many names are being looked for.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    literals: Option<AhoCorasick>,
+    patterns: Option<RegexSet>,
+    required: Option<AhoCorasick>,
+}
+
+impl Matcher {
+    /// Build a matcher from literals and regex patterns. Returns an error if a
+    /// pattern is not a valid regex.
+    pub fn new<L, P>(literals: L, patterns: P) -> Result<Self, RenomError>
+    where
+        L: IntoIterator,
+        L::Item: AsRef<str>,
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        let literals = literals
+            .into_iter()
+            .map(|literal| literal.as_ref().to_owned())
+            .collect::<Vec<String>>();
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| pattern.as_ref().to_owned())
+            .collect::<Vec<String>>();
+
+        Ok(Self {
+            literals: match literals.is_empty() {
+                true => None,
+                false => Some(
+                    AhoCorasick::new(&literals)
+                        .map_err(|err| RenomError::Parse(err.to_string()))?,
+                ),
+            },
+            patterns: match patterns.is_empty() {
+                true => None,
+                false => Some(
+                    RegexSet::new(&patterns).map_err(|err| RenomError::Parse(err.to_string()))?,
+                ),
+            },
+            required: None,
+        })
+    }
+
+    /// Build a matcher from literals only.
+    pub fn literals<L>(literals: L) -> Self
+    where
+        L: IntoIterator,
+        L::Item: AsRef<str>,
+    {
+        Self::new(literals, std::iter::empty::<&str>()).expect("literals should always compile")
+    }
+
+    /// Only match haystacks that contain one of these literals, e.g. the old
+    /// name that every pattern looks for. Haystacks without any are ruled out
+    /// with Aho-Corasick before the patterns are run, which is much faster
+    /// for the many files that do not mention the name at all.
+    pub fn with_required<L>(mut self, literals: L) -> Self
+    where
+        L: IntoIterator,
+        L::Item: AsRef<str>,
+    {
+        let literals = literals
+            .into_iter()
+            .map(|literal| literal.as_ref().to_owned())
+            .collect::<Vec<String>>();
+        self.required = Some(AhoCorasick::new(literals).expect("literals should always compile"));
+        self
+    }
+
+    /// Whether any literal or pattern matches the haystack.
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.has_required(haystack)
+            && (self
+                .literals
+                .as_ref()
+                .is_some_and(|literals| literals.is_match(haystack))
+                || self
+                    .patterns
+                    .as_ref()
+                    .is_some_and(|patterns| patterns.is_match(haystack)))
+    }
+
+    /// Get the indices of every literal and pattern that matches the
+    /// haystack, in a single pass per kind. Literals are numbered first, then
+    /// patterns, in the order they were given.
+    pub fn matches(&self, haystack: &str) -> BTreeSet<usize> {
+        let mut matches = BTreeSet::new();
+        if !self.has_required(haystack) {
+            return matches;
+        }
+        let mut offset = 0;
+        if let Some(literals) = &self.literals {
+            matches.extend(
+                literals
+                    .find_overlapping_iter(haystack)
+                    .map(|found| found.pattern().as_usize()),
+            );
+            offset = literals.patterns_len();
+        }
+        if let Some(patterns) = &self.patterns {
+            matches.extend(
+                patterns
+                    .matches(haystack)
+                    .iter()
+                    .map(|index| offset + index),
+            );
+        }
+        matches
+    }
+
+    /// Whether any literal or pattern matches the contents of a file. Files
+    /// that cannot be read never match. With `mmap`, UTF-8 files are searched
+    /// through a memory map rather than read into memory; other encodings and
+    /// files that cannot be mapped fall back to a regular read.
+    pub fn is_match_in_file(&self, path: &Path, mmap: bool) -> bool {
+        search_file(path, mmap, |text| self.is_match(text)).unwrap_or(false)
+    }
+
+    /// Get the indices of every literal and pattern that matches the contents
+    /// of a file, see [`Matcher::matches`] and [`Matcher::is_match_in_file`].
+    pub fn matches_in_file(&self, path: &Path, mmap: bool) -> BTreeSet<usize> {
+        search_file(path, mmap, |text| self.matches(text)).unwrap_or_default()
+    }
+
+    /// Keep the files whose contents match, see [`Matcher::is_match_in_file`].
+    /// Files are searched in parallel, sharing the matcher, and kept in the
+    /// order they were given.
+    pub fn files_matching(&self, paths: Vec<PathBuf>, mmap: bool) -> Vec<PathBuf> {
+        paths
+            .into_par_iter()
+            .filter(|path| self.is_match_in_file(path, mmap))
+            .collect()
+    }
+
+    /// Get the indices of every literal and pattern that matches the contents
+    /// of each file, see [`Matcher::matches_in_file`]. Files are searched in
+    /// parallel, sharing the matcher, and the matches are in the order of the
+    /// files.
+    pub fn matches_in_files(&self, paths: &[PathBuf], mmap: bool) -> Vec<BTreeSet<usize>> {
+        paths
+            .par_iter()
+            .map(|path| self.matches_in_file(path, mmap))
+            .collect()
+    }
+
+    fn has_required(&self, haystack: &str) -> bool {
+        self.required
+            .as_ref()
+            .is_none_or(|required| required.is_match(haystack))
+    }
+}
+
+/// Search the text of a file, through a memory map with `mmap` where
+/// possible, or get `None` if it cannot be read.
+fn search_file<T>(path: &Path, mmap: bool, search: impl Fn(&str) -> T) -> Option<T> {
+    if mmap {
+        if let Some(found) = search_mapped_file(path, &search) {
+            return Some(found);
+        }
+    }
+    TextFile::read(path).ok().map(|file| search(&file.content))
+}
+
+fn search_mapped_file<T>(path: &Path, search: impl Fn(&str) -> T) -> Option<T> {
+    let file = File::open(path).ok()?;
+    // SAFETY: the map is read-only and dropped before returning. A file
+    // truncated by another process mid-scan is outside our control, as it
+    // would be for any tool that maps files.
+    let map = unsafe { Mmap::map(&file) }.ok()?;
+    let text = match Encoding::detect(&map) {
+        Encoding::Utf8 => std::str::from_utf8(&map).ok()?,
+        Encoding::Utf8Bom => std::str::from_utf8(&map[3..]).ok()?,
+        _ => return None,
+    };
+    Some(search(text))
+}
@@ -0,0 +1,127 @@
+use std::{fs, path::Path, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    changes::{Change, Changeset},
+    error::RenomError,
+};
+
+/// The version of the format plans are written in, raised whenever a change
+/// to it would stop older versions of Renom from reading plans correctly.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Changes planned for a rename, saved so that they can be reviewed and
+/// applied later. The parameters of the rename are kept so that the
+/// validation of the workflow can run again at apply time, against the state
+/// of the project at that point.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    /// The version of the format the plan was written in, see
+    /// [`SCHEMA_VERSION`]. Plans written before it was recorded are version 1.
+    #[serde(default = "first_version")]
+    pub version: u32,
+    /// The kind of element to rename (project, plugin, etc.).
+    pub kind: String,
+    /// The root of the project.
+    pub project_root: PathBuf,
+    /// The element to rename, or `None` when renaming the project itself.
+    pub name: Option<String>,
+    /// The new name for the element.
+    pub new_name: String,
+    /// The location of the element, when its name alone is ambiguous.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// The planned changes, in the order they will be applied.
+    pub changes: Vec<Change>,
+}
+
+impl Plan {
+    pub fn new(
+        kind: impl Into<String>,
+        project_root: impl Into<PathBuf>,
+        name: Option<String>,
+        new_name: impl Into<String>,
+        changeset: Changeset,
+    ) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            kind: kind.into(),
+            project_root: project_root.into(),
+            name,
+            new_name: new_name.into(),
+            path: None,
+            changes: changeset.into_changes(),
+        }
+    }
+
+    /// Record the location of the element to rename.
+    pub fn with_path(mut self, path: Option<PathBuf>) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Check that the plan was made for a kind of rename.
+    pub fn expect_kind(&self, kind: &str) -> Result<(), RenomError> {
+        match self.kind == kind {
+            true => Ok(()),
+            false => Err(RenomError::Parse(format!(
+                "plan is for renaming a {}, not a {}",
+                self.kind, kind
+            ))),
+        }
+    }
+
+    /// Get the element to rename, which every kind of rename except projects
+    /// needs.
+    pub fn expect_name(&self) -> Result<String, RenomError> {
+        self.name.clone().ok_or_else(|| {
+            RenomError::Parse(format!(
+                "plan for renaming a {} is missing its name",
+                self.kind
+            ))
+        })
+    }
+
+    pub fn changeset(&self) -> Changeset {
+        Changeset::new(self.changes.clone())
+    }
+}
+
+/// Write a plan to a file as JSON.
+pub fn write(path: &Path, plan: &Plan) -> Result<(), RenomError> {
+    let content =
+        serde_json::to_string_pretty(plan).map_err(|err| RenomError::Parse(err.to_string()))?;
+    fs::write(path, content + "\n")
+        .map_err(|err| RenomError::from(err).context("could not write plan"))
+}
+
+/// Read a plan written by [`write`].
+pub fn read(path: &Path) -> Result<Plan, RenomError> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| RenomError::Parse(format!("could not read plan: {}", err)))?;
+    let plan: Plan = serde_json::from_str(&content)
+        .map_err(|err| RenomError::Parse(format!("plan is not valid: {}", err)))?;
+    match plan.version <= SCHEMA_VERSION {
+        true => Ok(plan),
+        false => Err(RenomError::Parse(format!(
+            "plan was written in version {} of the plan format, but this version of Renom only reads up to version {}",
+            plan.version, SCHEMA_VERSION
+        ))),
+    }
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+/// Check that the changes a workflow generates now for the project still
+/// match the planned ones, so that a stale plan is not applied.
+pub fn check_is_current(planned: &Changeset, current: &[Change]) -> Result<(), RenomError> {
+    match planned.changes() == current {
+        true => Ok(()),
+        false => Err(RenomError::Validation(
+            "the project has changed since the plan was made, plan the rename again".into(),
+        )),
+    }
+}
@@ -1,6 +1,138 @@
 pub mod log {
     use colored::*;
-    use std::fmt::Display;
+    use regex::Regex;
+    use std::{
+        fmt::Display,
+        fs::{File, OpenOptions},
+        io::{self, Write},
+        path::Path,
+        sync::{
+            atomic::{AtomicBool, AtomicU8, Ordering},
+            Mutex, OnceLock,
+        },
+    };
+
+    static TO_STDERR: AtomicBool = AtomicBool::new(false);
+    static LEVEL: AtomicU8 = AtomicU8::new(Level::Normal as u8);
+    static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+    /// The progress bar shown on the last line of stderr, if any.
+    static PROGRESS: Mutex<Option<String>> = Mutex::new(None);
+
+    /// How much to print.
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum Level {
+        /// Only errors.
+        Quiet,
+        /// Progress, warnings, and errors.
+        Normal,
+        /// Also details of each operation as it executes.
+        Verbose,
+    }
+
+    /// Print like `println!`, or like `eprintln!` once output is redirected.
+    /// Prints nothing when quiet.
+    macro_rules! out {
+        ($($arg:tt)*) => {
+            if LEVEL.load(Ordering::Relaxed) > Level::Quiet as u8 {
+                emit!($($arg)*)
+            }
+        };
+    }
+
+    /// Print regardless of the level.
+    macro_rules! emit {
+        ($($arg:tt)*) => {
+            above_progress(|| match TO_STDERR.load(Ordering::Relaxed) {
+                true => eprintln!($($arg)*),
+                false => println!($($arg)*),
+            })
+        };
+    }
+
+    /// Print above the progress bar, if one is shown, by clearing it and
+    /// drawing it again below.
+    fn above_progress(print: impl FnOnce()) {
+        let progress = PROGRESS.lock();
+        let bar = progress.as_ref().ok().and_then(|bar| bar.as_deref());
+        if bar.is_some() {
+            eprint!("\r\x1b[K");
+        }
+        print();
+        if let Some(bar) = bar {
+            eprint!("{}", bar);
+        }
+    }
+
+    /// Show a progress bar on the last line of stderr, replacing the one shown
+    /// before, or clear it.
+    pub fn progress(bar: Option<String>) {
+        if let Ok(mut progress) = PROGRESS.lock() {
+            eprint!("\r\x1b[K{}", bar.as_deref().unwrap_or_default());
+            *progress = bar;
+        }
+    }
+
+    /// Also write every message from now on to a file as JSON lines, whatever
+    /// the level, for post-mortem analysis of a rename. Each line holds the
+    /// time, the kind of message, the process of steps, and the text without
+    /// colors. The file is appended to, starting with the command line.
+    pub fn open_log_file(path: &Path) -> io::Result<()> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        if let Ok(mut log_file) = LOG_FILE.lock() {
+            *log_file = Some(file);
+        }
+        let args = std::env::args().collect::<Vec<String>>().join(" ");
+        record("start", None, &args);
+        Ok(())
+    }
+
+    /// Write a message to the log file, if there is one.
+    fn record(kind: &str, process: Option<&str>, text: &str) {
+        static COLORS: OnceLock<Regex> = OnceLock::new();
+        let mut log_file = match LOG_FILE.lock() {
+            Ok(log_file) => log_file,
+            Err(_) => return,
+        };
+        let file = match log_file.as_mut() {
+            Some(file) => file,
+            None => return,
+        };
+        let colors = COLORS.get_or_init(|| Regex::new("\x1b\\[[0-9;]*m").unwrap());
+        let record = Record {
+            time: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            kind,
+            process: process.map(|process| colors.replace_all(process, "").into_owned()),
+            text: colors.replace_all(text.trim(), "").into_owned(),
+        };
+        // The log must never stop a rename
+        if let Ok(line) = serde_json::to_string(&record) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    /// A line of the log file.
+    #[derive(serde::Serialize)]
+    struct Record<'a> {
+        time: String,
+        kind: &'a str,
+        process: Option<String>,
+        text: String,
+    }
+
+    /// Set how much to print from now on.
+    pub fn set_level(level: Level) {
+        LEVEL.store(level as u8, Ordering::Relaxed);
+    }
+
+    fn is_verbose() -> bool {
+        LEVEL.load(Ordering::Relaxed) >= Level::Verbose as u8
+    }
+
+    /// Print all further output to stderr instead of stdout, keeping stdout
+    /// free for machine-readable output.
+    pub fn redirect_to_stderr() {
+        TO_STDERR.store(true, Ordering::Relaxed);
+    }
 
     /// Toggles coloring based on environment.
     /// For instance, colors do not work for `cmd`on Windows.
@@ -13,7 +145,8 @@ pub mod log {
 
     /// Print a header. Includes a preliminary newline.
     pub fn header<S: AsRef<str>>(text: S) {
-        println!(
+        record("header", None, text.as_ref());
+        out!(
             "\n{open_brace} {text} {close_brace}",
             open_brace = "[".green(),
             text = text.as_ref(),
@@ -23,12 +156,14 @@ pub mod log {
 
     /// Print the text without any frills.
     pub fn basic<S: AsRef<str>>(text: S) {
-        println!("{}", text.as_ref());
+        record("info", None, text.as_ref());
+        out!("{}", text.as_ref());
     }
 
     /// Print a step.
     pub fn step<A: Display, B: Display>(process: A, text: B) {
-        println!(
+        record("step", Some(&process.to_string()), &text.to_string());
+        out!(
             "{open_paren} {process} {close_paren} {text}",
             open_paren = "(".purple(),
             process = process,
@@ -37,13 +172,281 @@ pub mod log {
         )
     }
 
+    /// Print a step only when verbose, e.g. the details of an operation.
+    pub fn detail<A: Display, B: Display>(process: A, text: B) {
+        match is_verbose() {
+            true => step(process, text),
+            false => record("detail", Some(&process.to_string()), &text.to_string()),
+        }
+    }
+
     /// Print a success message.
     pub fn success<S: AsRef<str>>(text: S) {
-        println!("\n\t[ Success ]\n\t{}\n", text.as_ref().bright_green());
+        record("success", None, text.as_ref());
+        out!("\n\t[ Success ]\n\t{}\n", text.as_ref().bright_green());
+    }
+
+    /// Print a warning.
+    pub fn warning<S: AsRef<str>>(text: S) {
+        record("warning", None, text.as_ref());
+        out!("\n\t[ Warning ]\n\t{}\n", text.as_ref().yellow());
     }
 
     /// Print an error.
     pub fn error<S: AsRef<str>>(text: S) {
-        println!("\n\t[ Error ]\n\t{}\n", text.as_ref().red());
+        record("error", None, text.as_ref());
+        emit!("\n\t[ Error ]\n\t{}\n", text.as_ref().red());
+    }
+}
+
+/// A progress bar for changes being applied, drawn on stderr below the rest of
+/// the output.
+pub mod progress {
+    use colored::*;
+
+    use super::log;
+    use crate::{
+        changes::Change,
+        progress::{Progress, ProgressObserver},
+    };
+
+    const WIDTH: usize = 30;
+
+    /// Draws a bar as changes are applied and reverted, and clears it once
+    /// every change is applied.
+    #[derive(Debug, Default)]
+    pub struct Bar;
+
+    impl Bar {
+        fn draw(&self, process: &str, progress: Progress) {
+            let filled = match progress.total {
+                0 => WIDTH,
+                total => progress.completed.min(total) * WIDTH / total,
+            };
+            log::progress(Some(format!(
+                "{open_paren} {process} {close_paren} [{filled}{empty}] {completed}/{total}",
+                open_paren = "(".purple(),
+                close_paren = ")".purple(),
+                filled = "#".repeat(filled).green(),
+                empty = "-".repeat(WIDTH - filled),
+                completed = progress.completed,
+                total = progress.total,
+            )));
+        }
+    }
+
+    impl ProgressObserver for Bar {
+        fn started(&self, progress: Progress) {
+            self.draw("apply", progress);
+        }
+
+        fn change_completed(&self, _change: &Change, progress: Progress) {
+            self.draw("apply", progress);
+        }
+
+        fn change_reverted(&self, _change: &Change, progress: Progress) {
+            match progress.completed {
+                0 => log::progress(None),
+                _ => self.draw("revert", progress),
+            }
+        }
+
+        fn finished(&self, _progress: Progress) {
+            log::progress(None);
+        }
+    }
+}
+
+/// A small terminal interface for reviewing a changeset before it is applied.
+/// Affected files are listed a page at a time, can be filtered by typing part
+/// of their path, and can be expanded to show their diff.
+pub mod preview {
+    use colored::*;
+    use inquire::Select;
+    use std::{
+        collections::BTreeMap,
+        fmt::Display,
+        path::{Path, PathBuf},
+    };
+
+    use crate::{
+        changes::{Category, Change},
+        diff,
+        error::RenomError,
+        text::TextFile,
+    };
+
+    const FILES_PER_PAGE: usize = 15;
+    const DIFF_LINES_PER_PAGE: usize = 40;
+
+    /// The changes to a single file or directory.
+    struct FileChanges<'a> {
+        path: PathBuf,
+        category: Category,
+        label: String,
+        changes: Vec<&'a Change>,
+    }
+
+    enum Entry {
+        Apply(usize),
+        Cancel,
+        File(usize),
+    }
+
+    /// Let the user page through the files affected by a changeset before
+    /// applying it. Paths are shown relative to the root. Returns whether the
+    /// user chose to apply the changes. Failing prompts reject the changes.
+    pub fn review(changeset: &[Change], root: &Path) -> Result<bool, RenomError> {
+        let files = group_by_file(changeset, root);
+        let mut cursor = 0;
+        loop {
+            let mut entries = vec![Entry::Apply(changeset.len()), Entry::Cancel];
+            entries.extend((0..files.len()).map(Entry::File));
+            let labels = entries
+                .iter()
+                .map(|entry| describe(entry, &files))
+                .collect::<Vec<String>>();
+            let message = format!(
+                "Review {} change(s) to {} file(s):",
+                changeset.len(),
+                files.len()
+            );
+            let choice = Select::new(&message, labels)
+                .with_page_size(FILES_PER_PAGE)
+                .with_starting_cursor(cursor)
+                .with_help_message("↑↓ to move, type to filter, enter to expand a file")
+                .raw_prompt()
+                .map_err(|err| RenomError::Validation(err.to_string()))?;
+            cursor = choice.index;
+            match entries[choice.index] {
+                Entry::Apply(_) => return Ok(true),
+                Entry::Cancel => return Ok(false),
+                Entry::File(index) => {
+                    show_file(&files[index], root).map_err(RenomError::Validation)?
+                }
+            }
+        }
+    }
+
+    fn describe(entry: &Entry, files: &[FileChanges]) -> String {
+        match entry {
+            Entry::Apply(count) => format!("Apply {} change(s)", count),
+            Entry::Cancel => "Cancel".to_owned(),
+            Entry::File(index) => files[*index].to_string(),
+        }
+    }
+
+    fn group_by_file<'a>(changeset: &'a [Change], root: &Path) -> Vec<FileChanges<'a>> {
+        let mut files: BTreeMap<&Path, Vec<&Change>> = BTreeMap::new();
+        for change in changeset {
+            files.entry(change.path()).or_default().push(change);
+        }
+        files
+            .into_iter()
+            .map(|(path, changes)| FileChanges {
+                path: path.to_owned(),
+                category: Category::of(path),
+                label: relative_to(path, root),
+                changes,
+            })
+            .collect()
+    }
+
+    /// Display a path relative to the root, or in full if it is outside of
+    /// the root or is the root itself.
+    fn relative_to(path: &Path, root: &Path) -> String {
+        match path.strip_prefix(root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative.display().to_string(),
+            _ => path.display().to_string(),
+        }
+    }
+
+    /// Print the renames and diff of a file, a page at a time.
+    fn show_file(file: &FileChanges, root: &Path) -> Result<(), String> {
+        let mut lines = vec![file.label.bold().to_string()];
+        for change in &file.changes {
+            if let Change::RenameFile(params) = change {
+                lines.push(
+                    format!("rename to {}", relative_to(&params.to, root))
+                        .cyan()
+                        .to_string(),
+                );
+            }
+        }
+        if file
+            .changes
+            .iter()
+            .any(|change| !matches!(change, Change::RenameFile(_)))
+        {
+            match edited_content(&file.path, &file.changes) {
+                Ok((before, after)) => lines.extend(diff(&before, &after)),
+                Err(err) => lines.push(
+                    format!("could not preview edits: {}", err)
+                        .red()
+                        .to_string(),
+                ),
+            }
+        }
+
+        let pages = lines.chunks(DIFF_LINES_PER_PAGE).collect::<Vec<_>>();
+        for (number, page) in pages.iter().enumerate() {
+            println!("{}", page.join("\n"));
+            if number + 1 == pages.len() {
+                break;
+            }
+            let message = format!("Page {} of {}", number + 1, pages.len());
+            let more = Select::new(&message, vec!["Show more", "Back to files"])
+                .prompt()
+                .map_err(|err| err.to_string())?;
+            if more == "Back to files" {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the content of a file before and after applying its edits.
+    fn edited_content(path: &Path, changes: &[&Change]) -> Result<(String, String), String> {
+        let mut file = TextFile::read(path).map_err(|err| err.to_string())?;
+        let before = file.content.clone();
+        for change in changes {
+            if let Some(content) = change.edit(&file).map_err(|err| err.to_string())? {
+                file.content = content;
+            }
+        }
+        Ok((before, file.content))
+    }
+
+    /// Produce a line-based diff of two texts, with a few lines of context
+    /// around each hunk, colored for the terminal. See [`diff::unified`].
+    pub fn diff(before: &str, after: &str) -> Vec<String> {
+        diff::unified(before, after)
+            .into_iter()
+            .map(|line| match line.chars().next() {
+                Some('@') => line.cyan().to_string(),
+                Some('-') => line.red().to_string(),
+                Some('+') => line.green().to_string(),
+                _ => line,
+            })
+            .collect()
+    }
+
+    impl Display for FileChanges<'_> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let in_literals = self.changes.iter().any(
+                |change| matches!(change, Change::ReplaceInFile(params) if params.in_literals),
+            );
+            write!(
+                f,
+                "{} ({}, {} change(s){})",
+                self.label,
+                self.category.tag(),
+                self.changes.len(),
+                match in_literals {
+                    true => ", string literals",
+                    false => "",
+                }
+            )
+        }
     }
 }
@@ -0,0 +1,33 @@
+use crate::changes::Change;
+
+/// How far an execution has come, see [`ProgressObserver`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Progress {
+    /// The number of changes applied and not reverted so far.
+    pub completed: usize,
+    /// The number of changes in the execution.
+    pub total: usize,
+}
+
+/// Notified by an [`Engine`] as it applies and reverts changes, e.g. to render
+/// a progress bar. Every event is sent from the thread that drives the
+/// execution, in the order of the changeset, even when edits are applied on
+/// several threads at once. Every method does nothing by default.
+///
+/// [`Engine`]: crate::engine::Engine
+pub trait ProgressObserver: Send + Sync {
+    /// The execution started, or resumed with some changes already applied.
+    fn started(&self, _progress: Progress) {}
+
+    /// A change is about to be applied.
+    fn change_started(&self, _change: &Change, _progress: Progress) {}
+
+    /// A change was applied.
+    fn change_completed(&self, _change: &Change, _progress: Progress) {}
+
+    /// A change was reverted, or left as is because its file was edited since.
+    fn change_reverted(&self, _change: &Change, _progress: Progress) {}
+
+    /// Every change was applied.
+    fn finished(&self, _progress: Progress) {}
+}
@@ -0,0 +1,250 @@
+use serde::Serialize;
+
+use crate::{
+    changes::{Category, Change},
+    compatibility,
+    error::RenomError,
+};
+
+/// The outcome of a rename command.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// The params were invalid or the changes could not be planned.
+    Invalid,
+    /// The changes were planned but not applied, e.g. in a dry run.
+    Planned,
+    /// The changes were applied.
+    Applied,
+    /// Applying the changes failed, and the changes applied so far were
+    /// reverted.
+    Failed,
+}
+
+/// A machine-readable report of a rename command, so that build pipelines
+/// can parse the outcome instead of scraping log output.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// The kind of element renamed (project, plugin, etc.).
+    pub kind: String,
+    /// The outcome of the command.
+    pub status: Status,
+    /// The error that stopped the command, if any.
+    pub error: Option<String>,
+    /// The changes planned for the rename.
+    pub planned: Vec<Change>,
+    /// The changes that were applied and remain applied.
+    pub executed: Vec<Change>,
+    /// How the planned changes affect builds and data made before them, such
+    /// as the save games of packaged builds, see [`compatibility::notes`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub compatibility: Vec<String>,
+}
+
+impl Report {
+    /// Create a report for a rename whose changes could not be planned.
+    pub fn invalid(kind: impl Into<String>, error: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            status: Status::Invalid,
+            error: Some(error.into()),
+            planned: vec![],
+            executed: vec![],
+            compatibility: vec![],
+        }
+    }
+
+    /// Create a report for a rename whose changes were planned but not
+    /// applied.
+    pub fn planned(kind: impl Into<String>, planned: Vec<Change>) -> Self {
+        Self {
+            kind: kind.into(),
+            status: Status::Planned,
+            error: None,
+            compatibility: compatibility::notes(&planned),
+            planned,
+            executed: vec![],
+        }
+    }
+
+    /// Create a report for a rename whose planned changes were applied, or
+    /// reverted upon error.
+    pub fn applied(
+        kind: impl Into<String>,
+        planned: Vec<Change>,
+        result: &Result<(), RenomError>,
+    ) -> Self {
+        let (status, error, executed) = match result {
+            Ok(()) => (Status::Applied, None, planned.clone()),
+            Err(err) => (Status::Failed, Some(err.to_string()), vec![]),
+        };
+        Self {
+            kind: kind.into(),
+            status,
+            error,
+            compatibility: compatibility::notes(&planned),
+            planned,
+            executed,
+        }
+    }
+
+    /// Print the report as JSON on stdout.
+    pub fn print(&self) -> Result<(), RenomError> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|err| RenomError::Parse(err.to_string()))?;
+        println!("{}", json);
+        Ok(())
+    }
+
+    /// Get a row for each planned change, e.g. for producers to track what
+    /// changed in a spreadsheet.
+    pub fn rows(&self) -> Vec<Row> {
+        self.planned.iter().map(Row::of).collect()
+    }
+
+    /// Render the report as CSV, with a header and a row for each planned
+    /// change along with the status of the rename.
+    pub fn to_csv(&self) -> String {
+        let status = serde_json::to_value(self.status)
+            .ok()
+            .and_then(|status| status.as_str().map(str::to_owned))
+            .unwrap_or_default();
+        let mut csv = String::from("path,category,action,old_value,new_value,status\n");
+        for row in self.rows() {
+            let fields = [
+                row.path,
+                row.category.tag().to_owned(),
+                row.action.to_owned(),
+                row.old_value,
+                row.new_value,
+                status.clone(),
+            ];
+            let fields = fields.iter().map(|field| escape(field)).collect::<Vec<_>>();
+            csv += &fields.join(",");
+            csv += "\n";
+        }
+        csv
+    }
+
+    /// Print the report as CSV on stdout.
+    pub fn print_csv(&self) -> Result<(), RenomError> {
+        print!("{}", self.to_csv());
+        Ok(())
+    }
+}
+
+/// A consolidated report of a batch of renames applied in order, with a
+/// report for each rename that was attempted. Renames after the first one that
+/// fails are not attempted.
+#[derive(Debug, Serialize)]
+pub struct BatchReport {
+    /// The outcome of the batch: `applied` if every rename was applied, or
+    /// else the outcome of the rename that failed.
+    pub status: Status,
+    /// The number of renames in the batch.
+    pub total: usize,
+    /// The reports of the renames attempted, in order.
+    pub renames: Vec<Report>,
+}
+
+impl BatchReport {
+    /// Create a report for a batch from the reports of the renames attempted.
+    pub fn new(total: usize, renames: Vec<Report>) -> Self {
+        Self {
+            status: renames
+                .iter()
+                .map(|report| report.status)
+                .find(|status| *status != Status::Applied)
+                .unwrap_or(Status::Applied),
+            total,
+            renames,
+        }
+    }
+
+    /// Print the report as JSON on stdout.
+    pub fn print(&self) -> Result<(), RenomError> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|err| RenomError::Parse(err.to_string()))?;
+        println!("{}", json);
+        Ok(())
+    }
+
+    /// Print the rows of every rename as CSV on stdout, under a single
+    /// header.
+    pub fn print_csv(&self) -> Result<(), RenomError> {
+        let mut csv = String::new();
+        for (index, report) in self.renames.iter().enumerate() {
+            let rows = report.to_csv();
+            match index {
+                0 => csv += &rows,
+                _ => csv.extend(rows.split_inclusive('\n').skip(1)),
+            }
+        }
+        print!("{}", csv);
+        Ok(())
+    }
+}
+
+/// A change to a single file, flattened for tabular reports.
+#[derive(Debug, PartialEq)]
+pub struct Row {
+    /// The file or directory changed.
+    pub path: String,
+    /// The kind of file changed.
+    pub category: Category,
+    /// What was done to the file.
+    pub action: &'static str,
+    /// What was replaced, e.g. the old path of a renamed file, if known.
+    pub old_value: String,
+    /// What it was replaced with, e.g. the new path of a renamed file.
+    pub new_value: String,
+}
+
+impl Row {
+    fn of(change: &Change) -> Self {
+        let path = change.path().display().to_string();
+        match change {
+            Change::RenameFile(params) => Row {
+                path,
+                // A renamed directory is only found on disk under one of
+                // its names, depending on whether it was renamed yet
+                category: match Category::of(&params.from) {
+                    Category::Other => Category::of(&params.to),
+                    category => category,
+                },
+                action: "rename",
+                old_value: params.from.display().to_string(),
+                new_value: params.to.display().to_string(),
+            },
+            Change::ReplaceInFile(params) => Row {
+                path,
+                category: Category::of(&params.path),
+                action: "replace",
+                old_value: params.from.clone(),
+                new_value: params.to.clone(),
+            },
+            Change::SetIniEntry(params) => Row {
+                path,
+                category: Category::of(&params.path),
+                action: "set_ini_entry",
+                old_value: String::new(),
+                new_value: format!("[{}] {}={}", params.section, params.key, params.value),
+            },
+            Change::AppendIniEntry(params) => Row {
+                path,
+                category: Category::of(&params.path),
+                action: "append_ini_entry",
+                old_value: String::new(),
+                new_value: format!("[{}] {}={}", params.section, params.key, params.value),
+            },
+        }
+    }
+}
+
+/// Quote a CSV field if it contains separators, quotes, or line breaks.
+fn escape(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_owned(),
+    }
+}
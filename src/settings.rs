@@ -0,0 +1,216 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{backup::BackupPolicy, error::RenomError, unreal};
+
+/// User preferences read from the user-level config file and, for a specific
+/// project, from `.renom/config.toml` in the project, whose values take
+/// precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// General preferences.
+    pub general: GeneralSettings,
+    /// Preferences for the interactive wizard.
+    pub wizard: WizardSettings,
+    /// Limits on the backups kept for each project, enforced at the start of
+    /// each rename.
+    pub backup: BackupPolicy,
+    /// Preferences for how changes are applied.
+    pub engine: EngineSettings,
+    /// Answers to seed the prompts of the wizard with.
+    pub prompts: PromptSettings,
+}
+
+/// General preferences.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GeneralSettings {
+    /// When to color output.
+    pub color: ColorChoice,
+    /// Never prompt while a rename runs, e.g. to review the changes or
+    /// resolve conflicts, even when the rename was started from the wizard.
+    pub non_interactive: bool,
+    /// Names of directories to never scan, on top of those generated by the
+    /// engine, e.g. folders of vendored code.
+    pub ignore: Vec<String>,
+    /// Globs of the only files that renames may touch, relative to the
+    /// project root.
+    pub include: Vec<String>,
+    /// Globs of files that renames must leave alone, relative to the project
+    /// root, e.g. `ThirdParty/**`.
+    pub exclude: Vec<String>,
+    /// Directory to keep backups in instead of `.renom/backup` in the
+    /// project. Relative paths are resolved against the project root.
+    pub backup_dir: Option<PathBuf>,
+}
+
+/// When to color output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Color output if the terminal supports it.
+    #[default]
+    Auto,
+    /// Always color output.
+    Always,
+    /// Never color output.
+    Never,
+}
+
+/// Preferences for how changes are applied.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct EngineSettings {
+    /// The number of changes to apply between checkpoints of the journal and
+    /// backups. Checkpoints are made after every change if unset.
+    pub chunk_size: Option<usize>,
+    /// The number of threads to apply edits to different files on at once.
+    /// One thread per CPU is used if unset, and `1` applies every change in
+    /// turn.
+    pub threads: Option<usize>,
+    /// The size, in megabytes, from which files are edited a line at a time
+    /// instead of read into memory whole. 64 megabytes if unset.
+    pub stream_threshold_mb: Option<u64>,
+}
+
+impl EngineSettings {
+    /// Get the size in bytes from which files are edited a line at a time.
+    pub fn stream_threshold(&self) -> u64 {
+        self.stream_threshold_mb.unwrap_or(64) * 1024 * 1024
+    }
+}
+
+/// Answers to seed the yes/no prompts of the wizard with, for teams with
+/// established conventions. A seeded answer becomes the default at the
+/// prompt, so it is accepted with enter but can still be overridden. Prompts
+/// without a seeded answer keep their usual default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PromptSettings {
+    /// Remove redirects made redundant when a rename reverses an earlier one.
+    pub collapse_redirects: Option<bool>,
+    /// Rewrite config paths to content in a folder named after the project.
+    pub rewrite_content_paths: Option<bool>,
+    /// Rename the generated Visual Studio solution and project files.
+    pub update_project_files: Option<bool>,
+    /// Start a new workflow once one has finished.
+    pub start_new_workflow: Option<bool>,
+}
+
+/// Preferences for the interactive wizard.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct WizardSettings {
+    /// Offer to open the report of a rename and the backup folder with the
+    /// platform handlers after the rename. Disable on headless machines.
+    pub offer_open: bool,
+}
+
+impl Default for WizardSettings {
+    fn default() -> Self {
+        Self { offer_open: true }
+    }
+}
+
+impl Settings {
+    /// Load settings from the user-level config file, falling back to the
+    /// defaults if it does not exist.
+    pub fn load() -> Result<Self, RenomError> {
+        match user_config_path() {
+            Some(path) if path.is_file() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+        .map(Self::with_env_overrides)
+    }
+
+    /// Load settings for a project, layering its config file over the
+    /// user-level one. Either file may be missing.
+    pub fn load_for(project_root: &Path) -> Result<Self, RenomError> {
+        let mut table = toml::Table::new();
+        let paths = user_config_path()
+            .into_iter()
+            .chain(Some(project_config_path(project_root)));
+        for path in paths.filter(|path| path.is_file()) {
+            let layer = read_table(&path)?;
+            toml::Value::Table(layer.clone())
+                .try_into::<Settings>()
+                .map_err(|err| RenomError::Parse(format!("{}: {}", path.display(), err)))?;
+            merge(&mut table, layer);
+        }
+        toml::Value::Table(table)
+            .try_into()
+            .map(Self::with_env_overrides)
+            .map_err(|err: toml::de::Error| RenomError::Parse(err.to_string()))
+    }
+
+    /// Override settings with environment variables, e.g. for CI pipelines
+    /// that cannot easily ship a config file. `RENOM_NON_INTERACTIVE`
+    /// overrides `general.non_interactive`.
+    fn with_env_overrides(mut self) -> Self {
+        if let Ok(value) = env::var("RENOM_NON_INTERACTIVE") {
+            self.general.non_interactive = !matches!(value.as_str(), "" | "0" | "false");
+        }
+        self
+    }
+
+    /// Load settings from a specific config file.
+    pub fn load_from(path: &Path) -> Result<Self, RenomError> {
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content)
+            .map_err(|err| RenomError::Parse(format!("{}: {}", path.display(), err)))
+    }
+
+    /// Apply the settings that affect the whole process, i.e. when to color
+    /// output and which directories to ignore.
+    pub fn apply(&self) {
+        match self.general.color {
+            ColorChoice::Auto => {}
+            ColorChoice::Always => colored::control::set_override(true),
+            ColorChoice::Never => colored::control::set_override(false),
+        }
+        unreal::ignore_dirs(&self.general.ignore);
+    }
+}
+
+fn read_table(path: &Path) -> Result<toml::Table, RenomError> {
+    let content = fs::read_to_string(path)?;
+    toml::from_str(&content)
+        .map_err(|err| RenomError::Parse(format!("{}: {}", path.display(), err)))
+}
+
+/// Merge the values of one table into another, recursing into nested tables
+/// so that a project can override single values of a section.
+fn merge(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => merge(base, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Get the path to the config file of a project, i.e.
+/// `.renom/config.toml` in the project root.
+pub fn project_config_path(project_root: &Path) -> PathBuf {
+    project_root.join(".renom").join("config.toml")
+}
+
+/// Get the path to the user-level config file, i.e.
+/// `~/.config/renom/config.toml` or `%APPDATA%\renom\config.toml` on Windows.
+pub fn user_config_path() -> Option<PathBuf> {
+    if cfg!(windows) {
+        env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("renom").join("config.toml"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("renom").join("config.toml"))
+    }
+}
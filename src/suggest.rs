@@ -0,0 +1,114 @@
+use std::ffi::OsString;
+
+use clap::{
+    builder::StyledStr,
+    error::{ContextKind, ContextValue, ErrorKind},
+    Command, Error, Parser,
+};
+
+/// Parse command line arguments, suggesting the closest known subcommand,
+/// option, or value when one is not recognized, e.g. "did you mean
+/// `--new-name`?" for `--new-nam`.
+pub fn parse_args<P, I, T>(args: I) -> Result<P, Error>
+where
+    P: Parser,
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let args = args.into_iter().map(Into::into).collect::<Vec<OsString>>();
+    P::try_parse_from(&args).map_err(|err| suggest(err, P::command(), &args))
+}
+
+/// Add a suggestion to an error about an unknown argument, if a known one is
+/// close enough to be what was meant.
+pub fn suggest(mut err: Error, mut command: Command, args: &[OsString]) -> Error {
+    command.build();
+    let command = innermost_command(&command, args);
+    let suggestion = match err.kind() {
+        ErrorKind::InvalidSubcommand => {
+            string(&err, ContextKind::InvalidSubcommand).and_then(|unknown| {
+                let names = command
+                    .get_subcommands()
+                    .filter(|subcommand| !subcommand.is_hide_set())
+                    .flat_map(|subcommand| {
+                        std::iter::once(subcommand.get_name()).chain(subcommand.get_all_aliases())
+                    })
+                    .map(str::to_owned);
+                closest(&unknown, names)
+            })
+        }
+        ErrorKind::UnknownArgument => string(&err, ContextKind::InvalidArg).and_then(|unknown| {
+            let unknown = unknown
+                .split_once('=')
+                .map_or(unknown.as_str(), |(arg, _)| arg);
+            let longs = command
+                .get_arguments()
+                .filter(|arg| !arg.is_hide_set())
+                .flat_map(|arg| arg.get_long_and_visible_aliases().unwrap_or_default())
+                .map(|long| format!("--{}", long));
+            closest(unknown, longs)
+        }),
+        ErrorKind::InvalidValue => match err.get(ContextKind::ValidValue) {
+            Some(ContextValue::Strings(values)) => {
+                let values = values.clone();
+                string(&err, ContextKind::InvalidValue)
+                    .and_then(|unknown| closest(&unknown, values.into_iter()))
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+    if let Some(suggestion) = suggestion {
+        let mut tips = vec![StyledStr::from(format!("did you mean `{}`?", suggestion))];
+        if let Some(ContextValue::StyledStrs(existing)) = err.get(ContextKind::Suggested) {
+            tips.extend(existing.iter().cloned());
+        }
+        err.insert(ContextKind::Suggested, ContextValue::StyledStrs(tips));
+    }
+    err
+}
+
+/// Find the known word closest to an unknown one, as long as it is close
+/// enough to likely be a typo rather than something else entirely.
+pub fn closest(unknown: &str, known: impl Iterator<Item = String>) -> Option<String> {
+    let threshold = (unknown.chars().count() / 3).max(1);
+    known
+        .map(|word| (edit_distance(unknown, &word), word))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, word)| word)
+}
+
+/// Count the insertions, deletions, and substitutions needed to turn one word
+/// into another.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Find the subcommand that the arguments were being parsed for.
+fn innermost_command<'a>(command: &'a Command, args: &[OsString]) -> &'a Command {
+    let mut command = command;
+    for arg in args.iter().skip(1).filter_map(|arg| arg.to_str()) {
+        if let Some(subcommand) = command.find_subcommand(arg) {
+            command = subcommand;
+        }
+    }
+    command
+}
+
+fn string(err: &Error, kind: ContextKind) -> Option<String> {
+    match err.get(kind) {
+        Some(ContextValue::String(value)) => Some(value.clone()),
+        _ => None,
+    }
+}
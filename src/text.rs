@@ -0,0 +1,178 @@
+use std::{fs, io, path::Path};
+
+/// Text encodings that files may be read and written in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    /// UTF-8 without a byte order mark.
+    Utf8,
+    /// UTF-8 with a byte order mark.
+    Utf8Bom,
+    /// UTF-16 little endian without a byte order mark.
+    Utf16Le,
+    /// UTF-16 little endian with a byte order mark.
+    Utf16LeBom,
+    /// UTF-16 big endian with a byte order mark.
+    Utf16BeBom,
+}
+
+impl Encoding {
+    const UTF8_BOM: &'static [u8] = &[0xEF, 0xBB, 0xBF];
+    const UTF16_LE_BOM: &'static [u8] = &[0xFF, 0xFE];
+    const UTF16_BE_BOM: &'static [u8] = &[0xFE, 0xFF];
+
+    /// Detect the encoding of raw file content. Content without a byte order
+    /// mark is assumed to be UTF-8, unless it looks like little endian UTF-16
+    /// text (every other byte empty), as written by some Windows tools.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(Self::UTF8_BOM) {
+            Encoding::Utf8Bom
+        } else if bytes.starts_with(Self::UTF16_LE_BOM) {
+            Encoding::Utf16LeBom
+        } else if bytes.starts_with(Self::UTF16_BE_BOM) {
+            Encoding::Utf16BeBom
+        } else if Self::looks_like_utf16_le(bytes) {
+            Encoding::Utf16Le
+        } else {
+            Encoding::Utf8
+        }
+    }
+
+    fn looks_like_utf16_le(bytes: &[u8]) -> bool {
+        bytes.len() >= 2
+            && bytes.len().is_multiple_of(2)
+            && bytes.iter().skip(1).step_by(2).all(|byte| *byte == 0)
+    }
+
+    /// The byte order mark that content in the encoding starts with, if any.
+    pub fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 | Encoding::Utf16Le => &[],
+            Encoding::Utf8Bom => Self::UTF8_BOM,
+            Encoding::Utf16LeBom => Self::UTF16_LE_BOM,
+            Encoding::Utf16BeBom => Self::UTF16_BE_BOM,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> io::Result<String> {
+        let bytes = &bytes[self.bom().len()..];
+        match self {
+            Encoding::Utf8 | Encoding::Utf8Bom => {
+                String::from_utf8(bytes.to_vec()).map_err(|err| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("content is not valid UTF-8 ({})", err),
+                    )
+                })
+            }
+            Encoding::Utf16Le | Encoding::Utf16LeBom | Encoding::Utf16BeBom => {
+                if !bytes.len().is_multiple_of(2) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "content is not valid UTF-16 (odd number of bytes)",
+                    ));
+                }
+                let units = bytes
+                    .chunks_exact(2)
+                    .map(|pair| match self {
+                        Encoding::Utf16BeBom => u16::from_be_bytes([pair[0], pair[1]]),
+                        _ => u16::from_le_bytes([pair[0], pair[1]]),
+                    })
+                    .collect::<Vec<u16>>();
+                String::from_utf16(&units).map_err(|err| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("content is not valid UTF-16 ({})", err),
+                    )
+                })
+            }
+        }
+    }
+
+    fn encode(self, text: &str) -> Vec<u8> {
+        let content = match self {
+            Encoding::Utf8 | Encoding::Utf8Bom => text.as_bytes().to_vec(),
+            Encoding::Utf16Le | Encoding::Utf16LeBom => {
+                text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+            }
+            Encoding::Utf16BeBom => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        };
+        [self.bom(), &content].concat()
+    }
+}
+
+/// Line endings that files may use.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    /// Detect the dominant line ending in some text. Defaults to LF when the
+    /// text has no line breaks.
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        match crlf > lf {
+            true => LineEnding::CrLf,
+            false => LineEnding::Lf,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// Convert all line breaks in some text to this line ending.
+    pub fn apply(self, text: &str) -> String {
+        let normalized = text.replace("\r\n", "\n");
+        match self {
+            LineEnding::Lf => normalized,
+            LineEnding::CrLf => normalized.replace('\n', "\r\n"),
+        }
+    }
+}
+
+/// The decoded content of a text file, along with the details needed to write
+/// it back in its original form.
+pub struct TextFile {
+    /// The encoding of the file on disk.
+    pub encoding: Encoding,
+    /// The dominant line ending of the file.
+    pub line_ending: LineEnding,
+    /// The decoded content, excluding any byte order mark. Line endings are
+    /// left untouched.
+    pub content: String,
+}
+
+impl TextFile {
+    /// Read and decode a text file.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::decode(&fs::read(path)?)
+    }
+
+    /// Decode raw file content.
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let encoding = Encoding::detect(bytes);
+        let content = encoding.decode(bytes)?;
+        let line_ending = LineEnding::detect(&content);
+        Ok(Self {
+            encoding,
+            line_ending,
+            content,
+        })
+    }
+
+    /// Encode the content in the original encoding of the file.
+    pub fn encode(&self) -> Vec<u8> {
+        self.encoding.encode(&self.content)
+    }
+
+    /// Write the content to disk in the original encoding of the file.
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.encode())
+    }
+}
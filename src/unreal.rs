@@ -1,4 +1,9 @@
-use std::{fmt::Display, path::PathBuf};
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
 
 #[derive(Clone)]
 pub enum ModuleType {
@@ -34,12 +39,54 @@ pub struct Plugin {
     pub root: PathBuf,
 }
 
+impl Plugin {
+    /// Whether the plugin contains content only, i.e. has no Source folder.
+    pub fn is_content_only(&self) -> bool {
+        !self.root.join("Source").is_dir()
+    }
+}
+
 impl Display for Plugin {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", &self.name)
     }
 }
 
+/// Where a plugin referenced by a project but not located within it is
+/// installed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExternalPluginKind {
+    /// Installed from the marketplace into the engine.
+    Marketplace,
+    /// Shipped with or installed into the engine.
+    Engine,
+}
+
+impl Display for ExternalPluginKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalPluginKind::Marketplace => write!(f, "marketplace plugin"),
+            ExternalPluginKind::Engine => write!(f, "engine plugin"),
+        }
+    }
+}
+
+/// Information about a plugin referenced by a project but not located within
+/// it.
+#[derive(Clone)]
+pub struct ExternalPlugin {
+    /// The name of the plugin.
+    pub name: String,
+    /// Where the plugin is installed.
+    pub kind: ExternalPluginKind,
+}
+
+impl Display for ExternalPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", &self.name)
+    }
+}
+
 /// Information about an Unreal Engine target.
 #[derive(Clone)]
 pub struct Target {
@@ -54,3 +101,117 @@ impl Display for Target {
         write!(f, "{}", &self.name)
     }
 }
+
+/// Directories generated by the engine, version control, or Renom itself,
+/// which are never scanned.
+pub const IGNORED_DIRS: [&str; 6] = [
+    "Binaries",
+    "DerivedDataCache",
+    "Intermediate",
+    "Saved",
+    ".git",
+    ".renom",
+];
+
+/// Directories configured to be ignored on top of [`IGNORED_DIRS`].
+static EXTRA_IGNORED_DIRS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Never scan directories with these names either, e.g. vendored code that
+/// must be left alone.
+pub fn ignore_dirs(names: &[String]) {
+    if let Ok(mut ignored) = EXTRA_IGNORED_DIRS.write() {
+        for name in names {
+            if !ignored.contains(name) {
+                ignored.push(name.clone());
+            }
+        }
+    }
+}
+
+/// Whether directories with this name are never scanned, either because the
+/// engine or a tool generates them or because they were configured to be
+/// ignored.
+pub fn is_ignored_dir(name: &str) -> bool {
+    IGNORED_DIRS.contains(&name)
+        || EXTRA_IGNORED_DIRS
+            .read()
+            .is_ok_and(|ignored| ignored.iter().any(|ignored| ignored == name))
+}
+
+/// Get the directories that may contain plugins for a project: the Plugins
+/// folder and any directories listed under `AdditionalPluginDirectories` in
+/// the project descriptor. Relative directories are resolved against the
+/// project root.
+pub fn plugin_directories(project_root: &Path) -> Vec<PathBuf> {
+    let mut directories = vec![project_root.join("Plugins")];
+    let descriptor = fs::read_dir(project_root)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "uproject"));
+    let additional = descriptor
+        .and_then(|descriptor| fs::read_to_string(descriptor).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|json| {
+            json.get("AdditionalPluginDirectories")
+                .and_then(|directories| directories.as_array().cloned())
+        })
+        .unwrap_or_default();
+    directories.extend(
+        additional
+            .iter()
+            .filter_map(|directory| directory.as_str())
+            .map(|directory| project_root.join(directory)),
+    );
+    directories
+}
+
+/// Find the projects listed by `.uprojectdirs` files in a solution root, as
+/// used by source builds of the engine to locate projects. Each line of such
+/// a file names a directory, relative to the solution root, whose immediate
+/// subdirectories may contain projects. Lines starting with `;` are comments.
+/// Returns the roots of the projects found, sorted.
+pub fn projects_in_uprojectdirs(solution_root: &Path) -> Vec<PathBuf> {
+    let listed_dirs = fs::read_dir(solution_root)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "uprojectdirs"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with(';'))
+                .map(|line| solution_root.join(line))
+                .collect::<Vec<PathBuf>>()
+        })
+        .collect::<Vec<PathBuf>>();
+
+    let mut projects = listed_dirs
+        .iter()
+        .flat_map(|dir| fs::read_dir(dir).into_iter().flatten())
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && contains_project_descriptor(path))
+        .map(|path| path.components().collect::<PathBuf>())
+        .collect::<Vec<PathBuf>>();
+    projects.sort();
+    projects.dedup();
+    projects
+}
+
+fn contains_project_descriptor(dir: &Path) -> bool {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .any(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "uproject")
+        })
+}
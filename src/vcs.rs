@@ -0,0 +1,154 @@
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::{error::RenomError, text::LineEnding};
+
+/// Version control systems that renames can cooperate with.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Vcs {
+    Git,
+}
+
+/// Determine the line endings that git expects in the working tree for each
+/// of the given files, based on `eol` and `text` attributes and the
+/// `core.autocrlf` and `core.eol` settings. Files for which git does not
+/// convert line endings are left out.
+pub fn git_line_endings(
+    repo_dir: &Path,
+    files: &[PathBuf],
+) -> Result<BTreeMap<PathBuf, LineEnding>, RenomError> {
+    if files.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let query_error = |err: RenomError| err.context("could not query git for line endings");
+    let autocrlf = git_config(repo_dir, "core.autocrlf").map_err(query_error)?;
+    let eol = git_config(repo_dir, "core.eol").map_err(query_error)?;
+    let attributes = git_check_attr(repo_dir, files).map_err(query_error)?;
+
+    let mut line_endings = BTreeMap::new();
+    for (file, (text, eol_attr)) in files.iter().zip(attributes) {
+        let line_ending = match (text.as_str(), eol_attr.as_str()) {
+            ("unset", _) => None,
+            (_, "lf") => Some(LineEnding::Lf),
+            (_, "crlf") => Some(LineEnding::CrLf),
+            (text, _) => match autocrlf.as_deref() {
+                Some("true") => Some(LineEnding::CrLf),
+                Some("input") => Some(LineEnding::Lf),
+                _ if text == "unspecified" => None,
+                _ => match eol.as_deref() {
+                    Some("lf") => Some(LineEnding::Lf),
+                    Some("crlf") => Some(LineEnding::CrLf),
+                    _ if cfg!(windows) => Some(LineEnding::CrLf),
+                    _ => Some(LineEnding::Lf),
+                },
+            },
+        };
+        if let Some(line_ending) = line_ending {
+            line_endings.insert(file.clone(), line_ending);
+        }
+    }
+    Ok(line_endings)
+}
+
+/// Get the files staged for the next commit in the repository containing a
+/// directory, excluding deleted files. Paths are canonicalized, so that they
+/// can be compared with canonicalized paths found elsewhere.
+pub fn git_staged_files(repo_dir: &Path) -> Result<Vec<PathBuf>, RenomError> {
+    let git = |args: &[&str]| -> Result<Vec<u8>, RenomError> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(repo_dir)
+            .args(args)
+            .output()
+            .map_err(|err| RenomError::from(err).context("could not run git"))?;
+        match output.status.success() {
+            true => Ok(output.stdout),
+            false => Err(git_error(&output.stderr)),
+        }
+    };
+    let toplevel = git(&["rev-parse", "--show-toplevel"])?;
+    let toplevel = PathBuf::from(String::from_utf8_lossy(&toplevel).trim());
+    let staged = git(&[
+        "diff",
+        "--cached",
+        "--name-only",
+        "-z",
+        "--diff-filter=ACMR",
+    ])?;
+    Ok(String::from_utf8_lossy(&staged)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(|path| toplevel.join(path))
+        .map(|path| path.canonicalize().unwrap_or(path))
+        .collect())
+}
+
+/// Read a git setting, or `None` if it is not set.
+fn git_config(repo_dir: &Path, key: &str) -> Result<Option<String>, RenomError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_dir)
+        .args(["config", "--get", key])
+        .output()
+        .map_err(|err| RenomError::from(err).context("could not run git"))?;
+    match output.status.code() {
+        Some(0) => Ok(Some(
+            String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .to_lowercase(),
+        )),
+        // git exits with 1 when the key is not set
+        Some(1) => Ok(None),
+        _ => Err(git_error(&output.stderr)),
+    }
+}
+
+/// Get the `text` and `eol` attributes of each file, in order.
+fn git_check_attr(repo_dir: &Path, files: &[PathBuf]) -> Result<Vec<(String, String)>, RenomError> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(repo_dir)
+        .args(["check-attr", "--stdin", "-z", "text", "eol"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| RenomError::from(err).context("could not run git"))?;
+    let mut input = vec![];
+    for file in files {
+        let relative = file.strip_prefix(repo_dir).unwrap_or(file);
+        input.extend(relative.to_string_lossy().as_bytes());
+        input.push(0);
+    }
+    child
+        .stdin
+        .take()
+        .expect("stdin should be piped")
+        .write_all(&input)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(git_error(&output.stderr));
+    }
+
+    // Output is a sequence of <path> NUL <attribute> NUL <value> NUL, with
+    // the attributes of each path in the order they were requested
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fields = stdout.split('\0').collect::<Vec<&str>>();
+    let values = fields
+        .chunks_exact(3)
+        .map(|record| record[2].to_owned())
+        .collect::<Vec<String>>();
+    Ok(values
+        .chunks_exact(2)
+        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .collect())
+}
+
+/// Describe a failed git command by what it printed to stderr.
+fn git_error(stderr: &[u8]) -> RenomError {
+    RenomError::Validation(String::from_utf8_lossy(stderr).trim().to_owned())
+}
@@ -1,10 +1,18 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
 use inquire::{Confirm, Select};
 
 use crate::{
+    manifest,
     presentation::log,
+    settings::Settings,
     workflows::{
         rename_module_interactive, rename_plugin_interactive, rename_project_interactive,
-        rename_target_interactive, Workflow,
+        rename_target_interactive, Options, Workflow,
     },
 };
 
@@ -25,15 +33,25 @@ macro_rules! ok_or_quit {
 
 pub fn start_interactive_dialogue() {
     set_up_terminal();
+    let settings = ok_or_quit!(Settings::load().map_err(String::from));
+    settings.apply();
     log::header("Welcome to Renom");
     loop {
-        match ok_or_quit!(request_workflow_selection_from_user()) {
-            Workflow::RenameProject => ok_or_quit!(rename_project_interactive()),
-            Workflow::RenamePlugin => ok_or_quit!(rename_plugin_interactive()),
-            Workflow::RenameTarget => ok_or_quit!(rename_target_interactive()),
-            Workflow::RenameModule => ok_or_quit!(rename_module_interactive()),
+        let result = match ok_or_quit!(request_workflow_selection_from_user()) {
+            Workflow::RenameProject => rename_project_interactive(),
+            Workflow::RenamePlugin => rename_plugin_interactive(),
+            Workflow::RenameTarget => rename_target_interactive(),
+            Workflow::RenameModule => rename_module_interactive(),
         };
-        if !user_wants_to_start_new_workflow() {
+        // A failed rename has been reverted, so the user can start over
+        match result {
+            Ok(Some(project_root)) if settings.wizard.offer_open => {
+                offer_to_open_results(&project_root)
+            }
+            Ok(_) => {}
+            Err(e) => log::error(e.to_string()),
+        }
+        if !user_wants_to_start_new_workflow(settings.prompts.start_new_workflow) {
             break;
         }
     }
@@ -56,8 +74,81 @@ fn request_workflow_selection_from_user() -> Result<Workflow, String> {
         .map_err(|e| e.to_string())
 }
 
-fn user_wants_to_start_new_workflow() -> bool {
-    Confirm::new("Would you like to start a new workflow?")
-        .prompt()
-        .unwrap_or(false)
+fn user_wants_to_start_new_workflow(seeded: Option<bool>) -> bool {
+    let prompt = Confirm::new("Would you like to start a new workflow?");
+    match seeded {
+        Some(answer) => prompt.with_default(answer),
+        None => prompt,
+    }
+    .prompt()
+    .unwrap_or(false)
+}
+
+/// Something the user can open after a rename to see what happened.
+enum Followup {
+    Report(PathBuf),
+    BackupFolder(PathBuf),
+    Done,
+}
+
+impl Display for Followup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Followup::Report(_) => write!(f, "Open rename report"),
+            Followup::BackupFolder(_) => write!(f, "Open backup folder"),
+            Followup::Done => write!(f, "Continue"),
+        }
+    }
+}
+
+/// Let the user open the report of the rename of a project or its backup
+/// folder until they choose to continue.
+fn offer_to_open_results(project_root: &Path) {
+    let backup_dir = Options {
+        backup_dir: Settings::load_for(project_root)
+            .map(|settings| settings.general.backup_dir)
+            .unwrap_or_default(),
+        ..Options::default()
+    }
+    .backup_root(project_root);
+    loop {
+        let mut options = vec![];
+        let report = manifest::report_path(project_root);
+        if report.is_file() {
+            options.push(Followup::Report(report));
+        }
+        if backup_dir.is_dir() {
+            options.push(Followup::BackupFolder(backup_dir.clone()));
+        }
+        if options.is_empty() {
+            return;
+        }
+        options.push(Followup::Done);
+
+        match Select::new("What would you like to do next?", options).prompt() {
+            Ok(Followup::Report(path)) | Ok(Followup::BackupFolder(path)) => {
+                if let Err(e) = open_with_platform_handler(&path) {
+                    log::error(e);
+                }
+            }
+            Ok(Followup::Done) | Err(_) => return,
+        }
+    }
+}
+
+/// Open a file or folder with the default application of the platform, e.g.
+/// the file explorer for folders.
+fn open_with_platform_handler(path: &Path) -> Result<(), String> {
+    let program = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(program)
+        .arg(path)
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| format!("could not open {}: {}", path.display(), err))
 }
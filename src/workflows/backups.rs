@@ -0,0 +1,97 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    backup::{self, BackupPolicy, Pruned, SnapshotEntry},
+    error::RenomError,
+    presentation::log,
+};
+
+use super::Options;
+
+/// Get the directory to back up the files of a rename in: a new directory in
+/// the backup root, created once the backups of earlier renames are archived
+/// and pruned, and tagged with the command line of the process, see
+/// [`backup::create_snapshot`]. When backups are skipped, nothing is pruned or
+/// created, and the backup root is returned as is since nothing is written to
+/// it.
+pub fn prepare_backup_dir(backup_root: &Path, options: &Options) -> Result<PathBuf, RenomError> {
+    if options.no_backup {
+        return Ok(backup_root.to_owned());
+    }
+    archive_backups(backup_root)?;
+    prune_backups(backup_root, options.backup_policy.as_ref())?;
+    Ok(backup::create_snapshot(backup_root, command_line())?)
+}
+
+/// List the snapshots of the renames of a project, oldest first, see
+/// [`backup::snapshots`].
+pub fn list_snapshots(
+    project_root: &Path,
+    options: &Options,
+) -> Result<Vec<SnapshotEntry>, RenomError> {
+    Ok(backup::snapshots(&options.backup_root(project_root))?)
+}
+
+/// Archive the backups of the completed renames of a project and remove
+/// those that exceed the limits of the policy in the options, if any, e.g.
+/// for `renom backups prune`.
+pub fn prune_project_backups(project_root: &Path, options: &Options) -> Result<(), RenomError> {
+    let backup_root = options.backup_root(project_root);
+    archive_backups(&backup_root)?;
+    let pruned = prune_backups(&backup_root, options.backup_policy.as_ref())?;
+    if pruned.removed.is_empty() {
+        log::basic(format!("No backups to prune in {}.", backup_root.display()));
+    }
+    Ok(())
+}
+
+/// Compress the backups of completed renames in a backup root into an
+/// archive per rename, see [`backup::archive`].
+pub fn archive_backups(backup_root: &Path) -> Result<(), RenomError> {
+    let archived = backup::archive_completed(backup_root)?;
+    for path in &archived {
+        log::step("archive", path.display());
+    }
+    Ok(())
+}
+
+/// Remove backups of earlier renames in a backup root that exceed the limits
+/// of the policy, if there is one, and summarize what was removed.
+pub fn prune_backups(
+    backup_root: &Path,
+    policy: Option<&BackupPolicy>,
+) -> Result<Pruned, RenomError> {
+    let policy = match policy {
+        Some(policy) => policy,
+        None => return Ok(Pruned::default()),
+    };
+    let pruned = backup::prune(backup_root, policy, SystemTime::now())?;
+    if pruned.removed.is_empty() {
+        return Ok(pruned);
+    }
+    for path in &pruned.removed {
+        log::step("prune", path.display());
+    }
+    log::basic(format!(
+        "Pruned {} backup(s), freeing {:.1} MB.",
+        pruned.removed.len(),
+        pruned.freed as f64 / (1024.0 * 1024.0)
+    ));
+    Ok(pruned)
+}
+
+/// Get the command line that started the process, quoting arguments that
+/// contain whitespace.
+fn command_line() -> String {
+    env::args()
+        .map(|arg| match arg.contains(char::is_whitespace) {
+            true => format!("{:?}", arg),
+            false => arg,
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
@@ -0,0 +1,100 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    changes::{Change, Changeset},
+    error::RenomError,
+};
+
+/// Check that a batch renames each element once, and to names that no other
+/// element of the batch is renamed to or from.
+pub fn validate_batch(renames: &[(&str, &str)]) -> Result<(), RenomError> {
+    if renames.is_empty() {
+        return Err(RenomError::Validation(
+            "batch must contain at least one rename".into(),
+        ));
+    }
+    let mut old_names = HashSet::new();
+    let mut new_names = HashSet::new();
+    for (old_name, new_name) in renames {
+        if !old_names.insert(*old_name) {
+            return Err(RenomError::Validation(format!(
+                "{} is renamed more than once",
+                old_name
+            )));
+        }
+        if !new_names.insert(*new_name) {
+            return Err(RenomError::Validation(format!(
+                "more than one element is renamed to {}",
+                new_name
+            )));
+        }
+    }
+    match old_names.intersection(&new_names).next() {
+        Some(name) => Err(RenomError::Validation(format!(
+            "{} is both renamed and a new name, rename it in a separate run",
+            name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Combine the changesets of several renames, each planned against the
+/// current state of the project, into one that applies them in order. Paths
+/// in each changeset follow the files and directories renamed by the
+/// changesets before it, e.g. an edit to the descriptor of a plugin targets
+/// the descriptor where an earlier rename of that plugin moves it.
+pub fn combine_changesets(changesets: Vec<Vec<Change>>) -> Result<Vec<Change>, RenomError> {
+    let mut combined = vec![];
+    for changeset in changesets {
+        let moves = combined
+            .iter()
+            .filter_map(|change| match change {
+                Change::RenameFile(params) => Some((params.from.clone(), params.to.clone())),
+                _ => None,
+            })
+            .collect::<Vec<(PathBuf, PathBuf)>>();
+        combined.extend(
+            changeset
+                .into_iter()
+                .map(|change| follow_renames(change, &moves)),
+        );
+    }
+    Changeset::new(combined.clone())
+        .validate()
+        .map_err(|violations| {
+            RenomError::Validation(format!("renames in the batch conflict:\n{}", violations))
+        })?;
+    Ok(combined)
+}
+
+fn follow_renames(change: Change, moves: &[(PathBuf, PathBuf)]) -> Change {
+    let follow = |path: &mut PathBuf| {
+        for (from, to) in moves {
+            if let Some(moved) = moved_path(path, from, to) {
+                *path = moved;
+            }
+        }
+    };
+    let mut change = change;
+    match &mut change {
+        Change::RenameFile(params) => {
+            follow(&mut params.from);
+            follow(&mut params.to);
+        }
+        Change::ReplaceInFile(params) => follow(&mut params.path),
+        Change::SetIniEntry(params) => follow(&mut params.path),
+        Change::AppendIniEntry(params) => follow(&mut params.path),
+    }
+    change
+}
+
+fn moved_path(path: &Path, from: &Path, to: &Path) -> Option<PathBuf> {
+    let rest = path.strip_prefix(from).ok()?;
+    match rest.as_os_str().is_empty() {
+        true => Some(to.to_owned()),
+        false => Some(to.join(rest)),
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::error::RenomError;
+
+/// A rename listed in a batch file, see [`read_batch_file`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BatchRename {
+    Project {
+        new_name: String,
+    },
+    Plugin {
+        name: String,
+        new_name: String,
+        /// The root of the plugin, when several plugins share its name.
+        #[serde(default)]
+        plugin_path: Option<PathBuf>,
+    },
+    Module {
+        name: String,
+        new_name: String,
+    },
+    Target {
+        name: String,
+        new_name: String,
+    },
+}
+
+impl BatchRename {
+    /// The kind of element renamed, as named in reports.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BatchRename::Project { .. } => "project",
+            BatchRename::Plugin { .. } => "plugin",
+            BatchRename::Module { .. } => "module",
+            BatchRename::Target { .. } => "target",
+        }
+    }
+}
+
+impl Display for BatchRename {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchRename::Project { new_name } => write!(f, "project to {}", new_name),
+            BatchRename::Plugin { name, new_name, .. }
+            | BatchRename::Module { name, new_name }
+            | BatchRename::Target { name, new_name } => {
+                write!(f, "{} {} to {}", self.kind(), name, new_name)
+            }
+        }
+    }
+}
+
+/// A rename of a batch file along with the project it applies to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchItem {
+    /// The root of the project, as it was before the renames of the batch.
+    pub project_root: PathBuf,
+    /// The rename to apply to the project.
+    pub rename: BatchRename,
+}
+
+#[derive(Deserialize)]
+struct BatchFile {
+    /// The project of the renames that do not name their own.
+    #[serde(default)]
+    project: Option<PathBuf>,
+    renames: Vec<BatchEntry>,
+}
+
+#[derive(Deserialize)]
+struct BatchEntry {
+    #[serde(default)]
+    project: Option<PathBuf>,
+    #[serde(flatten)]
+    rename: BatchRename,
+}
+
+/// Read the renames listed in a YAML or JSON batch file, in the order they are
+/// to be applied. Each rename names its project, or falls back to the project
+/// given at the top of the file. Relative project paths are resolved against
+/// the directory of the batch file, so that it can be kept with the projects.
+pub fn read_batch_file(path: &Path) -> Result<Vec<BatchItem>, RenomError> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        RenomError::from(err).context(format!("could not read {}", path.display()))
+    })?;
+    let batch: BatchFile = match path.extension().is_some_and(|ext| ext == "json") {
+        true => serde_json::from_str(&content).map_err(|err| err.to_string()),
+        false => serde_yaml::from_str(&content).map_err(|err| err.to_string()),
+    }
+    .map_err(|err| {
+        RenomError::Parse(format!(
+            "{} is not a valid batch file: {}",
+            path.display(),
+            err
+        ))
+    })?;
+    if batch.renames.is_empty() {
+        return Err(RenomError::Validation(format!(
+            "{} lists no renames",
+            path.display()
+        )));
+    }
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let default_project = batch.project;
+    batch
+        .renames
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let project = entry
+                .project
+                .or_else(|| default_project.clone())
+                .ok_or_else(|| {
+                    RenomError::Validation(format!(
+                        "rename {} in {} does not name its project",
+                        index + 1,
+                        path.display()
+                    ))
+                })?;
+            Ok(BatchItem {
+                project_root: base_dir.join(project),
+                rename: entry.rename,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,142 @@
+use std::{collections::BTreeSet, path::Path};
+
+use inquire::Select;
+
+use crate::{
+    backup,
+    changes::{dedup, find_applied, find_conflicts, Change, Changeset, Conflict, ConflictKind},
+    engine::Engine,
+    error::RenomError,
+    fs::RealFs,
+    presentation::log,
+};
+
+use super::Options;
+
+/// Normalize a changeset and resolve any conflicts between its changes. In
+/// interactive mode, the user chooses which change to keep for each conflict.
+/// Otherwise, conflicts are reported as an error. In debug builds, the result
+/// is also checked with [`Changeset::validate`] to catch generator bugs.
+pub fn resolve_conflicts(
+    changeset: Vec<Change>,
+    interactive: bool,
+) -> Result<Vec<Change>, RenomError> {
+    let resolved = resolve(changeset, interactive)?;
+    if cfg!(debug_assertions) {
+        if let Err(violations) = Changeset::new(resolved.clone()).validate() {
+            panic!("generated changeset is invalid:\n{}", violations);
+        }
+    }
+    Ok(resolved)
+}
+
+fn resolve(changeset: Vec<Change>, interactive: bool) -> Result<Vec<Change>, RenomError> {
+    let changeset = dedup(changeset);
+    let conflicts = find_conflicts(&changeset);
+    if conflicts.is_empty() {
+        return Ok(changeset);
+    }
+    if !interactive {
+        return Err(RenomError::Validation(describe_conflicts(
+            &changeset, &conflicts,
+        )));
+    }
+
+    log::header("Resolve conflicts");
+    let mut discarded = BTreeSet::new();
+    for conflict in &conflicts {
+        let remaining = conflict
+            .changes
+            .iter()
+            .filter(|index| !discarded.contains(*index))
+            .copied()
+            .collect::<Vec<usize>>();
+        if remaining.len() < 2 {
+            continue;
+        }
+        let options = remaining
+            .iter()
+            .map(|index| changeset[*index].to_string())
+            .collect::<Vec<String>>();
+        let message = format!(
+            "Conflict: {}. Which change should be kept?",
+            conflict.reason
+        );
+        let kept = Select::new(&message, options)
+            .raw_prompt()
+            .map_err(|err| RenomError::Validation(err.to_string()))?
+            .index;
+        discarded.extend(
+            remaining
+                .into_iter()
+                .enumerate()
+                .filter(|(position, _)| *position != kept)
+                .map(|(_, index)| index),
+        );
+    }
+
+    Ok(changeset
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| !discarded.contains(index))
+        .map(|(_, change)| change)
+        .collect())
+}
+
+/// Simulate the execution of a changeset and fail if any of its changes would
+/// fail because of the files on disk, e.g. a rename onto an existing file,
+/// before anything is backed up or applied. See [`Engine::simulate`].
+/// Read-only files to edit are left for the engine to make writable if the
+/// options call for it, see [`Options::make_writable`], and changes already
+/// applied are left for it to skip, see [`Options::idempotent`].
+pub fn check_execution(
+    changeset: &[Change],
+    project_root: &Path,
+    options: &Options,
+) -> Result<(), RenomError> {
+    let applied = match options.idempotent {
+        true => {
+            let earlier = backup::applied_changes(&options.backup_root(project_root))?;
+            find_applied(&RealFs, changeset, &earlier)
+        }
+        false => BTreeSet::new(),
+    };
+    let conflicts = Engine::simulate(changeset)
+        .into_iter()
+        .filter(|conflict| {
+            !(options.make_writable
+                && conflict.kind == ConflictKind::NotWritable
+                && !matches!(conflict.change, Change::RenameFile(_)))
+        })
+        .filter(|conflict| !applied.contains(&conflict.index))
+        .collect::<Vec<_>>();
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    let details = conflicts
+        .iter()
+        .map(|conflict| format!("\t- {}", conflict))
+        .collect::<Vec<String>>()
+        .join("\n");
+    Err(RenomError::Validation(format!(
+        "changeset would fail against the files on disk:\n{}",
+        details
+    )))
+}
+
+fn describe_conflicts(changeset: &[Change], conflicts: &[Conflict]) -> String {
+    let details = conflicts
+        .iter()
+        .map(|conflict| {
+            let changes = conflict
+                .changes
+                .iter()
+                .map(|index| format!("\t\t- {}", changeset[*index]))
+                .collect::<Vec<String>>()
+                .join("\n");
+            format!("\t- {}:\n{}", conflict.reason, changes)
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    format!("changeset contains conflicting changes:\n{}", details)
+}
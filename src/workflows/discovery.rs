@@ -0,0 +1,44 @@
+use std::{fmt::Display, path::PathBuf};
+
+use inquire::Select;
+
+use crate::unreal;
+
+enum Candidate {
+    Project(PathBuf),
+    Other,
+}
+
+impl Display for Candidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Candidate::Project(root) => write!(f, "{}", root.display()),
+            Candidate::Other => write!(f, "Enter another path"),
+        }
+    }
+}
+
+/// Let the user pick one of the projects listed by `.uprojectdirs` files in
+/// the working directory, when run from a solution root. Only projects that
+/// pass the filter are offered. Returns `None` if there are no candidates or
+/// the user would rather enter a path.
+pub fn select_discovered_project(
+    filter: impl Fn(&PathBuf) -> bool,
+) -> Result<Option<PathBuf>, String> {
+    let mut candidates = unreal::projects_in_uprojectdirs(&PathBuf::from("."))
+        .into_iter()
+        .filter(filter)
+        .map(Candidate::Project)
+        .collect::<Vec<Candidate>>();
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+    candidates.push(Candidate::Other);
+    match Select::new("Choose a project:", candidates)
+        .prompt()
+        .map_err(|err| err.to_string())?
+    {
+        Candidate::Project(root) => Ok(Some(root)),
+        Candidate::Other => Ok(None),
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    changes::{dedup, Change, Changeset},
+    error::RenomError,
+};
+
+/// Read changes generated outside of Renom from an NDJSON file, one change
+/// per line in the same format as plans, and merge them into a generated
+/// changeset ahead of the generated changes. Relative paths are resolved
+/// against the project root, and no change may reach outside of it. The
+/// extra changes must fit the merged changeset, e.g. they must not move files
+/// that the generated changes edit. Violations between generated changes
+/// alone are left to [`resolve_conflicts`].
+///
+/// [`resolve_conflicts`]: crate::workflows::resolve_conflicts
+pub fn merge_extra_ops(
+    changeset: Vec<Change>,
+    extra_ops: Option<&Path>,
+    project_root: &Path,
+) -> Result<Vec<Change>, RenomError> {
+    let path = match extra_ops {
+        Some(path) => path,
+        None => return Ok(changeset),
+    };
+    // The extra changes come first, so they keep their positions once merged
+    let mut merged = dedup(read_extra_ops(path, project_root)?);
+    let extra_count = merged.len();
+    merged.extend(changeset);
+    let merged = dedup(merged);
+    let violations = Changeset::new(merged.clone())
+        .violations()
+        .into_iter()
+        .filter(|violation| violation.changes.iter().any(|index| *index < extra_count))
+        .map(|violation| violation.description)
+        .collect::<Vec<String>>();
+    match violations.is_empty() {
+        true => Ok(merged),
+        false => Err(RenomError::Validation(format!(
+            "extra operations in {} do not fit the rename:\n{}",
+            path.display(),
+            violations.join("\n")
+        ))),
+    }
+}
+
+fn read_extra_ops(path: &Path, project_root: &Path) -> Result<Vec<Change>, RenomError> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        RenomError::from(err).context(format!("could not read {}", path.display()))
+    })?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let change = serde_json::from_str::<Change>(line).map_err(|err| {
+                RenomError::Parse(format!("{}:{}: {}", path.display(), index + 1, err))
+            })?;
+            resolve_paths(change, project_root)
+                .map_err(|err| err.context(format!("{}:{}", path.display(), index + 1)))
+        })
+        .collect()
+}
+
+/// Resolve the paths of a change against the project root.
+fn resolve_paths(change: Change, project_root: &Path) -> Result<Change, RenomError> {
+    let resolve = |path: &mut PathBuf| -> Result<(), RenomError> {
+        *path = project_root.join(&path);
+        match path.starts_with(project_root)
+            && !path.components().any(|part| part == Component::ParentDir)
+        {
+            true => Ok(()),
+            false => Err(RenomError::Validation(format!(
+                "{} is outside of the project",
+                path.display()
+            ))),
+        }
+    };
+    let mut change = change;
+    match &mut change {
+        Change::RenameFile(params) => {
+            resolve(&mut params.from)?;
+            resolve(&mut params.to)?;
+        }
+        Change::ReplaceInFile(params) => resolve(&mut params.path)?,
+        Change::SetIniEntry(params) => resolve(&mut params.path)?,
+        Change::AppendIniEntry(params) => resolve(&mut params.path)?,
+    }
+    Ok(change)
+}
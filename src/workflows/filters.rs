@@ -0,0 +1,85 @@
+use std::path::{Component, Path};
+
+use regex::Regex;
+
+use crate::{changes::Change, error::RenomError, presentation::log};
+
+use super::Options;
+
+/// Leave out the generated changes to files that the include and exclude
+/// globs of the options rule out, e.g. to keep a vendored SDK that happens to
+/// contain the old name as is. A change is kept if its file matches any
+/// include glob, or if there are none, and matches no exclude glob. Globs are
+/// matched against paths relative to the project root, see [`glob_to_regex`].
+pub fn filter_changes(
+    changeset: Vec<Change>,
+    project_root: &Path,
+    options: &Options,
+) -> Result<Vec<Change>, RenomError> {
+    if options.include.is_empty() && options.exclude.is_empty() {
+        return Ok(changeset);
+    }
+    let include = compile_globs(&options.include)?;
+    let exclude = compile_globs(&options.exclude)?;
+    let (kept, skipped): (Vec<Change>, Vec<Change>) = changeset.into_iter().partition(|change| {
+        let path = relative_path(change.path(), project_root);
+        (include.is_empty() || include.iter().any(|glob| glob.is_match(&path)))
+            && !exclude.iter().any(|glob| glob.is_match(&path))
+    });
+    if !skipped.is_empty() {
+        for change in &skipped {
+            log::detail("skip", change);
+        }
+        log::basic(format!(
+            "Left out {} change(s) to files ruled out by include or exclude globs.",
+            skipped.len()
+        ));
+    }
+    Ok(kept)
+}
+
+fn compile_globs(globs: &[String]) -> Result<Vec<Regex>, RenomError> {
+    globs
+        .iter()
+        .map(|glob| {
+            Regex::new(&glob_to_regex(glob))
+                .map_err(|err| RenomError::Parse(format!("invalid glob {}: {}", glob, err)))
+        })
+        .collect()
+}
+
+/// Translate a glob into an anchored regex. `*` matches within a path
+/// component, `?` matches a single character within one, and `**` matches
+/// across components, so `ThirdParty/**` matches the folder and everything in
+/// it and `**/*.ini` matches config files anywhere.
+pub fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut rest = glob;
+    while let Some(c) = rest.chars().next() {
+        let (pattern, length) = match rest {
+            _ if rest.starts_with("**/") => ("(?:.*/)?".to_owned(), 3),
+            "/**" => ("(?:/.*)?".to_owned(), 3),
+            _ if rest.starts_with("**") => (".*".to_owned(), 2),
+            _ if c == '*' => ("[^/]*".to_owned(), 1),
+            _ if c == '?' => ("[^/]".to_owned(), 1),
+            _ => (regex::escape(&c.to_string()), c.len_utf8()),
+        };
+        regex += &pattern;
+        rest = &rest[length..];
+    }
+    regex + "$"
+}
+
+/// Get the path of a file relative to the project root with `/` separators,
+/// or the whole path for files outside of the project.
+fn relative_path(path: &Path, project_root: &Path) -> String {
+    path.strip_prefix(project_root)
+        .unwrap_or(path)
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
@@ -0,0 +1,111 @@
+use std::{
+    ffi::OsStr,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::RenomError;
+
+use super::{
+    rename_module::detect_modules, rename_plugin::detect_project_plugins,
+    rename_target::detect_project_targets,
+};
+
+/// A kind of project component that can be renamed.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ComponentKind {
+    Plugins,
+    Modules,
+    Targets,
+}
+
+impl Display for ComponentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComponentKind::Plugins => write!(f, "plugin"),
+            ComponentKind::Modules => write!(f, "module"),
+            ComponentKind::Targets => write!(f, "target"),
+        }
+    }
+}
+
+/// A component of a project, such as a plugin, and where it is located.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    /// The kind of the component.
+    pub kind: ComponentKind,
+    /// The name of the component, as passed to the rename commands.
+    pub name: String,
+    /// The root of the component, or its file for targets.
+    pub path: PathBuf,
+}
+
+/// List the components of a project of one kind, or of every kind. Components
+/// are detected the same way as by the rename workflows and sorted by name
+/// within each kind.
+pub fn list_components(
+    project_root: &Path,
+    kind: Option<ComponentKind>,
+) -> Result<Vec<Component>, RenomError> {
+    validate_project_root_is_dir(project_root)?;
+    validate_project_root_contains_project_descriptor(project_root)?;
+    let wanted = |candidate: ComponentKind| kind.is_none_or(|kind| kind == candidate);
+    let mut components = vec![];
+    if wanted(ComponentKind::Plugins) {
+        let mut plugins = detect_project_plugins(project_root)?;
+        plugins.sort_by(|a, b| a.name.cmp(&b.name));
+        components.extend(plugins.into_iter().map(|plugin| Component {
+            kind: ComponentKind::Plugins,
+            name: plugin.name,
+            path: plugin.root,
+        }));
+    }
+    if wanted(ComponentKind::Modules) {
+        components.extend(
+            detect_modules(project_root)?
+                .into_iter()
+                .map(|module| Component {
+                    kind: ComponentKind::Modules,
+                    name: module.name,
+                    path: module.root,
+                }),
+        );
+    }
+    if wanted(ComponentKind::Targets) && project_root.join("Source").is_dir() {
+        components.extend(
+            detect_project_targets(project_root)?
+                .into_iter()
+                .map(|target| Component {
+                    kind: ComponentKind::Targets,
+                    name: target.name,
+                    path: target.path,
+                }),
+        );
+    }
+    Ok(components)
+}
+
+fn validate_project_root_is_dir(project_root: &Path) -> Result<(), RenomError> {
+    match project_root.is_dir() {
+        true => Ok(()),
+        false => Err(RenomError::Validation(
+            "project root must be a directory".into(),
+        )),
+    }
+}
+
+fn validate_project_root_contains_project_descriptor(
+    project_root: &Path,
+) -> Result<(), RenomError> {
+    match fs::read_dir(project_root)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.path().extension().map(OsStr::to_owned))
+        .any(|ext| ext == "uproject")
+    {
+        true => Ok(()),
+        false => Err(RenomError::Validation(
+            "project root must contain a project descriptor".into(),
+        )),
+    }
+}
@@ -1,11 +1,45 @@
+mod backups;
+mod batch;
+mod batch_file;
+mod conflicts;
+mod discovery;
+mod extra_ops;
+mod filters;
+mod list;
+mod options;
+mod redirects;
 pub mod rename_module;
 pub mod rename_plugin;
 pub mod rename_project;
 pub mod rename_target;
+mod restore;
+mod transaction;
 mod workflow;
 
-pub use rename_module::*;
-pub use rename_plugin::*;
-pub use rename_project::*;
-pub use rename_target::*;
+pub use backups::*;
+pub use batch::*;
+pub use batch_file::*;
+pub use conflicts::*;
+pub use discovery::*;
+pub use extra_ops::*;
+pub use filters::*;
+pub use list::*;
+pub use options::*;
+pub use redirects::*;
+pub use rename_module::{
+    apply_planned_rename_module, plan_rename_module, plan_rename_modules, rename_module,
+    rename_module_interactive, rename_modules,
+};
+pub use rename_plugin::{
+    apply_planned_rename_plugin, plan_rename_plugin, plan_rename_plugins, rename_plugin,
+    rename_plugin_interactive, rename_plugins,
+};
+pub use rename_project::{
+    apply_planned_rename_project, plan_rename_project, rename_project, rename_project_interactive,
+};
+pub use rename_target::{
+    apply_planned_rename_target, plan_rename_target, rename_target, rename_target_interactive,
+};
+pub use restore::*;
+pub use transaction::*;
 pub use workflow::*;
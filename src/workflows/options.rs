@@ -0,0 +1,189 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    backup::{self, BackupPolicy},
+    cancel::CancellationToken,
+    changes::{ApplyOptions, Change},
+    engine::Engine,
+    error::RenomError,
+    progress::ProgressObserver,
+    vcs::{self, Vcs},
+};
+
+/// Options shared by all rename workflows.
+#[derive(Clone, Default)]
+pub struct Options {
+    /// Prompt the user to resolve issues (such as conflicting changes) instead
+    /// of failing.
+    pub interactive: bool,
+    /// Record the rename in the project ledger upon success.
+    pub ledger: bool,
+    /// Restore the original modification time of edited files.
+    pub preserve_mtime: bool,
+    /// Remove redirects made redundant by the rename, such as those left
+    /// behind when reversing an earlier rename.
+    pub collapse_redirects: bool,
+    /// Rewrite content paths in config that include the old project name,
+    /// e.g. `/Game/OldName/Maps/Start` (projects only).
+    pub rewrite_content_paths: bool,
+    /// Rename the Visual Studio solution and project files generated for the
+    /// project and the references to them, for users who do not regenerate
+    /// project files (projects only).
+    pub update_project_files: bool,
+    /// Add a module declared only in the `ExtraModuleNames` of targets to the
+    /// project descriptor (modules only).
+    pub normalize_descriptor: bool,
+    /// Keys of config entries to rename where their value is exactly the old
+    /// name, e.g. `ModuleNames` for `+ModuleNames=OldName` (modules only).
+    pub config_value_keys: Vec<String>,
+    /// The old name to search for in file contents, for projects in a mixed
+    /// state where the detected name is not the one used in source and config.
+    pub from_name: Option<String>,
+    /// Memory-map files when scanning the project instead of reading them,
+    /// falling back to regular reads where mapping fails.
+    pub mmap: bool,
+    /// Traverse symlinked folders, e.g. a shared `Content` folder, when
+    /// scanning the project for references. Changes never follow symlinks
+    /// out of the project root either way.
+    pub follow_symlinks: bool,
+    /// The version control system the project is managed with, if edits
+    /// should cooperate with it, e.g. by keeping the line endings it expects.
+    pub vcs: Option<Vcs>,
+    /// Limits enforced on the backups of earlier renames before renaming, or
+    /// `None` to keep every backup.
+    pub backup_policy: Option<BackupPolicy>,
+    /// The directory to keep backups in, e.g. on a scratch drive or outside
+    /// of source control, instead of `.renom/backup` in the project. Relative
+    /// paths are resolved against the project root.
+    pub backup_dir: Option<PathBuf>,
+    /// Take no backups, e.g. for projects under version control, keeping the
+    /// content of edited files in memory instead. A failed rename is still
+    /// reverted, but renames cannot be restored later.
+    pub no_backup: bool,
+    /// Clear the read-only flag of files to edit, e.g. files that are not
+    /// checked out of Perforce, and set it again once they are edited,
+    /// instead of failing before anything is applied.
+    pub make_writable: bool,
+    /// Skip changes already applied by an earlier run that partly succeeded,
+    /// e.g. files already renamed, instead of failing on them.
+    pub idempotent: bool,
+    /// The number of changes to apply between checkpoints of the journal and
+    /// backups, or `None` to checkpoint after every change.
+    pub chunk_size: Option<usize>,
+    /// The number of threads to apply edits to different files on at once,
+    /// or `None` for a thread per CPU.
+    pub threads: Option<usize>,
+    /// The size in bytes from which files are edited a line at a time rather
+    /// than read into memory, or `None` to edit every file whole.
+    pub stream_threshold: Option<u64>,
+    /// An NDJSON file of extra changes to apply along with the rename, such
+    /// as studio-specific file moves, see [`merge_extra_ops`].
+    ///
+    /// [`merge_extra_ops`]: crate::workflows::merge_extra_ops
+    pub extra_ops: Option<PathBuf>,
+    /// Globs of the files that generated changes may touch, relative to the
+    /// project root, or empty to allow every file, see [`filter_changes`].
+    ///
+    /// [`filter_changes`]: crate::workflows::filter_changes
+    pub include: Vec<String>,
+    /// Globs of the files that generated changes must leave alone, e.g.
+    /// `ThirdParty/**`.
+    pub exclude: Vec<String>,
+    /// Token used to cancel the rename from another thread.
+    pub cancellation: CancellationToken,
+    /// Observer notified as the changes of the rename are applied, e.g. to
+    /// render a progress bar.
+    pub progress: Option<Arc<dyn ProgressObserver>>,
+}
+
+impl Options {
+    /// Check that the old name to search for in file contents, if given, is
+    /// a valid identifier, since it is matched as part of patterns and an
+    /// empty name would match between every character.
+    pub fn validate_from_name(&self) -> Result<(), RenomError> {
+        let Some(from_name) = &self.from_name else {
+            return Ok(());
+        };
+        if from_name.is_empty() {
+            return Err(RenomError::Validation(
+                "old name to search for must not be empty".into(),
+            ));
+        }
+        match from_name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_')
+        {
+            true => Ok(()),
+            false => Err(RenomError::Validation(
+                "old name to search for must be comprised of alphanumeric characters and underscores only"
+                    .into(),
+            )),
+        }
+    }
+
+    /// Get the directory that contains the backups of every rename of a
+    /// project.
+    pub fn backup_root(&self, project_root: &Path) -> PathBuf {
+        match &self.backup_dir {
+            Some(backup_dir) => project_root.join(backup_dir),
+            None => backup::backup_root(project_root),
+        }
+    }
+
+    /// Create an engine that applies changes with the given options and
+    /// the cancellation, checkpoints, threads, and progress observer of
+    /// these options.
+    pub fn engine(&self, apply_options: ApplyOptions) -> Engine {
+        let engine = Engine::with_options(apply_options)
+            .with_cancellation(self.cancellation.clone())
+            .with_chunk_size(self.chunk_size.unwrap_or(1))
+            .with_threads(self.threads.unwrap_or(0));
+        match &self.progress {
+            Some(observer) => engine.with_observer(observer.clone()),
+            None => engine,
+        }
+    }
+
+    /// Get the options that control how changes are applied to disk.
+    pub fn apply_options(&self) -> ApplyOptions {
+        ApplyOptions {
+            preserve_mtime: self.preserve_mtime,
+            no_backup: self.no_backup,
+            make_writable: self.make_writable,
+            idempotent: self.idempotent,
+            stream_threshold: self.stream_threshold,
+            ..ApplyOptions::default()
+        }
+    }
+
+    /// Get the options that control how a changeset is applied to disk,
+    /// including any line endings expected by version control for the files
+    /// it edits, keeping changes from following symlinks out of the project.
+    /// Idempotent renames also get the changes applied by earlier renames of
+    /// the project, as recorded in their backups.
+    pub fn apply_options_for(
+        &self,
+        changeset: &[Change],
+        project_root: &Path,
+    ) -> Result<ApplyOptions, RenomError> {
+        let mut options = ApplyOptions {
+            root: Some(project_root.to_owned()),
+            ..self.apply_options()
+        };
+        if self.idempotent {
+            options.applied_earlier = backup::applied_changes(&self.backup_root(project_root))?;
+        }
+        if self.vcs == Some(Vcs::Git) {
+            let edited = changeset
+                .iter()
+                .filter(|change| !matches!(change, Change::RenameFile(_)))
+                .map(|change| change.path().to_owned())
+                .collect::<Vec<PathBuf>>();
+            options.line_endings = vcs::git_line_endings(project_root, &edited)?;
+        }
+        Ok(options)
+    }
+}
@@ -0,0 +1,48 @@
+use std::{collections::BTreeSet, io, path::Path};
+
+use crate::{changes::Change, error::RenomError, ledger, text::TextFile};
+
+/// Collect the redirects that a changeset adds to config, to record them in
+/// the ledger, see [`ledger::redirect_in`]. Redirects already present in
+/// config, e.g. added by an earlier run, are left out, so that the ledger
+/// only records the rename that actually added each redirect.
+pub fn added_redirects(changeset: &[Change]) -> Vec<String> {
+    changeset
+        .iter()
+        .enumerate()
+        .filter_map(|(index, change)| {
+            ledger::redirect_in(change).filter(|_| !is_present(&changeset[..=index]))
+        })
+        .collect()
+}
+
+/// Read the redirects that earlier renames of a project added to config, as
+/// recorded in its ledger, see [`ledger::recorded_redirects`].
+pub fn read_recorded_redirects(project_root: &Path) -> Result<BTreeSet<String>, RenomError> {
+    let entries = ledger::read(project_root)?;
+    Ok(ledger::recorded_redirects(&entries)
+        .into_iter()
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Check whether the entry appended by the last change is already in config
+/// once the earlier changes to the same file are applied.
+fn is_present(changeset: &[Change]) -> bool {
+    let (change, earlier) = match changeset.split_last() {
+        Some(split) => split,
+        None => return false,
+    };
+    let check = || -> io::Result<bool> {
+        let mut file = TextFile::read(change.path())?;
+        for other in earlier.iter().filter(|other| other.path() == change.path()) {
+            if let Some(content) = other.edit(&file)? {
+                file.content = content;
+            }
+        }
+        Ok(change
+            .edit(&file)?
+            .is_some_and(|content| content == file.content))
+    };
+    check().unwrap_or(false)
+}
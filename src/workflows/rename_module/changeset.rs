@@ -1,5 +1,5 @@
 use std::{
-    fs,
+    collections::BTreeSet,
     path::{Path, PathBuf},
 };
 
@@ -7,6 +7,7 @@ use regex::Regex;
 
 use crate::{
     changes::{AppendIniEntry, Change, RenameFile, ReplaceInFile},
+    text::TextFile,
     unreal::{Module, ModuleType, Plugin},
 };
 
@@ -27,13 +28,24 @@ pub fn generate_changeset(context: &Context) -> Vec<Change> {
                 r#type,
                 plugin,
             },
+        search_name,
         new_name,
         source_with_implement_macro,
         headers_with_export_macro,
+        sources_with_module_paths,
+        sources_with_module_lookups,
+        collapse_redirects,
+        recorded_redirects,
+        ..
     } = context;
 
     let mut changeset = vec![];
-    changeset.push(rename_build_class(module_root, old_name, new_name));
+    changeset.push(rename_build_class(
+        module_root,
+        old_name,
+        search_name,
+        new_name,
+    ));
     changeset.push(rename_build_file(module_root, old_name, new_name));
 
     if let Some(source_file) = source_with_implement_macro {
@@ -43,7 +55,19 @@ pub fn generate_changeset(context: &Context) -> Vec<Change> {
     changeset.extend(
         headers_with_export_macro
             .iter()
-            .map(|header_file| rename_api_macro_in_header(header_file, old_name, new_name)),
+            .map(|header_file| rename_api_macro_in_header(header_file, search_name, new_name)),
+    );
+
+    changeset.extend(
+        sources_with_module_paths
+            .iter()
+            .map(|source_file| replace_module_paths_in_source(source_file, old_name, new_name)),
+    );
+
+    changeset.extend(
+        sources_with_module_lookups.iter().map(|source_file| {
+            replace_module_lookups_in_source(source_file, search_name, new_name)
+        }),
     );
 
     changeset.push(rename_source_subfolder(module_root, new_name));
@@ -51,7 +75,7 @@ pub fn generate_changeset(context: &Context) -> Vec<Change> {
     changeset.extend(
         project_targets
             .iter()
-            .map(|target_file| replace_mod_reference_in_target(target_file, old_name, new_name)),
+            .map(|target_file| replace_mod_reference_in_target(target_file, search_name, new_name)),
     );
 
     changeset.extend(
@@ -61,41 +85,75 @@ pub fn generate_changeset(context: &Context) -> Vec<Change> {
             .map(|module| {
                 replace_mod_reference_in_mod(
                     &module.root.join(&module.name).with_extension("Build.cs"),
-                    old_name,
+                    search_name,
                     new_name,
                 )
             }),
     );
 
-    changeset.push(replace_mod_reference_in_project_descriptor(
-        project_root,
-        project_name,
-        old_name,
-        new_name,
-    ));
+    match (
+        context.is_declared_in_targets_only(),
+        context.normalize_descriptor,
+    ) {
+        (false, _) => changeset.push(replace_mod_reference_in_project_descriptor(
+            project_root,
+            project_name,
+            search_name,
+            new_name,
+        )),
+        (true, true) => changeset.push(declare_mod_in_project_descriptor(
+            project_root,
+            project_name,
+            context.descriptor_modules.as_deref(),
+            new_name,
+        )),
+        (true, false) => {}
+    }
 
     if let ModuleType::Plugin = r#type {
         changeset.push(replace_mod_reference_in_plugin_descriptor(
-            &plugin.as_ref().unwrap(),
-            old_name,
+            plugin.as_ref().unwrap(),
+            search_name,
             new_name,
         ));
     }
 
     changeset.extend(
-        project_config_files
-            .iter()
-            .map(|config_file| replace_mod_references_in_config(config_file, old_name, new_name)),
+        project_config_files.iter().map(|config_file| {
+            replace_mod_references_in_config(config_file, search_name, new_name)
+        }),
     );
 
-    changeset.push(update_existing_redirects(project_root, old_name, new_name));
-    changeset.push(append_mod_redirect(project_root, old_name, new_name));
+    let mut config_values = context
+        .config_values
+        .iter()
+        .filter(|value| context.config_value_keys.contains(&value.key))
+        .map(|value| (&value.path, &value.key))
+        .collect::<Vec<(&PathBuf, &String)>>();
+    config_values.dedup();
+    changeset.extend(config_values.into_iter().map(|(config_file, key)| {
+        replace_mod_values_in_config(config_file, key, search_name, new_name)
+    }));
+
+    changeset.push(update_existing_redirects(
+        project_root,
+        search_name,
+        new_name,
+    ));
+    if *collapse_redirects {
+        changeset.extend(remove_redundant_redirects(
+            project_root,
+            new_name,
+            recorded_redirects,
+        ));
+    }
+    changeset.push(append_mod_redirect(project_root, search_name, new_name));
 
     changeset
 }
 
 fn update_implement_macro(source_file: &PathBuf, new_name: &str) -> Change {
-    let content = fs::read_to_string(&source_file).unwrap();
+    let content = TextFile::read(source_file).unwrap().content;
     let regex =
         Regex::new(r#"(?P<macro>IMPLEMENT_(GAME_|PRIMARY_GAME_)?MODULE)\((?P<impl>.+?),"#).unwrap();
     let captures = regex.captures(&content).unwrap();
@@ -126,6 +184,32 @@ fn update_existing_redirects(project_root: &Path, old_name: &str, new_name: &str
     ))
 }
 
+/// Remove redirects that point a module at itself, as left behind by
+/// `update_existing_redirects` when a rename reverses an earlier one. Only
+/// done if an earlier rename recorded adding a redirect from the new name,
+/// so that redirects written by hand are left alone.
+fn remove_redundant_redirects(
+    project_root: &Path,
+    new_name: &str,
+    recorded_redirects: &BTreeSet<String>,
+) -> Option<Change> {
+    let recorded_from = format!(r#"+PackageRedirects=(OldName="/Script/{}","#, new_name);
+    if !recorded_redirects
+        .iter()
+        .any(|redirect| redirect.starts_with(&recorded_from))
+    {
+        return None;
+    }
+    Some(Change::ReplaceInFile(ReplaceInFile::new(
+        project_root.join("Config").join("DefaultEngine.ini"),
+        format!(
+            r#"(?m)^\+PackageRedirects=\(OldName="/Script/{0}",\s*NewName="/Script/{0}"\)\r?\n"#,
+            new_name
+        ),
+        "",
+    )))
+}
+
 fn append_mod_redirect(project_root: &Path, old_name: &str, new_name: &str) -> Change {
     Change::AppendIniEntry(AppendIniEntry::new(
         project_root.join("Config").join("DefaultEngine.ini"),
@@ -161,10 +245,15 @@ fn rename_build_file(module_root: &Path, old_name: &str, new_name: &str) -> Chan
     ))
 }
 
-fn rename_build_class(module_root: &Path, old_name: &str, new_name: &str) -> Change {
+fn rename_build_class(
+    module_root: &Path,
+    old_name: &str,
+    search_name: &str,
+    new_name: &str,
+) -> Change {
     Change::ReplaceInFile(ReplaceInFile::new(
         module_root.join(old_name).with_extension("Build.cs"),
-        old_name,
+        search_name,
         new_name,
     ))
 }
@@ -177,6 +266,40 @@ fn rename_api_macro_in_header(header_file: &Path, old_name: &str, new_name: &str
     ))
 }
 
+/// Replace paths into the module folder in string literals, keeping whichever
+/// separator style (`/`, `\`, or escaped `\\`) was used.
+fn replace_module_paths_in_source(source_file: &Path, old_name: &str, new_name: &str) -> Change {
+    Change::ReplaceInFile(ReplaceInFile::new(
+        source_file,
+        format!(
+            r#"(?P<prefix>Source(?:/|\\{{1,2}})){}(?P<suffix>/|\\{{1,2}}|")"#,
+            old_name
+        ),
+        format!("${{prefix}}{}${{suffix}}", new_name),
+    ))
+}
+
+/// Get a regex matching the name of a module passed to the module manager in
+/// a string literal, e.g. `LoadModuleChecked<IOld>("Old")` or
+/// `IsModuleLoaded(TEXT("Old"))`.
+pub fn module_lookup_pattern(name: &str) -> String {
+    format!(
+        r#"(?P<call>\b(?:LoadModule\w*|GetModule\w*|IsModuleLoaded|UnloadModule|ModuleExists)\s*(?:<[^;()"]*>)?\s*\(\s*(?:TEXT\s*\(\s*)?)"{}""#,
+        regex::escape(name)
+    )
+}
+
+fn replace_module_lookups_in_source(source_file: &Path, old_name: &str, new_name: &str) -> Change {
+    Change::ReplaceInFile(
+        ReplaceInFile::new(
+            source_file,
+            module_lookup_pattern(old_name),
+            format!(r#"${{call}}"{}""#, new_name),
+        )
+        .in_literals(),
+    )
+}
+
 fn rename_source_subfolder(module_root: &Path, new_name: &str) -> Change {
     Change::RenameFile(RenameFile::new(
         module_root,
@@ -197,6 +320,39 @@ fn replace_mod_reference_in_project_descriptor(
     ))
 }
 
+/// Add a runtime module to the `Modules` list of the project descriptor,
+/// creating the list if needed, without reformatting the rest of the file.
+fn declare_mod_in_project_descriptor(
+    project_root: &Path,
+    project_name: &str,
+    descriptor_modules: Option<&[String]>,
+    new_name: &str,
+) -> Change {
+    let entry = format!(
+        "\t\t{{\n\t\t\t\"Name\": \"{}\",\n\t\t\t\"Type\": \"Runtime\",\n\t\t\t\"LoadingPhase\": \"Default\"\n\t\t}}",
+        new_name
+    );
+    let (pattern, replacement) = match descriptor_modules {
+        None => (
+            r"^(?P<open>\s*\{)".to_owned(),
+            format!("${{open}}\n\t\"Modules\": [\n{}\n\t],", entry),
+        ),
+        Some([]) => (
+            r#""Modules"\s*:\s*\[\s*\]"#.to_owned(),
+            format!("\"Modules\": [\n{}\n\t]", entry),
+        ),
+        Some(_) => (
+            r#"(?P<open>"Modules"\s*:\s*\[)"#.to_owned(),
+            format!("${{open}}\n{},", entry),
+        ),
+    };
+    Change::ReplaceInFile(ReplaceInFile::new(
+        project_root.join(project_name).with_extension("uproject"),
+        pattern,
+        replacement,
+    ))
+}
+
 fn replace_mod_reference_in_plugin_descriptor(
     plugin: &Plugin,
     old_name: &str,
@@ -216,3 +372,23 @@ fn replace_mod_references_in_config(config: &Path, old_name: &str, new_name: &st
         format!(r#"/Script/{}."#, new_name),
     ))
 }
+
+/// Rename the values of config entries with a key that are exactly the old
+/// module name, e.g. `+ModuleNames=OldName`, keeping any array operator and
+/// quotes.
+fn replace_mod_values_in_config(
+    config: &Path,
+    key: &str,
+    old_name: &str,
+    new_name: &str,
+) -> Change {
+    Change::ReplaceInFile(ReplaceInFile::new(
+        config,
+        format!(
+            r#"(?m)^(?P<key>[ \t]*[+\-.!]?[ \t]*{}[ \t]*=[ \t]*"?){}(?P<end>"?[ \t]*\r?)$"#,
+            regex::escape(key),
+            regex::escape(old_name)
+        ),
+        format!("${{key}}{}${{end}}", new_name),
+    ))
+}
@@ -4,38 +4,85 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use inquire::{validator::Validation, CustomUserError, Select, Text};
+use inquire::{validator::Validation, Confirm, CustomUserError, MultiSelect, Select, Text};
 use regex::Regex;
 use walkdir::WalkDir;
 
-use crate::unreal::{Module, ModuleType, Plugin};
+use crate::{
+    ledger,
+    presentation::log,
+    settings::Settings,
+    unreal::{self, Module, ModuleType, Plugin},
+    workflows::select_discovered_project,
+};
 
-use super::Params;
+use super::{detect_modules, detect_project_config_files, find_config_values, Options, Params};
 
 pub fn get_params_from_user() -> Result<Params, String> {
     let project_root = get_project_root_from_user()?;
+    let settings = Settings::load_for(&project_root)?;
+    settings.apply();
+    let history = ledger::read(&project_root).map_err(|err| err.to_string())?;
     let project_plugins = detect_project_plugins(&project_root)?;
     let modules = detect_project_modules(&project_root)?
         .into_iter()
         .chain(detect_plugin_modules(&project_plugins)?)
         .collect::<Vec<Module>>();
     let target_module = get_target_module_from_user(&modules)?;
-    let target_name = get_target_name_from_user(&modules)?;
+    let target_name = get_target_name_from_user(&modules, ledger::naming_prefix(&history))?;
+    let collapse_redirects = confirm_collapse_redirects(
+        &history,
+        &target_module.name,
+        &target_name,
+        settings.prompts.collapse_redirects,
+    )?;
+    let config_value_keys = get_config_value_keys_from_user(&project_root, &target_module.name)?;
 
     Ok(Params {
         project_root,
         module: target_module.name,
         new_name: target_name,
+        options: Options {
+            collapse_redirects,
+            config_value_keys,
+            interactive: !settings.general.non_interactive,
+            backup_policy: Some(settings.backup),
+            backup_dir: settings.general.backup_dir,
+            include: settings.general.include,
+            exclude: settings.general.exclude,
+            chunk_size: settings.engine.chunk_size,
+            threads: settings.engine.threads,
+            ..Options::default()
+        },
     })
 }
 
-fn get_project_root_from_user() -> Result<PathBuf, String> {
+/// Ask the user to choose a module of a project, e.g. when it was not given on
+/// the command line.
+pub fn get_module_from_user(project_root: &Path) -> Result<Module, String> {
+    get_target_module_from_user(&detect_modules(project_root)?)
+}
+
+/// Ask the user for the new name of a module, e.g. when it was not given on the
+/// command line.
+pub fn get_new_name_from_user(project_root: &Path) -> Result<String, String> {
+    let history = ledger::read(project_root).map_err(|err| err.to_string())?;
+    let modules = detect_modules(project_root)?;
+    get_target_name_from_user(&modules, ledger::naming_prefix(&history))
+}
+
+/// Ask the user for the root of a project, offering the projects discovered
+/// around the working directory first.
+pub fn get_project_root_from_user() -> Result<PathBuf, String> {
+    if let Some(project_root) = select_discovered_project(|root| root.join("Source").is_dir())? {
+        return Ok(project_root);
+    }
     Text::new("Project root directory path:")
         .with_validator(validate_project_root_is_dir)
         .with_validator(validate_project_root_contains_project_descriptor)
         .with_validator(validate_project_root_contains_source_dir)
         .prompt()
-        .map(|project_root| PathBuf::from(project_root))
+        .map(PathBuf::from)
         .map_err(|err| err.to_string())
 }
 
@@ -80,17 +127,12 @@ fn validate_project_root_contains_source_dir(
 /// Detect all plugins in a project given the path to the project root
 /// directory. Detects top-level plugins and nested plugins. Returns an error in
 /// case of I/O issues.
-fn detect_project_plugins(project_root: &PathBuf) -> Result<Vec<Plugin>, String> {
-    let plugins_dir = project_root.join("Plugins");
-    Ok(WalkDir::new(plugins_dir)
+fn detect_project_plugins(project_root: &Path) -> Result<Vec<Plugin>, String> {
+    Ok(unreal::plugin_directories(project_root)
         .into_iter()
+        .flat_map(WalkDir::new)
         .filter_map(Result::ok)
-        .filter(|entry| {
-            entry
-                .path()
-                .extension()
-                .map_or(false, |ext| ext == "uplugin")
-        })
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "uplugin"))
         .map(|entry| Plugin {
             root: entry.path().parent().unwrap().to_owned(),
             name: entry
@@ -107,7 +149,7 @@ fn detect_project_plugins(project_root: &PathBuf) -> Result<Vec<Plugin>, String>
 /// Detect all project modules in a project given the path to the project root
 /// directory. Detects top-level modules and nested modules. Assumes that the
 /// Source folder exists. Returns an error in case of I/O issues.
-fn detect_project_modules(project_root: &PathBuf) -> Result<Vec<Module>, String> {
+fn detect_project_modules(project_root: &Path) -> Result<Vec<Module>, String> {
     let source_dir = project_root.join("Source");
     assert!(source_dir.is_dir());
     Ok(WalkDir::new(source_dir)
@@ -116,7 +158,7 @@ fn detect_project_modules(project_root: &PathBuf) -> Result<Vec<Module>, String>
         .filter(|entry| entry.path().is_dir() && dir_contains_module_descriptor(entry.path()))
         .map(|entry| Module {
             root: entry.path().to_owned(),
-            name: get_dir_name(&entry.path()),
+            name: get_dir_name(entry.path()),
             r#type: ModuleType::Project,
             plugin: None,
         })
@@ -138,7 +180,7 @@ fn detect_plugin_modules(project_plugins: &[Plugin]) -> Result<Vec<Module>, Stri
                 })
                 .map(move |entry| Module {
                     root: entry.path().to_owned(),
-                    name: get_dir_name(&entry.path()),
+                    name: get_dir_name(entry.path()),
                     r#type: ModuleType::Plugin,
                     plugin: Some(plugin.clone()),
                 })
@@ -166,15 +208,19 @@ fn get_target_module_from_user(modules: &[Module]) -> Result<Module, String> {
         .map_err(|err| err.to_string())
 }
 
-fn get_target_name_from_user(modules: &[Module]) -> Result<String, String> {
+fn get_target_name_from_user(modules: &[Module], prefix: Option<String>) -> Result<String, String> {
     let modules = modules.to_vec();
-    Text::new("Provide a new name for the module:")
+    let prompt = Text::new("Provide a new name for the module:")
         .with_validator(validate_target_name_is_not_empty)
         .with_validator(validate_target_name_is_concise)
         .with_validator(move |input: &str| validate_target_name_is_unique(input, &modules))
-        .with_validator(validate_target_name_is_valid_identifier)
-        .prompt()
-        .map_err(|err| err.to_string())
+        .with_validator(validate_target_name_is_valid_identifier);
+    match prefix.as_deref() {
+        Some(prefix) => prompt.with_initial_value(prefix),
+        None => prompt,
+    }
+    .prompt()
+    .map_err(|err| err.to_string())
 }
 
 fn validate_target_name_is_not_empty(target_name: &str) -> Result<Validation, CustomUserError> {
@@ -227,3 +273,53 @@ fn validate_target_name_is_valid_identifier(
         }
     }
 }
+
+/// Warn the user if the rename reverses an earlier one recorded in the ledger
+/// and offer to remove the redirects that would become redundant.
+fn confirm_collapse_redirects(
+    history: &[ledger::Entry],
+    old_name: &str,
+    new_name: &str,
+    seeded: Option<bool>,
+) -> Result<bool, String> {
+    match ledger::find_reversal(history, "module", old_name, new_name) {
+        Some(entry) => {
+            log::warning(format!(
+                "This reverses the rename of module {} to {} on {}.",
+                entry.old_name, entry.new_name, entry.date
+            ));
+            Confirm::new("Would you like to remove redirects made redundant by this rename?")
+                .with_default(seeded.unwrap_or(true))
+                .prompt()
+                .map_err(|err| err.to_string())
+        }
+        None => Ok(false),
+    }
+}
+
+/// Let the user choose which config entries set to the module name to rename,
+/// grouped by key, e.g. the `ModuleNames` arrays of map and mode overrides.
+fn get_config_value_keys_from_user(
+    project_root: &Path,
+    module_name: &str,
+) -> Result<Vec<String>, String> {
+    let config_files = detect_project_config_files(project_root)?;
+    let mut keys = find_config_values(&config_files, module_name)?
+        .into_iter()
+        .map(|value| value.key)
+        .collect::<Vec<String>>();
+    keys.sort();
+    keys.dedup();
+    if keys.is_empty() {
+        return Ok(keys);
+    }
+    MultiSelect::new(
+        &format!(
+            "Config entries with these keys are set to {}. Choose which to rename:",
+            module_name
+        ),
+        keys,
+    )
+    .prompt()
+    .map_err(|err| err.to_string())
+}
@@ -2,6 +2,7 @@ mod changeset;
 mod interactive;
 
 use std::{
+    collections::BTreeSet,
     ffi::OsStr,
     fs,
     path::{Path, PathBuf},
@@ -11,12 +12,30 @@ use regex::Regex;
 use walkdir::WalkDir;
 
 use crate::{
-    engine::Engine,
-    presentation::log,
-    unreal::{Module, ModuleType, Plugin},
+    changes::{Change, Changeset},
+    doctor,
+    error::RenomError,
+    ledger,
+    manifest::{self, Manifest},
+    matcher::Matcher,
+    plan,
+    presentation::{log, preview},
+    text::TextFile,
+    unreal::{self, Module, ModuleType, Plugin},
+    workflows::{
+        added_redirects, check_execution, combine_changesets, filter_changes, merge_extra_ops,
+        prepare_backup_dir, read_recorded_redirects, resolve_conflicts, validate_batch, Options,
+    },
 };
 
-use self::{changeset::generate_changeset, interactive::get_params_from_user};
+use self::{
+    changeset::{generate_changeset, module_lookup_pattern},
+    interactive::get_params_from_user,
+};
+
+pub use self::interactive::{
+    get_module_from_user, get_new_name_from_user, get_project_root_from_user,
+};
 
 /// Params needed to rename an Unreal Engine module.
 pub struct Params {
@@ -26,6 +45,20 @@ pub struct Params {
     pub module: String,
     /// The new name for the module.
     pub new_name: String,
+    /// Options for the rename.
+    pub options: Options,
+}
+
+/// A config entry whose value is exactly the name of a module, e.g.
+/// `+ModuleNames=OldName` in an array that enumerates modules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigValue {
+    /// The config file with the entry.
+    pub path: PathBuf,
+    /// The section of the entry.
+    pub section: String,
+    /// The key of the entry, without any array operator.
+    pub key: String,
 }
 
 /// Context needed to rename an Unreal Engine module.
@@ -40,42 +73,263 @@ pub struct Context {
     pub project_config_files: Vec<PathBuf>,
     /// Code modules in the project.
     pub modules: Vec<Module>,
+    /// Names of the modules declared in the project descriptor, or `None` if
+    /// it has no `Modules` list. Legacy projects may declare modules in the
+    /// `ExtraModuleNames` of their targets only.
+    pub descriptor_modules: Option<Vec<String>>,
     /// The specific module to rename.
     pub module: Module,
+    /// The name to search for in file contents. Matches the old name unless
+    /// overridden.
+    pub search_name: String,
     /// The new name for the module.
     pub new_name: String,
     /// The source file that includes the module implement macro.
     pub source_with_implement_macro: Option<PathBuf>,
     /// Header files that include the module export macro.
     pub headers_with_export_macro: Vec<PathBuf>,
+    /// Source files with paths into the module folder in string literals,
+    /// e.g. `"Source/OldName/Public"` or `"Source\\OldName\\Public"`.
+    pub sources_with_module_paths: Vec<PathBuf>,
+    /// Source files that pass the module name to the module manager in a
+    /// string literal, e.g. `FModuleManager::LoadModuleChecked<IOld>("Old")`.
+    pub sources_with_module_lookups: Vec<PathBuf>,
+    /// Config entries whose value is exactly the name of the module.
+    pub config_values: Vec<ConfigValue>,
+    /// Keys of the config entries in `config_values` to rename.
+    pub config_value_keys: Vec<String>,
+    /// Whether to remove redirects made redundant by the rename.
+    pub collapse_redirects: bool,
+    /// Redirects that earlier renames added to config, as recorded in the
+    /// ledger, see [`ledger::recorded_redirects`]. Only these are removed
+    /// when redirects are collapsed, so that those written by hand are kept.
+    pub recorded_redirects: BTreeSet<String>,
+    /// Whether to add the module to the project descriptor if it is declared
+    /// in targets only.
+    pub normalize_descriptor: bool,
+}
+
+impl Context {
+    /// Config entries set to the module name that the rename leaves alone,
+    /// because their keys were not chosen.
+    pub fn skipped_config_values(&self) -> Vec<&ConfigValue> {
+        self.config_values
+            .iter()
+            .filter(|value| !self.config_value_keys.contains(&value.key))
+            .collect()
+    }
+
+    /// Whether the module is a project module that the project descriptor
+    /// does not declare, leaving the targets as its only reference.
+    pub fn is_declared_in_targets_only(&self) -> bool {
+        matches!(self.module.r#type, ModuleType::Project)
+            && self
+                .descriptor_modules
+                .as_ref()
+                .is_none_or(|names| !names.contains(&self.search_name))
+    }
 }
 
 /// Rename an Unreal Engine module interactively, soliciting input parameters
-/// from the user with validation and guided selection.
-pub fn rename_module_interactive() -> Result<(), String> {
-    let params = get_params_from_user()?;
-    rename_module(params)
+/// from the user with validation and guided selection. Returns the root of the
+/// project, with a report of the rename written to it, or `None` if the user
+/// cancelled the rename.
+pub fn rename_module_interactive() -> Result<Option<PathBuf>, RenomError> {
+    let params = get_params_from_user().map_err(RenomError::Validation)?;
+    let project_root = params.project_root.clone();
+    let Some(manifest) = rename(params, None)? else {
+        return Ok(None);
+    };
+    manifest::write_report(&project_root, &manifest)?;
+    Ok(Some(project_root))
+}
+
+/// Generate the changes needed to rename an Unreal Engine module without
+/// applying them.
+pub fn plan_rename_module(params: &Params) -> Result<Changeset, RenomError> {
+    validate_params(params)?;
+    let context = gather_context(params)?;
+    params.options.cancellation.check()?;
+    let changeset = merge_extra_ops(
+        filter_changes(
+            generate_changeset(&context),
+            &context.project_root,
+            &params.options,
+        )?,
+        params.options.extra_ops.as_deref(),
+        &context.project_root,
+    )?;
+    let changeset = resolve_conflicts(changeset, params.options.interactive)?;
+    Ok(Changeset::new(changeset))
 }
 
 /// Rename an Unreal Engine module.
-pub fn rename_module(params: Params) -> Result<(), String> {
+pub fn rename_module(params: Params) -> Result<(), RenomError> {
+    rename(params, None).map(|_| ())
+}
+
+/// Apply changes planned earlier with [`plan_rename_module`]. The params are
+/// validated again and the plan is only applied if it still matches the
+/// changes needed for the current state of the project.
+pub fn apply_planned_rename_module(params: Params, plan: &Changeset) -> Result<(), RenomError> {
+    rename(params, Some(plan)).map(|_| ())
+}
+
+/// Generate the changes needed to rename several modules of a project at once
+/// without applying them, see [`rename_modules`].
+pub fn plan_rename_modules(params: &[Params]) -> Result<Changeset, RenomError> {
+    let (_, changeset) = plan_batch(params)?;
+    Ok(Changeset::new(changeset))
+}
+
+/// Rename several modules of a project at once, with one combined changeset and
+/// one backup, so that either all of them are renamed or none are. Each
+/// rename is planned against the current state of the project, and the
+/// options of the first params apply to the whole batch.
+pub fn rename_modules(params: Vec<Params>) -> Result<(), RenomError> {
+    let (batch, changeset) = plan_batch(&params)?;
+    let options = &params[0].options;
+    let project_root = &params[0].project_root;
+    let manifest = Manifest::new(
+        "module",
+        batch
+            .iter()
+            .map(|(context, _)| context.module.name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", "),
+        batch
+            .iter()
+            .map(|(context, _)| context.new_name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", "),
+        &changeset,
+    );
+    for (context, _) in &batch {
+        doctor::warn_about_unhandled_references(&context.project_root, &context.search_name, &[]);
+        warn_if_declared_in_targets_only(context);
+        warn_about_skipped_config_values(context);
+    }
+    check_execution(&changeset, project_root, options)?;
+    if options.interactive && !preview::review(&changeset, project_root)? {
+        log::basic("Rename cancelled.");
+        return Ok(());
+    }
+    let backup_root = options.backup_root(project_root);
+    let backup_dir = prepare_backup_dir(&backup_root, options)?;
+    let apply_options = options.apply_options_for(&changeset, project_root)?;
+    let mut engine = options.engine(apply_options);
+    if let Err(e) = engine.execute(changeset, backup_dir) {
+        engine.revert()?;
+        for (context, _) in &batch {
+            print_failure_message(context);
+        }
+        return Err(e);
+    }
+
+    for (context, redirects) in batch.iter() {
+        if options.ledger {
+            record_in_ledger(context, redirects.clone())?;
+        }
+    }
+
+    manifest::write_for_review(project_root, &manifest, options.vcs)?;
+
+    for (context, _) in &batch {
+        print_success_message(context);
+    }
+    Ok(())
+}
+
+/// The contexts of the renames in a batch, each with the redirects it adds.
+type Batch = Vec<(Context, Vec<String>)>;
+
+/// Plan each rename of a batch and combine their changes.
+fn plan_batch(params: &[Params]) -> Result<(Batch, Vec<Change>), RenomError> {
+    let renames = params
+        .iter()
+        .map(|params| (params.module.as_str(), params.new_name.as_str()))
+        .collect::<Vec<(&str, &str)>>();
+    validate_batch(&renames)?;
+    let mut batch = vec![];
+    let mut changesets = vec![];
+    for params in params {
+        validate_params(params)?;
+        let context = gather_context(params)?;
+        params.options.cancellation.check()?;
+        let changeset = resolve_conflicts(
+            filter_changes(
+                generate_changeset(&context),
+                &context.project_root,
+                &params.options,
+            )?,
+            params.options.interactive,
+        )?;
+        batch.push((context, added_redirects(&changeset)));
+        changesets.push(changeset);
+    }
+    let changeset = merge_extra_ops(
+        combine_changesets(changesets)?,
+        params[0].options.extra_ops.as_deref(),
+        &params[0].project_root,
+    )?;
+    Ok((batch, changeset))
+}
+
+fn rename(params: Params, plan: Option<&Changeset>) -> Result<Option<Manifest>, RenomError> {
     validate_params(&params)?;
     let context = gather_context(&params)?;
-    let changeset = generate_changeset(&context);
-    let backup_dir = create_backup_dir(&context.project_root)?;
-    let mut engine = Engine::new();
+    params.options.cancellation.check()?;
+    let changeset = merge_extra_ops(
+        filter_changes(
+            generate_changeset(&context),
+            &context.project_root,
+            &params.options,
+        )?,
+        params.options.extra_ops.as_deref(),
+        &context.project_root,
+    )?;
+    let changeset = resolve_conflicts(changeset, params.options.interactive)?;
+    if let Some(plan) = plan {
+        plan::check_is_current(plan, &changeset)?;
+    }
+    let redirects = added_redirects(&changeset);
+    let manifest = Manifest::new(
+        "module",
+        &context.module.name,
+        &context.new_name,
+        &changeset,
+    );
+    doctor::warn_about_unhandled_references(&context.project_root, &context.search_name, &[]);
+    warn_if_declared_in_targets_only(&context);
+    warn_about_skipped_config_values(&context);
+    check_execution(&changeset, &context.project_root, &params.options)?;
+    if params.options.interactive && !preview::review(&changeset, &context.project_root)? {
+        log::basic("Rename cancelled.");
+        return Ok(None);
+    }
+    let backup_root = params.options.backup_root(&context.project_root);
+    let backup_dir = prepare_backup_dir(&backup_root, &params.options)?;
+    let apply_options = params
+        .options
+        .apply_options_for(&changeset, &context.project_root)?;
+    let mut engine = params.options.engine(apply_options);
     if let Err(e) = engine.execute(changeset, backup_dir) {
-        log::error(&e);
         engine.revert()?;
         print_failure_message(&context);
-        return Ok(());
+        return Err(e);
     }
 
+    if params.options.ledger {
+        record_in_ledger(&context, redirects)?;
+    }
+
+    manifest::write_for_review(&context.project_root, &manifest, params.options.vcs)?;
+
     print_success_message(&context);
-    Ok(())
+    Ok(Some(manifest))
 }
 
-fn validate_params(params: &Params) -> Result<(), String> {
+fn validate_params(params: &Params) -> Result<(), RenomError> {
     validate_project_root_is_dir(&params.project_root)?;
     validate_project_root_contains_project_descriptor(&params.project_root)?;
     validate_project_root_contains_source_dir(&params.project_root)?;
@@ -89,50 +343,60 @@ fn validate_params(params: &Params) -> Result<(), String> {
     validate_new_name_is_concise(&params.new_name)?;
     validate_new_name_is_unique(&params.new_name, &modules)?;
     validate_new_name_is_valid_identifier(&params.new_name)?;
+    params.options.validate_from_name()?;
     Ok(())
 }
 
-fn validate_project_root_is_dir(project_root: &Path) -> Result<(), String> {
+fn validate_project_root_is_dir(project_root: &Path) -> Result<(), RenomError> {
     match project_root.is_dir() {
         true => Ok(()),
-        false => Err("project root must be a directory".into()),
+        false => Err(RenomError::Validation(
+            "project root must be a directory".into(),
+        )),
     }
 }
 
-fn validate_project_root_contains_project_descriptor(project_root: &Path) -> Result<(), String> {
-    match fs::read_dir(project_root)
-        .map_err(|e| e.to_string())?
+fn validate_project_root_contains_project_descriptor(
+    project_root: &Path,
+) -> Result<(), RenomError> {
+    match fs::read_dir(project_root)?
         .filter_map(Result::ok)
         .filter_map(|entry| entry.path().extension().map(OsStr::to_owned))
         .any(|ext| ext == "uproject")
     {
         true => Ok(()),
-        false => Err("project root must contain a project descriptor".into()),
+        false => Err(RenomError::Validation(
+            "project root must contain a project descriptor".into(),
+        )),
     }
 }
 
-fn validate_project_root_contains_source_dir(project_root: &Path) -> Result<(), String> {
+fn validate_project_root_contains_source_dir(project_root: &Path) -> Result<(), RenomError> {
     match project_root.join("Source").is_dir() {
         true => Ok(()),
-        false => Err("project root must contain a Source folder".into()),
+        false => Err(RenomError::Validation(
+            "project root must contain a Source folder".into(),
+        )),
     }
 }
 
-fn validate_module_exists(module: &str, modules: &[Module]) -> Result<(), String> {
+fn validate_module_exists(module: &str, modules: &[Module]) -> Result<(), RenomError> {
     match modules.iter().any(|other| other.name == module) {
         true => Ok(()),
-        false => Err("module must be part of project".into()),
+        false => Err(RenomError::Validation(
+            "module must be part of project".into(),
+        )),
     }
 }
 
-fn validate_new_name_is_not_empty(new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_not_empty(new_name: &str) -> Result<(), RenomError> {
     match !new_name.trim().is_empty() {
         true => Ok(()),
-        false => Err("new name must not be empty".into()),
+        false => Err(RenomError::Validation("new name must not be empty".into())),
     }
 }
 
-fn validate_new_name_is_concise(new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_concise(new_name: &str) -> Result<(), RenomError> {
     let new_name_max_len = 30;
     match new_name.len() <= new_name_max_len {
         true => Ok(()),
@@ -141,29 +405,29 @@ fn validate_new_name_is_concise(new_name: &str) -> Result<(), String> {
                 "new name must not be longer than {} characters",
                 new_name_max_len
             );
-            Err(error_message)
+            Err(RenomError::Validation(error_message))
         }
     }
 }
 
-fn validate_new_name_is_unique(new_name: &str, modules: &[Module]) -> Result<(), String> {
+fn validate_new_name_is_unique(new_name: &str, modules: &[Module]) -> Result<(), RenomError> {
     match modules.iter().all(|module| module.name != new_name) {
         true => Ok(()),
         false => {
             let error_message = "new name must not conflict with another module";
-            Err(error_message.into())
+            Err(RenomError::Validation(error_message.into()))
         }
     }
 }
 
-fn validate_new_name_is_valid_identifier(new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_valid_identifier(new_name: &str) -> Result<(), RenomError> {
     let identifier_regex = Regex::new("^[_[[:alnum:]]]*$").expect("regex should be valid");
     match identifier_regex.is_match(new_name) {
         true => Ok(()),
         false => {
             let error_message =
                 "new name must be comprised of alphanumeric characters and underscores only";
-            Err(error_message.into())
+            Err(RenomError::Validation(error_message.into()))
         }
     }
 }
@@ -171,38 +435,31 @@ fn validate_new_name_is_valid_identifier(new_name: &str) -> Result<(), String> {
 /// Detect the name of a project given the path to the project root directory.
 /// Assumes that the directory exists and that it contains a project descriptor.
 /// Returns an error in case of I/O issues.
-fn detect_project_name(project_root: &PathBuf) -> Result<String, String> {
+fn detect_project_name(project_root: &PathBuf) -> Result<String, RenomError> {
     assert!(project_root.is_dir());
 
-    let project_descriptor = fs::read_dir(project_root)
-        .map_err(|err| err.to_string())?
+    let project_descriptor = fs::read_dir(project_root)?
         .filter_map(Result::ok)
         .map(|entry| entry.path())
-        .filter(|path| path.extension().map_or(false, |ext| ext == "uproject"))
-        .next()
+        .find(|path| path.extension().is_some_and(|ext| ext == "uproject"))
         .expect("project descriptor should exist");
 
     project_descriptor
         .file_stem()
         .and_then(|stem| stem.to_str())
         .map(|name| name.to_owned())
-        .ok_or("project name is not valid Unicode".into())
+        .ok_or_else(|| RenomError::Validation("project name is not valid Unicode".into()))
 }
 
 /// Detect all plugins in a project given the path to the project root
 /// directory. Detects top-level plugins and nested plugins. Returns an error in
 /// case of I/O issues.
-fn detect_project_plugins(project_root: &PathBuf) -> Result<Vec<Plugin>, String> {
-    let plugins_dir = project_root.join("Plugins");
-    Ok(WalkDir::new(plugins_dir)
+fn detect_project_plugins(project_root: &Path) -> Result<Vec<Plugin>, RenomError> {
+    Ok(unreal::plugin_directories(project_root)
         .into_iter()
+        .flat_map(|dir| WalkDir::new(dir).sort_by_file_name())
         .filter_map(Result::ok)
-        .filter(|entry| {
-            entry
-                .path()
-                .extension()
-                .map_or(false, |ext| ext == "uplugin")
-        })
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "uplugin"))
         .map(|entry| Plugin {
             root: entry.path().parent().unwrap().to_owned(),
             name: entry
@@ -219,16 +476,17 @@ fn detect_project_plugins(project_root: &PathBuf) -> Result<Vec<Plugin>, String>
 /// Detect all project modules in a project given the path to the project root
 /// directory. Detects top-level modules and nested modules. Assumes that the
 /// Source folder exists. Returns an error in case of I/O issues.
-fn detect_project_modules(project_root: &PathBuf) -> Result<Vec<Module>, String> {
+fn detect_project_modules(project_root: &Path) -> Result<Vec<Module>, RenomError> {
     let source_dir = project_root.join("Source");
     assert!(source_dir.is_dir());
     Ok(WalkDir::new(source_dir)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(Result::ok)
         .filter(|entry| entry.path().is_dir() && dir_contains_module_descriptor(entry.path()))
         .map(|entry| Module {
             root: entry.path().to_owned(),
-            name: get_dir_name(&entry.path()),
+            name: get_dir_name(entry.path()),
             r#type: ModuleType::Project,
             plugin: None,
         })
@@ -238,11 +496,12 @@ fn detect_project_modules(project_root: &PathBuf) -> Result<Vec<Module>, String>
 /// Detect all plugin modules in a project given the list of project plugins.
 /// Detects top-level modules and nested modules. Returns an error in case of
 /// I/O issues.
-fn detect_plugin_modules(project_plugins: &[Plugin]) -> Result<Vec<Module>, String> {
+fn detect_plugin_modules(project_plugins: &[Plugin]) -> Result<Vec<Module>, RenomError> {
     Ok(project_plugins
         .iter()
         .flat_map(|plugin| {
             WalkDir::new(&plugin.root)
+                .sort_by_file_name()
                 .into_iter()
                 .filter_map(Result::ok)
                 .filter(|entry| {
@@ -250,7 +509,7 @@ fn detect_plugin_modules(project_plugins: &[Plugin]) -> Result<Vec<Module>, Stri
                 })
                 .map(move |entry| Module {
                     root: entry.path().to_owned(),
-                    name: get_dir_name(&entry.path()),
+                    name: get_dir_name(entry.path()),
                     r#type: ModuleType::Plugin,
                     plugin: Some(plugin.clone()),
                 })
@@ -258,11 +517,24 @@ fn detect_plugin_modules(project_plugins: &[Plugin]) -> Result<Vec<Module>, Stri
         .collect())
 }
 
-fn detect_project_targets(project_root: &Path) -> Result<Vec<PathBuf>, String> {
+/// Detect the modules of a project and of its plugins, sorted by name. A
+/// project without a Source folder only has plugin modules. Returns an error in
+/// case of I/O issues.
+pub fn detect_modules(project_root: &Path) -> Result<Vec<Module>, RenomError> {
+    let project_plugins = detect_project_plugins(project_root)?;
+    let mut modules = match project_root.join("Source").is_dir() {
+        true => detect_project_modules(project_root)?,
+        false => vec![],
+    };
+    modules.extend(detect_plugin_modules(&project_plugins)?);
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(modules)
+}
+
+fn detect_project_targets(project_root: &Path) -> Result<Vec<PathBuf>, RenomError> {
     let source_dir = project_root.join("Source");
     assert!(source_dir.is_dir());
-    Ok(fs::read_dir(source_dir)
-        .map_err(|err| err.to_string())?
+    let mut targets = fs::read_dir(source_dir)?
         .filter_map(Result::ok)
         .filter(|entry| {
             entry
@@ -272,7 +544,9 @@ fn detect_project_targets(project_root: &Path) -> Result<Vec<PathBuf>, String> {
                 .unwrap_or(false)
         })
         .map(|entry| entry.path().to_owned())
-        .collect())
+        .collect::<Vec<PathBuf>>();
+    targets.sort();
+    Ok(targets)
 }
 
 fn dir_contains_module_descriptor(dir: &Path) -> bool {
@@ -289,41 +563,153 @@ fn get_dir_name(dir: &Path) -> String {
         .to_string()
 }
 
-fn detect_project_config_files(project_root: &Path) -> Result<Vec<PathBuf>, String> {
+fn detect_project_config_files(project_root: &Path) -> Result<Vec<PathBuf>, RenomError> {
     let config_dir = project_root.join("Config");
     Ok(WalkDir::new(config_dir)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(Result::ok)
-        .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "ini"))
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "ini"))
         .map(|entry| entry.path().to_owned())
         .collect())
 }
 
-fn find_implementing_source(module_root: &Path) -> Option<PathBuf> {
+/// Find the config entries whose value is exactly the name of a module, e.g.
+/// `+ModuleNames=OldName` or `Module="OldName"`. Values that merely contain
+/// the name are left alone.
+fn find_config_values(
+    config_files: &[PathBuf],
+    module_name: &str,
+) -> Result<Vec<ConfigValue>, RenomError> {
+    let mut values = vec![];
+    for path in config_files {
+        let content = TextFile::read(path).map_err(|err| {
+            RenomError::from(err).context(format!("could not read {}", path.display()))
+        })?;
+        let mut section = String::new();
+        for line in content.content.lines().map(str::trim) {
+            if line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                section = name.to_owned();
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().trim_start_matches(['+', '-', '.', '!']).trim();
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            if !key.is_empty() && value == module_name {
+                values.push(ConfigValue {
+                    path: path.clone(),
+                    section: section.clone(),
+                    key: key.to_owned(),
+                });
+            }
+        }
+    }
+    Ok(values)
+}
+
+fn find_implementing_source(
+    module_root: &Path,
+    mmap: bool,
+    follow_symlinks: bool,
+) -> Option<PathBuf> {
+    let matcher = Matcher::literals(["_MODULE"]);
     WalkDir::new(module_root)
+        .follow_links(follow_symlinks)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(Result::ok)
         .map(|entry| entry.path().to_owned())
-        .filter(|path| path.is_file() && path.extension().map_or(false, |ext| ext == "cpp"))
-        .find(|source| {
-            fs::read_to_string(source).map_or(false, |content| content.contains("_MODULE"))
-        })
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "cpp"))
+        .find(|source| matcher.is_match_in_file(source, mmap))
 }
 
-fn find_headers_with_export_macro(module_root: &Path, module_name: &str) -> Vec<PathBuf> {
-    WalkDir::new(module_root)
+fn find_headers_with_export_macro(
+    module_root: &Path,
+    module_name: &str,
+    mmap: bool,
+    follow_symlinks: bool,
+) -> Vec<PathBuf> {
+    let matcher = Matcher::literals([format!("{}_API", module_name.to_uppercase())]);
+    let headers = WalkDir::new(module_root)
+        .follow_links(follow_symlinks)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(Result::ok)
         .map(|entry| entry.path().to_owned())
+        .filter(|path| path.is_file())
+        .collect::<Vec<PathBuf>>();
+    matcher.files_matching(headers, mmap)
+}
+
+/// Find source files that contain paths into the module folder, with either
+/// separator style, and source files that look the module up by name, in a
+/// single scan of every source folder. The module build file is skipped since
+/// it is rewritten in full, and C# files are never searched for lookups.
+fn find_sources_with_module_references(
+    project_root: &Path,
+    project_plugins: &[Plugin],
+    module: &Module,
+    search_name: &str,
+    mmap: bool,
+    follow_symlinks: bool,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>), RenomError> {
+    const MODULE_PATH: usize = 0;
+    const MODULE_LOOKUP: usize = 1;
+    let matcher = Matcher::new(
+        std::iter::empty::<&str>(),
+        [
+            format!(r#"Source(?:/|\\{{1,2}}){}(?:/|\\{{1,2}}|")"#, module.name),
+            module_lookup_pattern(search_name),
+        ],
+    )?
+    .with_required([module.name.as_str(), search_name]);
+    let build_file = module.root.join(&module.name).with_extension("Build.cs");
+    let source_extensions = ["h", "hpp", "inl", "c", "cpp", "cs"];
+    let mut sources_with_module_paths = vec![];
+    let mut sources_with_module_lookups = vec![];
+    let sources = std::iter::once(project_root.join("Source"))
+        .chain(
+            project_plugins
+                .iter()
+                .map(|plugin| plugin.root.join("Source")),
+        )
+        .flat_map(|dir| {
+            WalkDir::new(dir)
+                .follow_links(follow_symlinks)
+                .sort_by_file_name()
+        })
+        .filter_map(Result::ok)
+        .map(|entry| entry.path().to_owned())
         .filter(|path| {
-            fs::read_to_string(path).map_or(false, |content| {
-                content.contains(&format!("{}_API", module_name.to_uppercase()))
-            })
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| source_extensions.contains(&ext))
         })
-        .collect()
+        .collect::<Vec<PathBuf>>();
+    let matches = matcher.matches_in_files(&sources, mmap);
+    for (path, matches) in sources.into_iter().zip(matches) {
+        if matches.contains(&MODULE_PATH) && path != build_file {
+            sources_with_module_paths.push(path.clone());
+        }
+        if matches.contains(&MODULE_LOOKUP) && path.extension().is_some_and(|ext| ext != "cs") {
+            sources_with_module_lookups.push(path);
+        }
+    }
+    Ok((sources_with_module_paths, sources_with_module_lookups))
 }
 
-fn gather_context(params: &Params) -> Result<Context, String> {
+fn gather_context(params: &Params) -> Result<Context, RenomError> {
     let project_root = params.project_root.clone();
     let project_name = detect_project_name(&project_root)?;
     let project_plugins = detect_project_plugins(&project_root)?;
@@ -333,14 +719,33 @@ fn gather_context(params: &Params) -> Result<Context, String> {
         .collect::<Vec<Module>>();
     let project_targets = detect_project_targets(&project_root)?;
     let project_config_files = detect_project_config_files(&project_root)?;
+    let descriptor_modules = read_descriptor_modules(&project_root, &project_name)?;
     let target_module = modules
         .iter()
         .find(|module| module.name == params.module)
         .unwrap()
         .clone();
-    let implementing_source = find_implementing_source(&target_module.root);
+    let search_name = params
+        .options
+        .from_name
+        .clone()
+        .unwrap_or_else(|| target_module.name.clone());
+    let config_values = find_config_values(&project_config_files, &search_name)?;
+    params.options.cancellation.check()?;
+    let mmap = params.options.mmap;
+    let follow_symlinks = params.options.follow_symlinks;
+    let implementing_source = find_implementing_source(&target_module.root, mmap, follow_symlinks);
     let headers_with_export_macro =
-        find_headers_with_export_macro(&target_module.root, &target_module.name);
+        find_headers_with_export_macro(&target_module.root, &search_name, mmap, follow_symlinks);
+    let (sources_with_module_paths, sources_with_module_lookups) =
+        find_sources_with_module_references(
+            &project_root,
+            &project_plugins,
+            &target_module,
+            &search_name,
+            mmap,
+            follow_symlinks,
+        )?;
 
     Ok(Context {
         project_root,
@@ -348,17 +753,95 @@ fn gather_context(params: &Params) -> Result<Context, String> {
         project_targets,
         project_config_files,
         modules,
+        descriptor_modules,
         module: target_module,
+        search_name,
         new_name: params.new_name.clone(),
         source_with_implement_macro: implementing_source,
         headers_with_export_macro,
+        sources_with_module_paths,
+        sources_with_module_lookups,
+        config_values,
+        config_value_keys: params.options.config_value_keys.clone(),
+        collapse_redirects: params.options.collapse_redirects,
+        recorded_redirects: read_recorded_redirects(&params.project_root)?,
+        normalize_descriptor: params.options.normalize_descriptor,
     })
 }
 
-fn create_backup_dir(project_root: &Path) -> Result<PathBuf, String> {
-    let backup_dir = project_root.join(".renom/backup");
-    fs::create_dir_all(&backup_dir).map_err(|err| err.to_string())?;
-    Ok(backup_dir)
+/// Read the names of the modules declared in the project descriptor, or
+/// `None` if it has no `Modules` list.
+fn read_descriptor_modules(
+    project_root: &Path,
+    project_name: &str,
+) -> Result<Option<Vec<String>>, RenomError> {
+    let descriptor = project_root.join(project_name).with_extension("uproject");
+    let content = fs::read_to_string(&descriptor)?;
+    let json: serde_json::Value = serde_json::from_str(&content).map_err(|err| {
+        RenomError::Parse(format!(
+            "{} is not valid JSON: {}",
+            descriptor.display(),
+            err
+        ))
+    })?;
+    Ok(json
+        .get("Modules")
+        .and_then(|modules| modules.as_array())
+        .map(|modules| {
+            modules
+                .iter()
+                .filter_map(|module| module.get("Name").and_then(|name| name.as_str()))
+                .map(str::to_owned)
+                .collect()
+        }))
+}
+
+/// Record the rename of the module in the ledger of the project, along with the
+/// redirects it added.
+fn record_in_ledger(context: &Context, redirects: Vec<String>) -> Result<(), RenomError> {
+    let entry = ledger::Entry::new("module", &context.module.name, &context.new_name, redirects);
+    ledger::append(&context.project_root, &entry).map_err(RenomError::from)
+}
+
+/// Let the user know that the module is declared in targets only, in which
+/// case the targets are the references that matter.
+fn warn_if_declared_in_targets_only(context: &Context) {
+    if !context.is_declared_in_targets_only() {
+        return;
+    }
+    let action = match context.normalize_descriptor {
+        true => "It will be added to the project descriptor.",
+        false => "Pass --normalize-descriptor to add it to the project descriptor.",
+    };
+    log::warning(format!(
+        "Module {} is declared in the ExtraModuleNames of targets only.\n\t{}",
+        context.search_name, action
+    ));
+}
+
+/// Let the user know about config entries set to the module name that the
+/// rename leaves alone, so that they can choose to rename them too.
+fn warn_about_skipped_config_values(context: &Context) {
+    let skipped = context.skipped_config_values();
+    if skipped.is_empty() {
+        return;
+    }
+    let entries = skipped
+        .iter()
+        .map(|value| {
+            format!(
+                "\t- [{}] {} in {}",
+                value.section,
+                value.key,
+                value.path.display()
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    log::warning(format!(
+        "The following config entries are set to {} but will not be updated:\n{}\n\tPass --config-value-key with their keys to update them.",
+        context.search_name, entries
+    ));
 }
 
 fn print_success_message(context: &Context) {
@@ -1,4 +1,7 @@
-use std::path::Path;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     changes::{AppendIniEntry, Change, RenameFile, ReplaceInFile},
@@ -13,31 +16,66 @@ pub fn generate_changeset(context: &Context) -> Vec<Change> {
         project_root,
         project_name,
         project_plugins,
-        plugin: Plugin {
-            name: old_name,
-            root: plugin_root,
-        },
+        plugin_name: old_name,
+        plugin,
+        search_name,
         new_name,
+        collapse_redirects,
+        recorded_redirects,
+        config_references,
+        sources_with_plugin_lookups,
+        ..
     } = context;
 
-    let descriptor = plugin_root.join(old_name).with_extension("uplugin");
     let mut changeset = vec![];
 
-    changeset.push(rename_plugin_descriptor(&descriptor, new_name));
-    changeset.push(rename_plugin_root(plugin_root, new_name));
+    // Sources in the plugin are edited before the plugin is moved
+    changeset.extend(
+        sources_with_plugin_lookups
+            .iter()
+            .map(|source_file| rename_plugin_lookups_in_source(source_file, search_name, new_name)),
+    );
+
+    // Plugins installed outside of the project only have their references
+    // updated.
+    if let Some(Plugin {
+        root: plugin_root, ..
+    }) = plugin
+    {
+        let descriptor = plugin_root.join(old_name).with_extension("uplugin");
+        changeset.push(rename_plugin_descriptor(&descriptor, new_name));
+        changeset.push(rename_plugin_root(plugin_root, new_name));
+    }
     changeset.push(rename_plugin_reference_in_project_descriptor(
         project_root,
         project_name,
-        old_name,
+        search_name,
         new_name,
     ));
     changeset.extend(rename_cross_plugin_references(
         project_plugins,
         old_name,
+        search_name,
+        new_name,
+    ));
+    changeset.extend(rename_plugin_references_in_config(
+        config_references,
+        search_name,
+        new_name,
+    ));
+    changeset.push(update_existing_redirects(
+        project_root,
+        search_name,
         new_name,
     ));
-    changeset.push(update_existing_redirects(project_root, old_name, new_name));
-    changeset.push(append_plugin_redirect(project_root, old_name, new_name));
+    if *collapse_redirects {
+        changeset.extend(remove_redundant_redirects(
+            project_root,
+            new_name,
+            recorded_redirects,
+        ));
+    }
+    changeset.push(append_plugin_redirect(project_root, search_name, new_name));
 
     changeset
 }
@@ -69,12 +107,13 @@ fn rename_plugin_reference_in_project_descriptor(
 fn rename_cross_plugin_references(
     project_plugins: &[Plugin],
     old_name: &str,
+    search_name: &str,
     new_name: &str,
 ) -> Vec<Change> {
     project_plugins
         .iter()
-        .filter(|plugin| &plugin.name != old_name)
-        .map(|plugin| rename_plugin_references_in_plugin(&plugin, old_name, new_name))
+        .filter(|plugin| plugin.name != old_name)
+        .map(|plugin| rename_plugin_references_in_plugin(plugin, search_name, new_name))
         .collect()
 }
 
@@ -87,6 +126,52 @@ fn rename_plugin_references_in_plugin(plugin: &Plugin, old_name: &str, new_name:
     ))
 }
 
+/// Get a regex matching the name of a plugin looked up in a string literal,
+/// e.g. `FindPlugin("OldName")` or `FindPlugin(TEXT("OldName"))`.
+pub fn plugin_lookup_pattern(name: &str) -> String {
+    format!(
+        r#"(?P<call>\bFindPlugin\s*\(\s*(?:TEXT\s*\(\s*)?)"{}""#,
+        regex::escape(name)
+    )
+}
+
+fn rename_plugin_lookups_in_source(source_file: &Path, old_name: &str, new_name: &str) -> Change {
+    Change::ReplaceInFile(
+        ReplaceInFile::new(
+            source_file,
+            plugin_lookup_pattern(old_name),
+            format!(r#"${{call}}"{new_name}""#),
+        )
+        .in_literals(),
+    )
+}
+
+/// Get a regex matching a config array entry that enables or disables a
+/// plugin by name, e.g. `+EnabledPlugins=OldName`.
+pub fn plugin_list_entry_pattern(name: &str) -> String {
+    format!(
+        r#"(?m)^(?P<key>[ \t]*[+\-.!]?(?:Enabled|Disabled)Plugins[ \t]*=[ \t]*"?){}\b"#,
+        regex::escape(name)
+    )
+}
+
+fn rename_plugin_references_in_config(
+    config_files: &[PathBuf],
+    old_name: &str,
+    new_name: &str,
+) -> Vec<Change> {
+    config_files
+        .iter()
+        .map(|path| {
+            Change::ReplaceInFile(ReplaceInFile::new(
+                path,
+                plugin_list_entry_pattern(old_name),
+                format!("${{key}}{new_name}"),
+            ))
+        })
+        .collect()
+}
+
 fn update_existing_redirects(project_root: &Path, old_name: &str, new_name: &str) -> Change {
     Change::ReplaceInFile(ReplaceInFile::new(
         project_root.join("Config").join("DefaultEngine.ini"),
@@ -101,6 +186,32 @@ fn update_existing_redirects(project_root: &Path, old_name: &str, new_name: &str
     ))
 }
 
+/// Remove redirects that point a plugin at itself, as left behind by
+/// `update_existing_redirects` when a rename reverses an earlier one. Only
+/// done if an earlier rename recorded adding a redirect from the new name,
+/// so that redirects written by hand are left alone.
+fn remove_redundant_redirects(
+    project_root: &Path,
+    new_name: &str,
+    recorded_redirects: &BTreeSet<String>,
+) -> Option<Change> {
+    let recorded_from = format!(r#"+PackageRedirects=(OldName="/{}/","#, new_name);
+    if !recorded_redirects
+        .iter()
+        .any(|redirect| redirect.starts_with(&recorded_from))
+    {
+        return None;
+    }
+    Some(Change::ReplaceInFile(ReplaceInFile::new(
+        project_root.join("Config").join("DefaultEngine.ini"),
+        format!(
+            r#"(?m)^\+PackageRedirects=\(OldName="/{0}/",\s*NewName="/{0}/",\s*MatchSubstring=true\)\r?\n"#,
+            new_name
+        ),
+        "",
+    )))
+}
+
 fn append_plugin_redirect(project_root: &Path, old_name: &str, new_name: &str) -> Change {
     Change::AppendIniEntry(AppendIniEntry::new(
         project_root.join("Config").join("DefaultEngine.ini"),
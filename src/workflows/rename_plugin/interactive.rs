@@ -1,33 +1,84 @@
-use std::{ffi::OsStr, fs, path::PathBuf};
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
 
-use inquire::{validator::Validation, CustomUserError, Select, Text};
+use inquire::{validator::Validation, Confirm, CustomUserError, Select, Text};
 use regex::Regex;
 use walkdir::WalkDir;
 
-use crate::unreal::Plugin;
+use crate::{
+    ledger,
+    presentation::log,
+    settings::Settings,
+    unreal::{self, Plugin},
+    workflows::select_discovered_project,
+};
 
-use super::Params;
+use super::{Options, Params};
 
 pub fn get_params_from_user() -> Result<Params, String> {
     let project_root = get_project_root_from_user()?;
+    let settings = Settings::load_for(&project_root)?;
+    settings.apply();
+    let history = ledger::read(&project_root).map_err(|err| err.to_string())?;
     let project_plugins = detect_project_plugins(&project_root)?;
-    let target_plugin = get_target_plugin_from_user(&project_plugins)?;
-    let target_name = get_target_name_from_user(&project_plugins)?;
+    let target_plugin = get_target_plugin_from_user(&project_root, &project_plugins)?;
+    let target_name = get_target_name_from_user(&project_plugins, ledger::naming_prefix(&history))?;
+    let collapse_redirects = confirm_collapse_redirects(
+        &history,
+        &target_plugin.name,
+        &target_name,
+        settings.prompts.collapse_redirects,
+    )?;
 
     Ok(Params {
         project_root,
         plugin: target_plugin.name,
         new_name: target_name,
+        plugin_root: Some(target_plugin.root),
+        options: Options {
+            collapse_redirects,
+            interactive: !settings.general.non_interactive,
+            backup_policy: Some(settings.backup),
+            backup_dir: settings.general.backup_dir,
+            include: settings.general.include,
+            exclude: settings.general.exclude,
+            chunk_size: settings.engine.chunk_size,
+            threads: settings.engine.threads,
+            ..Options::default()
+        },
     })
 }
 
-fn get_project_root_from_user() -> Result<PathBuf, String> {
+/// Ask the user to choose a plugin of a project, e.g. when it was not given on
+/// the command line.
+pub fn get_plugin_from_user(project_root: &Path) -> Result<Plugin, String> {
+    let plugins = detect_project_plugins(project_root)?;
+    get_target_plugin_from_user(project_root, &plugins)
+}
+
+/// Ask the user for the new name of a plugin, e.g. when it was not given on the
+/// command line.
+pub fn get_new_name_from_user(project_root: &Path) -> Result<String, String> {
+    let history = ledger::read(project_root).map_err(|err| err.to_string())?;
+    let plugins = detect_project_plugins(project_root)?;
+    get_target_name_from_user(&plugins, ledger::naming_prefix(&history))
+}
+
+/// Ask the user for the root of a project, offering the projects discovered
+/// around the working directory first.
+pub fn get_project_root_from_user() -> Result<PathBuf, String> {
+    if let Some(project_root) = select_discovered_project(|root| root.join("Source").is_dir())? {
+        return Ok(project_root);
+    }
     Text::new("Project root directory path:")
         .with_validator(validate_project_root_is_dir)
         .with_validator(validate_project_root_contains_project_descriptor)
         .with_validator(validate_project_root_contains_source_dir)
         .prompt()
-        .map(|project_root| PathBuf::from(project_root))
+        .map(PathBuf::from)
         .map_err(|err| err.to_string())
 }
 
@@ -72,17 +123,12 @@ fn validate_project_root_contains_source_dir(
 /// Detect all plugins in a project given the path to the project root
 /// directory. Detects top-level plugins and nested plugins. Returns an error in
 /// case of I/O issues.
-fn detect_project_plugins(project_root: &PathBuf) -> Result<Vec<Plugin>, String> {
-    let plugins_dir = project_root.join("Plugins");
-    Ok(WalkDir::new(plugins_dir)
+fn detect_project_plugins(project_root: &Path) -> Result<Vec<Plugin>, String> {
+    Ok(unreal::plugin_directories(project_root)
         .into_iter()
+        .flat_map(WalkDir::new)
         .filter_map(Result::ok)
-        .filter(|entry| {
-            entry
-                .path()
-                .extension()
-                .map_or(false, |ext| ext == "uplugin")
-        })
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "uplugin"))
         .map(|entry| Plugin {
             root: entry.path().parent().unwrap().to_owned(),
             name: entry
@@ -96,21 +142,49 @@ fn detect_project_plugins(project_root: &PathBuf) -> Result<Vec<Plugin>, String>
         .collect())
 }
 
-fn get_target_plugin_from_user(plugins: &[Plugin]) -> Result<Plugin, String> {
-    Select::new("Choose a plugin:", plugins.to_vec())
-        .prompt()
-        .map_err(|err| err.to_string())
+/// Ask the user to choose a plugin. Plugins that share a name are labeled
+/// with their location in the project so they can be told apart.
+fn get_target_plugin_from_user(project_root: &Path, plugins: &[Plugin]) -> Result<Plugin, String> {
+    let labels: Vec<String> = plugins
+        .iter()
+        .map(|plugin| {
+            match plugins
+                .iter()
+                .filter(|other| other.name == plugin.name)
+                .count()
+            {
+                1 => plugin.name.clone(),
+                _ => format!(
+                    "{} ({})",
+                    plugin.name,
+                    plugin
+                        .root
+                        .strip_prefix(project_root)
+                        .unwrap_or(&plugin.root)
+                        .display()
+                ),
+            }
+        })
+        .collect();
+    let choice = Select::new("Choose a plugin:", labels)
+        .raw_prompt()
+        .map_err(|err| err.to_string())?;
+    Ok(plugins[choice.index].clone())
 }
 
-fn get_target_name_from_user(plugins: &[Plugin]) -> Result<String, String> {
+fn get_target_name_from_user(plugins: &[Plugin], prefix: Option<String>) -> Result<String, String> {
     let plugins = plugins.to_vec();
-    Text::new("Provide a new name for the plugin:")
+    let prompt = Text::new("Provide a new name for the plugin:")
         .with_validator(validate_target_name_is_not_empty)
         .with_validator(validate_target_name_is_concise)
         .with_validator(move |input: &str| validate_target_name_is_unique(input, &plugins))
-        .with_validator(validate_target_name_is_valid_identifier)
-        .prompt()
-        .map_err(|err| err.to_string())
+        .with_validator(validate_target_name_is_valid_identifier);
+    match prefix.as_deref() {
+        Some(prefix) => prompt.with_initial_value(prefix),
+        None => prompt,
+    }
+    .prompt()
+    .map_err(|err| err.to_string())
 }
 
 fn validate_target_name_is_not_empty(target_name: &str) -> Result<Validation, CustomUserError> {
@@ -163,3 +237,26 @@ fn validate_target_name_is_valid_identifier(
         }
     }
 }
+
+/// Warn the user if the rename reverses an earlier one recorded in the ledger
+/// and offer to remove the redirects that would become redundant.
+fn confirm_collapse_redirects(
+    history: &[ledger::Entry],
+    old_name: &str,
+    new_name: &str,
+    seeded: Option<bool>,
+) -> Result<bool, String> {
+    match ledger::find_reversal(history, "plugin", old_name, new_name) {
+        Some(entry) => {
+            log::warning(format!(
+                "This reverses the rename of plugin {} to {} on {}.",
+                entry.old_name, entry.new_name, entry.date
+            ));
+            Confirm::new("Would you like to remove redirects made redundant by this rename?")
+                .with_default(seeded.unwrap_or(true))
+                .prompt()
+                .map_err(|err| err.to_string())
+        }
+        None => Ok(false),
+    }
+}
@@ -2,6 +2,7 @@ mod changeset;
 mod interactive;
 
 use std::{
+    collections::BTreeSet,
     ffi::OsStr,
     fs,
     path::{Path, PathBuf},
@@ -10,9 +11,30 @@ use std::{
 use regex::Regex;
 use walkdir::WalkDir;
 
-use crate::{engine::Engine, presentation::log, unreal::Plugin};
+use crate::{
+    changes::{Category, Change, Changeset},
+    doctor,
+    error::RenomError,
+    ledger,
+    manifest::{self, Manifest},
+    matcher::Matcher,
+    plan,
+    presentation::{log, preview},
+    unreal::{self, ExternalPlugin, ExternalPluginKind, Plugin},
+    workflows::{
+        added_redirects, check_execution, combine_changesets, filter_changes, merge_extra_ops,
+        prepare_backup_dir, read_recorded_redirects, resolve_conflicts, validate_batch, Options,
+    },
+};
+
+use self::{
+    changeset::{generate_changeset, plugin_list_entry_pattern, plugin_lookup_pattern},
+    interactive::get_params_from_user,
+};
 
-use self::{changeset::generate_changeset, interactive::get_params_from_user};
+pub use self::interactive::{
+    get_new_name_from_user, get_plugin_from_user, get_project_root_from_user,
+};
 
 /// Params needed to rename an Unreal Engine plugin.
 pub struct Params {
@@ -22,6 +44,11 @@ pub struct Params {
     pub plugin: String,
     /// The new name for the plugin.
     pub new_name: String,
+    /// The root of the specific plugin to rename, needed when several plugins
+    /// in the project share its name.
+    pub plugin_root: Option<PathBuf>,
+    /// Options for the rename.
+    pub options: Options,
 }
 
 /// Context needed to rename an Unreal Engine plugin.
@@ -32,91 +59,370 @@ pub struct Context {
     pub project_name: String,
     /// Plugins for the project.
     pub project_plugins: Vec<Plugin>,
-    /// The specific plugin to rename.
-    pub plugin: Plugin,
+    /// The name of the plugin to rename.
+    pub plugin_name: String,
+    /// The plugin to rename, if it is located within the project.
+    pub plugin: Option<Plugin>,
+    /// The name to search for in file contents. Matches the old name unless
+    /// overridden.
+    pub search_name: String,
+    /// Plugins referenced by the project but installed elsewhere.
+    pub external_plugins: Vec<ExternalPlugin>,
     /// The new name for the plugin.
     pub new_name: String,
+    /// Whether the plugin contains content only.
+    pub content_only: bool,
+    /// Whether to remove redirects made redundant by the rename.
+    pub collapse_redirects: bool,
+    /// Redirects that earlier renames added to config, as recorded in the
+    /// ledger, see [`ledger::recorded_redirects`]. Only these are removed
+    /// when redirects are collapsed, so that those written by hand are kept.
+    pub recorded_redirects: BTreeSet<String>,
+    /// Config files that enable or disable the plugin by name, e.g. through
+    /// `+EnabledPlugins=OldName` in a platform config.
+    pub config_references: Vec<PathBuf>,
+    /// Source files that look the plugin up by name in a string literal,
+    /// e.g. `IPluginManager::Get().FindPlugin(TEXT("OldName"))`.
+    pub sources_with_plugin_lookups: Vec<PathBuf>,
 }
 
 /// Rename an Unreal Engine plugin interactively, soliciting input parameters
-/// from the user with validation and guided selection.
-pub fn rename_plugin_interactive() -> Result<(), String> {
-    let params = get_params_from_user()?;
-    rename_plugin(params)
+/// from the user with validation and guided selection. Returns the root of the
+/// project, with a report of the rename written to it, or `None` if the user
+/// cancelled the rename.
+pub fn rename_plugin_interactive() -> Result<Option<PathBuf>, RenomError> {
+    let params = get_params_from_user().map_err(RenomError::Validation)?;
+    let project_root = params.project_root.clone();
+    let Some(manifest) = rename(params, None)? else {
+        return Ok(None);
+    };
+    manifest::write_report(&project_root, &manifest)?;
+    Ok(Some(project_root))
+}
+
+/// Generate the changes needed to rename an Unreal Engine plugin without
+/// applying them.
+pub fn plan_rename_plugin(params: &Params) -> Result<Changeset, RenomError> {
+    validate_params(params)?;
+    let context = gather_context(params)?;
+    params.options.cancellation.check()?;
+    let changeset = merge_extra_ops(
+        filter_changes(
+            generate_changeset(&context),
+            &context.project_root,
+            &params.options,
+        )?,
+        params.options.extra_ops.as_deref(),
+        &context.project_root,
+    )?;
+    let changeset = resolve_conflicts(changeset, params.options.interactive)?;
+    Ok(Changeset::new(changeset))
 }
 
 /// Rename an Unreal Engine plugin.
-pub fn rename_plugin(params: Params) -> Result<(), String> {
+pub fn rename_plugin(params: Params) -> Result<(), RenomError> {
+    rename(params, None).map(|_| ())
+}
+
+/// Apply changes planned earlier with [`plan_rename_plugin`]. The params are
+/// validated again and the plan is only applied if it still matches the
+/// changes needed for the current state of the project.
+pub fn apply_planned_rename_plugin(params: Params, plan: &Changeset) -> Result<(), RenomError> {
+    rename(params, Some(plan)).map(|_| ())
+}
+
+/// Generate the changes needed to rename several plugins of a project at once
+/// without applying them, see [`rename_plugins`].
+pub fn plan_rename_plugins(params: &[Params]) -> Result<Changeset, RenomError> {
+    let (_, changeset) = plan_batch(params)?;
+    Ok(Changeset::new(changeset))
+}
+
+/// Rename several plugins of a project at once, with one combined changeset and
+/// one backup, so that either all of them are renamed or none are. Each
+/// rename is planned against the current state of the project, and the
+/// options of the first params apply to the whole batch.
+pub fn rename_plugins(params: Vec<Params>) -> Result<(), RenomError> {
+    let (batch, changeset) = plan_batch(&params)?;
+    let options = &params[0].options;
+    let project_root = &params[0].project_root;
+    let manifest = Manifest::new(
+        "plugin",
+        batch
+            .iter()
+            .map(|(context, _)| context.plugin_name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", "),
+        batch
+            .iter()
+            .map(|(context, _)| context.new_name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", "),
+        &changeset,
+    );
+    for (context, _) in &batch {
+        warn_if_external(context);
+        doctor::warn_about_unhandled_references(&context.project_root, &context.search_name, &[]);
+    }
+    check_execution(&changeset, project_root, options)?;
+    if options.interactive && !preview::review(&changeset, project_root)? {
+        log::basic("Rename cancelled.");
+        return Ok(());
+    }
+    let backup_root = options.backup_root(project_root);
+    let backup_dir = prepare_backup_dir(&backup_root, options)?;
+    let apply_options = options.apply_options_for(&changeset, project_root)?;
+    let mut engine = options.engine(apply_options);
+    if let Err(e) = engine.execute(changeset, backup_dir) {
+        engine.revert()?;
+        for (context, _) in &batch {
+            print_failure_message(context);
+        }
+        return Err(e);
+    }
+
+    for (context, redirects) in batch.iter() {
+        if options.ledger {
+            record_in_ledger(context, redirects.clone())?;
+        }
+    }
+
+    manifest::write_for_review(project_root, &manifest, options.vcs)?;
+
+    for (context, _) in &batch {
+        print_success_message(context);
+        if context.content_only {
+            print_content_redirect_guidance(context);
+        }
+    }
+    if let Some((context, _)) = batch.first() {
+        warn_about_unresolved_maps(context);
+    }
+    Ok(())
+}
+
+/// The contexts of the renames in a batch, each with the redirects it adds.
+type Batch = Vec<(Context, Vec<String>)>;
+
+/// Plan each rename of a batch and combine their changes.
+fn plan_batch(params: &[Params]) -> Result<(Batch, Vec<Change>), RenomError> {
+    let renames = params
+        .iter()
+        .map(|params| (params.plugin.as_str(), params.new_name.as_str()))
+        .collect::<Vec<(&str, &str)>>();
+    validate_batch(&renames)?;
+    let mut batch = vec![];
+    let mut changesets = vec![];
+    for params in params {
+        validate_params(params)?;
+        let context = gather_context(params)?;
+        params.options.cancellation.check()?;
+        let changeset = resolve_conflicts(
+            filter_changes(
+                generate_changeset(&context),
+                &context.project_root,
+                &params.options,
+            )?,
+            params.options.interactive,
+        )?;
+        batch.push((context, added_redirects(&changeset)));
+        changesets.push(changeset);
+    }
+    let changeset = merge_extra_ops(
+        combine_changesets(changesets)?,
+        params[0].options.extra_ops.as_deref(),
+        &params[0].project_root,
+    )?;
+    Ok((batch, changeset))
+}
+
+fn rename(params: Params, plan: Option<&Changeset>) -> Result<Option<Manifest>, RenomError> {
     validate_params(&params)?;
     let context = gather_context(&params)?;
-    let changeset = generate_changeset(&context);
-    let backup_dir = create_backup_dir(&context.project_root)?;
-    let mut engine = Engine::new();
+    params.options.cancellation.check()?;
+    let changeset = merge_extra_ops(
+        filter_changes(
+            generate_changeset(&context),
+            &context.project_root,
+            &params.options,
+        )?,
+        params.options.extra_ops.as_deref(),
+        &context.project_root,
+    )?;
+    let changeset = resolve_conflicts(changeset, params.options.interactive)?;
+    if let Some(plan) = plan {
+        plan::check_is_current(plan, &changeset)?;
+    }
+    let redirects = added_redirects(&changeset);
+    let manifest = Manifest::new(
+        "plugin",
+        &context.plugin_name,
+        &context.new_name,
+        &changeset,
+    );
+    warn_if_external(&context);
+    doctor::warn_about_unhandled_references(&context.project_root, &context.search_name, &[]);
+    check_execution(&changeset, &context.project_root, &params.options)?;
+    if params.options.interactive && !preview::review(&changeset, &context.project_root)? {
+        log::basic("Rename cancelled.");
+        return Ok(None);
+    }
+    let backup_root = params.options.backup_root(&context.project_root);
+    let backup_dir = prepare_backup_dir(&backup_root, &params.options)?;
+    let apply_options = params
+        .options
+        .apply_options_for(&changeset, &context.project_root)?;
+    let mut engine = params.options.engine(apply_options);
     if let Err(e) = engine.execute(changeset, backup_dir) {
-        log::error(&e);
         engine.revert()?;
         print_failure_message(&context);
-        return Ok(());
+        return Err(e);
     }
 
+    if params.options.ledger {
+        record_in_ledger(&context, redirects)?;
+    }
+
+    manifest::write_for_review(&context.project_root, &manifest, params.options.vcs)?;
+
     print_success_message(&context);
-    Ok(())
+    if context.content_only {
+        print_content_redirect_guidance(&context);
+    }
+    warn_about_unresolved_maps(&context);
+    Ok(Some(manifest))
 }
 
-fn validate_params(params: &Params) -> Result<(), String> {
+fn validate_params(params: &Params) -> Result<(), RenomError> {
     validate_project_root_is_dir(&params.project_root)?;
     validate_project_root_contains_project_descriptor(&params.project_root)?;
     validate_project_root_contains_source_dir(&params.project_root)?;
+    let project_name = detect_project_name(&params.project_root)?;
     let plugins = detect_project_plugins(&params.project_root)?;
-    validate_plugin_exists(&params.plugin, &plugins)?;
+    let external_plugins = detect_external_plugins(&params.project_root, &project_name, &plugins);
+    validate_plugin_exists(&params.plugin, &plugins, &external_plugins)?;
+    validate_plugin_is_unambiguous(
+        &params.plugin,
+        params.plugin_root.as_deref(),
+        &params.project_root,
+        &plugins,
+    )?;
     validate_new_name_is_not_empty(&params.new_name)?;
     validate_new_name_is_concise(&params.new_name)?;
-    validate_new_name_is_unique(&params.new_name, &plugins)?;
+    validate_new_name_is_unique(&params.new_name, &plugins, &external_plugins)?;
     validate_new_name_is_valid_identifier(&params.new_name)?;
+    params.options.validate_from_name()?;
     Ok(())
 }
 
-fn validate_project_root_is_dir(project_root: &Path) -> Result<(), String> {
+fn validate_project_root_is_dir(project_root: &Path) -> Result<(), RenomError> {
     match project_root.is_dir() {
         true => Ok(()),
-        false => Err("project root must be a directory".into()),
+        false => Err(RenomError::Validation(
+            "project root must be a directory".into(),
+        )),
     }
 }
 
-fn validate_project_root_contains_project_descriptor(project_root: &Path) -> Result<(), String> {
-    match fs::read_dir(project_root)
-        .map_err(|e| e.to_string())?
+fn validate_project_root_contains_project_descriptor(
+    project_root: &Path,
+) -> Result<(), RenomError> {
+    match fs::read_dir(project_root)?
         .filter_map(Result::ok)
         .filter_map(|entry| entry.path().extension().map(OsStr::to_owned))
         .any(|ext| ext == "uproject")
     {
         true => Ok(()),
-        false => Err("project root must contain a project descriptor".into()),
+        false => Err(RenomError::Validation(
+            "project root must contain a project descriptor".into(),
+        )),
     }
 }
 
-fn validate_project_root_contains_source_dir(project_root: &Path) -> Result<(), String> {
+fn validate_project_root_contains_source_dir(project_root: &Path) -> Result<(), RenomError> {
     match project_root.join("Source").is_dir() {
         true => Ok(()),
-        false => Err("project root must contain a Source folder".into()),
+        false => Err(RenomError::Validation(
+            "project root must contain a Source folder".into(),
+        )),
     }
 }
 
-fn validate_plugin_exists(plugin: &str, plugins: &[Plugin]) -> Result<(), String> {
-    match plugins.iter().any(|other| other.name == plugin) {
+fn validate_plugin_exists(
+    plugin: &str,
+    plugins: &[Plugin],
+    external_plugins: &[ExternalPlugin],
+) -> Result<(), RenomError> {
+    match plugins.iter().any(|other| other.name == plugin)
+        || external_plugins.iter().any(|other| other.name == plugin)
+    {
         true => Ok(()),
-        false => Err("plugin must be part of project".into()),
+        false => Err(RenomError::Validation(
+            "plugin must be part of or referenced by project".into(),
+        )),
     }
 }
 
-fn validate_new_name_is_not_empty(new_name: &str) -> Result<(), String> {
+/// Plugins nested in different directories may share a name, in which case
+/// the root of the plugin to rename must be given to tell them apart.
+fn validate_plugin_is_unambiguous(
+    plugin: &str,
+    plugin_root: Option<&Path>,
+    project_root: &Path,
+    plugins: &[Plugin],
+) -> Result<(), RenomError> {
+    let candidates: Vec<&Plugin> = plugins
+        .iter()
+        .filter(|other| other.name == plugin)
+        .collect();
+    match plugin_root {
+        Some(plugin_root) => match candidates
+            .iter()
+            .any(|candidate| is_plugin_root(&candidate.root, plugin_root, project_root))
+        {
+            true => Ok(()),
+            false => Err(RenomError::Validation(format!(
+                "{} is not the root of a plugin named {}",
+                plugin_root.display(),
+                plugin
+            ))),
+        },
+        None if candidates.len() > 1 => {
+            let roots = candidates
+                .iter()
+                .map(|candidate| format!("  {}", candidate.root.display()))
+                .collect::<Vec<String>>()
+                .join("\n");
+            Err(RenomError::Validation(format!(
+                "several plugins are named {}, use --plugin-path to choose one of:\n{}",
+                plugin, roots
+            )))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Whether a plugin root given by the user, either as is or relative to the
+/// project root, points to the root of a detected plugin.
+fn is_plugin_root(root: &Path, plugin_root: &Path, project_root: &Path) -> bool {
+    let root = match fs::canonicalize(root) {
+        Ok(root) => root,
+        Err(_) => return false,
+    };
+    [plugin_root.to_owned(), project_root.join(plugin_root)]
+        .iter()
+        .filter_map(|path| fs::canonicalize(path).ok())
+        .any(|path| path == root)
+}
+
+fn validate_new_name_is_not_empty(new_name: &str) -> Result<(), RenomError> {
     match !new_name.trim().is_empty() {
         true => Ok(()),
-        false => Err("new name must not be empty".into()),
+        false => Err(RenomError::Validation("new name must not be empty".into())),
     }
 }
 
-fn validate_new_name_is_concise(new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_concise(new_name: &str) -> Result<(), RenomError> {
     let new_name_max_len = 30;
     match new_name.len() <= new_name_max_len {
         true => Ok(()),
@@ -125,80 +431,142 @@ fn validate_new_name_is_concise(new_name: &str) -> Result<(), String> {
                 "new name must not be longer than {} characters",
                 new_name_max_len
             );
-            Err(error_message)
+            Err(RenomError::Validation(error_message))
         }
     }
 }
 
-fn validate_new_name_is_unique(new_name: &str, plugins: &[Plugin]) -> Result<(), String> {
-    match plugins.iter().all(|plugin| plugin.name != new_name) {
+fn validate_new_name_is_unique(
+    new_name: &str,
+    plugins: &[Plugin],
+    external_plugins: &[ExternalPlugin],
+) -> Result<(), RenomError> {
+    match plugins.iter().all(|plugin| plugin.name != new_name)
+        && external_plugins
+            .iter()
+            .all(|plugin| plugin.name != new_name)
+    {
         true => Ok(()),
         false => {
             let error_message = "new name must not conflict with another plugin";
-            Err(error_message.into())
+            Err(RenomError::Validation(error_message.into()))
         }
     }
 }
 
-fn validate_new_name_is_valid_identifier(new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_valid_identifier(new_name: &str) -> Result<(), RenomError> {
     let identifier_regex = Regex::new("^[_[[:alnum:]]]*$").expect("regex should be valid");
     match identifier_regex.is_match(new_name) {
         true => Ok(()),
         false => {
             let error_message =
                 "new name must be comprised of alphanumeric characters and underscores only";
-            Err(error_message.into())
+            Err(RenomError::Validation(error_message.into()))
         }
     }
 }
 
-fn gather_context(params: &Params) -> Result<Context, String> {
+fn gather_context(params: &Params) -> Result<Context, RenomError> {
     let project_name = detect_project_name(&params.project_root)?;
     let project_plugins = detect_project_plugins(&params.project_root)?;
+    let external_plugins =
+        detect_external_plugins(&params.project_root, &project_name, &project_plugins);
     let plugin = project_plugins
         .iter()
-        .find(|plugin| plugin.name == params.plugin)
-        .unwrap()
-        .clone();
+        .filter(|plugin| plugin.name == params.plugin)
+        .find(|plugin| {
+            params.plugin_root.as_deref().is_none_or(|plugin_root| {
+                is_plugin_root(&plugin.root, plugin_root, &params.project_root)
+            })
+        })
+        .cloned();
+
+    let content_only = plugin.as_ref().is_some_and(Plugin::is_content_only);
+    let search_name = params
+        .options
+        .from_name
+        .clone()
+        .unwrap_or_else(|| params.plugin.clone());
+    let config_references =
+        find_config_references(&params.project_root, &search_name, &params.options)?;
+    let sources_with_plugin_lookups = find_sources_with_plugin_lookups(
+        &params.project_root,
+        &project_plugins,
+        &search_name,
+        &params.options,
+    )?;
 
     Ok(Context {
         project_root: params.project_root.clone(),
         project_name,
         project_plugins,
+        plugin_name: params.plugin.clone(),
         plugin,
+        search_name,
+        external_plugins,
+        content_only,
         new_name: params.new_name.clone(),
+        collapse_redirects: params.options.collapse_redirects,
+        recorded_redirects: read_recorded_redirects(&params.project_root)?,
+        config_references,
+        sources_with_plugin_lookups,
     })
 }
 
-fn detect_project_name(project_root: &PathBuf) -> Result<String, String> {
+/// Find config files that enable or disable the plugin by name, so that the
+/// renamed plugin does not silently end up disabled.
+fn find_config_references(
+    project_root: &Path,
+    plugin_name: &str,
+    options: &Options,
+) -> Result<Vec<PathBuf>, RenomError> {
+    let matcher = Matcher::new(
+        std::iter::empty::<&str>(),
+        [plugin_list_entry_pattern(plugin_name)],
+    )?
+    .with_required([plugin_name]);
+    let config_files = WalkDir::new(project_root)
+        .follow_links(options.follow_symlinks)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_none_or(|name| !unreal::is_ignored_dir(name))
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| Category::of(entry.path()) == Category::Config)
+        .map(|entry| entry.path().to_owned())
+        .collect::<Vec<PathBuf>>();
+    Ok(matcher.files_matching(config_files, options.mmap))
+}
+
+fn detect_project_name(project_root: &PathBuf) -> Result<String, RenomError> {
     assert!(project_root.is_dir());
 
-    let project_descriptor = fs::read_dir(project_root)
-        .map_err(|err| err.to_string())?
+    let project_descriptor = fs::read_dir(project_root)?
         .filter_map(Result::ok)
         .map(|entry| entry.path())
-        .filter(|path| path.extension().map_or(false, |ext| ext == "uproject"))
-        .next()
+        .find(|path| path.extension().is_some_and(|ext| ext == "uproject"))
         .expect("project descriptor should exist");
 
     project_descriptor
         .file_stem()
         .and_then(|stem| stem.to_str())
         .map(|name| name.to_owned())
-        .ok_or("project name is not valid Unicode".into())
+        .ok_or_else(|| RenomError::Validation("project name is not valid Unicode".into()))
 }
 
-fn detect_project_plugins(project_root: &PathBuf) -> Result<Vec<Plugin>, String> {
-    let plugins_dir = project_root.join("Plugins");
-    Ok(WalkDir::new(plugins_dir)
+/// Detect all plugins in a project, including nested plugins and plugins in
+/// additional plugin directories.
+pub fn detect_project_plugins(project_root: &Path) -> Result<Vec<Plugin>, RenomError> {
+    Ok(unreal::plugin_directories(project_root)
         .into_iter()
+        .flat_map(|dir| WalkDir::new(dir).sort_by_file_name())
         .filter_map(Result::ok)
-        .filter(|entry| {
-            entry
-                .path()
-                .extension()
-                .map_or(false, |ext| ext == "uplugin")
-        })
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "uplugin"))
         .map(|entry| Plugin {
             root: entry.path().parent().unwrap().to_owned(),
             name: entry
@@ -212,22 +580,145 @@ fn detect_project_plugins(project_root: &PathBuf) -> Result<Vec<Plugin>, String>
         .collect())
 }
 
-fn create_backup_dir(project_root: &Path) -> Result<PathBuf, String> {
-    let backup_dir = project_root.join(".renom/backup");
-    fs::create_dir_all(&backup_dir).map_err(|err| err.to_string())?;
-    Ok(backup_dir)
+/// Detect plugins that are referenced by the project or its plugins but are
+/// not located within the project, such as engine and marketplace plugins.
+/// Descriptors that cannot be parsed are ignored.
+fn detect_external_plugins(
+    project_root: &Path,
+    project_name: &str,
+    project_plugins: &[Plugin],
+) -> Vec<ExternalPlugin> {
+    let descriptors = std::iter::once(project_root.join(project_name).with_extension("uproject"))
+        .chain(
+            project_plugins
+                .iter()
+                .map(|plugin| plugin.root.join(&plugin.name).with_extension("uplugin")),
+        );
+
+    let mut external_plugins: Vec<ExternalPlugin> = vec![];
+    for descriptor in descriptors {
+        for reference in read_plugin_references(&descriptor) {
+            let name = match reference.get("Name").and_then(|name| name.as_str()) {
+                Some(name) => name,
+                None => continue,
+            };
+            if project_plugins.iter().any(|plugin| plugin.name == name)
+                || external_plugins.iter().any(|plugin| plugin.name == name)
+            {
+                continue;
+            }
+            let kind = match reference.get("MarketplaceURL") {
+                Some(_) => ExternalPluginKind::Marketplace,
+                None => ExternalPluginKind::Engine,
+            };
+            external_plugins.push(ExternalPlugin {
+                name: name.to_owned(),
+                kind,
+            });
+        }
+    }
+    external_plugins
+}
+
+fn read_plugin_references(descriptor: &Path) -> Vec<serde_json::Value> {
+    fs::read_to_string(descriptor)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|json| {
+            json.get("Plugins")
+                .and_then(|plugins| plugins.as_array().cloned())
+        })
+        .unwrap_or_default()
+}
+
+/// Find source files that look the plugin up by name, in the project and in
+/// every plugin, including the one renamed.
+fn find_sources_with_plugin_lookups(
+    project_root: &Path,
+    project_plugins: &[Plugin],
+    plugin_name: &str,
+    options: &Options,
+) -> Result<Vec<PathBuf>, RenomError> {
+    let matcher = Matcher::new(
+        std::iter::empty::<&str>(),
+        [plugin_lookup_pattern(plugin_name)],
+    )?
+    .with_required([plugin_name]);
+    let sources = std::iter::once(project_root.join("Source"))
+        .chain(
+            project_plugins
+                .iter()
+                .map(|plugin| plugin.root.join("Source")),
+        )
+        .flat_map(|dir| {
+            WalkDir::new(dir)
+                .follow_links(options.follow_symlinks)
+                .sort_by_file_name()
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            Category::of(entry.path()) == Category::Source
+                && entry.path().extension().is_some_and(|ext| ext != "cs")
+        })
+        .map(|entry| entry.path().to_owned())
+        .collect::<Vec<PathBuf>>();
+    Ok(matcher.files_matching(sources, options.mmap))
+}
+
+/// Record the rename of the plugin in the ledger of the project, along with the
+/// redirects it added.
+fn record_in_ledger(context: &Context, redirects: Vec<String>) -> Result<(), RenomError> {
+    let entry = ledger::Entry::new("plugin", &context.plugin_name, &context.new_name, redirects);
+    ledger::append(&context.project_root, &entry).map_err(RenomError::from)
+}
+
+/// Let the user know that only references will be updated when the plugin is
+/// installed outside of the project.
+fn warn_if_external(context: &Context) {
+    if let Some(plugin) = context
+        .external_plugins
+        .iter()
+        .find(|plugin| plugin.name == context.plugin_name)
+    {
+        log::warning(format!(
+            "Plugin {} is a {} installed outside of the project. \
+            Only references to it will be updated.",
+            plugin.name, plugin.kind
+        ));
+    }
+}
+
+/// Check that maps referenced in config still resolve after the rename, since
+/// they may live under a folder or mount point named after the renamed plugin.
+fn warn_about_unresolved_maps(context: &Context) {
+    let project_root = context.project_root.clone();
+    for diagnostic in doctor::check_default_maps(&project_root) {
+        log::warning(diagnostic.to_string());
+    }
 }
 
 fn print_success_message(context: &Context) {
     log::success(format!(
         "Successfully renamed plugin {} to {}.",
-        context.plugin.name, context.new_name
+        context.plugin_name, context.new_name
+    ));
+}
+
+/// Content-only plugins are referenced solely through their mount point, so
+/// point the user at the redirect that keeps existing references working.
+fn print_content_redirect_guidance(context: &Context) {
+    log::warning(format!(
+        "Plugin {0} contains content only. References to /{0}/ are redirected to /{1}/ \
+        through [CoreRedirects] in Config/DefaultEngine.ini. Resave referencing assets \
+        and fix up redirectors in the editor to make the change permanent.",
+        context.plugin_name, context.new_name
     ));
 }
 
 fn print_failure_message(context: &Context) {
     log::error(format!(
         "Failed to rename plugin {} to {}.",
-        context.plugin.name, context.new_name
+        context.plugin_name, context.new_name
     ));
 }
@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use crate::changes::{Change, RenameFile, SetIniEntry};
+use crate::changes::{Change, RenameFile, ReplaceInFile, SetIniEntry};
 
 use super::Context;
 
@@ -9,15 +9,89 @@ pub fn generate_changeset(context: &Context) -> Vec<Change> {
     let Context {
         project_root,
         project_name: old_name,
+        search_name,
         new_name,
+        configs_with_content_paths,
+        rewrite_content_paths,
+        project_files,
+        update_project_files,
     } = context;
 
-    vec![
+    let mut changeset = vec![
         add_game_name_to_engine_config(project_root, new_name),
         add_project_name_to_game_config(project_root, new_name),
-        rename_project_descriptor(project_root, old_name, new_name),
-        rename_project_root(project_root, new_name),
-    ]
+    ];
+
+    if *rewrite_content_paths {
+        changeset.extend(
+            configs_with_content_paths
+                .iter()
+                .map(|config| rewrite_content_paths_in_config(config, search_name, new_name)),
+        );
+    }
+
+    if *update_project_files {
+        changeset.extend(
+            project_files
+                .iter()
+                .filter_map(|file| update_project_file(file, old_name, new_name)),
+        );
+        changeset.extend(
+            project_files
+                .iter()
+                .map(|file| rename_project_file(file, old_name, new_name)),
+        );
+    }
+
+    changeset.push(rename_project_descriptor(project_root, old_name, new_name));
+    changeset.push(rename_project_root(project_root, new_name));
+    changeset
+}
+
+fn rewrite_content_paths_in_config(config: &Path, old_name: &str, new_name: &str) -> Change {
+    Change::ReplaceInFile(ReplaceInFile::new(
+        config,
+        format!("/Game/{}/", old_name),
+        format!("/Game/{}/", new_name),
+    ))
+}
+
+/// Update the references to the project in a generated project file: the
+/// project entry of the solution and the project descriptor that the Visual
+/// Studio project passes to the build tool and lists among its files.
+fn update_project_file(file: &Path, old_name: &str, new_name: &str) -> Option<Change> {
+    let file_name = file.file_name()?.to_str()?;
+    let (from, to) = if file_name.ends_with(".sln") {
+        (
+            format!(
+                r#"(?P<name>\) = "){old}(?P<path>", "Intermediate[\\/]ProjectFiles[\\/]){old}(?P<ext>\.vcxproj")"#,
+                old = regex::escape(old_name)
+            ),
+            format!("${{name}}{new}${{path}}{new}${{ext}}", new = new_name),
+        )
+    } else if file_name.ends_with(".vcxproj") || file_name.ends_with(".vcxproj.filters") {
+        (
+            format!(r"\b{}\.uproject", regex::escape(old_name)),
+            format!("{}.uproject", new_name),
+        )
+    } else {
+        return None;
+    };
+    Some(Change::ReplaceInFile(ReplaceInFile::new(file, from, to)))
+}
+
+/// Rename a generated project file after the new project name, keeping its
+/// extensions, e.g. `OldName.vcxproj.filters`.
+fn rename_project_file(file: &Path, old_name: &str, new_name: &str) -> Change {
+    let file_name = file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let extensions = file_name.strip_prefix(old_name).unwrap_or(file_name);
+    Change::RenameFile(RenameFile::new(
+        file,
+        file.with_file_name(format!("{}{}", new_name, extensions)),
+    ))
 }
 
 fn rename_project_descriptor(project_root: &Path, old_name: &str, new_name: &str) -> Change {
@@ -47,7 +121,7 @@ fn add_project_name_to_game_config(project_root: &Path, new_name: &str) -> Chang
 
 fn rename_project_root(project_root: &Path, new_name: &str) -> Change {
     Change::RenameFile(RenameFile::new(
-        &project_root,
+        project_root,
         project_root.with_file_name(new_name),
     ))
 }
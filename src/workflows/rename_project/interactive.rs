@@ -1,26 +1,75 @@
-use std::{ffi::OsStr, fs, path::PathBuf};
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
 
-use inquire::{validator::Validation, CustomUserError, Text};
+use inquire::{validator::Validation, Confirm, CustomUserError, Text};
 use regex::Regex;
 
-use super::Params;
+use crate::{ledger, presentation::log, settings::Settings, workflows::select_discovered_project};
+
+use super::{
+    detect_generated_project_files, detect_project_config_files, find_configs_with_content_paths,
+    Options, Params,
+};
 
 pub fn get_params_from_user() -> Result<Params, String> {
     let project_root = get_project_root_from_user()?;
-    let target_name = get_target_name_from_user()?;
+    let settings = Settings::load_for(&project_root)?;
+    settings.apply();
+    let project_name = detect_project_name(&project_root)?;
+    let history = ledger::read(&project_root).map_err(|err| err.to_string())?;
+    let target_name = get_target_name_from_user(ledger::naming_prefix(&history))?;
+    warn_if_reversal(&history, &project_name, &target_name);
+    let rewrite_content_paths = confirm_rewrite_content_paths(
+        &project_root,
+        &project_name,
+        &target_name,
+        settings.prompts.rewrite_content_paths,
+    )?;
+    let update_project_files = confirm_update_project_files(
+        &project_root,
+        &project_name,
+        settings.prompts.update_project_files,
+    )?;
     Ok(Params {
         project_root,
         new_name: target_name,
+        options: Options {
+            rewrite_content_paths,
+            update_project_files,
+            interactive: !settings.general.non_interactive,
+            backup_policy: Some(settings.backup),
+            backup_dir: settings.general.backup_dir,
+            include: settings.general.include,
+            exclude: settings.general.exclude,
+            chunk_size: settings.engine.chunk_size,
+            threads: settings.engine.threads,
+            ..Options::default()
+        },
     })
 }
 
-fn get_project_root_from_user() -> Result<PathBuf, String> {
+/// Ask the user for the new name of a project, e.g. when it was not given on the
+/// command line.
+pub fn get_new_name_from_user(project_root: &Path) -> Result<String, String> {
+    let history = ledger::read(project_root).map_err(|err| err.to_string())?;
+    get_target_name_from_user(ledger::naming_prefix(&history))
+}
+
+/// Ask the user for the root of a project, offering the projects discovered
+/// around the working directory first.
+pub fn get_project_root_from_user() -> Result<PathBuf, String> {
+    if let Some(project_root) = select_discovered_project(|_| true)? {
+        return Ok(project_root);
+    }
     Text::new("Project root directory path:")
         .with_validator(validate_project_root_is_not_special)
         .with_validator(validate_project_root_is_dir)
         .with_validator(validate_project_root_contains_project_descriptor)
         .prompt()
-        .map(|project_root| PathBuf::from(project_root))
+        .map(PathBuf::from)
         .map_err(|err| err.to_string())
 }
 
@@ -60,13 +109,17 @@ fn validate_project_root_contains_project_descriptor(
     }
 }
 
-fn get_target_name_from_user() -> Result<String, String> {
-    Text::new("Provide a new name for the project:")
+fn get_target_name_from_user(prefix: Option<String>) -> Result<String, String> {
+    let prompt = Text::new("Provide a new name for the project:")
         .with_validator(validate_target_name_is_not_empty)
         .with_validator(validate_target_name_is_concise)
-        .with_validator(validate_target_name_is_valid_identifier)
-        .prompt()
-        .map_err(|err| err.to_string())
+        .with_validator(validate_target_name_is_valid_identifier);
+    match prefix.as_deref() {
+        Some(prefix) => prompt.with_initial_value(prefix),
+        None => prompt,
+    }
+    .prompt()
+    .map_err(|err| err.to_string())
 }
 
 fn validate_target_name_is_not_empty(target_name: &str) -> Result<Validation, CustomUserError> {
@@ -106,3 +159,68 @@ fn validate_target_name_is_valid_identifier(
         }
     }
 }
+
+/// Warn the user if the rename reverses an earlier one recorded in the ledger.
+fn warn_if_reversal(history: &[ledger::Entry], old_name: &str, new_name: &str) {
+    if let Some(entry) = ledger::find_reversal(history, "project", old_name, new_name) {
+        log::warning(format!(
+            "This reverses the rename of project {} to {} on {}.",
+            entry.old_name, entry.new_name, entry.date
+        ));
+    }
+}
+
+fn detect_project_name(project_root: &Path) -> Result<String, String> {
+    let project_descriptor = fs::read_dir(project_root)
+        .map_err(|err| err.to_string())?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "uproject"))
+        .expect("project descriptor should exist");
+
+    project_descriptor
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(|name| name.to_owned())
+        .ok_or("project name is not valid Unicode".into())
+}
+
+/// Offer to rewrite config paths to content in a folder named after the
+/// project, if there are any.
+fn confirm_rewrite_content_paths(
+    project_root: &Path,
+    project_name: &str,
+    new_name: &str,
+    seeded: Option<bool>,
+) -> Result<bool, String> {
+    let config_files = detect_project_config_files(project_root)?;
+    if find_configs_with_content_paths(&config_files, project_name).is_empty() {
+        return Ok(false);
+    }
+    let message = format!(
+        "Config files reference content under /Game/{}/. Would you like to rewrite these paths to /Game/{}/?",
+        project_name, new_name
+    );
+    Confirm::new(&message)
+        .with_help_message("The content folder itself must still be moved from within the editor")
+        .with_default(seeded.unwrap_or(false))
+        .prompt()
+        .map_err(|err| err.to_string())
+}
+
+/// Offer to rename the Visual Studio files generated for the project, if
+/// there are any.
+fn confirm_update_project_files(
+    project_root: &Path,
+    project_name: &str,
+    seeded: Option<bool>,
+) -> Result<bool, String> {
+    if detect_generated_project_files(project_root, project_name).is_empty() {
+        return Ok(false);
+    }
+    Confirm::new("Would you like to rename the generated Visual Studio project files as well?")
+        .with_help_message("Otherwise, regenerate project files after the rename")
+        .with_default(seeded.unwrap_or(false))
+        .prompt()
+        .map_err(|err| err.to_string())
+}
@@ -8,17 +8,38 @@ use std::{
 };
 
 use regex::Regex;
+use walkdir::WalkDir;
 
-use crate::{engine::Engine, presentation::log};
+use crate::{
+    changes::{Change, Changeset},
+    compatibility, doctor,
+    error::RenomError,
+    ledger,
+    manifest::{self, Manifest},
+    plan,
+    presentation::{log, preview},
+    text::TextFile,
+    workflows::{
+        added_redirects, check_execution, filter_changes, merge_extra_ops, prepare_backup_dir,
+        resolve_conflicts, Options,
+    },
+};
 
 use self::{changeset::generate_changeset, interactive::get_params_from_user};
 
+pub use self::interactive::{get_new_name_from_user, get_project_root_from_user};
+
+/// The most characters that the new name of a project may have.
+pub const NEW_NAME_MAX_LEN: usize = 20;
+
 /// Params needed to rename an Unreal Engine project.
 pub struct Params {
     /// The root of the project.
     pub project_root: PathBuf,
     /// The new name for the project.
     pub new_name: String,
+    /// Options for the rename.
+    pub options: Options,
 }
 
 /// Context needed to rename an Unreal Engine project.
@@ -27,36 +48,134 @@ pub struct Context {
     pub project_root: PathBuf,
     /// The name of the project.
     pub project_name: String,
+    /// The name to search for in file contents. Matches the old name unless
+    /// overridden.
+    pub search_name: String,
     /// The new name for the project.
     pub new_name: String,
+    /// Config files that reference content in a folder named after the
+    /// project, e.g. `/Game/OldName/Maps/Start`.
+    pub configs_with_content_paths: Vec<PathBuf>,
+    /// Whether to rewrite content paths that include the project name.
+    pub rewrite_content_paths: bool,
+    /// Visual Studio files generated for the project, e.g. `OldName.sln` and
+    /// `Intermediate/ProjectFiles/OldName.vcxproj`.
+    pub project_files: Vec<PathBuf>,
+    /// Whether to rename the generated project files and the references to
+    /// them.
+    pub update_project_files: bool,
 }
 
 /// Rename an Unreal Engine project interactively, soliciting input parameters
-/// from the user with validation and guided selection.
-pub fn rename_project_interactive() -> Result<(), String> {
-    let params = get_params_from_user()?;
-    rename_project(params)
+/// from the user with validation and guided selection. Returns the new root of
+/// the project, with a report of the rename written to it, or `None` if the
+/// user cancelled the rename.
+pub fn rename_project_interactive() -> Result<Option<PathBuf>, RenomError> {
+    let params = get_params_from_user().map_err(RenomError::Validation)?;
+    let renamed_root = params.project_root.with_file_name(&params.new_name);
+    let Some(manifest) = rename(params, None)? else {
+        return Ok(None);
+    };
+    manifest::write_report(&renamed_root, &manifest)?;
+    Ok(Some(renamed_root))
+}
+
+/// Generate the changes needed to rename an Unreal Engine project without
+/// applying them.
+pub fn plan_rename_project(params: &Params) -> Result<Changeset, RenomError> {
+    validate_params(params)?;
+    let context = gather_context(params)?;
+    params.options.cancellation.check()?;
+    let changeset = merge_extra_ops(
+        filter_changes(
+            generate_changeset(&context),
+            &context.project_root,
+            &params.options,
+        )?,
+        params.options.extra_ops.as_deref(),
+        &context.project_root,
+    )?;
+    let changeset = resolve_conflicts(changeset, params.options.interactive)?;
+    Ok(Changeset::new(changeset))
 }
 
 /// Rename an Unreal Engine project.
-pub fn rename_project(params: Params) -> Result<(), String> {
+pub fn rename_project(params: Params) -> Result<(), RenomError> {
+    rename(params, None).map(|_| ())
+}
+
+/// Apply changes planned earlier with [`plan_rename_project`]. The params are
+/// validated again and the plan is only applied if it still matches the
+/// changes needed for the current state of the project.
+pub fn apply_planned_rename_project(params: Params, plan: &Changeset) -> Result<(), RenomError> {
+    rename(params, Some(plan)).map(|_| ())
+}
+
+fn rename(params: Params, plan: Option<&Changeset>) -> Result<Option<Manifest>, RenomError> {
     validate_params(&params)?;
     let context = gather_context(&params)?;
-    let changeset = generate_changeset(&context);
-    let backup_dir = create_backup_dir(&context.project_root)?;
-    let mut engine = Engine::new();
+    params.options.cancellation.check()?;
+    let changeset = merge_extra_ops(
+        filter_changes(
+            generate_changeset(&context),
+            &context.project_root,
+            &params.options,
+        )?,
+        params.options.extra_ops.as_deref(),
+        &context.project_root,
+    )?;
+    let changeset = resolve_conflicts(changeset, params.options.interactive)?;
+    if let Some(plan) = plan {
+        plan::check_is_current(plan, &changeset)?;
+    }
+    let redirects = added_redirects(&changeset);
+    let manifest = Manifest::new(
+        "project",
+        &context.project_name,
+        &context.new_name,
+        &changeset,
+    );
+    warn_about_content_paths(&context);
+    warn_about_project_files(&context);
+    warn_about_compatibility(&changeset);
+    let updated = match context.update_project_files {
+        true => context.project_files.as_slice(),
+        false => &[],
+    };
+    doctor::warn_about_unhandled_references(&context.project_root, &context.search_name, updated);
+    check_execution(&changeset, &context.project_root, &params.options)?;
+    if params.options.interactive && !preview::review(&changeset, &context.project_root)? {
+        log::basic("Rename cancelled.");
+        return Ok(None);
+    }
+    let backup_root = params.options.backup_root(&context.project_root);
+    let backup_dir = prepare_backup_dir(&backup_root, &params.options)?;
+    let apply_options = params
+        .options
+        .apply_options_for(&changeset, &context.project_root)?;
+    let mut engine = params.options.engine(apply_options);
     if let Err(e) = engine.execute(changeset, backup_dir) {
-        log::error(&e);
         engine.revert()?;
         print_failure_message(&context);
-        return Ok(());
+        return Err(e);
+    }
+
+    if params.options.ledger {
+        record_in_ledger(&context, redirects)?;
     }
 
+    manifest::write_for_review(
+        &context.project_root.with_file_name(&context.new_name),
+        &manifest,
+        params.options.vcs,
+    )?;
+
     print_success_message(&context);
-    Ok(())
+    warn_about_unresolved_maps(&context);
+    Ok(Some(manifest))
 }
 
-fn validate_params(params: &Params) -> Result<(), String> {
+fn validate_params(params: &Params) -> Result<(), RenomError> {
     validate_project_root_is_not_special(&params.project_root)?;
     validate_project_root_is_dir(&params.project_root)?;
     validate_project_root_contains_project_descriptor(&params.project_root)?;
@@ -65,108 +184,245 @@ fn validate_params(params: &Params) -> Result<(), String> {
     validate_new_name_is_novel(&project_name, &params.new_name)?;
     validate_new_name_is_concise(&params.new_name)?;
     validate_new_name_is_valid_identifier(&params.new_name)?;
+    params.options.validate_from_name()?;
     Ok(())
 }
 
-fn validate_project_root_is_not_special(project_root: &Path) -> Result<(), String> {
+fn validate_project_root_is_not_special(project_root: &Path) -> Result<(), RenomError> {
     match project_root {
-        path if path == Path::new(".") => Err("project root cannot be '.'".into()),
-        path if path == Path::new("..") => Err("project root cannot be '..'".into()),
+        path if path == Path::new(".") => {
+            Err(RenomError::Validation("project root cannot be '.'".into()))
+        }
+        path if path == Path::new("..") => {
+            Err(RenomError::Validation("project root cannot be '..'".into()))
+        }
         _ => Ok(()),
     }
 }
 
-fn validate_project_root_is_dir(project_root: &Path) -> Result<(), String> {
+fn validate_project_root_is_dir(project_root: &Path) -> Result<(), RenomError> {
     match project_root.is_dir() {
         true => Ok(()),
-        false => Err("project root must be a directory".into()),
+        false => Err(RenomError::Validation(
+            "project root must be a directory".into(),
+        )),
     }
 }
 
-fn validate_project_root_contains_project_descriptor(project_root: &Path) -> Result<(), String> {
-    match fs::read_dir(&project_root)
-        .map_err(|err| err.to_string())?
+fn validate_project_root_contains_project_descriptor(
+    project_root: &Path,
+) -> Result<(), RenomError> {
+    match fs::read_dir(project_root)?
         .filter_map(Result::ok)
         .filter_map(|entry| entry.path().extension().map(OsStr::to_owned))
         .any(|ext| ext == "uproject")
     {
         true => Ok(()),
-        false => Err("project root must contain a project descriptor".into()),
+        false => Err(RenomError::Validation(
+            "project root must contain a project descriptor".into(),
+        )),
     }
 }
 
-fn validate_new_name_is_novel(old_name: &str, new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_novel(old_name: &str, new_name: &str) -> Result<(), RenomError> {
     match old_name != new_name {
         true => Ok(()),
-        false => Err("new name must be different than current name".into()),
+        false => Err(RenomError::Validation(
+            "new name must be different than current name".into(),
+        )),
     }
 }
 
-fn validate_new_name_is_not_empty(new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_not_empty(new_name: &str) -> Result<(), RenomError> {
     match !new_name.trim().is_empty() {
         true => Ok(()),
-        false => Err("new name must not be empty".into()),
+        false => Err(RenomError::Validation("new name must not be empty".into())),
     }
 }
 
-fn validate_new_name_is_concise(new_name: &str) -> Result<(), String> {
-    let new_name_max_len = 20;
-    match new_name.len() <= new_name_max_len {
+fn validate_new_name_is_concise(new_name: &str) -> Result<(), RenomError> {
+    match new_name.len() <= NEW_NAME_MAX_LEN {
         true => Ok(()),
         false => {
             let error_message = format!(
                 "new name must not be longer than {} characters",
-                new_name_max_len
+                NEW_NAME_MAX_LEN
             );
-            Err(error_message)
+            Err(RenomError::Validation(error_message))
         }
     }
 }
 
-fn validate_new_name_is_valid_identifier(new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_valid_identifier(new_name: &str) -> Result<(), RenomError> {
     let identifier_regex = Regex::new("^[_[[:alnum:]]]*$").expect("regex should be valid");
     match identifier_regex.is_match(new_name) {
         true => Ok(()),
         false => {
             let error_message =
                 "new name must be comprised of alphanumeric characters and underscores only";
-            Err(error_message.into())
+            Err(RenomError::Validation(error_message.into()))
         }
     }
 }
 
-fn gather_context(params: &Params) -> Result<Context, String> {
+fn gather_context(params: &Params) -> Result<Context, RenomError> {
     let project_name = detect_project_name(&PathBuf::from(&params.project_root))?;
+    let project_config_files = detect_project_config_files(&params.project_root)?;
+    let search_name = params
+        .options
+        .from_name
+        .clone()
+        .unwrap_or_else(|| project_name.clone());
+    let configs_with_content_paths =
+        find_configs_with_content_paths(&project_config_files, &search_name);
     Ok(Context {
         project_root: params.project_root.clone(),
-        project_name,
+        project_name: project_name.clone(),
+        search_name,
         new_name: params.new_name.clone(),
+        configs_with_content_paths,
+        rewrite_content_paths: params.options.rewrite_content_paths,
+        project_files: detect_generated_project_files(&params.project_root, &project_name),
+        update_project_files: params.options.update_project_files,
     })
 }
 
-fn detect_project_name(project_root: &PathBuf) -> Result<String, String> {
+fn detect_project_name(project_root: &PathBuf) -> Result<String, RenomError> {
     assert!(project_root.is_dir());
 
-    let project_descriptor = fs::read_dir(project_root)
-        .map_err(|err| err.to_string())?
+    let project_descriptor = fs::read_dir(project_root)?
         .filter_map(Result::ok)
         .map(|entry| entry.path())
-        .filter(|path| path.extension().map_or(false, |ext| ext == "uproject"))
-        .next()
+        .find(|path| path.extension().is_some_and(|ext| ext == "uproject"))
         .expect("project descriptor should exist");
 
     project_descriptor
         .file_stem()
         .and_then(|stem| stem.to_str())
         .map(|name| name.to_owned())
-        .ok_or("project name is not valid Unicode".into())
+        .ok_or_else(|| RenomError::Validation("project name is not valid Unicode".into()))
+}
+
+fn detect_project_config_files(project_root: &Path) -> Result<Vec<PathBuf>, RenomError> {
+    let config_dir = project_root.join("Config");
+    Ok(WalkDir::new(config_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "ini"))
+        .map(|entry| entry.path().to_owned())
+        .collect())
+}
+
+/// Find config files that reference content in a folder named after the
+/// project, e.g. `/Game/OldName/Maps/Start`.
+fn find_configs_with_content_paths(config_files: &[PathBuf], project_name: &str) -> Vec<PathBuf> {
+    let content_path = format!("/Game/{}/", project_name);
+    config_files
+        .iter()
+        .filter(|config| {
+            TextFile::read(config).is_ok_and(|file| file.content.contains(&content_path))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Detect the Visual Studio solution and project files generated for a
+/// project, which are named after it.
+fn detect_generated_project_files(project_root: &Path, project_name: &str) -> Vec<PathBuf> {
+    let project_files_dir = project_root.join("Intermediate/ProjectFiles");
+    vec![
+        project_root.join(format!("{}.sln", project_name)),
+        project_files_dir.join(format!("{}.vcxproj", project_name)),
+        project_files_dir.join(format!("{}.vcxproj.filters", project_name)),
+        project_files_dir.join(format!("{}.vcxproj.user", project_name)),
+    ]
+    .into_iter()
+    .filter(|path| path.is_file())
+    .collect()
 }
 
-/// Create a directory to store backup files in
-fn create_backup_dir(project_root: &Path) -> Result<PathBuf, String> {
-    let backup_dir = project_root.join(".renom/backup");
-    fs::create_dir_all(&backup_dir).map_err(|err| err.to_string())?;
-    Ok(backup_dir)
+/// Let the user know about generated project files that still carry the old
+/// name, since Visual Studio loads phantom projects from them until they are
+/// regenerated.
+fn warn_about_project_files(context: &Context) {
+    if context.project_files.is_empty() {
+        return;
+    }
+    let files = context
+        .project_files
+        .iter()
+        .map(|file| format!("\t- {}", file.display()))
+        .collect::<Vec<String>>()
+        .join("\n");
+    let action = match context.update_project_files {
+        true => "These files will be renamed along with the references to them.\n\tRegenerate project files if the project fails to build, since they may\n\tcontain absolute paths to the old project root.",
+        false => "Pass --update-project-files to rename them, or regenerate project files\n\tafter the rename.",
+    };
+    log::warning(format!(
+        "The following generated project files are named after {}:\n{}\n\t{}",
+        context.project_name, files, action
+    ));
+}
+
+/// Warn the user about content paths that include the project name, since
+/// the content folder itself can only be moved safely from within the editor.
+fn warn_about_content_paths(context: &Context) {
+    if context.configs_with_content_paths.is_empty() {
+        return;
+    }
+    let configs = context
+        .configs_with_content_paths
+        .iter()
+        .map(|config| format!("\t- {}", config.display()))
+        .collect::<Vec<String>>()
+        .join("\n");
+    let action = match context.rewrite_content_paths {
+        true => format!(
+            "These paths will be rewritten to /Game/{}/.",
+            context.new_name
+        ),
+        false => "These paths will be left as is.".to_owned(),
+    };
+    log::warning(format!(
+        "The following config files reference content under /Game/{old}/:\n{configs}\n\t{action}\n\tMove the Content/{old} folder to Content/{new} from within the Unreal Editor\n\tso that references in assets are fixed up as well.",
+        old = context.search_name,
+        new = context.new_name,
+        configs = configs,
+        action = action,
+    ));
+}
+
+/// Let the user know how the rename affects builds made before it, as their
+/// save games are kept in a folder named after the project.
+fn warn_about_compatibility(changeset: &[Change]) {
+    let notes = compatibility::notes(changeset);
+    if notes.is_empty() {
+        return;
+    }
+    log::warning(format!("Compatibility:\n\t- {}", notes.join("\n\t- ")));
+}
+
+/// Record the rename in the ledger of the project, found under its new root
+/// now that the project has moved.
+fn record_in_ledger(context: &Context, redirects: Vec<String>) -> Result<(), RenomError> {
+    let entry = ledger::Entry::new(
+        "project",
+        &context.project_name,
+        &context.new_name,
+        redirects,
+    );
+    let project_root = context.project_root.with_file_name(&context.new_name);
+    ledger::append(&project_root, &entry).map_err(RenomError::from)
+}
+
+/// Check that maps referenced in config still resolve after the rename, since
+/// they may live under a folder or mount point named after the renamed project.
+fn warn_about_unresolved_maps(context: &Context) {
+    let project_root = context.project_root.with_file_name(&context.new_name);
+    for diagnostic in doctor::check_default_maps(&project_root) {
+        log::warning(diagnostic.to_string());
+    }
 }
 
 fn print_success_message(context: &Context) {
@@ -1,38 +1,63 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::{
     changes::{Change, RenameFile, ReplaceInFile},
     unreal::Target,
 };
 
-use super::Context;
+use super::{Context, TargetReference};
 
 /// Generate a changeset to rename an Unreal Engine target.
 pub fn generate_changeset(context: &Context) -> Vec<Change> {
     let Context {
         project_targets,
         target: Target {
-            name: old_name,
-            path: target_file,
+            path: target_file, ..
         },
+        search_name,
         new_name,
+        external_references,
         ..
     } = context;
 
     let mut changeset = vec![];
 
-    changeset.push(rename_target_class(target_file, old_name, new_name));
+    changeset.push(rename_target_class(target_file, search_name, new_name));
     changeset.push(rename_target_file(target_file, new_name));
     changeset.extend(rename_cross_target_references(
         target_file,
         project_targets,
-        old_name,
+        search_name,
+        new_name,
+    ));
+    changeset.extend(rename_external_references(
+        external_references,
+        search_name,
         new_name,
     ));
 
     changeset
 }
 
+fn rename_external_references(
+    external_references: &[(PathBuf, Vec<TargetReference>)],
+    old_name: &str,
+    new_name: &str,
+) -> Vec<Change> {
+    external_references
+        .iter()
+        .flat_map(|(path, kinds)| {
+            kinds.iter().map(move |kind| {
+                Change::ReplaceInFile(ReplaceInFile::new(
+                    path,
+                    kind.pattern(old_name),
+                    kind.replacement(new_name),
+                ))
+            })
+        })
+        .collect()
+}
+
 fn rename_cross_target_references(
     target_file: &Path,
     project_targets: &[Target],
@@ -41,7 +66,7 @@ fn rename_cross_target_references(
 ) -> Vec<Change> {
     project_targets
         .iter()
-        .filter(|target| &&target.path != &target_file)
+        .filter(|target| target.path != target_file)
         .map(|target| rename_target_references_in_target(&target.path, old_name, new_name))
         .collect()
 }
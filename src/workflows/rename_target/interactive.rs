@@ -7,29 +7,65 @@ use std::{
 use inquire::{validator::Validation, CustomUserError, Select, Text};
 use regex::Regex;
 
-use crate::unreal::Target;
+use crate::{
+    ledger, presentation::log, settings::Settings, unreal::Target,
+    workflows::select_discovered_project,
+};
 
-use super::Params;
+use super::{Options, Params};
 
 pub fn get_params_from_user() -> Result<Params, String> {
     let project_root = get_project_root_from_user()?;
+    let settings = Settings::load_for(&project_root)?;
+    settings.apply();
+    let history = ledger::read(&project_root).map_err(|err| err.to_string())?;
     let project_targets = detect_project_targets(&project_root)?;
     let target_target = get_target_target_from_user(&project_targets)?;
-    let target_name = get_target_name_from_user(&project_targets)?;
+    let target_name = get_target_name_from_user(&project_targets, ledger::naming_prefix(&history))?;
+    warn_if_reversal(&history, &target_target.name, &target_name);
     Ok(Params {
         project_root,
         target: target_target.name,
         new_name: target_name,
+        options: Options {
+            interactive: !settings.general.non_interactive,
+            backup_policy: Some(settings.backup),
+            backup_dir: settings.general.backup_dir,
+            include: settings.general.include,
+            exclude: settings.general.exclude,
+            chunk_size: settings.engine.chunk_size,
+            threads: settings.engine.threads,
+            ..Options::default()
+        },
     })
 }
 
-fn get_project_root_from_user() -> Result<PathBuf, String> {
+/// Ask the user to choose a target of a project, e.g. when it was not given on
+/// the command line.
+pub fn get_target_from_user(project_root: &Path) -> Result<Target, String> {
+    get_target_target_from_user(&detect_project_targets(project_root)?)
+}
+
+/// Ask the user for the new name of a target, e.g. when it was not given on the
+/// command line.
+pub fn get_new_name_from_user(project_root: &Path) -> Result<String, String> {
+    let history = ledger::read(project_root).map_err(|err| err.to_string())?;
+    let targets = detect_project_targets(project_root)?;
+    get_target_name_from_user(&targets, ledger::naming_prefix(&history))
+}
+
+/// Ask the user for the root of a project, offering the projects discovered
+/// around the working directory first.
+pub fn get_project_root_from_user() -> Result<PathBuf, String> {
+    if let Some(project_root) = select_discovered_project(|root| root.join("Source").is_dir())? {
+        return Ok(project_root);
+    }
     Text::new("Project root directory path:")
         .with_validator(validate_project_root_is_dir)
         .with_validator(validate_project_root_contains_project_descriptor)
         .with_validator(validate_project_root_contains_source_dir)
         .prompt()
-        .map(|project_root| PathBuf::from(project_root))
+        .map(PathBuf::from)
         .map_err(|err| err.to_string())
 }
 
@@ -98,15 +134,19 @@ fn get_target_target_from_user(targets: &[Target]) -> Result<Target, String> {
         .map_err(|err| err.to_string())
 }
 
-fn get_target_name_from_user(targets: &[Target]) -> Result<String, String> {
+fn get_target_name_from_user(targets: &[Target], prefix: Option<String>) -> Result<String, String> {
     let targets = targets.to_vec();
-    Text::new("Provide a new name for the target:")
+    let prompt = Text::new("Provide a new name for the target:")
         .with_validator(validate_target_name_is_not_empty)
         .with_validator(validate_target_name_is_concise)
         .with_validator(move |input: &str| validate_target_name_is_unique(input, &targets))
-        .with_validator(validate_target_name_is_valid_identifier)
-        .prompt()
-        .map_err(|err| err.to_string())
+        .with_validator(validate_target_name_is_valid_identifier);
+    match prefix.as_deref() {
+        Some(prefix) => prompt.with_initial_value(prefix),
+        None => prompt,
+    }
+    .prompt()
+    .map_err(|err| err.to_string())
 }
 
 fn validate_target_name_is_not_empty(target_name: &str) -> Result<Validation, CustomUserError> {
@@ -159,3 +199,13 @@ fn validate_target_name_is_valid_identifier(
         }
     }
 }
+
+/// Warn the user if the rename reverses an earlier one recorded in the ledger.
+fn warn_if_reversal(history: &[ledger::Entry], old_name: &str, new_name: &str) {
+    if let Some(entry) = ledger::find_reversal(history, "target", old_name, new_name) {
+        log::warning(format!(
+            "This reverses the rename of target {} to {} on {}.",
+            entry.old_name, entry.new_name, entry.date
+        ));
+    }
+}
@@ -8,11 +8,30 @@ use std::{
 };
 
 use regex::Regex;
+use walkdir::WalkDir;
 
-use crate::{engine::Engine, presentation::log, unreal::Target};
+use crate::{
+    changes::{Category, Changeset},
+    doctor,
+    error::RenomError,
+    ledger,
+    manifest::{self, Manifest},
+    matcher::Matcher,
+    plan,
+    presentation::{log, preview},
+    unreal::{self, Target},
+    workflows::{
+        added_redirects, check_execution, filter_changes, merge_extra_ops, prepare_backup_dir,
+        resolve_conflicts, Options,
+    },
+};
 
 use self::{changeset::generate_changeset, interactive::get_params_from_user};
 
+pub use self::interactive::{
+    get_new_name_from_user, get_project_root_from_user, get_target_from_user,
+};
+
 /// Params needed to rename an Unreal Engine target.
 pub struct Params {
     /// The root of the project.
@@ -21,6 +40,8 @@ pub struct Params {
     pub target: String,
     /// The new name for the target.
     pub new_name: String,
+    /// Options for the rename.
+    pub options: Options,
 }
 
 /// Context needed to rename an Unreal Engine target.
@@ -31,36 +52,167 @@ pub struct Context {
     pub project_targets: Vec<Target>,
     /// The specific target to rename.
     pub target: Target,
+    /// The name to search for in file contents. Matches the old name unless
+    /// overridden.
+    pub search_name: String,
     /// The new name for the target.
     pub new_name: String,
+    /// Config files and scripts outside of target files that reference the
+    /// target, along with the kinds of references found in each.
+    pub external_references: Vec<(PathBuf, Vec<TargetReference>)>,
+}
+
+/// A kind of reference to a target outside of target files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetReference {
+    /// A config section named after the target, e.g. `[OldName Deployment]`,
+    /// or with a path that names it, e.g. `[/Script/OldName.OldNameSettings]`,
+    /// where only the component that is the name as a whole is renamed.
+    Section,
+    /// The target to package in config, e.g. `BuildTarget=OldName`.
+    BuildTarget,
+    /// A RunUAT argument in config or a script, e.g. `-Target=OldName`.
+    RunUat,
+}
+
+impl TargetReference {
+    const ALL: [TargetReference; 3] = [
+        TargetReference::Section,
+        TargetReference::BuildTarget,
+        TargetReference::RunUat,
+    ];
+
+    /// Get a regex matching this kind of reference to a target.
+    pub fn pattern(self, name: &str) -> String {
+        let name = regex::escape(name);
+        match self {
+            TargetReference::Section => {
+                format!(r"(?m)^\[(?P<before>[^\]\r\n]*?\b){name}(?P<after>\b[^\]\r\n]*)\]")
+            }
+            TargetReference::BuildTarget => {
+                format!(r"(?m)^(?P<key>[ \t]*BuildTarget[ \t]*=[ \t]*){name}\b")
+            }
+            TargetReference::RunUat => format!(r#"(?P<flag>(?i:-Target=))(?P<quote>"?){name}\b"#),
+        }
+    }
+
+    /// Get the replacement for [`TargetReference::pattern`] that refers to
+    /// the target by a new name.
+    pub fn replacement(self, new_name: &str) -> String {
+        match self {
+            TargetReference::Section => format!("[${{before}}{new_name}${{after}}]"),
+            TargetReference::BuildTarget => format!("${{key}}{new_name}"),
+            TargetReference::RunUat => format!("${{flag}}${{quote}}{new_name}"),
+        }
+    }
+
+    /// Whether this kind of reference can appear in files of a category.
+    fn applies_to(self, category: Category) -> bool {
+        match self {
+            TargetReference::RunUat => matches!(category, Category::Config | Category::Script),
+            _ => category == Category::Config,
+        }
+    }
 }
 
 /// Rename an Unreal Engine target interactively, soliciting input parameters
-/// from the user with validation and guided selection.
-pub fn rename_target_interactive() -> Result<(), String> {
-    let params = get_params_from_user()?;
-    rename_target(params)
+/// from the user with validation and guided selection. Returns the root of the
+/// project, with a report of the rename written to it, or `None` if the user
+/// cancelled the rename.
+pub fn rename_target_interactive() -> Result<Option<PathBuf>, RenomError> {
+    let params = get_params_from_user().map_err(RenomError::Validation)?;
+    let project_root = params.project_root.clone();
+    let Some(manifest) = rename(params, None)? else {
+        return Ok(None);
+    };
+    manifest::write_report(&project_root, &manifest)?;
+    Ok(Some(project_root))
+}
+
+/// Generate the changes needed to rename an Unreal Engine target without
+/// applying them.
+pub fn plan_rename_target(params: &Params) -> Result<Changeset, RenomError> {
+    validate_params(params)?;
+    let context = gather_context(params)?;
+    params.options.cancellation.check()?;
+    let changeset = merge_extra_ops(
+        filter_changes(
+            generate_changeset(&context),
+            &context.project_root,
+            &params.options,
+        )?,
+        params.options.extra_ops.as_deref(),
+        &context.project_root,
+    )?;
+    let changeset = resolve_conflicts(changeset, params.options.interactive)?;
+    Ok(Changeset::new(changeset))
 }
 
 /// Rename an Unreal Engine target.
-pub fn rename_target(params: Params) -> Result<(), String> {
+pub fn rename_target(params: Params) -> Result<(), RenomError> {
+    rename(params, None).map(|_| ())
+}
+
+/// Apply changes planned earlier with [`plan_rename_target`]. The params are
+/// validated again and the plan is only applied if it still matches the
+/// changes needed for the current state of the project.
+pub fn apply_planned_rename_target(params: Params, plan: &Changeset) -> Result<(), RenomError> {
+    rename(params, Some(plan)).map(|_| ())
+}
+
+fn rename(params: Params, plan: Option<&Changeset>) -> Result<Option<Manifest>, RenomError> {
     validate_params(&params)?;
     let context = gather_context(&params)?;
-    let changeset = generate_changeset(&context);
-    let backup_dir = create_backup_dir(&context.project_root)?;
-    let mut engine = Engine::new();
+    params.options.cancellation.check()?;
+    let changeset = merge_extra_ops(
+        filter_changes(
+            generate_changeset(&context),
+            &context.project_root,
+            &params.options,
+        )?,
+        params.options.extra_ops.as_deref(),
+        &context.project_root,
+    )?;
+    let changeset = resolve_conflicts(changeset, params.options.interactive)?;
+    if let Some(plan) = plan {
+        plan::check_is_current(plan, &changeset)?;
+    }
+    let redirects = added_redirects(&changeset);
+    let manifest = Manifest::new(
+        "target",
+        &context.target.name,
+        &context.new_name,
+        &changeset,
+    );
+    doctor::warn_about_unhandled_references(&context.project_root, &context.search_name, &[]);
+    check_execution(&changeset, &context.project_root, &params.options)?;
+    if params.options.interactive && !preview::review(&changeset, &context.project_root)? {
+        log::basic("Rename cancelled.");
+        return Ok(None);
+    }
+    let backup_root = params.options.backup_root(&context.project_root);
+    let backup_dir = prepare_backup_dir(&backup_root, &params.options)?;
+    let apply_options = params
+        .options
+        .apply_options_for(&changeset, &context.project_root)?;
+    let mut engine = params.options.engine(apply_options);
     if let Err(e) = engine.execute(changeset, backup_dir) {
-        log::error(&e);
         engine.revert()?;
         print_failure_message(&context);
-        return Ok(());
+        return Err(e);
     }
 
+    if params.options.ledger {
+        record_in_ledger(&context, redirects)?;
+    }
+
+    manifest::write_for_review(&context.project_root, &manifest, params.options.vcs)?;
+
     print_success_message(&context);
-    Ok(())
+    Ok(Some(manifest))
 }
 
-fn validate_params(params: &Params) -> Result<(), String> {
+fn validate_params(params: &Params) -> Result<(), RenomError> {
     validate_project_root_is_dir(&params.project_root)?;
     validate_project_root_contains_project_descriptor(&params.project_root)?;
     validate_project_root_contains_source_dir(&params.project_root)?;
@@ -70,50 +222,60 @@ fn validate_params(params: &Params) -> Result<(), String> {
     validate_new_name_is_concise(&params.new_name)?;
     validate_new_name_is_unique(&params.new_name, &targets)?;
     validate_new_name_is_valid_identifier(&params.new_name)?;
+    params.options.validate_from_name()?;
     Ok(())
 }
 
-fn validate_project_root_is_dir(project_root: &Path) -> Result<(), String> {
+fn validate_project_root_is_dir(project_root: &Path) -> Result<(), RenomError> {
     match project_root.is_dir() {
         true => Ok(()),
-        false => Err("project root must be a directory".into()),
+        false => Err(RenomError::Validation(
+            "project root must be a directory".into(),
+        )),
     }
 }
 
-fn validate_project_root_contains_project_descriptor(project_root: &Path) -> Result<(), String> {
-    match fs::read_dir(project_root)
-        .map_err(|e| e.to_string())?
+fn validate_project_root_contains_project_descriptor(
+    project_root: &Path,
+) -> Result<(), RenomError> {
+    match fs::read_dir(project_root)?
         .filter_map(Result::ok)
         .filter_map(|entry| entry.path().extension().map(OsStr::to_owned))
         .any(|ext| ext == "uproject")
     {
         true => Ok(()),
-        false => Err("project root must contain a project descriptor".into()),
+        false => Err(RenomError::Validation(
+            "project root must contain a project descriptor".into(),
+        )),
     }
 }
 
-fn validate_project_root_contains_source_dir(project_root: &Path) -> Result<(), String> {
+fn validate_project_root_contains_source_dir(project_root: &Path) -> Result<(), RenomError> {
     match project_root.join("Source").is_dir() {
         true => Ok(()),
-        false => Err("project root must contain a Source folder".into()),
+        false => Err(RenomError::Validation(
+            "project root must contain a Source folder".into(),
+        )),
     }
 }
 
-fn validate_target_exists(target: &str, targets: &[Target]) -> Result<(), String> {
+fn validate_target_exists(target: &str, targets: &[Target]) -> Result<(), RenomError> {
     match targets.iter().any(|other| other.name == target) {
         true => Ok(()),
-        false => Err("target must be part of project".into()),
+        false => Err(RenomError::Validation(
+            "target must be part of project".into(),
+        )),
     }
 }
 
-fn validate_new_name_is_not_empty(new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_not_empty(new_name: &str) -> Result<(), RenomError> {
     match !new_name.trim().is_empty() {
         true => Ok(()),
-        false => Err("new name must not be empty".into()),
+        false => Err(RenomError::Validation("new name must not be empty".into())),
     }
 }
 
-fn validate_new_name_is_concise(new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_concise(new_name: &str) -> Result<(), RenomError> {
     let new_name_max_len = 30;
     match new_name.len() <= new_name_max_len {
         true => Ok(()),
@@ -122,38 +284,39 @@ fn validate_new_name_is_concise(new_name: &str) -> Result<(), String> {
                 "new name must not be longer than {} characters",
                 new_name_max_len
             );
-            Err(error_message)
+            Err(RenomError::Validation(error_message))
         }
     }
 }
 
-fn validate_new_name_is_unique(new_name: &str, targets: &[Target]) -> Result<(), String> {
+fn validate_new_name_is_unique(new_name: &str, targets: &[Target]) -> Result<(), RenomError> {
     match targets.iter().all(|target| target.name != new_name) {
         true => Ok(()),
         false => {
             let error_message = "new name must not conflict with another target";
-            Err(error_message.into())
+            Err(RenomError::Validation(error_message.into()))
         }
     }
 }
 
-fn validate_new_name_is_valid_identifier(new_name: &str) -> Result<(), String> {
+fn validate_new_name_is_valid_identifier(new_name: &str) -> Result<(), RenomError> {
     let identifier_regex = Regex::new("^[_[[:alnum:]]]*$").expect("regex should be valid");
     match identifier_regex.is_match(new_name) {
         true => Ok(()),
         false => {
             let error_message =
                 "new name must be comprised of alphanumeric characters and underscores only";
-            Err(error_message.into())
+            Err(RenomError::Validation(error_message.into()))
         }
     }
 }
 
-fn detect_project_targets(project_root: &Path) -> Result<Vec<Target>, String> {
+/// Detect the targets of a project, sorted by name. Assumes that the Source
+/// folder exists. Returns an error in case of I/O issues.
+pub fn detect_project_targets(project_root: &Path) -> Result<Vec<Target>, RenomError> {
     let source_dir = project_root.join("Source");
     assert!(source_dir.is_dir());
-    Ok(fs::read_dir(&source_dir)
-        .map_err(|err| err.to_string())?
+    let mut targets = fs::read_dir(&source_dir)?
         .filter_map(Result::ok)
         .filter_map(|entry| {
             entry
@@ -167,30 +330,97 @@ fn detect_project_targets(project_root: &Path) -> Result<Vec<Target>, String> {
             name: target_name.clone(),
             path: source_dir.join(target_name).with_extension("Target.cs"),
         })
-        .collect())
+        .collect::<Vec<Target>>();
+    targets.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(targets)
 }
 
-fn gather_context(params: &Params) -> Result<Context, String> {
+fn gather_context(params: &Params) -> Result<Context, RenomError> {
     let project_root = params.project_root.clone();
     let project_targets = detect_project_targets(&project_root)?;
     let target = project_targets
         .iter()
         .find(|target| target.name == params.target)
-        .unwrap()
+        .ok_or_else(|| RenomError::Validation("target must be part of project".into()))?
         .clone();
+    let search_name = params
+        .options
+        .from_name
+        .clone()
+        .unwrap_or_else(|| target.name.clone());
+    let external_references =
+        find_external_references(&project_root, &search_name, &params.options)?;
 
     Ok(Context {
         project_root,
         project_targets,
         target,
+        search_name,
         new_name: params.new_name.clone(),
+        external_references,
     })
 }
 
-fn create_backup_dir(project_root: &Path) -> Result<PathBuf, String> {
-    let backup_dir = project_root.join(".renom/backup");
-    fs::create_dir_all(&backup_dir).map_err(|err| err.to_string())?;
-    Ok(backup_dir)
+/// Find config files and scripts that reference the target through config
+/// sections, packaging settings, or RunUAT arguments. Every kind of reference
+/// is looked for in a single pass over each file.
+fn find_external_references(
+    project_root: &Path,
+    target_name: &str,
+    options: &Options,
+) -> Result<Vec<(PathBuf, Vec<TargetReference>)>, RenomError> {
+    let matcher = Matcher::new(
+        std::iter::empty::<&str>(),
+        TargetReference::ALL
+            .iter()
+            .map(|kind| kind.pattern(target_name)),
+    )?
+    .with_required([target_name]);
+    let files = WalkDir::new(project_root)
+        .follow_links(options.follow_symlinks)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_none_or(|name| !unreal::is_ignored_dir(name))
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.path().to_owned())
+        .filter(|path| {
+            let category = Category::of(path);
+            TargetReference::ALL
+                .iter()
+                .any(|kind| kind.applies_to(category))
+        })
+        .collect::<Vec<PathBuf>>();
+    let matches = matcher.matches_in_files(&files, options.mmap);
+    Ok(files
+        .into_iter()
+        .zip(matches)
+        .filter_map(|(path, matches)| {
+            let category = Category::of(&path);
+            let kinds = TargetReference::ALL
+                .iter()
+                .enumerate()
+                .filter(|(index, kind)| kind.applies_to(category) && matches.contains(index))
+                .map(|(_, kind)| *kind)
+                .collect::<Vec<TargetReference>>();
+            match kinds.is_empty() {
+                true => None,
+                false => Some((path, kinds)),
+            }
+        })
+        .collect())
+}
+
+/// Record the rename of the target in the ledger of the project, along with the
+/// redirects it added.
+fn record_in_ledger(context: &Context, redirects: Vec<String>) -> Result<(), RenomError> {
+    let entry = ledger::Entry::new("target", &context.target.name, &context.new_name, redirects);
+    ledger::append(&context.project_root, &entry).map_err(RenomError::from)
 }
 
 fn print_success_message(context: &Context) {
@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    backup::{self, SnapshotState},
+    changes::ApplyOptions,
+    engine::Engine,
+    error::RenomError,
+    journal,
+    presentation::log,
+};
+
+use super::Options;
+
+/// Revert the most recent rename of a project that can still be reverted,
+/// replaying the inverse of its changes from the journal kept with its
+/// backups. Repeated restores revert earlier renames in turn. Returns the
+/// backup directory that was restored from.
+pub fn restore_last_backup(project_root: &Path, options: &Options) -> Result<PathBuf, RenomError> {
+    let backup_root = options.backup_root(project_root);
+    let backup_dir = backup::last_revertible_run(&backup_root)?.ok_or_else(|| {
+        RenomError::Validation(format!("no backup to restore in {}", backup_root.display()))
+    })?;
+    restore_run(backup_dir)
+}
+
+/// Revert the rename of a project that took a snapshot, see
+/// [`list_snapshots`](super::list_snapshots). Renames made after it are left
+/// applied, and files they changed since are left as is. Returns the backup
+/// directory that was restored from.
+pub fn restore_snapshot(
+    project_root: &Path,
+    id: &str,
+    options: &Options,
+) -> Result<PathBuf, RenomError> {
+    let backup_root = options.backup_root(project_root);
+    let backup_dir = backup::find_snapshot(&backup_root, id)?.ok_or_else(|| {
+        RenomError::Validation(format!(
+            "no snapshot {} to restore in {}",
+            id,
+            backup_root.display()
+        ))
+    })?;
+    let later = backup::snapshots(&backup_root)?
+        .into_iter()
+        .filter(|entry| entry.path > backup_dir && entry.state != SnapshotState::Restored)
+        .count();
+    if later > 0 {
+        log::warning(format!(
+            "{} later rename(s) are still applied; files they changed are left as is.",
+            later
+        ));
+    }
+    restore_run(backup_dir)
+}
+
+fn restore_run(backup_dir: PathBuf) -> Result<PathBuf, RenomError> {
+    match journal::read_changes(&journal::changes_path(&backup_dir)) {
+        Ok(changes) => {
+            log::basic(format!(
+                "Restoring {} change(s) from {}.",
+                changes.len(),
+                backup_dir.display()
+            ));
+            for change in &changes {
+                log::detail("restoring", change);
+            }
+        }
+        // Interrupted runs only have a journal
+        Err(_) => log::basic(format!(
+            "Restoring an incomplete rename from {}.",
+            backup_dir.display()
+        )),
+    }
+    let journal = Engine::revert_from_journal(journal::journal_path(&backup_dir))?;
+    // Restoring a project rename moves the backups back along with the project
+    let backup_dir = journal.parent().map_or(backup_dir, Path::to_owned);
+    log::success(format!("Restored the backup in {}.", backup_dir.display()));
+    Ok(backup_dir)
+}
+
+/// Complete the most recent rename of a project if it was interrupted, e.g.
+/// because the process was killed, by applying the changes it had left from
+/// its operation log. If one of them fails, the whole rename is reverted.
+/// Returns the backup directory of the rename.
+pub fn resume_interrupted_run(
+    project_root: &Path,
+    options: &Options,
+) -> Result<PathBuf, RenomError> {
+    let backup_root = options.backup_root(project_root);
+    let backup_dir = backup::interrupted_run(&backup_root)?.ok_or_else(|| {
+        RenomError::Validation(format!(
+            "no interrupted rename in {}",
+            backup_root.display()
+        ))
+    })?;
+    if !journal::log_path(&backup_dir).is_file() {
+        return Err(RenomError::Validation(format!(
+            "the rename in {} can only be restored, as it has no operation log",
+            backup_dir.display()
+        )));
+    }
+    log::basic(format!(
+        "Resuming an interrupted rename from {}.",
+        backup_dir.display()
+    ));
+    let mut engine = options.engine(ApplyOptions::default());
+    if let Err(err) = engine.resume(&backup_dir) {
+        engine.revert()?;
+        log::basic("Resuming failed; the rename was reverted.");
+        return Err(err);
+    }
+    log::success("Resumed and completed the interrupted rename.");
+    Ok(backup_dir)
+}
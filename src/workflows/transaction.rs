@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use crate::{
+    changes::Changeset,
+    error::RenomError,
+    presentation::{log, preview},
+    workflows::{
+        check_execution, combine_changesets, merge_extra_ops, plan_rename_module,
+        plan_rename_plugin, plan_rename_project, plan_rename_target, prepare_backup_dir,
+        rename_module, rename_plugin, rename_project, rename_target, BatchRename, Options,
+    },
+};
+
+/// Generate the changes needed to apply several renames of a project as one
+/// transaction without applying them, see [`rename_in_transaction`].
+pub fn plan_transaction(
+    project_root: &Path,
+    renames: &[BatchRename],
+    options: &Options,
+) -> Result<Changeset, RenomError> {
+    if renames.is_empty() {
+        return Err(RenomError::Validation(
+            "transaction must contain at least one rename".into(),
+        ));
+    }
+    // Extra ops are merged once for the whole transaction
+    let planning = Options {
+        extra_ops: None,
+        ..options.clone()
+    };
+    let mut changesets = vec![];
+    for (index, rename) in renames.iter().enumerate() {
+        let changeset = plan_rename(project_root, rename.clone(), &planning)
+            .map_err(|err| err.context(format!("rename {} ({})", index + 1, rename)))?;
+        changesets.push(changeset.into_changes());
+    }
+    let changeset = merge_extra_ops(
+        combine_changesets(changesets)?,
+        options.extra_ops.as_deref(),
+        project_root,
+    )?;
+    Ok(Changeset::new(changeset))
+}
+
+/// Apply several renames of a project as one transaction, e.g. the project
+/// along with its primary module and targets, rather than one after another.
+/// Each rename is planned against the current state of the project, and their
+/// changes are combined in order and applied by a single engine with a single
+/// backup, so that either all of them are applied or none are. The options
+/// apply to the whole transaction. Unlike single renames, a transaction is
+/// not recorded in the ledger or the rename manifest.
+pub fn rename_in_transaction(
+    project_root: &Path,
+    renames: &[BatchRename],
+    options: &Options,
+) -> Result<(), RenomError> {
+    let changeset = plan_transaction(project_root, renames, options)?.into_changes();
+    check_execution(&changeset, project_root, options)?;
+    if options.interactive && !preview::review(&changeset, project_root)? {
+        log::basic("Transaction cancelled.");
+        return Ok(());
+    }
+    let backup_root = options.backup_root(project_root);
+    let backup_dir = prepare_backup_dir(&backup_root, options)?;
+    let apply_options = options.apply_options_for(&changeset, project_root)?;
+    let mut engine = options.engine(apply_options);
+    if let Err(e) = engine.execute(changeset, backup_dir) {
+        engine.revert()?;
+        log::error("Failed to apply the transaction; none of its renames were applied.");
+        return Err(e);
+    }
+    log::success(format!(
+        "Successfully applied {} rename(s) as one transaction.",
+        renames.len()
+    ));
+    Ok(())
+}
+
+fn plan_rename(
+    project_root: &Path,
+    rename: BatchRename,
+    options: &Options,
+) -> Result<Changeset, RenomError> {
+    let project_root = project_root.to_owned();
+    let options = options.clone();
+    match rename {
+        BatchRename::Project { new_name } => plan_rename_project(&rename_project::Params {
+            project_root,
+            new_name,
+            options,
+        }),
+        BatchRename::Plugin {
+            name,
+            new_name,
+            plugin_path,
+        } => plan_rename_plugin(&rename_plugin::Params {
+            plugin_root: plugin_path.map(|path| project_root.join(path)),
+            project_root,
+            plugin: name,
+            new_name,
+            options,
+        }),
+        BatchRename::Module { name, new_name } => plan_rename_module(&rename_module::Params {
+            project_root,
+            module: name,
+            new_name,
+            options,
+        }),
+        BatchRename::Target { name, new_name } => plan_rename_target(&rename_target::Params {
+            project_root,
+            target: name,
+            new_name,
+            options,
+        }),
+    }
+}
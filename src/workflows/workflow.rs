@@ -9,7 +9,7 @@ pub enum Workflow {
 
 impl Display for Workflow {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &*self {
+        match self {
             Workflow::RenameProject => write!(f, "Rename a project"),
             Workflow::RenamePlugin => write!(f, "Rename a plugin"),
             Workflow::RenameTarget => write!(f, "Rename a target"),
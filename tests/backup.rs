@@ -0,0 +1,146 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use renom::{
+    backup::{self, BackupPolicy, SnapshotState},
+    changes::{Change, ReplaceInFile},
+    engine::Engine,
+    journal,
+};
+use ulid::Ulid;
+
+fn create_backup(project_root: &Path, age_days: u64, size: usize) -> std::path::PathBuf {
+    let created = SystemTime::now() - Duration::from_secs(age_days * 24 * 60 * 60);
+    let run_dir = backup::backup_root(project_root).join(Ulid::from_datetime(created).to_string());
+    fs::create_dir_all(&run_dir).unwrap();
+    fs::write(run_dir.join("backup"), vec![b'x'; size]).unwrap();
+    run_dir
+}
+
+#[test]
+fn backups_should_be_pruned_oldest_first() {
+    let project_root = Path::new("tests/temp/backup/backups_should_be_pruned_oldest_first");
+    if project_root.is_dir() {
+        fs::remove_dir_all(project_root).unwrap();
+    }
+    let newest = create_backup(project_root, 0, 1024);
+    let recent = create_backup(project_root, 1, 1024);
+    let old = create_backup(project_root, 10, 1024);
+    let interrupted = create_backup(project_root, 30, 1024);
+    fs::write(interrupted.join("journal.json"), "[]").unwrap();
+    let ancient = create_backup(project_root, 40, 1024);
+
+    let policy = BackupPolicy {
+        max_age_days: Some(7),
+        max_count: Some(3),
+        ..BackupPolicy::default()
+    };
+    let pruned = backup::prune(
+        &backup::backup_root(project_root),
+        &policy,
+        SystemTime::now(),
+    )
+    .unwrap();
+
+    assert_eq!(pruned.removed, vec![old.clone(), ancient.clone()]);
+    assert_eq!(pruned.freed, 2048);
+    assert!(newest.is_dir() && recent.is_dir() && interrupted.is_dir());
+    assert!(!old.exists() && !ancient.exists());
+}
+
+#[test]
+fn completed_runs_should_be_archived_and_restored_from_their_archive() {
+    let project_root = Path::new("tests/temp/backup/completed_runs_should_be_archived");
+    if project_root.is_dir() {
+        fs::remove_dir_all(project_root).unwrap();
+    }
+    fs::create_dir_all(project_root).unwrap();
+    let file = project_root.join("Old.h");
+    fs::write(&file, "class Old {};").unwrap();
+    let backup_root = backup::backup_root(project_root);
+    let run_dir = backup::create_run_dir(&backup_root).unwrap();
+    let changeset = vec![Change::ReplaceInFile(ReplaceInFile::new(
+        &file, "Old", "New",
+    ))];
+    Engine::new().execute(changeset, &run_dir).unwrap();
+
+    let archived = backup::archive_completed(&backup_root).unwrap();
+
+    assert_eq!(archived, vec![run_dir.clone()]);
+    let mut names = fs::read_dir(&run_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<String>>();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "backups.tar.gz",
+            "changes.json",
+            "journal.json",
+            "operations.jsonl"
+        ]
+    );
+
+    Engine::revert_from_journal(journal::journal_path(&run_dir)).unwrap();
+
+    assert_eq!(fs::read_to_string(&file).unwrap(), "class Old {};");
+    assert!(!backup::archive_path(&run_dir).exists());
+}
+
+#[test]
+fn snapshots_should_be_listed_with_their_command_and_state() {
+    let project_root = Path::new("tests/temp/backup/snapshots_should_be_listed");
+    if project_root.is_dir() {
+        fs::remove_dir_all(project_root).unwrap();
+    }
+    let backup_root = backup::backup_root(project_root);
+    let untagged = create_backup(project_root, 1, 16);
+    let restored = backup::create_snapshot(&backup_root, "renom rename-plugin").unwrap();
+    // Snapshots taken within the same millisecond are not ordered
+    std::thread::sleep(Duration::from_millis(2));
+    let completed = backup::create_snapshot(&backup_root, "renom rename-module").unwrap();
+    fs::write(journal::journal_path(&completed), "[]").unwrap();
+    fs::write(journal::changes_path(&completed), "[]").unwrap();
+
+    let snapshots = backup::snapshots(&backup_root).unwrap();
+
+    let listed = snapshots
+        .iter()
+        .map(|entry| {
+            (
+                entry.path.clone(),
+                entry.snapshot.command.clone(),
+                entry.state,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        listed,
+        vec![
+            (untagged, None, SnapshotState::Restored),
+            (
+                restored.clone(),
+                Some("renom rename-plugin".into()),
+                SnapshotState::Restored
+            ),
+            (
+                completed.clone(),
+                Some("renom rename-module".into()),
+                SnapshotState::Completed
+            ),
+        ]
+    );
+    let id = |path: &Path| path.file_name().unwrap().to_str().unwrap().to_owned();
+    assert_eq!(
+        backup::find_snapshot(&backup_root, &id(&completed)).unwrap(),
+        Some(completed.clone())
+    );
+    assert_eq!(
+        backup::find_snapshot(&backup_root, &id(&restored)).unwrap(),
+        None
+    );
+}
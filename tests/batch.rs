@@ -0,0 +1,82 @@
+use std::{fs, path::Path};
+
+use clap::Parser;
+use renom::{
+    cli::{Cli, Command},
+    workflows::{read_batch_file, BatchRename},
+};
+
+mod common;
+
+use common::stage;
+
+#[test]
+fn renames_of_batch_file_should_be_applied_in_order() {
+    let resource = Path::new("tests/resources/batch/studio");
+    let staging_dir =
+        Path::new("tests/temp/batch/renames_of_batch_file_should_be_applied_in_order");
+    stage(resource, staging_dir);
+    let manifest = staging_dir.join("renames.yaml");
+
+    let items = read_batch_file(&manifest).unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1].project_root, staging_dir.join("Demo"));
+    assert_eq!(
+        items[1].rename,
+        BatchRename::Project {
+            new_name: "Arcade".into()
+        }
+    );
+
+    let cli = Cli::try_parse_from([
+        "renom",
+        "batch",
+        "--manifest",
+        manifest.to_str().unwrap(),
+        "--no-prune",
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Command::Batch(command)) => command.run().unwrap(),
+        _ => panic!("expected batch command"),
+    }
+
+    // The module is renamed in the project as renamed by the rename before
+    let project_root = staging_dir.join("Arcade");
+    assert!(project_root.join("Arcade.uproject").is_file());
+    assert!(project_root
+        .join("Plugins/Scenery/Source/SceneryRuntime/SceneryRuntime.Build.cs")
+        .is_file());
+}
+
+#[test]
+fn renames_of_batch_file_should_be_applied_as_one_transaction() {
+    let resource = Path::new("tests/resources/batch/studio");
+    let staging_dir =
+        Path::new("tests/temp/batch/renames_of_batch_file_should_be_applied_as_one_transaction");
+    stage(resource, staging_dir);
+    let manifest = staging_dir.join("renames.yaml");
+
+    let cli = Cli::try_parse_from([
+        "renom",
+        "batch",
+        "--manifest",
+        manifest.to_str().unwrap(),
+        "--transaction",
+        "--no-prune",
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Command::Batch(command)) => command.run().unwrap(),
+        _ => panic!("expected batch command"),
+    }
+
+    let project_root = staging_dir.join("Arcade");
+    assert!(project_root.join("Arcade.uproject").is_file());
+    assert!(project_root
+        .join("Plugins/Scenery/Source/SceneryRuntime/SceneryRuntime.Build.cs")
+        .is_file());
+    // A single backup is taken for the whole transaction
+    let backups = fs::read_dir(project_root.join(".renom/backup")).unwrap();
+    assert_eq!(backups.count(), 1);
+}
@@ -0,0 +1,32 @@
+use std::path::Path;
+
+use renom::{
+    bench::{bench, Params},
+    workflows::{rename_project, Options},
+};
+
+mod common;
+
+use common::stage;
+
+#[test]
+fn projects_with_long_names_should_be_benchmarked() {
+    let resource = Path::new("tests/resources/project/project_files");
+    let staging_dir = Path::new("tests/temp/bench/projects_with_long_names");
+    stage(resource, staging_dir);
+    rename_project::rename_project(rename_project::Params {
+        project_root: staging_dir.join("Demo"),
+        new_name: "ArcadeShooterDeluxe".into(),
+        options: Options::default(),
+    })
+    .unwrap();
+    let project_root = staging_dir.join("ArcadeShooterDeluxe");
+
+    bench(Params {
+        project_root: project_root.clone(),
+        mmap: false,
+    })
+    .unwrap();
+
+    assert!(project_root.join("ArcadeShooterDeluxe.uproject").is_file());
+}
@@ -0,0 +1,29 @@
+use std::{fs, path::PathBuf};
+
+use renom::{
+    cancel::CancellationToken,
+    changes::{Change, RenameFile},
+    engine::Engine,
+};
+
+#[test]
+fn cancelled_engine_should_not_apply_changes() {
+    let staging_dir = PathBuf::from("tests/temp/cancel/cancelled_engine_should_not_apply_changes");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    let original = staging_dir.join("Old.txt");
+    fs::write(&original, "content").unwrap();
+
+    let token = CancellationToken::new();
+    let mut engine = Engine::new().with_cancellation(token.clone());
+    token.cancel();
+    let changeset = vec![Change::RenameFile(RenameFile::new(
+        &original,
+        staging_dir.join("New.txt"),
+    ))];
+
+    assert!(engine.execute(changeset, &staging_dir).is_err());
+    assert!(original.is_file());
+}
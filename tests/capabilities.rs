@@ -0,0 +1,44 @@
+use clap::CommandFactory;
+use renom::{build_info, capabilities, cli::Cli, plan};
+
+#[test]
+fn capabilities_should_list_visible_commands_and_change_kinds() {
+    let capabilities = capabilities::describe(Cli::command());
+
+    let names = capabilities
+        .commands
+        .iter()
+        .map(|command| command.name.as_str())
+        .collect::<Vec<&str>>();
+    assert!(names.contains(&"rename-module"));
+    assert!(!names.contains(&"bench"));
+    let plan = capabilities
+        .commands
+        .iter()
+        .find(|command| command.name == "plan")
+        .unwrap();
+    assert!(plan
+        .subcommands
+        .iter()
+        .any(|command| command.name == "rename-project"));
+    let output = capabilities.commands[0]
+        .options
+        .iter()
+        .find(|option| option.name == "output")
+        .unwrap();
+    assert_eq!(output.env.as_deref(), Some("RENOM_OUTPUT"));
+    assert!(output.values.contains(&"json".to_owned()));
+    assert!(capabilities
+        .change_kinds
+        .contains(&"rename_file".to_owned()));
+    assert_eq!(capabilities.plan_schema_version, plan::SCHEMA_VERSION);
+}
+
+#[test]
+fn long_version_should_identify_the_build() {
+    let version = build_info::LONG_VERSION;
+    assert!(version.starts_with(env!("CARGO_PKG_VERSION")));
+    for field in ["commit: ", "built: ", "target: ", "features: "] {
+        assert!(version.contains(field), "long version lacks {}", field);
+    }
+}
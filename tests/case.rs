@@ -0,0 +1,53 @@
+use std::{fs, path::PathBuf};
+
+use renom::{
+    changes::{Change, RenameFile},
+    engine::Engine,
+};
+
+fn stage(name: &str) -> PathBuf {
+    let staging_dir = PathBuf::from("tests/temp/case").join(name);
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(staging_dir.join("backup")).unwrap();
+    fs::create_dir_all(staging_dir.join("MyGame")).unwrap();
+    fs::write(staging_dir.join("MyGame/MyGame.uproject"), "{}").unwrap();
+    staging_dir
+}
+
+fn file_names(dir: &PathBuf) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect::<Vec<String>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn case_only_rename_should_be_applied_and_reverted() {
+    let staging_dir = stage("case_only_rename_should_be_applied_and_reverted");
+    let changeset = vec![Change::RenameFile(RenameFile::new(
+        staging_dir.join("MyGame"),
+        staging_dir.join("MYGAME"),
+    ))];
+
+    assert!(Engine::simulate(&changeset).is_empty());
+    let mut engine = Engine::new();
+    engine
+        .execute(changeset, staging_dir.join("backup"))
+        .unwrap();
+    assert_eq!(file_names(&staging_dir), ["MYGAME", "backup"]);
+    assert!(staging_dir.join("MYGAME/MyGame.uproject").is_file());
+
+    engine.revert().unwrap();
+    assert_eq!(file_names(&staging_dir), ["MyGame", "backup"]);
+}
+
+#[test]
+fn case_only_rename_should_be_detected() {
+    assert!(RenameFile::new("Source/MyGame", "Source/MYGAME").is_case_only());
+    assert!(!RenameFile::new("Source/MyGame", "Source/MyGame").is_case_only());
+    assert!(!RenameFile::new("Source/MyGame", "Source/Arcade").is_case_only());
+}
@@ -0,0 +1,100 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use renom::changes::{
+    simulate_revert, Category, Change, Changeset, ChangesetBuilder, RenameFile, ReplaceInFile,
+    SetIniEntry,
+};
+
+#[test]
+fn files_should_be_grouped_by_category() {
+    let changeset = Changeset::new(vec![
+        Change::SetIniEntry(SetIniEntry::new(
+            "Config/DefaultEngine.ini",
+            "URL",
+            "GameName",
+            "New",
+        )),
+        Change::ReplaceInFile(ReplaceInFile::new("Source/Old.Target.cs", "Old", "New")),
+        Change::RenameFile(RenameFile::new(
+            "Source/Old.Target.cs",
+            "Source/New.Target.cs",
+        )),
+        Change::RenameFile(RenameFile::new("Old.uproject", "New.uproject")),
+    ]);
+
+    let files = changeset.files_by_category();
+
+    assert_eq!(files.len(), 3);
+    assert_eq!(
+        files[&Category::Config],
+        vec![PathBuf::from("Config/DefaultEngine.ini")]
+    );
+    assert_eq!(
+        files[&Category::Build],
+        vec![PathBuf::from("Source/Old.Target.cs")]
+    );
+    assert_eq!(
+        files[&Category::Descriptor],
+        vec![PathBuf::from("Old.uproject")]
+    );
+}
+
+#[test]
+fn changes_to_renamed_paths_should_be_invalid() {
+    let changeset = ChangesetBuilder::new()
+        .rename_file("Source/Old", "Source/New")
+        .replace_in_file("Source/Old/Old.Build.cs", "Old", "New")
+        .build();
+
+    assert!(changeset.validate().is_err());
+}
+
+#[test]
+fn simulation_should_follow_earlier_renames() {
+    let root = Path::new("tests/temp/changeset/simulation_should_follow_earlier_renames");
+    if root.is_dir() {
+        fs::remove_dir_all(root).unwrap();
+    }
+    fs::create_dir_all(root.join("Source/Old")).unwrap();
+    fs::write(root.join("Source/Old/Old.Build.cs"), "class Old {}").unwrap();
+
+    let changeset = ChangesetBuilder::new()
+        .rename_file(root.join("Source/Old"), root.join("Source/New"))
+        .replace_in_file(root.join("Source/New/Old.Build.cs"), "Old", "New")
+        .rename_file(
+            root.join("Source/New/Old.Build.cs"),
+            root.join("Source/New/New.Build.cs"),
+        )
+        .replace_in_file(root.join("Source/Old/Old.Build.cs"), "Old", "New")
+        .build();
+
+    let issues = simulate_revert(changeset.changes());
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].index, 3);
+    assert!(root.join("Source/Old/Old.Build.cs").is_file());
+}
+
+#[test]
+fn changeset_should_round_trip_through_json() {
+    let changeset = ChangesetBuilder::new()
+        .replace_in_file("Source/Old/Old.Build.cs", "Old", "New")
+        .rename_file("Source/Old/Old.Build.cs", "Source/Old/New.Build.cs")
+        .set_ini_key("Config/DefaultEngine.ini", "URL", "GameName", "New")
+        .append_ini_entry(
+            "Config/DefaultEngine.ini",
+            "CoreRedirects",
+            "+ClassRedirects",
+            "X",
+        )
+        .build();
+
+    let json = changeset.to_json().unwrap();
+
+    assert!(json.trim_start().starts_with('['));
+    assert!(json.contains(r#""kind": "rename_file""#));
+    assert_eq!(Changeset::from_json(&json).unwrap(), changeset);
+}
@@ -0,0 +1,19 @@
+use std::{fs, path::Path};
+
+use walkdir::WalkDir;
+
+/// Copy a project from the test resources to a staging directory, replacing
+/// whatever a previous run left there.
+pub fn stage(resource: &Path, staging_dir: &Path) {
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(staging_dir).unwrap();
+    }
+    for entry in WalkDir::new(resource).into_iter().filter_map(Result::ok) {
+        let target = staging_dir.join(entry.path().strip_prefix(resource).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).unwrap();
+        } else {
+            fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use clap::CommandFactory;
+use renom::{
+    cli::Cli,
+    completions::{self, Shell},
+};
+
+#[test]
+fn completions_should_cover_subcommands_and_options() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::Powershell] {
+        let script = completions::generate(shell, Cli::command());
+        for word in [
+            "rename-project",
+            "rename-plugin",
+            "completions",
+            "plugin-path",
+        ] {
+            assert!(script.contains(word), "{:?} script lacks {}", shell, word);
+        }
+        assert!(!script.contains("bench"), "{:?} script offers bench", shell);
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use renom::{
+    changes::{Change, ConflictKind, RenameFile, ReplaceInFile},
+    engine::Engine,
+    workflows::{merge_extra_ops, resolve_conflicts},
+};
+
+#[test]
+fn duplicate_changes_should_be_merged() {
+    let rename = Change::RenameFile(RenameFile::new("Source/Old", "Source/New"));
+    let changeset = vec![rename.clone(), rename.clone()];
+
+    let resolved = resolve_conflicts(changeset, false).unwrap();
+
+    assert_eq!(resolved, vec![rename]);
+}
+
+#[test]
+fn conflicting_renames_should_fail_when_not_interactive() {
+    let changeset = vec![
+        Change::RenameFile(RenameFile::new("Source/Old", "Source/New")),
+        Change::RenameFile(RenameFile::new("Source/Old", "Source/Other")),
+    ];
+
+    let result = resolve_conflicts(changeset, false);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn extra_ops_should_only_be_blamed_for_conflicts_they_are_part_of() {
+    let project_root = Path::new("tests/temp/conflicts/extra_ops_blamed_for_own_conflicts");
+    if project_root.is_dir() {
+        fs::remove_dir_all(project_root).unwrap();
+    }
+    fs::create_dir_all(project_root).unwrap();
+    let extra_ops = project_root.join("ops.ndjson");
+    let generated = vec![
+        Change::RenameFile(RenameFile::new(
+            project_root.join("Source/Old"),
+            project_root.join("Source/New"),
+        )),
+        Change::RenameFile(RenameFile::new(
+            project_root.join("Source/Old"),
+            project_root.join("Source/Other"),
+        )),
+    ];
+
+    fs::write(
+        &extra_ops,
+        r#"{"kind":"rename_file","from":"Docs/Old.md","to":"Docs/New.md"}"#,
+    )
+    .unwrap();
+    let merged = merge_extra_ops(generated.clone(), Some(&extra_ops), project_root).unwrap();
+    assert_eq!(merged.len(), 3);
+    assert!(resolve_conflicts(merged, false).is_err());
+
+    fs::write(
+        &extra_ops,
+        r#"{"kind":"rename_file","from":"Docs/Old.md","to":"Source/New"}"#,
+    )
+    .unwrap();
+    let err = merge_extra_ops(generated, Some(&extra_ops), project_root).unwrap_err();
+    assert!(err.to_string().contains("ops.ndjson"));
+}
+
+#[test]
+fn simulation_should_report_changes_that_would_fail_on_disk() {
+    let staging_dir = PathBuf::from("tests/temp/conflicts/simulation_reports_changes");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    let locked = staging_dir.join("Locked.txt");
+    fs::write(staging_dir.join("Old.txt"), "Old").unwrap();
+    fs::write(staging_dir.join("Taken.txt"), "Taken").unwrap();
+    fs::write(&locked, "Old").unwrap();
+    let mut permissions = fs::metadata(&locked).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&locked, permissions).unwrap();
+
+    let changeset = vec![
+        Change::RenameFile(RenameFile::new(
+            staging_dir.join("Old.txt"),
+            staging_dir.join("New.txt"),
+        )),
+        Change::ReplaceInFile(ReplaceInFile::new(
+            staging_dir.join("New.txt"),
+            "Old",
+            "New",
+        )),
+        Change::ReplaceInFile(ReplaceInFile::new(
+            staging_dir.join("Old.txt"),
+            "Old",
+            "New",
+        )),
+        Change::RenameFile(RenameFile::new(
+            staging_dir.join("New.txt"),
+            staging_dir.join("Taken.txt"),
+        )),
+        Change::ReplaceInFile(ReplaceInFile::new(&locked, "Old", "New")),
+        Change::RenameFile(RenameFile::new(
+            staging_dir.join("Taken.txt"),
+            staging_dir.join("X".repeat(300)),
+        )),
+    ];
+    let conflicts = Engine::simulate(&changeset)
+        .into_iter()
+        .map(|conflict| (conflict.index, conflict.kind))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        conflicts,
+        vec![
+            (2, ConflictKind::SourceMissing),
+            (3, ConflictKind::DestinationExists),
+            (4, ConflictKind::NotWritable),
+            (5, ConflictKind::PathTooLong),
+        ]
+    );
+    assert!(staging_dir.join("Old.txt").is_file());
+    let mut permissions = fs::metadata(&locked).unwrap().permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(&locked, permissions).unwrap();
+}
@@ -0,0 +1,35 @@
+use std::{fs, panic, path::Path};
+
+use renom::crash;
+
+#[test]
+fn crash_report_should_be_written_next_to_backups() {
+    let project_root = Path::new("tests/temp/crash/crash_report_should_be_written");
+    if project_root.is_dir() {
+        fs::remove_dir_all(project_root).unwrap();
+    }
+    let backup_dir = project_root.join(".renom/backup/01J0000000000000000000000");
+    let journal = backup_dir.join("journal.json");
+    fs::create_dir_all(&backup_dir).unwrap();
+
+    crash::install_panic_hook();
+    crash::set_operation(Some("rename module Demo to Game".into()));
+    crash::set_journal(&backup_dir, &journal);
+    let result = panic::catch_unwind(|| panic!("something went wrong"));
+    // Let any later panic of the test fail it as usual
+    let _ = panic::take_hook();
+
+    assert!(result.is_err());
+    let reports = fs::read_dir(project_root.join(".renom/crash"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].extension().is_some_and(|ext| ext == "toml"));
+    let report = fs::read_to_string(&reports[0]).unwrap();
+    assert!(report.contains(r#"panic_message = "something went wrong""#));
+    assert!(report.contains(r#"operation = "rename module Demo to Game""#));
+    assert!(report.contains("journal.json"));
+    assert!(report.contains(&format!(r#"version = "{}""#, env!("CARGO_PKG_VERSION"))));
+    assert!(report.contains("panic_location = \"tests"));
+}
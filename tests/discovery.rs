@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+
+use renom::unreal::projects_in_uprojectdirs;
+
+#[test]
+fn projects_should_be_found_through_uprojectdirs() {
+    let solution_root = PathBuf::from("tests/resources/discovery/uprojectdirs");
+
+    let projects = projects_in_uprojectdirs(&solution_root);
+
+    assert_eq!(
+        projects,
+        vec![
+            solution_root.join("Game"),
+            solution_root.join("Samples/Demo"),
+        ]
+    );
+}
@@ -0,0 +1,90 @@
+use std::path::Path;
+
+use renom::doctor::{
+    check_default_maps, check_project, check_project_files, check_unhandled_references,
+};
+
+#[test]
+fn unhandled_references_should_skip_handled_file_types() {
+    let project_root = Path::new("tests/resources/doctor/unhandled_references");
+
+    let mut diagnostics = check_unhandled_references(project_root, "Demo")
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect::<Vec<String>>();
+    diagnostics.sort();
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            "1 reference(s) to Demo in a file type that is not updated (.ini.template)",
+            "2 reference(s) to Demo in a file type that is not updated (.json)",
+        ]
+    );
+}
+
+#[test]
+fn project_check_should_find_inconsistencies() {
+    let project_root = Path::new("tests/resources/doctor/inconsistent");
+
+    let diagnostics = check_project(project_root)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect::<Vec<String>>();
+
+    assert_eq!(diagnostics.len(), 8);
+    assert!(diagnostics[0].starts_with("declares module Missing"));
+    assert_eq!(
+        diagnostics[1],
+        "is named Prop, but its folder is named Props"
+    );
+    assert_eq!(
+        diagnostics[2],
+        "is named Tool, but its folder is named Tools"
+    );
+    assert_eq!(
+        diagnostics[3],
+        "declares class GameTarget, but the engine expects DemoTarget"
+    );
+    assert_eq!(
+        diagnostics[5],
+        "PackageRedirects redirects /Script/Old to both /Script/Game and /Script/Arcade"
+    );
+}
+
+#[test]
+fn project_file_check_should_only_check_included_files() {
+    let project_root = Path::new("tests/resources/doctor/inconsistent");
+    let included = project_root.join("Source/Tools/Tool.Build.cs");
+
+    let diagnostics = check_project_files(project_root, |path| path == included)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect::<Vec<String>>();
+
+    assert_eq!(
+        diagnostics,
+        vec!["is named Tool, but its folder is named Tools"]
+    );
+}
+
+#[test]
+fn default_map_check_should_find_maps_that_do_not_resolve() {
+    let project_root = Path::new("tests/resources/doctor/default_maps");
+
+    let diagnostics = check_default_maps(project_root)
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect::<Vec<String>>();
+
+    assert_eq!(diagnostics.len(), 2);
+    assert!(
+        diagnostics[0].starts_with("EditorStartupMap references /Game/Maps/Missing.Missing, but")
+    );
+    assert_eq!(
+        diagnostics[1],
+        "TransitionMap references /Gone/Maps/Loading.Loading under mount point /Gone/, which does not match the project or any project plugin"
+    );
+}
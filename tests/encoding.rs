@@ -0,0 +1,111 @@
+use std::{fs, path::PathBuf};
+
+use renom::{
+    changes::{Change, ReplaceInFile, SetIniEntry},
+    text::{Encoding, LineEnding, TextFile},
+};
+
+#[test]
+fn replace_should_preserve_bom_and_line_endings() {
+    let resources_dir = PathBuf::from("tests/resources");
+    let original_file = resources_dir.join("encoding/bom_crlf.h");
+    let temp_dir = PathBuf::from("tests/temp");
+    let staging_dir = temp_dir.join("encoding/replace_should_preserve_bom_and_line_endings");
+    let result_file = staging_dir.join("bom_crlf.h");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    fs::copy(&original_file, &result_file).unwrap();
+
+    let replace_in_file = ReplaceInFile::new(&result_file, "CODE_API", "GAME_API");
+    let replace_change = Change::ReplaceInFile(replace_in_file);
+    let _revert = replace_change.apply(&staging_dir).unwrap();
+
+    let actual = fs::read(&result_file).unwrap();
+    let expected = fs::read(&original_file)
+        .map(|bytes| String::from_utf8(bytes).unwrap())
+        .unwrap()
+        .replace("CODE_API", "GAME_API")
+        .into_bytes();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn replace_should_preserve_utf16_encoding() {
+    let resources_dir = PathBuf::from("tests/resources");
+    let original_config = resources_dir.join("encoding/utf16le_bom.ini");
+    let temp_dir = PathBuf::from("tests/temp");
+    let staging_dir = temp_dir.join("encoding/replace_should_preserve_utf16_encoding");
+    let result_config = staging_dir.join("utf16le_bom.ini");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    fs::copy(&original_config, &result_config).unwrap();
+
+    let replace_in_file = ReplaceInFile::new(&result_config, r"/Script/Code\.", "/Script/Game.");
+    let replace_change = Change::ReplaceInFile(replace_in_file);
+    let _revert = replace_change.apply(&staging_dir).unwrap();
+
+    let actual = TextFile::read(&result_config).unwrap();
+    assert_eq!(actual.encoding, Encoding::Utf16LeBom);
+    assert!(actual.content.contains("[/Script/Game.CodeGameModeBase]"));
+    assert!(fs::read(&result_config).unwrap().starts_with(&[0xFF, 0xFE]));
+}
+
+#[test]
+fn ini_set_should_preserve_utf16_encoding() {
+    let resources_dir = PathBuf::from("tests/resources");
+    let original_config = resources_dir.join("encoding/utf16le_bom.ini");
+    let temp_dir = PathBuf::from("tests/temp");
+    let staging_dir = temp_dir.join("encoding/ini_set_should_preserve_utf16_encoding");
+    let result_config = staging_dir.join("utf16le_bom.ini");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    fs::copy(&original_config, &result_config).unwrap();
+
+    let set_ini_entry = SetIniEntry::new(
+        &result_config,
+        "/Script/EngineSettings.GeneralProjectSettings",
+        "ProjectName",
+        "Game",
+    );
+    let set_change = Change::SetIniEntry(set_ini_entry);
+    let _revert = set_change.apply(&staging_dir).unwrap();
+
+    let actual = TextFile::read(&result_config).unwrap();
+    assert_eq!(actual.encoding, Encoding::Utf16LeBom);
+    assert!(actual.content.contains("ProjectName=Game"));
+}
+
+#[test]
+fn ini_set_should_preserve_bom_and_line_endings() {
+    let resources_dir = PathBuf::from("tests/resources");
+    let original_config = resources_dir.join("encoding/bom_crlf.ini");
+    let temp_dir = PathBuf::from("tests/temp");
+    let staging_dir = temp_dir.join("encoding/ini_set_should_preserve_bom_and_line_endings");
+    let result_config = staging_dir.join("bom_crlf.ini");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    fs::copy(&original_config, &result_config).unwrap();
+
+    let set_ini_entry = SetIniEntry::new(
+        &result_config,
+        "/Script/EngineSettings.GeneralProjectSettings",
+        "ProjectName",
+        "Game",
+    );
+    let set_change = Change::SetIniEntry(set_ini_entry);
+    let _revert = set_change.apply(&staging_dir).unwrap();
+
+    let actual = TextFile::read(&result_config).unwrap();
+    assert_eq!(actual.encoding, Encoding::Utf8Bom);
+    assert_eq!(actual.line_ending, LineEnding::CrLf);
+    assert!(actual.content.contains("ProjectName=Game\r\n"));
+    assert!(!actual.content.replace("\r\n", "").contains('\n'));
+}
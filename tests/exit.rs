@@ -0,0 +1,36 @@
+use std::{fs, path::PathBuf};
+
+use clap::Parser;
+use renom::{
+    changes::{Change, RenameFile},
+    cli::{Cli, Command},
+    engine::Engine,
+    exit::ExitCode,
+};
+
+#[test]
+fn missing_arguments_should_exit_with_argument_code() {
+    let cli = Cli::try_parse_from(["renom", "rename-project", "--no-prune"]).unwrap();
+    let err = match cli.command {
+        Some(Command::RenameProject(command)) => command.run().unwrap_err(),
+        _ => panic!("expected rename-project command"),
+    };
+    assert_eq!(err.exit_code(), ExitCode::Argument);
+}
+
+#[test]
+fn failed_execution_should_exit_with_execution_code() {
+    let staging_dir = PathBuf::from("tests/temp/exit/failed_execution");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    let changeset = vec![Change::RenameFile(RenameFile::new(
+        staging_dir.join("Missing.txt"),
+        staging_dir.join("Renamed.txt"),
+    ))];
+    let mut engine = Engine::new();
+    let err = engine.execute(changeset, &staging_dir).unwrap_err();
+    engine.revert().unwrap();
+    assert_eq!(err.exit_code(), ExitCode::Execution);
+}
@@ -0,0 +1,393 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use renom::{
+    backup,
+    changes::{is_line_bounded, ApplyOptions, Change, RenameFile, ReplaceInFile, RevertAction},
+    engine::Engine,
+    error::RenomError,
+    fs::{Fs, MemoryFs},
+};
+
+fn project() -> MemoryFs {
+    let fs = MemoryFs::default();
+    fs.create_dir(Path::new("Project/Source")).unwrap();
+    fs.create_dir(Path::new("Project/.renom/backup")).unwrap();
+    fs.write(Path::new("Project/Source/Old.h"), b"class Old {};")
+        .unwrap();
+    fs
+}
+
+#[test]
+fn engine_should_apply_changes_in_memory() {
+    let mut engine = Engine::with_fs(project(), ApplyOptions::default());
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new("Project/Source/Old.h", "Old", "New")),
+        Change::RenameFile(RenameFile::new("Project/Source", "Project/Code")),
+    ];
+
+    engine.execute(changeset, "Project/.renom/backup").unwrap();
+
+    assert_eq!(
+        engine.fs().read(Path::new("Project/Code/Old.h")).unwrap(),
+        b"class New {};"
+    );
+    assert!(engine.fs().metadata(Path::new("Project/Source")).is_err());
+    assert!(!Path::new("Project").exists());
+}
+
+#[test]
+fn engine_should_revert_changes_in_memory() {
+    let mut engine = Engine::with_fs(project(), ApplyOptions::default());
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new("Project/Source/Old.h", "Old", "New")),
+        Change::RenameFile(RenameFile::new("Project/Source", "Project/Code")),
+    ];
+    engine.execute(changeset, "Project/.renom/backup").unwrap();
+
+    engine.revert().unwrap();
+
+    assert_eq!(
+        engine.fs().read(Path::new("Project/Source/Old.h")).unwrap(),
+        b"class Old {};"
+    );
+    assert!(engine.fs().metadata(Path::new("Project/Code")).is_err());
+}
+
+#[test]
+fn edits_in_memory_should_keep_permissions() {
+    let fs = project();
+    let header = Path::new("Project/Source/Old.h");
+    let mut metadata = fs.metadata(header).unwrap();
+    metadata.mode = Some(0o755);
+    fs.set_permissions(header, &metadata).unwrap();
+    let mut engine = Engine::with_fs(fs, ApplyOptions::default());
+    let changeset = vec![Change::ReplaceInFile(ReplaceInFile::new(
+        header, "Old", "New",
+    ))];
+
+    engine.execute(changeset, "Project/.renom/backup").unwrap();
+
+    assert_eq!(engine.fs().metadata(header).unwrap().mode, Some(0o755));
+}
+
+fn make_read_only(fs: &MemoryFs, path: &Path) {
+    let mut metadata = fs.metadata(path).unwrap();
+    metadata.readonly = true;
+    fs.set_permissions(path, &metadata).unwrap();
+}
+
+#[test]
+fn read_only_files_should_not_be_edited() {
+    let fs = project();
+    let header = Path::new("Project/Source/Old.h");
+    make_read_only(&fs, header);
+    let mut engine = Engine::with_fs(fs, ApplyOptions::default());
+    let changeset = vec![Change::ReplaceInFile(ReplaceInFile::new(
+        header, "Old", "New",
+    ))];
+
+    assert!(engine.execute(changeset, "Project/.renom/backup").is_err());
+    assert_eq!(engine.fs().read(header).unwrap(), b"class Old {};");
+}
+
+#[test]
+fn read_only_files_should_be_edited_and_reverted_when_made_writable() {
+    let fs = project();
+    let header = Path::new("Project/Source/Old.h");
+    make_read_only(&fs, header);
+    let options = ApplyOptions {
+        make_writable: true,
+        ..ApplyOptions::default()
+    };
+    let mut engine = Engine::with_fs(fs, options);
+    let changeset = vec![Change::ReplaceInFile(ReplaceInFile::new(
+        header, "Old", "New",
+    ))];
+
+    engine.execute(changeset, "Project/.renom/backup").unwrap();
+    assert_eq!(engine.fs().read(header).unwrap(), b"class New {};");
+    assert!(engine.fs().metadata(header).unwrap().readonly);
+
+    engine.revert().unwrap();
+    assert_eq!(engine.fs().read(header).unwrap(), b"class Old {};");
+    assert!(engine.fs().metadata(header).unwrap().readonly);
+}
+
+#[test]
+fn locked_files_should_abort_execution_before_any_change() {
+    let fs = project();
+    fs.lock("Project/Source/Old.h");
+    let mut engine = Engine::with_fs(fs, ApplyOptions::default());
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new("Project/Source/Old.h", "Old", "New")),
+        Change::RenameFile(RenameFile::new("Project/Source", "Project/Code")),
+    ];
+
+    let err = engine
+        .execute(changeset, "Project/.renom/backup")
+        .unwrap_err();
+
+    assert!(err.to_string().contains("Old.h"));
+    assert_eq!(
+        engine.fs().read(Path::new("Project/Source/Old.h")).unwrap(),
+        b"class Old {};"
+    );
+}
+
+#[test]
+fn files_past_max_path_should_be_edited_and_renamed_on_disk() {
+    let staging_dir = PathBuf::from("tests/temp/fs/files_past_max_path");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    let source =
+        staging_dir.join("Plugins/Props/Source/PropsRuntime/Private/Components/Interaction");
+    let source = (0..8).fold(source, |dir, _| dir.join("NestedForLongPaths"));
+    let header = source.join("PropsRuntimeInteractionComponent.h");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(&header, "class UPropsRuntimeInteractionComponent {};").unwrap();
+    fs::create_dir_all(staging_dir.join("backup")).unwrap();
+    assert!(fs::canonicalize(&header).unwrap().as_os_str().len() > 260);
+    let mut engine = Engine::new();
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new(&header, "Props", "Scenery")),
+        Change::RenameFile(RenameFile::new(
+            staging_dir.join("Plugins/Props"),
+            staging_dir.join("Plugins/Scenery"),
+        )),
+    ];
+
+    engine
+        .execute(changeset, staging_dir.join("backup"))
+        .unwrap();
+
+    let moved = staging_dir.join("Plugins/Scenery").join(
+        header
+            .strip_prefix(staging_dir.join("Plugins/Props"))
+            .unwrap(),
+    );
+    assert_eq!(
+        fs::read_to_string(moved).unwrap(),
+        "class USceneryRuntimeInteractionComponent {};"
+    );
+}
+
+#[test]
+fn changes_after_rollback_point_should_be_reverted_alone() {
+    let mut engine = Engine::with_fs(project(), ApplyOptions::default());
+    let renames = vec![Change::RenameFile(RenameFile::new(
+        "Project/Source",
+        "Project/Code",
+    ))];
+    engine.execute(renames, "Project/.renom/backup").unwrap();
+    let point = engine.rollback_point("edits");
+    let edits = vec![
+        Change::ReplaceInFile(ReplaceInFile::new("Project/Code/Old.h", "Old", "New")),
+        Change::ReplaceInFile(ReplaceInFile::new("Project/Code/Missing.h", "Old", "New")),
+    ];
+    assert!(engine.execute(edits, "Project/.renom/backup").is_err());
+
+    engine.revert_to(&point).unwrap();
+
+    assert_eq!(
+        engine.fs().read(Path::new("Project/Code/Old.h")).unwrap(),
+        b"class Old {};"
+    );
+    assert!(engine.fs().metadata(Path::new("Project/Source")).is_err());
+    assert_eq!(engine.rollback_points(), [point]);
+}
+
+#[test]
+fn hooks_should_run_around_each_change() {
+    let mut engine = Engine::with_fs(project(), ApplyOptions::default());
+    let calls = Arc::new(Mutex::new(vec![]));
+    let before = calls.clone();
+    let after = calls.clone();
+    engine
+        .on_before_op(move |change| {
+            before
+                .lock()
+                .unwrap()
+                .push(format!("before {}", change.path().display()));
+            Ok(())
+        })
+        .on_after_op(move |change| {
+            after
+                .lock()
+                .unwrap()
+                .push(format!("after {}", change.path().display()));
+            Ok(())
+        });
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new("Project/Source/Old.h", "Old", "New")),
+        Change::RenameFile(RenameFile::new("Project/Source", "Project/Code")),
+    ];
+
+    engine.execute(changeset, "Project/.renom/backup").unwrap();
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        [
+            "before Project/Source/Old.h",
+            "after Project/Source/Old.h",
+            "before Project/Source",
+            "after Project/Source",
+        ]
+    );
+}
+
+#[test]
+fn failing_before_hook_should_stop_execution_before_its_change() {
+    let mut engine = Engine::with_fs(project(), ApplyOptions::default());
+    engine.on_before_op(|change| match change {
+        Change::RenameFile(_) => Err(RenomError::Validation(
+            "Project/Source is not checked out".into(),
+        )),
+        _ => Ok(()),
+    });
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new("Project/Source/Old.h", "Old", "New")),
+        Change::RenameFile(RenameFile::new("Project/Source", "Project/Code")),
+    ];
+
+    let err = engine
+        .execute(changeset, "Project/.renom/backup")
+        .unwrap_err();
+
+    assert!(err.to_string().contains("not checked out"));
+    assert!(engine.fs().metadata(Path::new("Project/Source")).is_ok());
+    assert!(engine.fs().metadata(Path::new("Project/Code")).is_err());
+}
+
+#[test]
+fn changes_applied_by_earlier_run_should_be_skipped_when_idempotent() {
+    let fs = project();
+    fs.write(Path::new("Project/Source/Old.h"), b"class New {};")
+        .unwrap();
+    fs.rename(Path::new("Project/Source"), Path::new("Project/Code"))
+        .unwrap();
+    let edit = Change::ReplaceInFile(ReplaceInFile::new("Project/Source/Old.h", "Old", "New"));
+    let rename = Change::RenameFile(RenameFile::new("Project/Source", "Project/Code"));
+    let options = ApplyOptions {
+        idempotent: true,
+        applied_earlier: vec![
+            (
+                edit.clone(),
+                RevertAction::Rewrite {
+                    content: b"class Old {};".to_vec(),
+                    target: PathBuf::from("Project/Source/Old.h"),
+                    checksum: None,
+                },
+            ),
+            (
+                rename.clone(),
+                RevertAction::Rename {
+                    from: PathBuf::from("Project/Source"),
+                    to: PathBuf::from("Project/Code"),
+                },
+            ),
+        ],
+        ..ApplyOptions::default()
+    };
+    let mut engine = Engine::with_fs(fs, options);
+    let changeset = vec![
+        edit,
+        rename,
+        Change::ReplaceInFile(ReplaceInFile::new("Project/Code/Old.h", "class", "struct")),
+    ];
+
+    engine.execute(changeset, "Project/.renom/backup").unwrap();
+
+    assert_eq!(
+        engine.fs().read(Path::new("Project/Code/Old.h")).unwrap(),
+        b"struct New {};"
+    );
+    engine.revert().unwrap();
+    assert_eq!(
+        engine.fs().read(Path::new("Project/Code/Old.h")).unwrap(),
+        b"class New {};"
+    );
+}
+
+#[test]
+fn edits_recorded_by_earlier_run_should_not_be_applied_twice_when_idempotent() {
+    let staging_dir = PathBuf::from("tests/temp/fs/edits_recorded_by_earlier_run");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    let backup_root = staging_dir.join("backup");
+    fs::create_dir_all(&backup_root).unwrap();
+    let source = staging_dir.join("Demo.Target.cs");
+    fs::write(&source, "public class DemoTarget : TargetRules").unwrap();
+    // The new name contains the old one, so the pattern still matches once
+    // the edit is applied
+    let edit = Change::ReplaceInFile(ReplaceInFile::new(&source, "DemoTarget", "XDemoTarget"));
+    Engine::new()
+        .execute(
+            vec![edit.clone()],
+            backup::create_run_dir(&backup_root).unwrap(),
+        )
+        .unwrap();
+    let mut engine = Engine::with_options(ApplyOptions {
+        idempotent: true,
+        applied_earlier: backup::applied_changes(&backup_root).unwrap(),
+        ..ApplyOptions::default()
+    });
+
+    engine
+        .execute(vec![edit], backup::create_run_dir(&backup_root).unwrap())
+        .unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&source).unwrap(),
+        "public class XDemoTarget : TargetRules"
+    );
+}
+
+#[test]
+fn files_past_stream_threshold_should_be_edited_a_line_at_a_time() {
+    let staging_dir = PathBuf::from("tests/temp/fs/files_past_stream_threshold");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(staging_dir.join("backup")).unwrap();
+    let log = staging_dir.join("Build.log");
+    let content = "\u{feff}Building Old\r\nLinking OldEditor.dll\r\nDone\n";
+    fs::write(&log, content).unwrap();
+    let mut engine = Engine::with_options(ApplyOptions {
+        stream_threshold: Some(16),
+        ..ApplyOptions::default()
+    });
+    let changeset = vec![Change::ReplaceInFile(ReplaceInFile::new(
+        &log,
+        r"\bOld(Editor)?\b",
+        "New$1",
+    ))];
+
+    engine
+        .execute(changeset, staging_dir.join("backup"))
+        .unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        "\u{feff}Building New\r\nLinking NewEditor.dll\r\nDone\n"
+    );
+    engine.revert().unwrap();
+    assert_eq!(fs::read_to_string(&log).unwrap(), content);
+}
+
+#[test]
+fn patterns_that_may_match_across_lines_should_not_be_line_bounded() {
+    assert!(is_line_bounded(r"(?m)^Old\b"));
+    assert!(is_line_bounded(r#""[^"\n]*Old""#));
+    assert!(is_line_bounded(r"(?m)(Old|New)Name$"));
+    assert!(!is_line_bounded(r"Old\s*\("));
+    assert!(!is_line_bounded(r#""[^"]*Old""#));
+    assert!(!is_line_bounded(r"^Old"));
+    assert!(!is_line_bounded(r"x*"));
+    assert!(!is_line_bounded(r"Old\z"));
+}
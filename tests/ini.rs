@@ -2,9 +2,13 @@ use std::{
     fs::{self, File},
     io::{BufRead, BufReader},
     path::PathBuf,
+    slice,
 };
 
-use renom::changes::{AppendIniEntry, Change, SetIniEntry};
+use renom::{
+    changes::{AppendIniEntry, Change, SetIniEntry},
+    workflows::added_redirects,
+};
 
 #[test]
 fn ini_append_should_not_strip_quotes() {
@@ -57,3 +61,29 @@ fn ini_set_should_not_strip_quotes() {
     let expected = r#"key="value""#;
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn ini_append_should_not_duplicate_entries() {
+    let staging_dir = PathBuf::from("tests/temp/ini/append_should_not_duplicate_entries");
+    let config = staging_dir.join("DefaultEngine.ini");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    fs::write(&config, "[URL]\nGameName=Demo\n").unwrap();
+
+    let redirect = Change::AppendIniEntry(AppendIniEntry::new(
+        &config,
+        "CoreRedirects",
+        "+PackageRedirects",
+        r#"(OldName="/Old/",NewName="/New/",MatchSubstring=true)"#,
+    ));
+    assert_eq!(added_redirects(slice::from_ref(&redirect)).len(), 1);
+    let _revert = redirect.apply(&staging_dir).unwrap();
+    let once = fs::read_to_string(&config).unwrap();
+    assert_eq!(added_redirects(slice::from_ref(&redirect)).len(), 0);
+    let _revert = redirect.apply(&staging_dir).unwrap();
+
+    assert_eq!(fs::read_to_string(&config).unwrap(), once);
+    assert_eq!(once.matches("+PackageRedirects").count(), 1);
+}
@@ -0,0 +1,310 @@
+use std::{fs, path::PathBuf};
+
+use renom::{
+    changes::{ApplyOptions, Change, RenameFile, ReplaceInFile, RevertAction},
+    engine::Engine,
+    error::RenomError,
+    journal,
+};
+
+#[test]
+fn journal_should_revert_changes_without_engine() {
+    let staging_dir = PathBuf::from("tests/temp/journal/revert_changes_without_engine");
+    let backup_dir = staging_dir.join("backup");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&backup_dir).unwrap();
+    let original = staging_dir.join("Old.txt");
+    let renamed = staging_dir.join("New.txt");
+    fs::write(&original, "Old content").unwrap();
+
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new(&original, "Old", "New")),
+        Change::RenameFile(RenameFile::new(&original, &renamed)),
+    ];
+    Engine::new().execute(changeset, &backup_dir).unwrap();
+    assert!(renamed.is_file());
+
+    let journal = journal::journal_path(&backup_dir);
+    Engine::revert_from_journal(&journal).unwrap();
+
+    assert_eq!(fs::read_to_string(&original).unwrap(), "Old content");
+    assert!(!renamed.exists());
+    assert!(!journal.exists());
+}
+
+#[test]
+fn journal_should_follow_rename_of_its_directory() {
+    let staging_dir = PathBuf::from("tests/temp/journal/follow_rename_of_its_directory");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    let original = staging_dir.join("Old");
+    let renamed = staging_dir.join("New");
+    let backup_dir = original.join(".renom/backup");
+    fs::create_dir_all(&backup_dir).unwrap();
+
+    let changeset = vec![Change::RenameFile(RenameFile::new(&original, &renamed))];
+    let mut engine = Engine::new();
+    engine.execute(changeset, &backup_dir).unwrap();
+    assert!(journal::journal_path(&renamed.join(".renom/backup")).is_file());
+
+    engine.revert().unwrap();
+    assert!(original.is_dir());
+    assert!(!renamed.exists());
+    assert!(!journal::journal_path(&backup_dir).exists());
+}
+
+#[test]
+fn journal_should_be_complete_when_chunked_execution_fails() {
+    let staging_dir = PathBuf::from("tests/temp/journal/complete_when_chunked_execution_fails");
+    let backup_dir = staging_dir.join("backup");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&backup_dir).unwrap();
+    let files = ["A.txt", "B.txt", "C.txt"].map(|name| staging_dir.join(name));
+    for file in &files {
+        fs::write(file, "Old content").unwrap();
+    }
+
+    let mut changeset = files
+        .iter()
+        .map(|file| Change::ReplaceInFile(ReplaceInFile::new(file, "Old", "New")))
+        .collect::<Vec<Change>>();
+    changeset.push(Change::ReplaceInFile(ReplaceInFile::new(
+        staging_dir.join("Missing.txt"),
+        "Old",
+        "New",
+    )));
+    let result = Engine::new()
+        .with_chunk_size(2)
+        .execute(changeset, &backup_dir);
+    assert!(result.is_err());
+
+    let journal = journal::journal_path(&backup_dir);
+    assert_eq!(journal::read(&journal).unwrap().len(), 3);
+    Engine::revert_from_journal(&journal).unwrap();
+    for file in &files {
+        assert_eq!(fs::read_to_string(file).unwrap(), "Old content");
+    }
+}
+
+#[test]
+fn journal_should_be_written_when_execution_is_stepped() {
+    fn assert_send<T: Send>(_: &T) {}
+
+    let staging_dir = PathBuf::from("tests/temp/journal/written_when_execution_is_stepped");
+    let backup_dir = staging_dir.join("backup");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&backup_dir).unwrap();
+    let original = staging_dir.join("Old.txt");
+    let renamed = staging_dir.join("New.txt");
+    fs::write(&original, "Old content").unwrap();
+
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new(&original, "Old", "New")),
+        Change::RenameFile(RenameFile::new(&original, &renamed)),
+    ];
+    let mut engine = Engine::new();
+    let mut execution = engine.start(changeset, &backup_dir);
+    assert_send(&execution);
+    assert_eq!(execution.remaining(), 2);
+    assert_eq!(execution.step(1).unwrap(), 1);
+    assert_eq!(fs::read_to_string(&original).unwrap(), "New content");
+    assert_eq!(execution.step(1).unwrap(), 0);
+    assert!(renamed.is_file());
+
+    let journal = journal::journal_path(&backup_dir);
+    assert!(journal.is_file());
+    engine.revert().unwrap();
+    assert_eq!(fs::read_to_string(&original).unwrap(), "Old content");
+    assert!(!journal.exists());
+}
+
+#[test]
+fn revert_should_skip_files_edited_since_they_were_changed() {
+    let staging_dir = PathBuf::from("tests/temp/journal/skip_files_edited_since_they_were_changed");
+    let backup_dir = staging_dir.join("backup");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&backup_dir).unwrap();
+    let edited = staging_dir.join("Edited.txt");
+    let untouched = staging_dir.join("Untouched.txt");
+    fs::write(&edited, "Old content").unwrap();
+    fs::write(&untouched, "Old content").unwrap();
+
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new(&edited, "Old", "New")),
+        Change::ReplaceInFile(ReplaceInFile::new(&untouched, "Old", "New")),
+    ];
+    let mut engine = Engine::new();
+    engine.execute(changeset, &backup_dir).unwrap();
+    fs::write(&edited, "Fixed content").unwrap();
+    engine.revert().unwrap();
+
+    assert_eq!(fs::read_to_string(&edited).unwrap(), "Fixed content");
+    assert_eq!(fs::read_to_string(&untouched).unwrap(), "Old content");
+    assert_eq!(engine.skipped_reverts().len(), 1);
+    assert_eq!(fs::read_dir(&backup_dir).unwrap().count(), 1);
+}
+
+#[test]
+fn changes_should_be_reverted_in_process_without_backups() {
+    let staging_dir = PathBuf::from("tests/temp/journal/reverted_in_process_without_backups");
+    let backup_dir = staging_dir.join("backup");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    let original = staging_dir.join("Old.txt");
+    let renamed = staging_dir.join("New.txt");
+    fs::write(&original, "Old content").unwrap();
+
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new(&original, "Old", "New")),
+        Change::RenameFile(RenameFile::new(&original, &renamed)),
+        Change::ReplaceInFile(ReplaceInFile::new(
+            staging_dir.join("Missing.txt"),
+            "Old",
+            "New",
+        )),
+    ];
+    let mut engine = Engine::with_options(ApplyOptions {
+        no_backup: true,
+        ..ApplyOptions::default()
+    });
+    assert!(engine.execute(changeset, &backup_dir).is_err());
+    assert!(!backup_dir.exists());
+    engine.revert().unwrap();
+
+    assert_eq!(fs::read_to_string(&original).unwrap(), "Old content");
+    assert!(!renamed.exists());
+}
+
+#[test]
+fn interrupted_execution_should_be_resumed_from_operation_log() {
+    let staging_dir = PathBuf::from("tests/temp/journal/resumed_from_operation_log");
+    let backup_dir = staging_dir.join("backup");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&backup_dir).unwrap();
+    let original = staging_dir.join("Old.txt");
+    let renamed = staging_dir.join("New.txt");
+    let other = staging_dir.join("Other.txt");
+    fs::write(&original, "Old content").unwrap();
+    fs::write(&other, "Old reference").unwrap();
+
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new(&original, "Old", "New")),
+        Change::RenameFile(RenameFile::new(&original, &renamed)),
+        Change::ReplaceInFile(ReplaceInFile::new(&other, "Old", "New")),
+    ];
+    // Kill the process right after the rename is applied, before it is logged
+    let mut engine = Engine::new();
+    engine.start(changeset, &backup_dir).step(1).unwrap();
+    let log = journal::log_path(&backup_dir);
+    let before = journal::Entry::Before {
+        index: 1,
+        checksum: None,
+    };
+    journal::append(&log, &before).unwrap();
+    fs::rename(&original, &renamed).unwrap();
+
+    Engine::new().resume(&backup_dir).unwrap();
+    assert_eq!(fs::read_to_string(&renamed).unwrap(), "New content");
+    assert_eq!(fs::read_to_string(&other).unwrap(), "New reference");
+    assert_eq!(
+        journal::read_changes(&journal::changes_path(&backup_dir))
+            .unwrap()
+            .len(),
+        3
+    );
+
+    Engine::revert_from_journal(journal::journal_path(&backup_dir)).unwrap();
+    assert_eq!(fs::read_to_string(&original).unwrap(), "Old content");
+    assert_eq!(fs::read_to_string(&other).unwrap(), "Old reference");
+    assert!(!log.exists());
+}
+
+#[test]
+fn edits_applied_at_once_should_be_reverted_like_others() {
+    let staging_dir = PathBuf::from("tests/temp/journal/edits_applied_at_once");
+    let backup_dir = staging_dir.join("backup");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&backup_dir).unwrap();
+    let files = (0..8)
+        .map(|number| staging_dir.join(format!("Old{}.txt", number)))
+        .collect::<Vec<PathBuf>>();
+    let mut changeset = vec![];
+    for file in &files {
+        fs::write(file, "Old Old").unwrap();
+        changeset.push(Change::ReplaceInFile(ReplaceInFile::new(
+            file, "Old", "Mid",
+        )));
+        changeset.push(Change::ReplaceInFile(ReplaceInFile::new(
+            file, "Mid", "New",
+        )));
+    }
+    let renamed = staging_dir.join("New0.txt");
+    changeset.push(Change::RenameFile(RenameFile::new(&files[0], &renamed)));
+    changeset.push(Change::ReplaceInFile(ReplaceInFile::new(
+        &renamed, "New", "Last",
+    )));
+
+    let mut engine = Engine::new().with_threads(4);
+    engine.execute(changeset, &backup_dir).unwrap();
+    assert_eq!(fs::read_to_string(&renamed).unwrap(), "Last Last");
+    for file in &files[1..] {
+        assert_eq!(fs::read_to_string(file).unwrap(), "New New");
+    }
+
+    engine.revert().unwrap();
+    assert!(!renamed.exists());
+    for file in &files {
+        assert_eq!(fs::read_to_string(file).unwrap(), "Old Old");
+    }
+}
+
+#[test]
+fn corrupted_backup_should_be_detected_before_reverting() {
+    let staging_dir =
+        PathBuf::from("tests/temp/journal/corrupted_backup_detected_before_reverting");
+    let backup_dir = staging_dir.join("backup");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&backup_dir).unwrap();
+    let edited = staging_dir.join("Edited.txt");
+    let original = staging_dir.join("Old.txt");
+    let renamed = staging_dir.join("New.txt");
+    fs::write(&edited, "Old content").unwrap();
+    fs::write(&original, "").unwrap();
+
+    let changeset = vec![
+        Change::ReplaceInFile(ReplaceInFile::new(&edited, "Old", "New")),
+        Change::RenameFile(RenameFile::new(&original, &renamed)),
+    ];
+    Engine::new().execute(changeset, &backup_dir).unwrap();
+    let journal = journal::journal_path(&backup_dir);
+    let backup = match &journal::read(&journal).unwrap()[0] {
+        RevertAction::Restore { backup, .. } => backup.clone(),
+        action => panic!("unexpected revert action {:?}", action),
+    };
+    fs::write(&backup, "Tampered content").unwrap();
+
+    let err = Engine::revert_from_journal(&journal).unwrap_err();
+
+    assert!(matches!(err, RenomError::Revert(_)), "{}", err);
+    assert!(err.to_string().contains("corrupted"), "{}", err);
+    assert!(renamed.is_file());
+    assert_eq!(fs::read_to_string(&edited).unwrap(), "New content");
+    assert!(journal.is_file());
+}
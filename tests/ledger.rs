@@ -0,0 +1,166 @@
+use std::{fs, path::Path};
+
+use renom::{
+    ledger::{self, Entry},
+    workflows::{
+        rename_module::{self, Params},
+        Options,
+    },
+};
+
+mod common;
+
+use common::stage;
+
+#[test]
+fn renames_should_be_recorded_in_ledger_when_enabled() {
+    let resource = Path::new("tests/resources/module/source_paths");
+    let project_root = Path::new("tests/temp/ledger/renames_should_be_recorded");
+    stage(resource, project_root);
+
+    rename_module::rename_module(Params {
+        project_root: project_root.to_owned(),
+        module: "Demo".into(),
+        new_name: "Game".into(),
+        options: Options {
+            ledger: true,
+            ..Options::default()
+        },
+    })
+    .unwrap();
+
+    let entries = ledger::read(project_root).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].kind, "module");
+    assert_eq!(entries[0].old_name, "Demo");
+    assert_eq!(entries[0].new_name, "Game");
+    assert!(entries[0]
+        .redirects
+        .contains(&r#"+PackageRedirects=(OldName="/Script/Demo",NewName="/Script/Game")"#.into()));
+}
+
+#[test]
+fn renames_should_not_be_recorded_in_ledger_by_default() {
+    let resource = Path::new("tests/resources/module/source_paths");
+    let project_root = Path::new("tests/temp/ledger/renames_should_not_be_recorded");
+    stage(resource, project_root);
+
+    rename_module::rename_module(Params {
+        project_root: project_root.to_owned(),
+        module: "Demo".into(),
+        new_name: "Game".into(),
+        options: Options::default(),
+    })
+    .unwrap();
+
+    assert!(!ledger::ledger_path(project_root).exists());
+}
+
+#[test]
+fn entries_should_be_read_back_as_appended() {
+    let project_root = Path::new("tests/temp/ledger/entries_should_be_read_back");
+    if project_root.is_dir() {
+        fs::remove_dir_all(project_root).unwrap();
+    }
+    fs::create_dir_all(project_root).unwrap();
+
+    ledger::append(project_root, &Entry::new("plugin", "Old", "New", vec![])).unwrap();
+    ledger::append(
+        project_root,
+        &Entry::new(
+            "module",
+            "Core",
+            "Kernel",
+            vec!["+A=(1)".into(), "+B=(2)".into()],
+        ),
+    )
+    .unwrap();
+
+    let entries = ledger::read(project_root).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(
+        (entries[0].kind.as_str(), entries[0].redirects.len()),
+        ("plugin", 0)
+    );
+    assert_eq!(entries[1].new_name, "Kernel");
+    assert_eq!(entries[1].redirects, vec!["+A=(1)", "+B=(2)"]);
+}
+
+#[test]
+fn ledger_should_skip_malformed_rows() {
+    let project_root = Path::new("tests/temp/ledger/ledger_should_skip_malformed_rows");
+    if project_root.is_dir() {
+        fs::remove_dir_all(project_root).unwrap();
+    }
+    fs::create_dir_all(project_root).unwrap();
+    fs::write(
+        ledger::ledger_path(project_root),
+        "# Renames\n\
+         \n\
+         | Date | Actor | Kind | Old name | New name | Redirects |\n\
+         | --- | --- | --- | --- | --- | --- |\n\
+         | 2024-01-02 | jo | module | Core | Kernel | `+A=(1)`<br>`+B=(2)` |\n\
+         | 2024-01-03 | jo | module | Kernel |\n\
+         2024-01-04 | jo | plugin | Old | New | - |\n\
+         | 2024-01-05 | jo | plugin | Old | New | - | extra |\n\
+         | 2024-01-06 | jo | target | Demo | Game | - |\n",
+    )
+    .unwrap();
+
+    let entries = ledger::read(project_root).unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].date, "2024-01-02");
+    assert_eq!(entries[0].actor, "jo");
+    assert_eq!(entries[0].redirects, vec!["+A=(1)", "+B=(2)"]);
+    assert_eq!(entries[1].kind, "target");
+    assert!(entries[1].redirects.is_empty());
+}
+
+#[test]
+fn ledger_of_project_without_one_should_be_empty() {
+    let project_root = Path::new("tests/temp/ledger/ledger_of_project_without_one");
+    if project_root.is_dir() {
+        fs::remove_dir_all(project_root).unwrap();
+    }
+    fs::create_dir_all(project_root).unwrap();
+
+    assert!(ledger::read(project_root).unwrap().is_empty());
+}
+
+#[test]
+fn naming_prefix_should_be_shared_by_recent_names() {
+    let entry = |new_name: &str| Entry::new("module", "Old", new_name, vec![]);
+
+    assert_eq!(
+        ledger::naming_prefix(&[
+            entry("Foo"),
+            entry("AbcCore"),
+            entry("AbcTools"),
+            entry("AbcKit"),
+            entry("AbcMenu"),
+            entry("AbcHud"),
+        ]),
+        Some("Abc".into())
+    );
+    assert_eq!(ledger::naming_prefix(&[entry("AbcCore")]), None);
+    assert_eq!(
+        ledger::naming_prefix(&[entry("AbcCore"), entry("Tools")]),
+        None
+    );
+}
+
+#[test]
+fn reversal_should_be_most_recent_opposite_rename() {
+    let entries = [
+        Entry::new("module", "Core", "Kernel", vec!["first".into()]),
+        Entry::new("plugin", "Core", "Kernel", vec![]),
+        Entry::new("module", "Core", "Kernel", vec!["second".into()]),
+    ];
+
+    let reversal = ledger::find_reversal(&entries, "module", "Kernel", "Core").unwrap();
+
+    assert_eq!(reversal.redirects, vec!["second"]);
+    assert!(ledger::find_reversal(&entries, "module", "Core", "Kernel").is_none());
+    assert!(ledger::find_reversal(&entries, "target", "Kernel", "Core").is_none());
+}
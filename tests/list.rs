@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use renom::workflows::{list_components, ComponentKind};
+
+#[test]
+fn components_should_be_listed_by_kind_and_name() {
+    let project_root = Path::new("tests/resources/list/project");
+
+    let components = list_components(project_root, None).unwrap();
+
+    let listed = components
+        .iter()
+        .map(|component| (component.kind, component.name.as_str()))
+        .collect::<Vec<(ComponentKind, &str)>>();
+    assert_eq!(
+        listed,
+        [
+            (ComponentKind::Plugins, "Audio"),
+            (ComponentKind::Plugins, "Props"),
+            (ComponentKind::Modules, "Demo"),
+            (ComponentKind::Modules, "PropsRuntime"),
+            (ComponentKind::Targets, "Demo"),
+            (ComponentKind::Targets, "DemoEditor"),
+        ]
+    );
+    assert_eq!(
+        components[3].path,
+        project_root.join("Plugins/Props/Source/PropsRuntime")
+    );
+}
@@ -0,0 +1,32 @@
+use std::fs;
+
+use colored::Colorize;
+use renom::presentation::log;
+
+#[test]
+fn log_file_should_record_every_message_as_json_lines() {
+    let path = "tests/temp/log/run.jsonl";
+    fs::create_dir_all("tests/temp/log").unwrap();
+    let _ = fs::remove_file(path);
+    colored::control::set_override(true);
+
+    log::open_log_file(path.as_ref()).unwrap();
+    log::set_level(log::Level::Quiet);
+    log::step("apply", format!("rename file {}", "Old.txt".dimmed()));
+    log::detail("recorded", "rename back");
+    log::error("rename failed");
+
+    let records = fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    let kinds = records
+        .iter()
+        .map(|record| record["kind"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(kinds, vec!["start", "step", "detail", "error"]);
+    assert_eq!(records[1]["process"], "apply");
+    assert_eq!(records[1]["text"], "rename file Old.txt");
+    assert!(records[3]["time"].is_string());
+}
@@ -0,0 +1,84 @@
+use std::{fs, path::PathBuf};
+
+use renom::{
+    changes::{Category, ChangesetBuilder},
+    manifest::{self, Manifest},
+    vcs::Vcs,
+};
+
+#[test]
+fn manifest_should_summarize_changeset() {
+    let staging_dir = PathBuf::from("tests/temp/manifest/summarize_changeset");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    let changeset = ChangesetBuilder::new()
+        .replace_in_file("Source/Old/Old.Build.cs", "Old", "New")
+        .replace_in_file("Source/Old/Old.cpp", "OLD_API", "NEW_API")
+        .append_ini_entry(
+            "Config/DefaultEngine.ini",
+            "CoreRedirects",
+            "+PackageRedirects",
+            "(OldName=\"/Script/Old\",NewName=\"/Script/New\")",
+        )
+        .rename_file("Source/Old/Old.Build.cs", "Source/Old/New.Build.cs")
+        .build();
+
+    let manifest = Manifest::new("module", "Old", "New", changeset.changes());
+
+    assert_eq!(manifest.changes, 4);
+    assert_eq!(manifest.files_changed, 3);
+    assert_eq!(manifest.categories[&Category::Build], 1);
+    assert_eq!(manifest.categories[&Category::Source], 1);
+    assert_eq!(manifest.categories[&Category::Config], 1);
+    assert_eq!(
+        manifest.redirects,
+        vec!["+PackageRedirects=(OldName=\"/Script/Old\",NewName=\"/Script/New\")"]
+    );
+
+    manifest::write(&staging_dir, &manifest).unwrap();
+    assert_eq!(manifest::read(&staging_dir).unwrap(), manifest);
+}
+
+#[test]
+fn manifest_should_only_be_written_for_review_under_version_control() {
+    let staging_dir = PathBuf::from("tests/temp/manifest/written_for_review");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    let changeset = ChangesetBuilder::new()
+        .rename_file("Source/Old/Old.Build.cs", "Source/Old/New.Build.cs")
+        .build();
+    let manifest = Manifest::new("module", "Old", "New", changeset.changes());
+
+    manifest::write_for_review(&staging_dir, &manifest, None).unwrap();
+    assert!(!manifest::manifest_path(&staging_dir).exists());
+    manifest::write_for_review(&staging_dir, &manifest, Some(Vcs::Git)).unwrap();
+    assert_eq!(manifest::read(&staging_dir).unwrap(), manifest);
+}
+
+#[test]
+fn report_should_be_written_to_renom_dir_of_project() {
+    let staging_dir = PathBuf::from("tests/temp/manifest/written_as_report");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    let changeset = ChangesetBuilder::new()
+        .rename_file("Source/Old/Old.Build.cs", "Source/Old/New.Build.cs")
+        .build();
+    let manifest = Manifest::new("module", "Old", "New", changeset.changes());
+
+    manifest::write_report(&staging_dir, &manifest).unwrap();
+
+    let report = manifest::report_path(&staging_dir);
+    assert_eq!(report, staging_dir.join(".renom/rename-report.json"));
+    let content = fs::read_to_string(report).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Manifest>(&content).unwrap(),
+        manifest
+    );
+    assert!(!manifest::manifest_path(&staging_dir).exists());
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use renom::matcher::Matcher;
+
+#[test]
+fn matcher_should_match_literals_and_patterns() {
+    let matcher = Matcher::new(["CODE_API"], [r"IMPLEMENT_(GAME_)?MODULE"]).unwrap();
+
+    assert!(matcher.is_match("class CODE_API ACodeGameModeBase"));
+    assert!(matcher.is_match("IMPLEMENT_GAME_MODULE(FDefaultGameModuleImpl, Code);"));
+    assert!(!matcher.is_match("class GAME_API AGameModeBase"));
+}
+
+#[test]
+fn matcher_should_search_mapped_and_read_files_alike() {
+    let matcher = Matcher::literals(["value", "ProjectName"]);
+    let utf8 = Path::new("tests/resources/ini/quoted_value.ini");
+    let utf16 = Path::new("tests/resources/encoding/utf16le_bom.ini");
+
+    assert_eq!(
+        matcher.is_match_in_file(utf8, true),
+        matcher.is_match_in_file(utf8, false)
+    );
+    assert!(matcher.is_match_in_file(utf8, true));
+    assert_eq!(
+        matcher.is_match_in_file(utf16, true),
+        matcher.is_match_in_file(utf16, false)
+    );
+    assert!(matcher.is_match_in_file(utf16, true));
+}
+
+#[test]
+fn matcher_should_report_every_literal_and_pattern_matched_in_one_pass() {
+    let matcher = Matcher::new(
+        ["CODE_API"],
+        [r"IMPLEMENT_(GAME_)?MODULE", r#"LoadModule\("Code"#],
+    )
+    .unwrap()
+    .with_required(["Code", "CODE"]);
+    let source = "class CODE_API ACode;\nIMPLEMENT_GAME_MODULE(FDefaultGameModuleImpl, Code);";
+
+    assert_eq!(
+        matcher.matches(source).into_iter().collect::<Vec<_>>(),
+        [0, 1]
+    );
+    assert!(matcher
+        .matches("IMPLEMENT_MODULE(FDefaultModuleImpl, Game);")
+        .is_empty());
+    assert!(!matcher.is_match("IMPLEMENT_MODULE(FDefaultModuleImpl, Game);"));
+}
+
+#[test]
+fn matcher_should_search_files_in_parallel_keeping_their_order() {
+    let matcher = Matcher::literals(["value", "ProjectName"]);
+    let files = vec![
+        PathBuf::from("tests/resources/encoding/utf16le_bom.ini"),
+        PathBuf::from("tests/resources/encoding/bom_crlf.h"),
+        PathBuf::from("tests/resources/ini/quoted_value.ini"),
+    ];
+
+    assert_eq!(
+        matcher.files_matching(files.clone(), false),
+        [files[0].clone(), files[2].clone()]
+    );
+    assert_eq!(
+        matcher.matches_in_files(&files, false),
+        files
+            .iter()
+            .map(|file| matcher.matches_in_file(file, false))
+            .collect::<Vec<_>>()
+    );
+}
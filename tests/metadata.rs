@@ -0,0 +1,45 @@
+#![cfg(unix)]
+
+use std::{
+    fs::{self, File},
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use renom::changes::{ApplyOptions, Change, ReplaceInFile};
+
+#[test]
+fn replace_should_preserve_permissions_and_mtime() {
+    let resources_dir = PathBuf::from("tests/resources");
+    let original_file = resources_dir.join("metadata/build.sh");
+    let temp_dir = PathBuf::from("tests/temp");
+    let staging_dir = temp_dir.join("metadata/replace_should_preserve_permissions_and_mtime");
+    let result_file = staging_dir.join("build.sh");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    fs::copy(&original_file, &result_file).unwrap();
+    fs::set_permissions(&result_file, fs::Permissions::from_mode(0o755)).unwrap();
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    File::options()
+        .write(true)
+        .open(&result_file)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+
+    let replace_in_file = ReplaceInFile::new(&result_file, "Code", "Game");
+    let replace_change = Change::ReplaceInFile(replace_in_file);
+    let options = ApplyOptions {
+        preserve_mtime: true,
+        ..ApplyOptions::default()
+    };
+    let _revert = replace_change.apply_with(&staging_dir, &options).unwrap();
+
+    let metadata = fs::metadata(&result_file).unwrap();
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o755);
+    assert_eq!(metadata.modified().unwrap(), mtime);
+    assert!(fs::read_to_string(&result_file).unwrap().contains("Game"));
+}
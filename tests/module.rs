@@ -0,0 +1,206 @@
+use std::{ffi::OsStr, fs, path::Path};
+
+use clap::Parser;
+use renom::{
+    cli::{Cli, Command},
+    workflows::{
+        rename_module::{self, Params},
+        Options,
+    },
+};
+
+mod common;
+
+use common::stage;
+
+fn run(args: &[&OsStr]) {
+    let cli = Cli::try_parse_from([&["renom".as_ref(), "rename-module".as_ref()], args].concat())
+        .unwrap();
+    match cli.command {
+        Some(Command::RenameModule(command)) => command.run().unwrap(),
+        _ => panic!("expected rename-module command"),
+    }
+}
+
+#[test]
+fn module_paths_in_source_should_keep_their_separators() {
+    let resource = Path::new("tests/resources/module/source_paths");
+    let project_root =
+        Path::new("tests/temp/module/module_paths_in_source_should_keep_their_separators");
+    stage(resource, project_root);
+
+    rename_module::rename_module(Params {
+        project_root: project_root.to_owned(),
+        module: "Demo".into(),
+        new_name: "Game".into(),
+        options: Options::default(),
+    })
+    .unwrap();
+
+    let source = fs::read_to_string(project_root.join("Source/Game/Demo.cpp")).unwrap();
+    assert!(source.contains(r#"TEXT("Source/Game/Data")"#));
+    assert!(source.contains(r#"TEXT("Source\\Game\\Data")"#));
+    assert!(source.contains(r#"TEXT("Source/DemoEditor/Data")"#));
+}
+
+#[test]
+fn module_should_be_renamed_from_command_line_with_a_plan() {
+    let resource = Path::new("tests/resources/module/source_paths");
+    let project_root =
+        Path::new("tests/temp/module/module_should_be_renamed_from_command_line_with_a_plan");
+    stage(resource, project_root);
+    let plan_file = project_root.join("plan.json");
+
+    run(&[
+        "--project".as_ref(),
+        project_root.as_os_str(),
+        "--module".as_ref(),
+        "Demo".as_ref(),
+        "--new-name".as_ref(),
+        "Game".as_ref(),
+        "--plan-only".as_ref(),
+        plan_file.as_os_str(),
+    ]);
+    assert!(project_root.join("Source/Demo").is_dir());
+
+    run(&[
+        "--apply".as_ref(),
+        plan_file.as_os_str(),
+        "--no-prune".as_ref(),
+    ]);
+    assert!(project_root.join("Source/Game/Game.Build.cs").is_file());
+    assert!(!project_root.join("Source/Demo").exists());
+}
+
+#[test]
+fn module_declared_in_targets_only_should_be_added_to_descriptor() {
+    let resource = Path::new("tests/resources/module/target_only");
+    let project_root = Path::new(
+        "tests/temp/module/module_declared_in_targets_only_should_be_added_to_descriptor",
+    );
+    stage(resource, project_root);
+
+    rename_module::rename_module(Params {
+        project_root: project_root.to_owned(),
+        module: "Tools".into(),
+        new_name: "Utilities".into(),
+        options: Options {
+            normalize_descriptor: true,
+            ..Options::default()
+        },
+    })
+    .unwrap();
+
+    let target = fs::read_to_string(project_root.join("Source/Demo.Target.cs")).unwrap();
+    assert!(target.contains(r#"{ "Demo", "Utilities" }"#));
+    let descriptor = fs::read_to_string(project_root.join("Demo.uproject")).unwrap();
+    let descriptor: serde_json::Value = serde_json::from_str(&descriptor).unwrap();
+    let names = descriptor["Modules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|module| module["Name"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(names, ["Utilities", "Demo"]);
+}
+
+#[test]
+fn module_lookups_in_source_should_be_renamed() {
+    let resource = Path::new("tests/resources/module/api_literals");
+    let project_root = Path::new("tests/temp/module/module_lookups_in_source_should_be_renamed");
+    stage(resource, project_root);
+
+    rename_module::rename_module(Params {
+        project_root: project_root.to_owned(),
+        module: "Demo".into(),
+        new_name: "Game".into(),
+        options: Options::default(),
+    })
+    .unwrap();
+
+    let source = fs::read_to_string(project_root.join("Source/Tools/Tools.cpp")).unwrap();
+    assert!(source.contains(r#"LoadModuleChecked<IModuleInterface>("Game")"#));
+    assert!(source.contains(r#"IsModuleLoaded(TEXT("Game"))"#));
+    assert!(source.contains(r#"UE_LOG(LogTemp, Log, TEXT("Demo"))"#));
+}
+
+#[test]
+fn config_values_with_chosen_keys_should_be_renamed() {
+    let resource = Path::new("tests/resources/module/config_values");
+    let project_root =
+        Path::new("tests/temp/module/config_values_with_chosen_keys_should_be_renamed");
+    stage(resource, project_root);
+
+    rename_module::rename_module(Params {
+        project_root: project_root.to_owned(),
+        module: "Demo".into(),
+        new_name: "Game".into(),
+        options: Options {
+            config_value_keys: vec!["ModuleNames".into(), "DefaultModule".into()],
+            ..Options::default()
+        },
+    })
+    .unwrap();
+
+    let game = fs::read_to_string(project_root.join("Config/DefaultGame.ini")).unwrap();
+    assert!(game.contains("+ModuleNames=Game\n+ModuleNames=Tools\n"));
+    assert!(game.contains("DefaultModule=\"Game\"\n"));
+    assert!(game.contains("Description=Demo tools\n"));
+    let engine = fs::read_to_string(project_root.join("Config/DefaultEngine.ini")).unwrap();
+    assert!(engine.contains("GameName=Demo"));
+}
+
+#[test]
+fn redirects_recorded_in_ledger_should_be_collapsed_when_renamed_back() {
+    let resource = Path::new("tests/resources/module/source_paths");
+    let project_root =
+        Path::new("tests/temp/module/redirects_recorded_in_ledger_should_be_collapsed");
+    stage(resource, project_root);
+    let rename = |module: &str, new_name: &str| {
+        rename_module::rename_module(Params {
+            project_root: project_root.to_owned(),
+            module: module.into(),
+            new_name: new_name.into(),
+            options: Options {
+                ledger: true,
+                collapse_redirects: true,
+                ..Options::default()
+            },
+        })
+        .unwrap();
+    };
+
+    rename("Demo", "Game");
+    rename("Game", "Demo");
+
+    let engine = fs::read_to_string(project_root.join("Config/DefaultEngine.ini")).unwrap();
+    assert!(!engine.contains(r#"OldName="/Script/Demo", NewName="/Script/Demo""#));
+    assert!(engine.contains(r#"(OldName="/Script/Game",NewName="/Script/Demo")"#));
+}
+
+#[test]
+fn redirects_written_by_hand_should_survive_collapse() {
+    let resource = Path::new("tests/resources/module/source_paths");
+    let project_root = Path::new("tests/temp/module/redirects_written_by_hand_should_survive");
+    stage(resource, project_root);
+    let config = project_root.join("Config/DefaultEngine.ini");
+    let mut content = fs::read_to_string(&config).unwrap();
+    content += "\n[CoreRedirects]\n+PackageRedirects=(OldName=\"/Script/Game\", NewName=\"/Script/Demo\")\n";
+    fs::write(&config, content).unwrap();
+
+    rename_module::rename_module(Params {
+        project_root: project_root.to_owned(),
+        module: "Demo".into(),
+        new_name: "Game".into(),
+        options: Options {
+            collapse_redirects: true,
+            ..Options::default()
+        },
+    })
+    .unwrap();
+
+    let engine = fs::read_to_string(&config).unwrap();
+    assert!(
+        engine.contains(r#"+PackageRedirects=(OldName="/Script/Game", NewName="/Script/Game")"#)
+    );
+}
@@ -0,0 +1,96 @@
+use std::{fs, path::Path};
+
+use clap::Parser;
+use renom::{
+    cli::{Cli, Command},
+    plan::{self, Plan},
+    workflows::{apply_planned_rename_target, plan_rename_target, rename_target::Params, Options},
+};
+
+mod common;
+
+use common::stage;
+
+fn params(project_root: &Path) -> Params {
+    Params {
+        project_root: project_root.to_owned(),
+        target: "Demo".into(),
+        new_name: "Arcade".into(),
+        options: Options::default(),
+    }
+}
+
+#[test]
+fn stale_plan_should_not_be_applied() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root = Path::new("tests/temp/plan/stale_plan_should_not_be_applied");
+    stage(resource, project_root);
+    let plan_file = project_root.join("plan.json");
+
+    let changeset = plan_rename_target(&params(project_root)).unwrap();
+    let plan = Plan::new(
+        "target",
+        project_root,
+        Some("Demo".into()),
+        "Arcade",
+        changeset,
+    );
+    plan::write(&plan_file, &plan).unwrap();
+    let plan = plan::read(&plan_file).unwrap();
+    fs::write(
+        project_root.join("Build/Deploy.sh"),
+        "RunUAT.sh -target=Demo\n",
+    )
+    .unwrap();
+
+    let result = apply_planned_rename_target(params(project_root), &plan.changeset());
+
+    assert!(result.is_err());
+    assert!(project_root.join("Source/Demo.Target.cs").is_file());
+
+    fs::remove_file(project_root.join("Build/Deploy.sh")).unwrap();
+    apply_planned_rename_target(params(project_root), &plan.changeset()).unwrap();
+    assert!(project_root.join("Source/Arcade.Target.cs").is_file());
+}
+
+#[test]
+fn plan_written_by_plan_command_should_be_applied_by_apply_command() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root = Path::new("tests/temp/plan/plan_and_apply_commands");
+    stage(resource, project_root);
+    let plan_file = project_root.join("plan.json");
+    let run = |args: Vec<&str>| match Cli::try_parse_from(args).unwrap().command {
+        Some(Command::Plan(command)) => command.run(),
+        Some(Command::Apply(command)) => command.run(),
+        _ => panic!("expected plan or apply command"),
+    };
+
+    run(vec![
+        "renom",
+        "plan",
+        "rename-target",
+        "--project",
+        project_root.to_str().unwrap(),
+        "--target",
+        "Demo",
+        "--new-name",
+        "Arcade",
+        "--out",
+        plan_file.to_str().unwrap(),
+        "--no-prune",
+    ])
+    .unwrap();
+
+    assert_eq!(plan::read(&plan_file).unwrap().kind, "target");
+    assert!(project_root.join("Source/Demo.Target.cs").is_file());
+
+    run(vec![
+        "renom",
+        "apply",
+        plan_file.to_str().unwrap(),
+        "--no-prune",
+    ])
+    .unwrap();
+
+    assert!(project_root.join("Source/Arcade.Target.cs").is_file());
+}
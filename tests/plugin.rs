@@ -0,0 +1,204 @@
+use std::{fs, path::Path};
+
+use clap::Parser;
+use renom::{
+    cli::{Cli, Command},
+    workflows::{
+        rename_plugin::{self, Params},
+        Options,
+    },
+};
+
+mod common;
+
+use common::stage;
+
+#[test]
+fn content_only_plugin_should_be_renamed() {
+    let resource = Path::new("tests/resources/plugin/content_only");
+    let project_root = Path::new("tests/temp/plugin/content_only_plugin_should_be_renamed");
+    stage(resource, project_root);
+
+    rename_plugin::rename_plugin(Params {
+        project_root: project_root.to_owned(),
+        plugin: "Props".into(),
+        new_name: "Scenery".into(),
+        plugin_root: None,
+        options: Options::default(),
+    })
+    .unwrap();
+
+    let plugin_root = project_root.join("Plugins/Scenery");
+    assert!(plugin_root.join("Scenery.uplugin").is_file());
+    assert!(plugin_root.join("Content/Crate.uasset").is_file());
+    let descriptor = fs::read_to_string(project_root.join("Demo.uproject")).unwrap();
+    assert!(descriptor.contains(r#""Name": "Scenery""#));
+    let config = fs::read_to_string(project_root.join("Config/DefaultEngine.ini")).unwrap();
+    assert!(config.contains(r#"(OldName="/Props/",NewName="/Scenery/",MatchSubstring=true)"#));
+    let platform_config =
+        fs::read_to_string(project_root.join("Config/Windows/WindowsEngine.ini")).unwrap();
+    assert!(platform_config.contains("+EnabledPlugins=Scenery\n"));
+    assert!(platform_config.contains("+EnabledPlugins=PropsExtra\n"));
+}
+
+#[test]
+fn plugin_should_be_renamed_from_command_line() {
+    let resource = Path::new("tests/resources/plugin/content_only");
+    let project_root = Path::new("tests/temp/plugin/plugin_should_be_renamed_from_command_line");
+    stage(resource, project_root);
+
+    let cli = Cli::try_parse_from([
+        "renom".as_ref(),
+        "rename-plugin".as_ref(),
+        "--project".as_ref(),
+        project_root.as_os_str(),
+        "--plugin".as_ref(),
+        "Props".as_ref(),
+        "--new-name".as_ref(),
+        "Scenery".as_ref(),
+        "--no-prune".as_ref(),
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Command::RenamePlugin(command)) => command.run().unwrap(),
+        _ => panic!("expected rename-plugin command"),
+    }
+
+    let plugin_root = project_root.join("Plugins/Scenery");
+    assert!(plugin_root.join("Scenery.uplugin").is_file());
+    let descriptor = fs::read_to_string(project_root.join("Demo.uproject")).unwrap();
+    assert!(descriptor.contains(r#""Name": "Scenery""#));
+}
+
+#[test]
+fn dry_run_should_not_change_plugin() {
+    let resource = Path::new("tests/resources/plugin/content_only");
+    let project_root = Path::new("tests/temp/plugin/dry_run_should_not_change_plugin");
+    stage(resource, project_root);
+
+    let cli = Cli::try_parse_from([
+        "renom".as_ref(),
+        "rename-plugin".as_ref(),
+        "--project".as_ref(),
+        project_root.as_os_str(),
+        "--plugin".as_ref(),
+        "Props".as_ref(),
+        "--new-name".as_ref(),
+        "Scenery".as_ref(),
+        "--dry-run".as_ref(),
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Command::RenamePlugin(command)) => command.run().unwrap(),
+        _ => panic!("expected rename-plugin command"),
+    }
+
+    assert!(project_root.join("Plugins/Props/Props.uplugin").is_file());
+    assert!(!project_root.join(".renom").exists());
+}
+
+#[test]
+fn external_plugin_should_only_have_references_renamed() {
+    let resource = Path::new("tests/resources/plugin/external");
+    let project_root =
+        Path::new("tests/temp/plugin/external_plugin_should_only_have_references_renamed");
+    stage(resource, project_root);
+
+    rename_plugin::rename_plugin(Params {
+        project_root: project_root.to_owned(),
+        plugin: "Weather".into(),
+        new_name: "Climate".into(),
+        plugin_root: None,
+        options: Options::default(),
+    })
+    .unwrap();
+
+    assert!(!project_root.join("Plugins/Climate").exists());
+    assert!(project_root.join("Plugins/Props/Props.uplugin").is_file());
+    let descriptor = fs::read_to_string(project_root.join("Demo.uproject")).unwrap();
+    assert!(descriptor.contains(r#""Name": "Climate""#));
+}
+
+#[test]
+fn plugin_in_additional_directory_should_be_renamed() {
+    let resource = Path::new("tests/resources/plugin/additional_directory");
+    let project_root =
+        Path::new("tests/temp/plugin/plugin_in_additional_directory_should_be_renamed");
+    stage(resource, project_root);
+
+    rename_plugin::rename_plugin(Params {
+        project_root: project_root.to_owned(),
+        plugin: "Tools".into(),
+        new_name: "Utilities".into(),
+        plugin_root: None,
+        options: Options::default(),
+    })
+    .unwrap();
+
+    assert!(project_root
+        .join("External/Utilities/Utilities.uplugin")
+        .is_file());
+    let descriptor = fs::read_to_string(project_root.join("Demo.uproject")).unwrap();
+    assert!(descriptor.contains(r#""Name": "Utilities""#));
+}
+
+#[test]
+fn plugins_sharing_a_name_should_be_told_apart_by_path() {
+    let resource = Path::new("tests/resources/plugin/duplicate_names");
+    let project_root =
+        Path::new("tests/temp/plugin/plugins_sharing_a_name_should_be_told_apart_by_path");
+    stage(resource, project_root);
+
+    let ambiguous = rename_plugin::rename_plugin(Params {
+        project_root: project_root.to_owned(),
+        plugin: "Props".into(),
+        new_name: "Scenery".into(),
+        plugin_root: None,
+        options: Options::default(),
+    });
+    assert!(ambiguous.unwrap_err().to_string().contains("--plugin-path"));
+
+    rename_plugin::rename_plugin(Params {
+        project_root: project_root.to_owned(),
+        plugin: "Props".into(),
+        new_name: "Scenery".into(),
+        plugin_root: Some("Plugins/Vendor/Props".into()),
+        options: Options::default(),
+    })
+    .unwrap();
+
+    assert!(project_root.join("Plugins/Props/Props.uplugin").is_file());
+    assert!(project_root
+        .join("Plugins/Vendor/Scenery/Scenery.uplugin")
+        .is_file());
+}
+
+#[test]
+fn plugins_should_be_renamed_in_one_batch() {
+    let resource = Path::new("tests/resources/plugin/batch");
+    let project_root = Path::new("tests/temp/plugin/plugins_should_be_renamed_in_one_batch");
+    stage(resource, project_root);
+
+    let cli = Cli::try_parse_from([
+        "renom".as_ref(),
+        "rename-plugin".as_ref(),
+        project_root.as_os_str(),
+        "--rename".as_ref(),
+        "Props=Scenery".as_ref(),
+        "--rename".as_ref(),
+        "Tools=Kit".as_ref(),
+        "--no-prune".as_ref(),
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Command::RenamePlugin(command)) => command.run().unwrap(),
+        _ => panic!("expected rename-plugin command"),
+    }
+
+    assert!(project_root.join("Plugins/Kit/Kit.uplugin").is_file());
+    let descriptor =
+        fs::read_to_string(project_root.join("Plugins/Scenery/Scenery.uplugin")).unwrap();
+    assert!(descriptor.contains(r#""Name": "Kit""#));
+    let backups = fs::read_dir(project_root.join(".renom/backup")).unwrap();
+    assert_eq!(backups.count(), 1);
+}
@@ -0,0 +1,79 @@
+use std::{fs, path::PathBuf};
+
+use renom::{
+    changes::{Change, RenameFile, ReplaceInFile},
+    engine::Engine,
+    presentation::preview::diff,
+};
+
+#[test]
+fn diff_should_show_changed_lines_with_context() {
+    colored::control::set_override(false);
+    let before = "a\nb\nc\nOld\nd\ne\nf\ng\nh\ni\nOld\nj\n";
+    let after = "a\nb\nc\nNew\nd\ne\nf\ng\nh\ni\nNew\nj\n";
+
+    let lines = diff(before, after);
+
+    assert_eq!(
+        lines,
+        vec![
+            "@@ -2,5 +2,5 @@",
+            " b",
+            " c",
+            "-Old",
+            "+New",
+            " d",
+            " e",
+            "@@ -9,4 +9,4 @@",
+            " h",
+            " i",
+            "-Old",
+            "+New",
+            " j",
+        ]
+    );
+}
+
+#[test]
+fn diff_should_be_empty_for_identical_text() {
+    assert!(diff("a\nb\n", "a\nb\n").is_empty());
+}
+
+#[test]
+fn preview_should_diff_edits_on_top_of_earlier_changes() {
+    let staging_dir = PathBuf::from("tests/temp/preview/diff_edits_on_top_of_earlier_changes");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    let original = staging_dir.join("Old.Build.cs");
+    let renamed = staging_dir.join("New.Build.cs");
+    fs::write(&original, "class Old\n{\n    Old();\n}\n").unwrap();
+
+    let changeset = vec![
+        Change::RenameFile(RenameFile::new(&original, &renamed)),
+        Change::ReplaceInFile(ReplaceInFile::new(&renamed, "class Old", "class New")),
+        Change::ReplaceInFile(ReplaceInFile::new(&renamed, "Old\\(", "New(")),
+    ];
+    let files = Engine::preview(&changeset).unwrap();
+
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, original);
+    assert_eq!(
+        files[0].hunks(),
+        vec![
+            "@@ -1,4 +1,4 @@",
+            "-class Old",
+            "+class New",
+            " {",
+            "-    Old();",
+            "+    New();",
+            " }",
+        ]
+    );
+    assert!(original.is_file());
+    assert_eq!(
+        fs::read_to_string(&original).unwrap(),
+        "class Old\n{\n    Old();\n}\n"
+    );
+}
@@ -0,0 +1,80 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use renom::{
+    changes::{Change, ReplaceInFile},
+    engine::Engine,
+    progress::{Progress, ProgressObserver},
+};
+
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<(&'static str, usize, usize)>>,
+}
+
+impl Recorder {
+    fn record(&self, event: &'static str, progress: Progress) {
+        let mut events = self.events.lock().unwrap();
+        events.push((event, progress.completed, progress.total));
+    }
+}
+
+impl ProgressObserver for Recorder {
+    fn started(&self, progress: Progress) {
+        self.record("started", progress);
+    }
+
+    fn change_completed(&self, _change: &Change, progress: Progress) {
+        self.record("completed", progress);
+    }
+
+    fn change_reverted(&self, _change: &Change, progress: Progress) {
+        self.record("reverted", progress);
+    }
+
+    fn finished(&self, progress: Progress) {
+        self.record("finished", progress);
+    }
+}
+
+#[test]
+fn observer_should_be_notified_in_order_of_changeset() {
+    let staging_dir = PathBuf::from("tests/temp/progress/notified_in_order_of_changeset");
+    let backup_dir = staging_dir.join("backup");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&backup_dir).unwrap();
+    let files = ["A.txt", "B.txt", "C.txt"].map(|name| staging_dir.join(name));
+    for file in &files {
+        fs::write(file, "Old content").unwrap();
+    }
+
+    let changeset = files
+        .iter()
+        .map(|file| Change::ReplaceInFile(ReplaceInFile::new(file, "Old", "New")))
+        .collect::<Vec<Change>>();
+    let recorder = Arc::new(Recorder::default());
+    let mut engine = Engine::new()
+        .with_threads(4)
+        .with_observer(recorder.clone());
+    engine.execute(changeset, &backup_dir).unwrap();
+    engine.revert().unwrap();
+
+    assert_eq!(
+        *recorder.events.lock().unwrap(),
+        vec![
+            ("started", 0, 3),
+            ("completed", 1, 3),
+            ("completed", 2, 3),
+            ("completed", 3, 3),
+            ("finished", 3, 3),
+            ("reverted", 2, 3),
+            ("reverted", 1, 3),
+            ("reverted", 0, 3),
+        ]
+    );
+}
@@ -0,0 +1,97 @@
+use std::{fs, path::Path};
+
+use renom::workflows::{rename_project, rename_project::Params, restore_last_backup, Options};
+
+mod common;
+
+use common::stage;
+
+#[test]
+fn generated_project_files_should_be_renamed_when_opted_in() {
+    let resource = Path::new("tests/resources/project/project_files");
+    let staging_dir = Path::new("tests/temp/project/generated_project_files_should_be_renamed");
+    stage(resource, staging_dir);
+
+    rename_project(Params {
+        project_root: staging_dir.join("Demo"),
+        new_name: "Arcade".into(),
+        options: Options {
+            update_project_files: true,
+            ..Options::default()
+        },
+    })
+    .unwrap();
+
+    let project_root = staging_dir.join("Arcade");
+    let solution = fs::read_to_string(project_root.join("Arcade.sln")).unwrap();
+    assert!(solution.contains(r#"= "Arcade", "Intermediate\ProjectFiles\Arcade.vcxproj", "#));
+    let project_files = project_root.join("Intermediate/ProjectFiles");
+    let project = fs::read_to_string(project_files.join("Arcade.vcxproj")).unwrap();
+    assert!(project.contains(r#"-Project="$(SolutionDir)Arcade.uproject""#));
+    assert!(project.contains("Build.bat DemoEditor"));
+    let filters = fs::read_to_string(project_files.join("Arcade.vcxproj.filters")).unwrap();
+    assert!(filters.contains(r#"Include="..\..\Arcade.uproject""#));
+}
+
+#[test]
+fn renamed_project_should_be_restored_from_its_backup() {
+    let resource = Path::new("tests/resources/project/project_files");
+    let staging_dir = Path::new("tests/temp/project/renamed_project_should_be_restored");
+    stage(resource, staging_dir);
+
+    rename_project(Params {
+        project_root: staging_dir.join("Demo"),
+        new_name: "Arcade".into(),
+        options: Options::default(),
+    })
+    .unwrap();
+    restore_last_backup(&staging_dir.join("Arcade"), &Options::default()).unwrap();
+
+    let project_root = staging_dir.join("Demo");
+    assert!(project_root.join("Demo.uproject").is_file());
+    assert!(!staging_dir.join("Arcade").exists());
+    let config = fs::read_to_string(project_root.join("Config/DefaultEngine.ini")).unwrap();
+    assert!(config.contains("GameName=Demo"));
+    assert!(restore_last_backup(&project_root, &Options::default()).is_err());
+}
+
+#[test]
+fn content_paths_in_config_should_be_rewritten_when_opted_in() {
+    let resource = Path::new("tests/resources/project/content_paths");
+    let staging_dir = Path::new("tests/temp/project/content_paths_should_be_rewritten");
+    stage(resource, staging_dir);
+
+    rename_project(Params {
+        project_root: staging_dir.join("Demo"),
+        new_name: "Arcade".into(),
+        options: Options {
+            rewrite_content_paths: true,
+            ..Options::default()
+        },
+    })
+    .unwrap();
+
+    let config = staging_dir.join("Arcade/Config");
+    let engine = fs::read_to_string(config.join("DefaultEngine.ini")).unwrap();
+    assert!(engine.contains("GameDefaultMap=/Game/Arcade/Maps/Start.Start"));
+    assert!(engine.contains("EditorStartupMap=/Game/Arcade/Maps/Start.Start"));
+    let game = fs::read_to_string(config.join("DefaultGame.ini")).unwrap();
+    assert!(game.contains(r#"+DirectoriesToAlwaysCook=(Path="/Game/Arcade/Data")"#));
+}
+
+#[test]
+fn content_paths_in_config_should_be_left_by_default() {
+    let resource = Path::new("tests/resources/project/content_paths");
+    let staging_dir = Path::new("tests/temp/project/content_paths_should_be_left");
+    stage(resource, staging_dir);
+
+    rename_project(Params {
+        project_root: staging_dir.join("Demo"),
+        new_name: "Arcade".into(),
+        options: Options::default(),
+    })
+    .unwrap();
+
+    let engine = fs::read_to_string(staging_dir.join("Arcade/Config/DefaultEngine.ini")).unwrap();
+    assert!(engine.contains("GameDefaultMap=/Game/Demo/Maps/Start.Start"));
+}
@@ -0,0 +1,63 @@
+use std::io;
+
+use renom::{
+    changes::{Change, RenameFile, ReplaceInFile},
+    error::RenomError,
+    report::{Report, Status},
+};
+
+#[test]
+fn failed_rename_should_report_nothing_executed() {
+    let planned = vec![Change::RenameFile(RenameFile::new("Old.txt", "New.txt"))];
+
+    let report = Report::applied(
+        "project",
+        planned.clone(),
+        &Err(RenomError::Io(io::Error::other("disk full"))),
+    );
+
+    assert_eq!(report.status, Status::Failed);
+    assert_eq!(report.error.as_deref(), Some("disk full"));
+    assert_eq!(report.planned, planned);
+    assert!(report.executed.is_empty());
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["status"], "failed");
+}
+
+#[test]
+fn csv_report_should_have_a_row_per_change() {
+    let planned = vec![
+        Change::RenameFile(RenameFile::new("Old.txt", "New.txt")),
+        Change::ReplaceInFile(ReplaceInFile::new("Demo.ini", "Old, \"Name\"", "New")),
+    ];
+
+    let csv = Report::applied("project", planned, &Ok(())).to_csv();
+
+    assert_eq!(
+        csv,
+        concat!(
+            "path,category,action,old_value,new_value,status\n",
+            "Old.txt,other,rename,Old.txt,New.txt,applied\n",
+            "Demo.ini,config,replace,\"Old, \"\"Name\"\"\",New,applied\n",
+        )
+    );
+}
+
+#[test]
+fn project_rename_should_report_compatibility_of_save_games() {
+    let planned = vec![
+        Change::ReplaceInFile(ReplaceInFile::new("Demo.ini", "Demo", "Arcade")),
+        Change::RenameFile(RenameFile::new(
+            "Game/Demo.uproject",
+            "Game/Arcade.uproject",
+        )),
+    ];
+
+    let report = Report::planned("project", planned);
+
+    assert!(report.compatibility[0].contains("%LOCALAPPDATA%/Arcade/Saved"));
+    let json = serde_json::to_value(&report).unwrap();
+    assert!(json["compatibility"].is_array());
+    let report = Report::planned("module", vec![]);
+    assert!(report.compatibility.is_empty());
+}
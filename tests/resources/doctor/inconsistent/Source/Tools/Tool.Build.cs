@@ -0,0 +1,9 @@
+using UnrealBuildTool;
+
+public class Tool : ModuleRules
+{
+	public Tool(ReadOnlyTargetRules Target) : base(Target)
+	{
+		PublicDependencyModuleNames.Add("Core");
+	}
+}
@@ -0,0 +1,5 @@
+﻿#pragma once
+
+class CODE_API ACodeActor
+{
+};
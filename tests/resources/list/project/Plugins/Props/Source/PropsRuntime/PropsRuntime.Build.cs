@@ -0,0 +1,9 @@
+using UnrealBuildTool;
+
+public class PropsRuntime : ModuleRules
+{
+	public PropsRuntime(ReadOnlyTargetRules Target) : base(Target)
+	{
+		PublicDependencyModuleNames.Add("Core");
+	}
+}
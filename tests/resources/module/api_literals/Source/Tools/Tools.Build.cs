@@ -0,0 +1,9 @@
+using UnrealBuildTool;
+
+public class Tools : ModuleRules
+{
+	public Tools(ReadOnlyTargetRules Target) : base(Target)
+	{
+		PublicDependencyModuleNames.Add("Core");
+	}
+}
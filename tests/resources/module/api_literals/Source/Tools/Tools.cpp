@@ -0,0 +1,12 @@
+#include "Modules/ModuleManager.h"
+
+void LoadDemo()
+{
+	FModuleManager::LoadModuleChecked<IModuleInterface>("Demo");
+	if (FModuleManager::Get().IsModuleLoaded(TEXT("Demo")))
+	{
+		UE_LOG(LogTemp, Log, TEXT("Demo"));
+	}
+}
+
+IMPLEMENT_MODULE(FDefaultModuleImpl, Tools);
@@ -0,0 +1,9 @@
+using UnrealBuildTool;
+
+public class Demo : ModuleRules
+{
+	public Demo(ReadOnlyTargetRules Target) : base(Target)
+	{
+		PublicDependencyModuleNames.Add("Core");
+	}
+}
@@ -0,0 +1,7 @@
+#include "Modules/ModuleManager.h"
+
+static const TCHAR* DataDir = TEXT("Source/Demo/Data");
+static const TCHAR* WindowsDataDir = TEXT("Source\\Demo\\Data");
+static const TCHAR* EditorDir = TEXT("Source/DemoEditor/Data");
+
+IMPLEMENT_PRIMARY_GAME_MODULE(FDefaultGameModuleImpl, Demo, "Demo");
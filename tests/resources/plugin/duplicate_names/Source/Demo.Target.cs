@@ -0,0 +1,9 @@
+using UnrealBuildTool;
+
+public class DemoTarget : TargetRules
+{
+	public DemoTarget(TargetInfo Target) : base(Target)
+	{
+		Type = TargetType.Game;
+	}
+}
@@ -0,0 +1,3 @@
+@echo off
+call "%UE_ROOT%\Engine\Build\BatchFiles\RunUAT.bat" BuildCookRun -project="%~dp0..\Demo.uproject" -target="Demo" -platform=Win64 -cook -stage -pak
+call "%UE_ROOT%\Engine\Build\BatchFiles\RunUAT.bat" BuildCookRun -project="%~dp0..\Demo.uproject" -Target=DemoEditor -platform=Win64 -cook
@@ -0,0 +1,10 @@
+using UnrealBuildTool;
+
+public class DemoTarget : TargetRules
+{
+	public DemoTarget(TargetInfo Target) : base(Target)
+	{
+		Type = TargetType.Game;
+		ExtraModuleNames.Add("Demo");
+	}
+}
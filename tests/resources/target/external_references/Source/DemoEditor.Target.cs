@@ -0,0 +1,10 @@
+using UnrealBuildTool;
+
+public class DemoEditorTarget : TargetRules
+{
+	public DemoEditorTarget(TargetInfo Target) : base(Target)
+	{
+		Type = TargetType.Editor;
+		ExtraModuleNames.Add("Demo");
+	}
+}
@@ -0,0 +1,24 @@
+use std::{fs, path::Path};
+
+use renom::settings::{self, ColorChoice, Settings};
+
+#[test]
+fn project_config_should_be_loaded_for_project() {
+    let project_root = Path::new("tests/temp/settings/project_config_should_be_loaded_for_project");
+    if project_root.is_dir() {
+        fs::remove_dir_all(project_root).unwrap();
+    }
+    let config = settings::project_config_path(project_root);
+    fs::create_dir_all(config.parent().unwrap()).unwrap();
+    fs::write(
+        &config,
+        "[general]\ncolor = \"never\"\nignore = [\"ThirdParty\"]\n\n[backup]\nmax_count = 3\n",
+    )
+    .unwrap();
+
+    let settings = Settings::load_for(project_root).unwrap();
+
+    assert_eq!(settings.general.color, ColorChoice::Never);
+    assert_eq!(settings.general.ignore, ["ThirdParty"]);
+    assert_eq!(settings.backup.max_count, Some(3));
+}
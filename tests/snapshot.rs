@@ -0,0 +1,34 @@
+use std::{env, fs, path::Path};
+
+use renom::workflows::{plan_rename_target, rename_target::Params, Options};
+
+mod common;
+
+use common::stage;
+
+/// Compare a snapshot with the one recorded in the resources, or record it
+/// when `RENOM_UPDATE_SNAPSHOTS` is set.
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new("tests/resources/snapshots").join(name);
+    if env::var_os("RENOM_UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+    }
+    assert_eq!(fs::read_to_string(&path).unwrap(), actual);
+}
+
+#[test]
+fn target_changeset_should_match_snapshot() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root = Path::new("tests/temp/snapshot/target_changeset_should_match_snapshot");
+    stage(resource, project_root);
+
+    let changeset = plan_rename_target(&Params {
+        project_root: project_root.to_owned(),
+        target: "Demo".into(),
+        new_name: "Arcade".into(),
+        options: Options::default(),
+    })
+    .unwrap();
+
+    assert_snapshot("target.json", &changeset.snapshot(project_root));
+}
@@ -0,0 +1,32 @@
+use renom::{cli::Cli, suggest::parse_args};
+
+#[test]
+fn unknown_arguments_should_suggest_the_closest_known_ones() {
+    let cases = [
+        (
+            vec!["renom", "rename-projet"],
+            "did you mean `rename-project`?",
+        ),
+        (
+            vec!["renom", "rename-project", "--new-nam", "Arcade"],
+            "did you mean `--new-name`?",
+        ),
+        (
+            vec!["renom", "list", "--project", "Game", "modulse"],
+            "did you mean `modules`?",
+        ),
+    ];
+    for (args, suggestion) in cases {
+        let err = parse_args::<Cli, _, _>(args.clone()).err().unwrap();
+        assert!(
+            err.render().to_string().contains(suggestion),
+            "{:?} did not suggest {}",
+            args,
+            suggestion
+        );
+    }
+    let err = parse_args::<Cli, _, _>(vec!["renom", "rename-project", "--zzzzzzzz"])
+        .err()
+        .unwrap();
+    assert!(!err.render().to_string().contains("did you mean"));
+}
@@ -0,0 +1,76 @@
+#![cfg(unix)]
+
+use std::{
+    fs,
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+};
+
+use renom::{
+    changes::{ApplyOptions, Change, RenameFile, ReplaceInFile},
+    engine::Engine,
+    error::RenomError,
+};
+
+/// Set up a project whose plugin folder links to a folder outside of it, as
+/// studios do to share plugins between projects.
+fn stage(name: &str) -> PathBuf {
+    let staging_dir = PathBuf::from("tests/temp/symlinks").join(name);
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(staging_dir.join("Shared/Props")).unwrap();
+    fs::create_dir_all(staging_dir.join("Project/Plugins")).unwrap();
+    fs::create_dir_all(staging_dir.join("Project/.renom/backup")).unwrap();
+    fs::write(staging_dir.join("Shared/Props/Props.h"), "class Props {};").unwrap();
+    symlink(
+        "../../Shared/Props",
+        staging_dir.join("Project/Plugins/Props"),
+    )
+    .unwrap();
+    staging_dir
+}
+
+fn engine(staging_dir: &Path) -> Engine {
+    Engine::with_options(ApplyOptions {
+        root: Some(staging_dir.join("Project")),
+        ..ApplyOptions::default()
+    })
+}
+
+#[test]
+fn edits_through_symlinks_out_of_the_project_should_abort_execution() {
+    let staging_dir = stage("edits_out_of_the_project");
+    let header = staging_dir.join("Project/Plugins/Props/Props.h");
+    let changeset = vec![Change::ReplaceInFile(ReplaceInFile::new(
+        &header, "Props", "Scenery",
+    ))];
+
+    let err = engine(&staging_dir)
+        .execute(changeset, staging_dir.join("Project/.renom/backup"))
+        .unwrap_err();
+
+    assert!(matches!(err, RenomError::Validation(_)));
+    assert!(err.to_string().contains("Props.h"));
+    assert_eq!(fs::read_to_string(header).unwrap(), "class Props {};");
+}
+
+#[test]
+fn symlinks_should_be_renamed_rather_than_their_targets() {
+    let staging_dir = stage("renamed_rather_than_their_targets");
+    let changeset = vec![Change::RenameFile(RenameFile::new(
+        staging_dir.join("Project/Plugins/Props"),
+        staging_dir.join("Project/Plugins/Scenery"),
+    ))];
+
+    engine(&staging_dir)
+        .execute(changeset, staging_dir.join("Project/.renom/backup"))
+        .unwrap();
+
+    let link = staging_dir.join("Project/Plugins/Scenery");
+    assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+    assert!(staging_dir
+        .join("Project/Plugins/Scenery/Props.h")
+        .is_file());
+    assert!(staging_dir.join("Shared/Props").is_dir());
+}
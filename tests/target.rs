@@ -0,0 +1,285 @@
+use std::{env, ffi::OsStr, fs, path::Path};
+
+use clap::Parser;
+use renom::{
+    cli::{Cli, Command},
+    workflows::{
+        rename_target::{self, Params},
+        Options,
+    },
+};
+
+mod common;
+
+use common::stage;
+
+#[test]
+fn target_references_in_config_and_scripts_should_be_renamed() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root =
+        Path::new("tests/temp/target/target_references_in_config_and_scripts_should_be_renamed");
+    stage(resource, project_root);
+
+    rename_target::rename_target(Params {
+        project_root: project_root.to_owned(),
+        target: "Demo".into(),
+        new_name: "Arcade".into(),
+        options: Options::default(),
+    })
+    .unwrap();
+
+    assert!(project_root.join("Source/Arcade.Target.cs").is_file());
+    let config = fs::read_to_string(project_root.join("Config/DefaultGame.ini")).unwrap();
+    assert!(config.contains("[/Script/Arcade.DemoSettings]"));
+    assert!(config.contains("BuildTarget=Arcade"));
+    assert!(config.contains("[Arcade Deployment]"));
+    assert!(config.contains("StagingArgs=-Target=Arcade -Platform=Win64"));
+    let script = fs::read_to_string(project_root.join("Build/Package.bat")).unwrap();
+    assert!(script.contains(r#"-target="Arcade""#));
+    assert!(script.contains("-Target=DemoEditor"));
+    assert!(script.contains(r"%~dp0..\Demo.uproject"));
+}
+
+#[test]
+fn target_should_be_renamed_from_command_line() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root = Path::new("tests/temp/target/target_should_be_renamed_from_command_line");
+    stage(resource, project_root);
+
+    let cli = Cli::try_parse_from([
+        "renom".as_ref(),
+        "rename-target".as_ref(),
+        "--project".as_ref(),
+        project_root.as_os_str(),
+        "--target".as_ref(),
+        "DemoEditor".as_ref(),
+        "--new-name".as_ref(),
+        "ArcadeEditor".as_ref(),
+        "--no-prune".as_ref(),
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Command::RenameTarget(command)) => command.run().unwrap(),
+        _ => panic!("expected rename-target command"),
+    }
+
+    assert!(project_root.join("Source/ArcadeEditor.Target.cs").is_file());
+    assert!(project_root.join("Source/Demo.Target.cs").is_file());
+}
+
+#[test]
+fn target_should_be_renamed_with_positional_arguments_and_short_flags() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root = Path::new(
+        "tests/temp/target/target_should_be_renamed_with_positional_arguments_and_short_flags",
+    );
+    stage(resource, project_root);
+    let run = |args: &[&OsStr]| {
+        let cli =
+            Cli::try_parse_from([&["renom".as_ref(), "rename-target".as_ref()], args].concat())
+                .unwrap();
+        match cli.command {
+            Some(Command::RenameTarget(command)) => command.run().unwrap(),
+            _ => panic!("expected rename-target command"),
+        }
+    };
+
+    run(&[
+        project_root.as_os_str(),
+        "DemoEditor".as_ref(),
+        "ArcadeEditor".as_ref(),
+        "--no-prune".as_ref(),
+    ]);
+    assert!(project_root.join("Source/ArcadeEditor.Target.cs").is_file());
+
+    run(&[
+        "-p".as_ref(),
+        project_root.as_os_str(),
+        "-t".as_ref(),
+        "ArcadeEditor".as_ref(),
+        "-n".as_ref(),
+        "DemoEditor".as_ref(),
+        "--no-prune".as_ref(),
+    ]);
+    assert!(project_root.join("Source/DemoEditor.Target.cs").is_file());
+}
+
+#[test]
+fn target_should_be_renamed_with_arguments_from_environment() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root =
+        Path::new("tests/temp/target/target_should_be_renamed_with_arguments_from_environment");
+    stage(resource, project_root);
+    env::set_var("RENOM_PROJECT", project_root);
+    env::set_var("RENOM_NEW_NAME", "ArcadeEditor");
+
+    let cli = Cli::try_parse_from([
+        "renom",
+        "rename-target",
+        "--target",
+        "DemoEditor",
+        "--no-prune",
+    ])
+    .unwrap();
+    let result = match cli.command {
+        Some(Command::RenameTarget(command)) => command.run(),
+        _ => panic!("expected rename-target command"),
+    };
+    env::remove_var("RENOM_PROJECT");
+    env::remove_var("RENOM_NEW_NAME");
+
+    result.unwrap();
+    assert!(project_root.join("Source/ArcadeEditor.Target.cs").is_file());
+}
+
+#[test]
+fn extra_ops_should_be_applied_with_the_rename() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root = Path::new("tests/temp/target/extra_ops_should_be_applied_with_the_rename");
+    stage(resource, project_root);
+    let extra_ops = project_root.join("ops.ndjson");
+    fs::write(
+        &extra_ops,
+        concat!(
+            r#"{"kind":"rename_file","from":"Source/Demo/Demo.Build.cs","to":"Source/Demo/Demo.Build.cs.bak"}"#,
+            "\n",
+            r#"{"kind":"rename_file","from":"../Outside.txt","to":"Inside.txt"}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    let params = |extra_ops: &Path| Params {
+        project_root: project_root.to_owned(),
+        target: "Demo".into(),
+        new_name: "Arcade".into(),
+        options: Options {
+            extra_ops: Some(extra_ops.to_owned()),
+            ..Options::default()
+        },
+    };
+    let err = rename_target::rename_target(params(&extra_ops)).unwrap_err();
+    assert!(err.to_string().contains(":2:"));
+    assert!(project_root.join("Source/Demo.Target.cs").is_file());
+
+    fs::write(
+        &extra_ops,
+        r#"{"kind":"rename_file","from":"Source/Demo/Demo.Build.cs","to":"Source/Demo/Demo.Build.cs.bak"}"#,
+    )
+    .unwrap();
+    rename_target::rename_target(params(&extra_ops)).unwrap();
+
+    assert!(project_root.join("Source/Arcade.Target.cs").is_file());
+    assert!(project_root.join("Source/Demo/Demo.Build.cs.bak").is_file());
+}
+
+#[test]
+fn target_should_be_renamed_with_name_from_names_file() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root =
+        Path::new("tests/temp/target/target_should_be_renamed_with_name_from_names_file");
+    stage(resource, project_root);
+    let names_file = project_root.with_extension("txt");
+    fs::write(&names_file, "ArcadeEditor\n").unwrap();
+
+    let cli = Cli::try_parse_from([
+        OsStr::new("renom"),
+        OsStr::new("rename-target"),
+        OsStr::new("--project"),
+        project_root.as_os_str(),
+        OsStr::new("--target"),
+        OsStr::new("DemoEditor"),
+        OsStr::new("--names-file"),
+        names_file.as_os_str(),
+        OsStr::new("--no-prune"),
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Command::RenameTarget(command)) => command.run().unwrap(),
+        _ => panic!("expected rename-target command"),
+    }
+
+    assert!(project_root.join("Source/ArcadeEditor.Target.cs").is_file());
+}
+
+#[test]
+fn backups_should_be_kept_in_custom_backup_dir() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root = Path::new("tests/temp/target/backups_should_be_kept_in_custom_backup_dir");
+    stage(resource, project_root);
+    let backup_dir = project_root.with_extension("backup");
+    if backup_dir.is_dir() {
+        fs::remove_dir_all(&backup_dir).unwrap();
+    }
+
+    rename_target::rename_target(Params {
+        project_root: project_root.to_owned(),
+        target: "Demo".into(),
+        new_name: "Arcade".into(),
+        options: Options {
+            backup_dir: Some(
+                fs::canonicalize(project_root)
+                    .unwrap()
+                    .with_extension("backup"),
+            ),
+            ..Options::default()
+        },
+    })
+    .unwrap();
+
+    assert!(project_root.join("Source/Arcade.Target.cs").is_file());
+    assert_eq!(fs::read_dir(&backup_dir).unwrap().count(), 1);
+    assert!(!project_root.join(".renom/backup").exists());
+}
+
+#[test]
+fn files_ruled_out_by_globs_should_be_left_alone() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root = Path::new("tests/temp/target/files_ruled_out_by_globs_should_be_left_alone");
+    stage(resource, project_root);
+
+    rename_target::rename_target(Params {
+        project_root: project_root.to_owned(),
+        target: "Demo".into(),
+        new_name: "Arcade".into(),
+        options: Options {
+            include: vec!["Source/**".into(), "**/*.ini".into()],
+            exclude: vec!["Config/Default*.ini".into()],
+            ..Options::default()
+        },
+    })
+    .unwrap();
+
+    assert!(project_root.join("Source/Arcade.Target.cs").is_file());
+    let config = fs::read_to_string(project_root.join("Config/DefaultGame.ini")).unwrap();
+    assert!(config.contains("BuildTarget=Demo"));
+    let script = fs::read_to_string(project_root.join("Build/Package.bat")).unwrap();
+    assert!(script.contains(r#"-target="Demo""#));
+}
+
+#[test]
+fn invalid_from_name_should_be_rejected() {
+    let resource = Path::new("tests/resources/target/external_references");
+    let project_root = Path::new("tests/temp/target/invalid_from_name_should_be_rejected");
+    stage(resource, project_root);
+
+    for from_name in ["", "Demo|Source", ".*"] {
+        let err = rename_target::rename_target(Params {
+            project_root: project_root.to_owned(),
+            target: "Demo".into(),
+            new_name: "Arcade".into(),
+            options: Options {
+                from_name: Some(from_name.into()),
+                ..Options::default()
+            },
+        })
+        .unwrap_err();
+
+        assert!(err.to_string().contains("old name to search for"));
+    }
+    assert!(project_root.join("Source/Demo.Target.cs").is_file());
+    assert_eq!(
+        fs::read(project_root.join("Config/DefaultGame.ini")).unwrap(),
+        fs::read(resource.join("Config/DefaultGame.ini")).unwrap()
+    );
+}
@@ -0,0 +1,59 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use renom::{
+    changes::{ApplyOptions, Change, ReplaceInFile},
+    text::LineEnding,
+    vcs::git_line_endings,
+};
+
+#[test]
+fn git_line_endings_should_follow_attributes_and_autocrlf() {
+    let staging_dir = PathBuf::from("tests/temp/vcs/git_line_endings");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(&staging_dir).unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&staging_dir)
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "--quiet"]);
+    git(&["config", "core.autocrlf", "input"]);
+    fs::write(
+        staging_dir.join(".gitattributes"),
+        "*.ini eol=crlf\n*.bin -text\n",
+    )
+    .unwrap();
+    let config = staging_dir.join("DefaultGame.ini");
+    let source = staging_dir.join("Code.h");
+    let binary = staging_dir.join("Code.bin");
+    fs::write(&config, "[Project]\nProjectName=Code\n").unwrap();
+    fs::write(&source, "#pragma once\r\n").unwrap();
+    fs::write(&binary, "Code\r\n").unwrap();
+
+    let line_endings = git_line_endings(
+        &staging_dir,
+        &[config.clone(), source.clone(), binary.clone()],
+    )
+    .unwrap();
+
+    assert_eq!(line_endings.get(&config), Some(&LineEnding::CrLf));
+    assert_eq!(line_endings.get(&source), Some(&LineEnding::Lf));
+    assert_eq!(line_endings.get(&binary), None);
+
+    let options = ApplyOptions {
+        line_endings,
+        ..ApplyOptions::default()
+    };
+    let change = Change::ReplaceInFile(ReplaceInFile::new(&config, "Code", "Game"));
+    let _revert = change.apply_with(&staging_dir, &options).unwrap();
+    assert_eq!(
+        fs::read_to_string(&config).unwrap(),
+        "[Project]\r\nProjectName=Game\r\n"
+    );
+}
@@ -0,0 +1,101 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use renom::{
+    changes::{ApplyOptions, Change, DiscrepancyKind, RenameFile, ReplaceInFile},
+    engine::Engine,
+    error::RenomError,
+    fs::{Fs, MemoryFs},
+    progress::{Progress, ProgressObserver},
+};
+
+fn project() -> MemoryFs {
+    let fs = MemoryFs::default();
+    fs.create_dir(Path::new("Project/Source")).unwrap();
+    fs.create_dir(Path::new("backup")).unwrap();
+    fs.write(Path::new("Project/Source/Old.h"), b"class Old {};")
+        .unwrap();
+    fs
+}
+
+fn changeset() -> Vec<Change> {
+    vec![
+        Change::ReplaceInFile(ReplaceInFile::new("Project/Source/Old.h", "Old", "New")),
+        Change::RenameFile(RenameFile::new("Project/Source", "Project/Code")),
+    ]
+}
+
+#[test]
+fn executed_changes_should_hold() {
+    let mut engine = Engine::with_fs(project(), ApplyOptions::default());
+
+    engine.execute(changeset(), "backup").unwrap();
+
+    assert_eq!(engine.verify(), vec![]);
+}
+
+#[test]
+fn old_names_written_back_should_be_reported_where_files_were_moved() {
+    let mut engine = Engine::with_fs(project(), ApplyOptions::default());
+    engine.execute(changeset(), "backup").unwrap();
+    let header = Path::new("Project/Code/Old.h");
+
+    engine.fs().write(header, b"class Old {}; // Old").unwrap();
+
+    let discrepancies = engine.verify();
+    assert_eq!(discrepancies.len(), 1);
+    assert_eq!(discrepancies[0].kind, DiscrepancyKind::Unedited);
+    assert_eq!(discrepancies[0].path, header);
+}
+
+#[test]
+fn edits_to_files_that_still_hold_should_not_be_reported() {
+    let mut engine = Engine::with_fs(project(), ApplyOptions::default());
+    engine.execute(changeset(), "backup").unwrap();
+
+    engine
+        .fs()
+        .write(Path::new("Project/Code/Old.h"), b"class New {}; // New")
+        .unwrap();
+
+    assert_eq!(engine.verify(), vec![]);
+}
+
+/// Write the old content back as soon as a file is edited, as an editor
+/// holding the file open would on save.
+struct Overwriter {
+    path: PathBuf,
+}
+
+impl ProgressObserver for Overwriter {
+    fn change_completed(&self, _change: &Change, _progress: Progress) {
+        fs::write(&self.path, "class Old {};").unwrap();
+    }
+}
+
+#[test]
+fn execution_should_fail_when_changes_do_not_hold() {
+    let staging_dir = PathBuf::from("tests/temp/verification/fail_when_changes_do_not_hold");
+    if staging_dir.is_dir() {
+        fs::remove_dir_all(&staging_dir).unwrap();
+    }
+    fs::create_dir_all(staging_dir.join("backup")).unwrap();
+    let header = staging_dir.join("Old.h");
+    fs::write(&header, "class Old {};").unwrap();
+    let mut engine = Engine::new().with_observer(Arc::new(Overwriter {
+        path: header.clone(),
+    }));
+    let changeset = vec![Change::ReplaceInFile(ReplaceInFile::new(
+        &header, "Old", "New",
+    ))];
+
+    let err = engine
+        .execute(changeset, staging_dir.join("backup"))
+        .unwrap_err();
+
+    assert!(matches!(err, RenomError::Verification(_)));
+    assert!(err.to_string().contains("Old.h"));
+}